@@ -0,0 +1,38 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use interface_rs::NetworkInterfaces;
+use std::fmt::Write;
+use std::fs;
+
+/// Writes an interfaces file with `count` interfaces and loads it.
+fn load_generated(count: usize) -> NetworkInterfaces {
+    let mut content = String::new();
+    for i in 0..count {
+        writeln!(content, "auto swp{}", i).unwrap();
+        writeln!(content, "allow-hotplug swp{}", i).unwrap();
+        writeln!(content, "iface swp{} inet static", i).unwrap();
+        writeln!(content, "    mtu 9216").unwrap();
+        writeln!(content, "    address 10.{}.{}.1/24", i / 256 % 256, i % 256).unwrap();
+        writeln!(content, "    bridge-access {}", i % 4094 + 1).unwrap();
+        writeln!(content, "    post-up ip link set swp{} promisc on", i).unwrap();
+        writeln!(content, "    alias port {}", i).unwrap();
+        writeln!(content).unwrap();
+    }
+    let path = std::env::temp_dir().join(format!("interface-rs-bench-{}", std::process::id()));
+    fs::write(&path, content).unwrap();
+    let net_ifaces = NetworkInterfaces::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    net_ifaces
+}
+
+fn display(c: &mut Criterion) {
+    let net_ifaces = load_generated(10_000);
+    c.bench_function("display 10k interfaces", |b| {
+        b.iter(|| black_box(&net_ifaces).to_string())
+    });
+    c.bench_function("plan_save 10k interfaces", |b| {
+        b.iter(|| black_box(&net_ifaces).plan_save().unwrap())
+    });
+}
+
+criterion_group!(benches, display);
+criterion_main!(benches);
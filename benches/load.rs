@@ -0,0 +1,154 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use interface_rs::interface::option::intern_key;
+use interface_rs::interface::OptionKey;
+use interface_rs::{NetworkInterfaces, Parser};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts allocations so the benchmark can report memory as well as time.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const COUNT: usize = 50_000;
+
+/// Renders an interfaces file with `count` access ports.
+fn generate(count: usize) -> String {
+    let mut content = String::new();
+    for i in 0..count {
+        writeln!(content, "auto swp{}", i).unwrap();
+        writeln!(content, "iface swp{}", i).unwrap();
+        writeln!(content, "    bridge-access {}", i % 4094 + 1).unwrap();
+        writeln!(content, "    mtu 9216").unwrap();
+        writeln!(content, "    mstpctl-bpduguard yes").unwrap();
+        writeln!(content, "    mstpctl-portadminedge yes").unwrap();
+        writeln!(content).unwrap();
+    }
+    content
+}
+
+/// Runs `f` and returns its result with the number of allocations and bytes
+/// it made.
+fn measure<T>(f: impl FnOnce() -> T) -> (T, usize, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let result = f();
+    (
+        result,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn load(c: &mut Criterion) {
+    let content = generate(COUNT);
+    let path = std::env::temp_dir().join(format!("interface-rs-bench-load-{}", std::process::id()));
+    fs::write(&path, &content).unwrap();
+
+    let (net_ifaces, allocations, bytes) = measure(|| NetworkInterfaces::load(&path).unwrap());
+    assert_eq!(net_ifaces.len(), COUNT);
+    println!(
+        "load {} interfaces: {} allocations, {} bytes",
+        COUNT, allocations, bytes
+    );
+
+    let mut group = c.benchmark_group("load");
+    group.throughput(Throughput::Elements(COUNT as u64));
+    group.bench_function("load 50k interfaces", |b| {
+        b.iter(|| NetworkInterfaces::load(black_box(&path)).unwrap())
+    });
+    group.finish();
+    fs::remove_file(&path).unwrap();
+}
+
+fn option_keys(c: &mut Criterion) {
+    let content = generate(COUNT);
+    let keys: Vec<&str> = content
+        .lines()
+        .filter(|line| line.starts_with(' '))
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+
+    let (_, owned, owned_bytes) = measure(|| {
+        keys.iter()
+            .map(|key| Cow::Owned(key.to_string()))
+            .collect::<Vec<OptionKey>>()
+    });
+    let (_, interned, interned_bytes) = measure(|| {
+        keys.iter()
+            .map(|&key| intern_key(key))
+            .collect::<Vec<OptionKey>>()
+    });
+    println!(
+        "{} option keys: owned {} allocations ({} bytes), interned {} allocations ({} bytes)",
+        keys.len(),
+        owned,
+        owned_bytes,
+        interned,
+        interned_bytes
+    );
+
+    let mut group = c.benchmark_group("option keys");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            black_box(&keys)
+                .iter()
+                .map(|key| Cow::Owned(key.to_string()))
+                .collect::<Vec<OptionKey>>()
+        })
+    });
+    group.bench_function("interned", |b| {
+        b.iter(|| {
+            black_box(&keys)
+                .iter()
+                .map(|&key| intern_key(key))
+                .collect::<Vec<OptionKey>>()
+        })
+    });
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let content = generate(COUNT);
+    let parser = Parser::new();
+
+    let (_, owned, owned_bytes) = measure(|| parser.parse(&content).unwrap());
+    let (_, borrowed, borrowed_bytes) = measure(|| parser.parse_borrowed(&content).unwrap());
+    println!(
+        "parse {} interfaces: owned {} allocations ({} bytes), borrowed {} allocations ({} bytes)",
+        COUNT, owned, owned_bytes, borrowed, borrowed_bytes
+    );
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(content.len() as u64));
+    group.bench_function("owned", |b| {
+        b.iter(|| parser.parse(black_box(&content)).unwrap())
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| parser.parse_borrowed(black_box(&content)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, load, option_keys, parse);
+criterion_main!(benches);
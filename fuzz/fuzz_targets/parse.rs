@@ -0,0 +1,32 @@
+//! Feeds arbitrary bytes to the parser.
+//!
+//! Run with `cargo +nightly fuzz run parse` from the repository root.
+
+#![no_main]
+
+use interface_rs::{NetworkInterfaces, Parser, ParserOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let options = ParserOptions {
+        max_input_size: Some(1 << 16),
+        max_line_length: Some(4096),
+        max_interfaces: Some(1024),
+        max_options_per_interface: Some(256),
+        ..Default::default()
+    };
+    let parser = Parser::new().with_options(options);
+    let Ok((interfaces, _, _)) = parser.parse_bytes(data) else {
+        return;
+    };
+
+    // The borrowed parser accepts whatever the owned one does
+    let content = std::str::from_utf8(data).unwrap();
+    parser.parse_borrowed(content).unwrap();
+
+    // Whatever parses reads back unchanged once written
+    let net_ifaces: NetworkInterfaces = interfaces.into_values().collect();
+    if let Err(err) = net_ifaces.check_roundtrip() {
+        panic!("{}", err);
+    }
+});
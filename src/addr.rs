@@ -0,0 +1,376 @@
+//! Conversions between netmasks, prefix lengths, and address specifications.
+//!
+//! An `interfaces(5)` file can give the prefix of an address as a length
+//! (`address 10.0.0.1/24`), as a dotted netmask after the slash
+//! (`address 10.0.0.1/255.255.255.0`), or in a separate `netmask` option that
+//! takes either form. The functions in this module implement these
+//! conversions once for the rest of the crate, e.g. for
+//! [`Address`](crate::interface::Address) and
+//! [`Interface::addresses`](crate::interface::Interface::addresses).
+//!
+//! Netmasks must be contiguous: a run of ones followed by a run of zeros.
+//! Dotted netmasks are only accepted for IPv4 addresses, since IPv6
+//! configurations always use prefix lengths; the IPv6 conversions are
+//! provided for completeness.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::addr;
+//! use std::net::Ipv4Addr;
+//!
+//! assert_eq!(addr::netmask_to_prefix(Ipv4Addr::new(255, 255, 255, 0)).unwrap(), 24);
+//! assert_eq!(addr::prefix_to_netmask(20), Ipv4Addr::new(255, 255, 240, 0));
+//!
+//! let (ip, prefix) = addr::parse_address_spec("10.0.0.1/255.255.0.0").unwrap();
+//! assert_eq!(ip.to_string(), "10.0.0.1");
+//! assert_eq!(prefix, Some(16));
+//! ```
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Converts a dotted IPv4 netmask to a prefix length.
+///
+/// # Errors
+///
+/// Returns `AddressSpecParseError::NonContiguous` if the ones of the mask
+/// are not contiguous from the top, as in `255.0.255.0`.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::addr::netmask_to_prefix;
+///
+/// assert_eq!(netmask_to_prefix("255.255.255.252".parse().unwrap()).unwrap(), 30);
+/// assert!(netmask_to_prefix("255.255.0.255".parse().unwrap()).is_err());
+/// ```
+pub fn netmask_to_prefix(mask: Ipv4Addr) -> Result<u8, AddressSpecParseError> {
+    let bits = u32::from(mask);
+    if bits.leading_ones() + bits.trailing_zeros() != 32 {
+        return Err(AddressSpecParseError::NonContiguous(IpAddr::V4(mask)));
+    }
+    Ok(bits.leading_ones() as u8)
+}
+
+/// Converts an IPv6 netmask to a prefix length.
+///
+/// # Errors
+///
+/// Returns `AddressSpecParseError::NonContiguous` if the ones of the mask
+/// are not contiguous from the top.
+pub fn netmask6_to_prefix(mask: Ipv6Addr) -> Result<u8, AddressSpecParseError> {
+    let bits = u128::from(mask);
+    if bits.leading_ones() + bits.trailing_zeros() != 128 {
+        return Err(AddressSpecParseError::NonContiguous(IpAddr::V6(mask)));
+    }
+    Ok(bits.leading_ones() as u8)
+}
+
+/// Converts a prefix length to a dotted IPv4 netmask.
+///
+/// Prefix lengths above 32 give the all-ones mask.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::addr::prefix_to_netmask;
+/// use std::net::Ipv4Addr;
+///
+/// assert_eq!(prefix_to_netmask(0), Ipv4Addr::UNSPECIFIED);
+/// assert_eq!(prefix_to_netmask(25), Ipv4Addr::new(255, 255, 255, 128));
+/// ```
+pub fn prefix_to_netmask(prefix: u8) -> Ipv4Addr {
+    let zeros = 32u32.saturating_sub(u32::from(prefix));
+    Ipv4Addr::from(u32::MAX.checked_shl(zeros).unwrap_or(0))
+}
+
+/// Converts a prefix length to an IPv6 netmask.
+///
+/// Prefix lengths above 128 give the all-ones mask.
+pub fn prefix_to_netmask6(prefix: u8) -> Ipv6Addr {
+    let zeros = 128u32.saturating_sub(u32::from(prefix));
+    Ipv6Addr::from(u128::MAX.checked_shl(zeros).unwrap_or(0))
+}
+
+/// Parses a prefix length, or for IPv4 a dotted netmask, for an address of
+/// the same family as `ip`.
+///
+/// This is the form of the part after the slash of an address, and of the
+/// value of a `netmask` option.
+///
+/// # Errors
+///
+/// Returns `AddressSpecParseError::NonContiguous` for a netmask with holes,
+/// and `AddressSpecParseError::InvalidPrefix` for anything else that is not
+/// a prefix length or netmask valid for the family of `ip`.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::addr::parse_prefix;
+/// use std::net::IpAddr;
+///
+/// let v4: IpAddr = "10.0.0.1".parse().unwrap();
+/// let v6: IpAddr = "2001:db8::1".parse().unwrap();
+/// assert_eq!(parse_prefix("255.255.255.0", v4).unwrap(), 24);
+/// assert_eq!(parse_prefix("64", v6).unwrap(), 64);
+/// assert!(parse_prefix("64", v4).is_err());
+/// ```
+pub fn parse_prefix(prefix: &str, ip: IpAddr) -> Result<u8, AddressSpecParseError> {
+    let invalid = || AddressSpecParseError::InvalidPrefix(prefix.to_string());
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    if let Ok(length) = prefix.parse::<u8>() {
+        return if length <= max {
+            Ok(length)
+        } else {
+            Err(invalid())
+        };
+    }
+    match (prefix.parse::<Ipv4Addr>(), ip) {
+        (Ok(mask), IpAddr::V4(_)) => netmask_to_prefix(mask),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses the value of an `address` option.
+///
+/// The accepted forms are a bare address (`10.0.0.1`, `2001:db8::1`), an
+/// address with a prefix length (`10.0.0.1/24`, `2001:db8::1/64`), and an
+/// IPv4 address with a dotted netmask (`10.0.0.1/255.255.255.0`).
+///
+/// # Returns
+///
+/// The address and its prefix length, if one was given.
+///
+/// # Errors
+///
+/// Returns `AddressSpecParseError::InvalidAddress` if the part before the
+/// slash is not an IP address, and the errors of [`parse_prefix`] for the
+/// part after it.
+pub fn parse_address_spec(s: &str) -> Result<(IpAddr, Option<u8>), AddressSpecParseError> {
+    let (ip, prefix) = match s.split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix)),
+        None => (s, None),
+    };
+    let ip: IpAddr = ip
+        .parse()
+        .map_err(|_| AddressSpecParseError::InvalidAddress(s.to_string()))?;
+    let prefix = match prefix {
+        Some(prefix) => Some(parse_prefix(prefix, ip)?),
+        None => None,
+    };
+    Ok((ip, prefix))
+}
+
+/// Rewrites the IPv6 addresses in an option value to the canonical text form
+/// of RFC 5952.
+///
+/// The value is split at spaces, so lists such as the value of
+/// `dns-nameservers` are handled word by word. Each word may carry a zone
+/// identifier (`fe80::1%swp1`) and a prefix (`2001:db8::1/64`), which are
+/// kept as written. Words that are not IPv6 addresses, including IPv4
+/// addresses, are left untouched, as are the spaces between words.
+///
+/// # Returns
+///
+/// The rewritten value, borrowed if it was already canonical.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::addr::canonical_ipv6;
+///
+/// assert_eq!(canonical_ipv6("2001:DB8:0:0:0:0:0:1/64"), "2001:db8::1/64");
+/// assert_eq!(canonical_ipv6("FE80:0:0:0:0:0:0:0001%swp1"), "fe80::1%swp1");
+/// assert_eq!(canonical_ipv6("10.0.0.1 2001:db8:0::53"), "10.0.0.1 2001:db8::53");
+/// assert_eq!(canonical_ipv6("not-an-address"), "not-an-address");
+/// ```
+pub fn canonical_ipv6(value: &str) -> Cow<'_, str> {
+    let words: Vec<Cow<'_, str>> = value.split(' ').map(canonical_ipv6_word).collect();
+    if words.iter().all(|word| matches!(word, Cow::Borrowed(_))) {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(words.join(" "))
+}
+
+/// Rewrites a single `address[%zone][/prefix]` word; see [`canonical_ipv6`].
+fn canonical_ipv6_word(word: &str) -> Cow<'_, str> {
+    let end = word.find(['%', '/']).unwrap_or(word.len());
+    let (ip, rest) = word.split_at(end);
+    match ip.parse::<Ipv6Addr>().map(|parsed| parsed.to_string()) {
+        Ok(canonical) if canonical != ip => Cow::Owned(canonical + rest),
+        _ => Cow::Borrowed(word),
+    }
+}
+
+/// An error that occurs when converting or parsing a netmask, prefix length,
+/// or address specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressSpecParseError {
+    /// The text is not an IP address.
+    InvalidAddress(String),
+    /// The text is neither a prefix length nor a netmask valid for the
+    /// address family.
+    InvalidPrefix(String),
+    /// The netmask is not a run of ones followed by a run of zeros.
+    NonContiguous(IpAddr),
+}
+
+impl fmt::Display for AddressSpecParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressSpecParseError::InvalidAddress(s) => write!(f, "Invalid address: {}", s),
+            AddressSpecParseError::InvalidPrefix(s) => {
+                write!(f, "Invalid prefix length or netmask: {}", s)
+            }
+            AddressSpecParseError::NonContiguous(mask) => {
+                write!(f, "Netmask is not contiguous: {}", mask)
+            }
+        }
+    }
+}
+
+impl Error for AddressSpecParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_netmask_prefix_roundtrip() {
+        for prefix in 0..=32 {
+            let mask = prefix_to_netmask(prefix);
+            assert_eq!(netmask_to_prefix(mask).unwrap(), prefix, "{}", mask);
+        }
+        for prefix in 0..=128 {
+            let mask = prefix_to_netmask6(prefix);
+            assert_eq!(netmask6_to_prefix(mask).unwrap(), prefix, "{}", mask);
+        }
+        assert_eq!(prefix_to_netmask(8), Ipv4Addr::new(255, 0, 0, 0));
+        assert_eq!(prefix_to_netmask(33), Ipv4Addr::BROADCAST);
+        assert_eq!(
+            prefix_to_netmask6(48),
+            "ffff:ffff:ffff::".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(prefix_to_netmask6(200), Ipv6Addr::from(u128::MAX));
+    }
+
+    #[test]
+    fn test_canonical_ipv6() {
+        for (input, expected) in [
+            ("2001:DB8:0:0:0:0:0:1", "2001:db8::1"),
+            (
+                "2001:0db8:0000:0000:0000:0000:0000:0001/64",
+                "2001:db8::1/64",
+            ),
+            ("2001:db8::1", "2001:db8::1"),
+            // The longest run of zeros is compressed, the first one on a tie
+            ("2001:db8:0:0:1:0:0:0", "2001:db8:0:0:1::"),
+            ("2001:db8:0:0:1:0:0:1", "2001:db8::1:0:0:1"),
+            // A single zero group is not compressed
+            ("2001:db8:0:1:1:1:1:1", "2001:db8:0:1:1:1:1:1"),
+            ("::FFFF:192.0.2.1", "::ffff:192.0.2.1"),
+            ("FE80:0000::0001%swp1", "fe80::1%swp1"),
+            ("fe80::00AB%eth0/64", "fe80::ab%eth0/64"),
+            (
+                "2001:DB8::53  10.0.0.53 2001:db8::0:54",
+                "2001:db8::53  10.0.0.53 2001:db8::54",
+            ),
+            ("10.0.0.1/24", "10.0.0.1/24"),
+            ("2001:DB8::g", "2001:DB8::g"),
+            ("", ""),
+        ] {
+            let canonical = canonical_ipv6(input);
+            assert_eq!(canonical, expected, "{}", input);
+            assert_eq!(canonical_ipv6(&canonical), canonical, "{}", input);
+        }
+        assert!(matches!(canonical_ipv6("2001:db8::1"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_non_contiguous_masks() {
+        for mask in [
+            "255.0.255.0",
+            "255.255.255.1",
+            "0.255.255.255",
+            "255.255.254.255",
+            "128.0.0.1",
+            "0.0.0.1",
+        ] {
+            let mask: Ipv4Addr = mask.parse().unwrap();
+            assert_eq!(
+                netmask_to_prefix(mask),
+                Err(AddressSpecParseError::NonContiguous(IpAddr::V4(mask)))
+            );
+        }
+        let mask: Ipv6Addr = "ffff::ffff".parse().unwrap();
+        assert!(netmask6_to_prefix(mask).is_err());
+        assert_eq!(
+            parse_address_spec("10.0.0.1/255.0.255.0")
+                .unwrap_err()
+                .to_string(),
+            "Netmask is not contiguous: 255.0.255.0"
+        );
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        let v4: IpAddr = "192.0.2.1".parse().unwrap();
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(parse_prefix("0", v4), Ok(0));
+        assert_eq!(parse_prefix("32", v4), Ok(32));
+        assert_eq!(parse_prefix("255.255.255.255", v4), Ok(32));
+        assert_eq!(parse_prefix("0.0.0.0", v4), Ok(0));
+        assert_eq!(parse_prefix("128", v6), Ok(128));
+
+        for (prefix, ip) in [
+            ("33", v4),
+            ("129", v6),
+            ("255.255.255.0", v6),
+            ("ffff:ffff::", v4),
+            ("ffff:ffff::", v6),
+            ("-1", v4),
+            ("", v4),
+            ("24 ", v4),
+        ] {
+            assert_eq!(
+                parse_prefix(prefix, ip),
+                Err(AddressSpecParseError::InvalidPrefix(prefix.to_string())),
+                "{} for {}",
+                prefix,
+                ip
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_address_spec() {
+        let v4: IpAddr = "10.0.0.1".parse().unwrap();
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(parse_address_spec("10.0.0.1"), Ok((v4, None)));
+        assert_eq!(parse_address_spec("10.0.0.1/24"), Ok((v4, Some(24))));
+        assert_eq!(
+            parse_address_spec("10.0.0.1/255.255.255.0"),
+            Ok((v4, Some(24)))
+        );
+        assert_eq!(parse_address_spec("2001:db8::1"), Ok((v6, None)));
+        assert_eq!(parse_address_spec("2001:db8::1/64"), Ok((v6, Some(64))));
+
+        assert_eq!(
+            parse_address_spec("host.example/24"),
+            Err(AddressSpecParseError::InvalidAddress(
+                "host.example/24".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_address_spec("10.0.0.1/"),
+            Err(AddressSpecParseError::InvalidPrefix(String::new()))
+        );
+        assert!(parse_address_spec("10.0.0.1/24/24").is_err());
+        assert!(parse_address_spec("10.0.0.256").is_err());
+        assert!(parse_address_spec("").is_err());
+    }
+}
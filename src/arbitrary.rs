@@ -0,0 +1,319 @@
+//! [`proptest`] support for the crate's data model.
+//!
+//! Enabled with the `proptest` feature, this module implements
+//! [`Arbitrary`] for [`Family`], [`Method`], [`InterfaceOption`],
+//! [`Interface`], and [`NetworkInterfaces`], so that code consuming this
+//! crate can be property-tested with realistic configurations:
+//!
+//! ```rust,ignore
+//! use interface_rs::NetworkInterfaces;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn my_tool_never_panics(net_ifaces in any::<NetworkInterfaces>()) {
+//!         my_tool::process(&net_ifaces);
+//!     }
+//! }
+//! ```
+//!
+//! Generated values are always representable in an `interfaces(5)` file:
+//! names, methods, and option keys are single tokens that cannot be confused
+//! with stanza keywords, and option values are whitespace-normalized, so
+//! every generated collection passes
+//! [`check_roundtrip`](NetworkInterfaces::check_roundtrip).
+
+use crate::interface::{Family, FamilyStanza, Interface, InterfaceOption, Mapping, Method};
+use crate::network_interfaces::NetworkInterfaces;
+use crate::parser::ParserOptions;
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+/// Strategy producing plausible interface names.
+fn interface_name() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("lo".to_string()),
+        "eth[0-9]",
+        "swp[1-9][0-9]?(s[0-3])?",
+        "bond[0-9]",
+        "br[0-9]",
+        "vlan[1-9][0-9]{0,3}",
+        "vni[1-9][0-9]{0,6}",
+        "eth[0-9]\\.[1-9][0-9]{0,3}",
+        "eth[0-9]:[0-9]",
+    ]
+}
+
+/// Strategy producing a whitespace-normalized option value.
+fn option_value() -> impl Strategy<Value = String> {
+    prop::collection::vec("[!-~]{1,12}", 0..4).prop_map(|words| words.join(" "))
+}
+
+/// Strategy producing a key/value pair as it would appear in a file.
+fn option_pair() -> impl Strategy<Value = (String, String)> {
+    let word = || "[a-z][a-z0-9]{0,7}";
+    prop_oneof![
+        (0u8..=255, 0u8..=255, 1u8..=32).prop_map(|(a, b, prefix)| (
+            "address".to_string(),
+            format!("10.{}.{}.1/{}", a, b, prefix)
+        )),
+        (1u16..=0xffff).prop_map(|n| ("address".to_string(), format!("2001:db8::{:x}/64", n))),
+        (0u8..=255).prop_map(|a| ("gateway".to_string(), format!("10.0.{}.254", a))),
+        (68u16..=9216).prop_map(|n| ("mtu".to_string(), n.to_string())),
+        (1u16..=4094).prop_map(|n| ("vlan-id".to_string(), n.to_string())),
+        (1u16..=4094).prop_map(|n| ("bridge-access".to_string(), n.to_string())),
+        (any::<bool>(), any::<[u8; 6]>()).prop_map(|(ether, o)| {
+            let mac = format!(
+                "{:02X}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+                o[0], o[1], o[2], o[3], o[4], o[5]
+            );
+            let value = if ether { format!("ether {}", mac) } else { mac };
+            ("hwaddress".to_string(), value)
+        }),
+        prop::collection::vec(interface_name(), 1..5)
+            .prop_map(|ports| ("bridge-ports".to_string(), ports.join(" "))),
+        (
+            prop_oneof![
+                Just("pre-up"),
+                Just("up"),
+                Just("post-up"),
+                Just("post-down")
+            ],
+            option_value()
+        )
+            .prop_map(|(key, value)| (key.to_string(), value)),
+        (word(), option_value()).prop_map(|(key, value)| (format!("x-{}", key), value)),
+        (word(), option_value()).prop_map(|(key, value)| (format!("bridge-{}", key), value)),
+    ]
+}
+
+/// Strategy producing a mapping stanza.
+fn mapping() -> impl Strategy<Value = Mapping> {
+    (
+        "/usr/local/bin/[a-z-]{1,12}",
+        prop::collection::vec("[a-z0-9-]{1,8} [a-z0-9-]{1,8}", 0..3),
+    )
+        .prop_map(|(script, maps)| Mapping { script, maps })
+}
+
+impl Arbitrary for Family {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            4 => Just(Family::Inet),
+            3 => Just(Family::Inet6),
+            1 => Just(Family::IpX),
+            1 => Just(Family::Can),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Method {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            4 => prop::sample::select(vec![
+                Method::Loopback,
+                Method::Static,
+                Method::Manual,
+                Method::Dhcp,
+                Method::Auto,
+                Method::SixToFour,
+            ]),
+            1 => "[a-z][a-z0-9-]{0,10}"
+                .prop_filter("method must not look like a family", |s| {
+                    s.parse::<Family>().is_err()
+                })
+                .prop_map(Method::from),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for InterfaceOption {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        option_pair()
+            .prop_map(|(key, value)| InterfaceOption::from_key_value(&key, &value))
+            .boxed()
+    }
+}
+
+impl Arbitrary for Interface {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            interface_name(),
+            any::<bool>(),
+            prop::collection::vec(prop_oneof![Just("hotplug"), Just("auto")], 0..2),
+            any::<Option<Family>>(),
+            any::<Option<Method>>(),
+            prop::collection::vec(option_pair(), 0..8),
+            prop::option::weighted(0.1, mapping()),
+            prop::bool::weighted(0.1),
+            prop::option::weighted(
+                0.2,
+                (
+                    any::<Option<Method>>(),
+                    prop::collection::vec(option_pair(), 0..4),
+                ),
+            ),
+        )
+            .prop_map(
+                |(name, auto, allow, family, method, options, mapping, disabled, inet6)| {
+                    let mut builder = Interface::builder(name)
+                        .with_auto(auto)
+                        .with_disabled(disabled);
+                    for allow_type in allow {
+                        builder = builder.with_allow(allow_type);
+                    }
+                    // A dual-stack interface, only possible with a first
+                    // stanza of another family
+                    let inet6 = inet6.filter(|_| family == Some(Family::Inet));
+                    if let Some(family) = family {
+                        builder = builder.with_family(family);
+                    }
+                    if let Some(method) = method {
+                        builder = builder.with_method(method);
+                    }
+                    for (key, value) in options {
+                        builder = builder.with_option(key, value);
+                    }
+                    if let Some(mapping) = mapping {
+                        builder = builder.with_mapping(mapping);
+                    }
+                    if let Some((method, options)) = inet6 {
+                        builder = builder.with_family_stanza(FamilyStanza {
+                            family: Some(Family::Inet6),
+                            method,
+                            options: options
+                                .into_iter()
+                                .map(|(key, value)| (key.into(), value))
+                                .collect(),
+                        });
+                    }
+                    builder.build()
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for NetworkInterfaces {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            prop::collection::vec(any::<Interface>(), 0..12),
+            prop::collection::vec("#( [!-~]{1,10}){0,3}", 0..3),
+            prop::collection::vec("/etc/network/interfaces\\.d/[a-z*]{1,8}", 0..2),
+        )
+            .prop_map(|(ifaces, comments, sources)| {
+                let interfaces: HashMap<String, Interface> = ifaces
+                    .into_iter()
+                    .map(|iface| (iface.name.clone(), iface))
+                    .collect();
+                let sources = sources
+                    .into_iter()
+                    .map(|s| format!("source {}", s))
+                    .collect();
+                NetworkInterfaces::from_parts(
+                    interfaces,
+                    comments,
+                    sources,
+                    None,
+                    None,
+                    ParserOptions::default(),
+                )
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    proptest! {
+        #[test]
+        fn interface_display_round_trips(iface in any::<Interface>()) {
+            let parsed: Interface = iface.to_string().parse().unwrap();
+            prop_assert_eq!(parsed, iface);
+        }
+
+        #[test]
+        fn network_interfaces_display_round_trips(net_ifaces in any::<NetworkInterfaces>()) {
+            let rendered = net_ifaces.to_string();
+            let reparsed = NetworkInterfaces::parse_str(&rendered).unwrap();
+
+            prop_assert_eq!(reparsed.to_string(), rendered);
+            prop_assert_eq!(reparsed.len(), net_ifaces.len());
+            for (name, iface) in net_ifaces.iter() {
+                let parsed = reparsed.get_interface(name).unwrap();
+                prop_assert_eq!(parsed, iface);
+            }
+        }
+
+        #[test]
+        fn network_interfaces_check_roundtrip(net_ifaces in any::<NetworkInterfaces>()) {
+            prop_assert_eq!(net_ifaces.check_roundtrip(), Ok(()));
+        }
+
+        #[test]
+        fn parse_bytes_never_panics(
+            input in prop::collection::vec(
+                prop_oneof![
+                    any::<u8>(),
+                    prop::sample::select(b" \t\n#".to_vec()),
+                ],
+                0..256,
+            ),
+            words in prop::collection::vec(
+                prop::sample::select(vec![
+                    "auto", "allow-hotplug", "iface", "mapping", "source", "inet", "script",
+                    "map", "eth0", "\n", "\n    ", " ", "\t", "\u{a0}", "\u{feff}",
+                ]),
+                0..64,
+            ),
+        ) {
+            let options = ParserOptions {
+                max_input_size: Some(4096),
+                max_line_length: Some(64),
+                max_interfaces: Some(4),
+                max_options_per_interface: Some(4),
+                ..Default::default()
+            };
+            let parser = Parser::new().with_options(options);
+            let _ = parser.parse_bytes(&input);
+            let content = words.concat();
+            if let Ok((interfaces, comments, sources)) = parser.parse_bytes(content.as_bytes()) {
+                prop_assert!(Parser::new().parse_borrowed(&content).is_ok());
+                let net_ifaces = NetworkInterfaces::from_parts(
+                    interfaces,
+                    comments,
+                    sources,
+                    None,
+                    None,
+                    ParserOptions::default(),
+                );
+                prop_assert_eq!(net_ifaces.check_roundtrip(), Ok(()));
+            }
+        }
+
+        #[test]
+        fn option_round_trips(opt in any::<InterfaceOption>()) {
+            prop_assert_eq!(InterfaceOption::from_key_value(opt.key(), &opt.value()), opt);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Breakout ports.
+//!
+//! A breakout cable splits one physical port into several lanes. Cumulus
+//! Linux names the lanes of port `swp1` `swp1s0`, `swp1s1`, and so on. A lane
+//! name has these parts:
+//!
+//! | Part | Example | Rule |
+//! |------|---------|------|
+//! | prefix | `swp` | one or more ASCII letters |
+//! | port number | `1` | one or more digits |
+//! | separator | `s` | the letter `s` |
+//! | lane number | `0` | one or more digits |
+//!
+//! The parent port is the prefix followed by the port number. Other prefixes
+//! follow the same rule, so `eth2s1` is lane 1 of `eth2`. Names with anything
+//! after the lane number, such as the VLAN sub-interface `swp1s0.100`, are
+//! not lanes.
+//!
+//! The helpers look at names only, so they work whether or not the parent
+//! port itself is declared.
+//!
+//! # Examples
+//!
+//! Setting an option on every lane of a port with
+//! [`NetworkInterfaces::set_option_on`]:
+//!
+//! ```rust
+//! use interface_rs::breakout;
+//! use interface_rs::interface::Interface;
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let mut net_ifaces: NetworkInterfaces = ["swp1s0", "swp1s1", "swp2s0", "swp3"]
+//!     .into_iter()
+//!     .map(|name| Interface::builder(name).build())
+//!     .collect();
+//! assert_eq!(net_ifaces.set_option_on(breakout::in_group("swp1"), "mtu", "9216"), 2);
+//! ```
+
+use crate::interface::Interface;
+use crate::network_interfaces::NetworkInterfaces;
+use std::collections::BTreeMap;
+
+/// Splits a lane name into its parent port and lane number.
+fn split_lane(name: &str) -> Option<(&str, &str)> {
+    let prefix_len = name.find(|c: char| !c.is_ascii_alphabetic())?;
+    if prefix_len == 0 {
+        return None;
+    }
+    let rest = &name[prefix_len..];
+    let port_len = rest.find(|c: char| !c.is_ascii_digit())?;
+    if port_len == 0 {
+        return None;
+    }
+    let lane = rest[port_len..].strip_prefix('s')?;
+    if lane.is_empty() || !lane.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((&name[..prefix_len + port_len], lane))
+}
+
+/// Returns the parent port of a breakout lane.
+///
+/// # Arguments
+///
+/// * `name` - An interface name such as `swp1s2`.
+///
+/// # Returns
+///
+/// The parent port name, or `None` if `name` is not a breakout lane.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::breakout::parent_port;
+///
+/// assert_eq!(parent_port("swp1s2").as_deref(), Some("swp1"));
+/// assert_eq!(parent_port("swp1"), None);
+/// ```
+pub fn parent_port(name: &str) -> Option<String> {
+    split_lane(name).map(|(parent, _)| parent.to_string())
+}
+
+/// Returns `true` if `name` is a breakout lane such as `swp1s0`.
+pub fn is_breakout_lane(name: &str) -> bool {
+    split_lane(name).is_some()
+}
+
+/// Returns a selector matching the lanes of the breakout port `parent`.
+///
+/// The selector can be passed to
+/// [`NetworkInterfaces::set_option_on`] and
+/// [`NetworkInterfaces::remove_option_on`] to change a whole group at once.
+/// It does not match the parent port itself.
+pub fn in_group(parent: &str) -> impl Fn(&Interface) -> bool + '_ {
+    move |iface| split_lane(&iface.name).is_some_and(|(port, _)| port == parent)
+}
+
+impl NetworkInterfaces {
+    /// Groups the breakout lanes in the collection by parent port.
+    ///
+    /// # Returns
+    ///
+    /// A map from parent port name to its lanes. The lanes of each port are
+    /// in natural order; the ports themselves are ordered as strings, so
+    /// `swp10` comes before `swp2`. Interfaces that are not lanes are left
+    /// out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// for (port, lanes) in net_ifaces.breakout_groups() {
+    ///     println!("{} is broken out into {} lanes", port, lanes.len());
+    /// }
+    /// ```
+    pub fn breakout_groups(&self) -> BTreeMap<String, Vec<&Interface>> {
+        let mut groups: BTreeMap<String, Vec<&Interface>> = BTreeMap::new();
+        for (name, iface) in self.iter_by_name() {
+            if let Some(parent) = parent_port(name) {
+                groups.entry(parent).or_default().push(iface);
+            }
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_port() {
+        let cases = [
+            ("swp1s0", Some("swp1")),
+            ("swp1s3", Some("swp1")),
+            ("swp49s12", Some("swp49")),
+            ("eth2s1", Some("eth2")),
+            ("swp1", None),
+            ("swp1s", None),
+            ("swps0", None),
+            ("s1s0", Some("s1")),
+            ("1s0", None),
+            ("swp1s0.100", None),
+            ("swp1s0s1", None),
+            ("swp1t0", None),
+            ("bond0", None),
+            ("", None),
+        ];
+        for (name, parent) in cases {
+            assert_eq!(parent_port(name).as_deref(), parent, "{}", name);
+            assert_eq!(is_breakout_lane(name), parent.is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_breakout_groups() {
+        let mut net_ifaces: NetworkInterfaces = [
+            "swp10s1",
+            "swp2s0",
+            "swp10s0",
+            "swp2s1",
+            "swp3",
+            "swp2s0.100",
+        ]
+        .into_iter()
+        .map(|name| Interface::builder(name).build())
+        .collect();
+        let groups: Vec<(String, Vec<String>)> = net_ifaces
+            .breakout_groups()
+            .into_iter()
+            .map(|(port, lanes)| (port, lanes.iter().map(|i| i.name.clone()).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "swp10".to_string(),
+                    vec!["swp10s0".to_string(), "swp10s1".to_string()]
+                ),
+                (
+                    "swp2".to_string(),
+                    vec!["swp2s0".to_string(), "swp2s1".to_string()]
+                ),
+            ]
+        );
+
+        assert_eq!(net_ifaces.set_option_on(in_group("swp2"), "mtu", "9216"), 2);
+        let with_mtu: Vec<&str> = net_ifaces
+            .iter()
+            .filter(|(_, iface)| iface.options.iter().any(|(k, _)| k == "mtu"))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(with_mtu, vec!["swp2s0", "swp2s1"]);
+    }
+}
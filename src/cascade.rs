@@ -0,0 +1,396 @@
+//! Deleting an interface together with the references to it.
+//!
+//! [`NetworkInterfaces::delete_interface`] removes a single stanza, which
+//! leaves any bridge, bond, VLAN, or VRF member that names the interface
+//! pointing at nothing. [`NetworkInterfaces::delete_interface_cascade`]
+//! also scrubs those references:
+//!
+//! - the name is removed from `bridge-ports` and `bond-slaves`;
+//! - `vrf` options naming it are removed from the VRF's members;
+//! - VLANs on top of it, through `vlan-raw-device` or a `<name>.<vid>`
+//!   name, are flagged or deleted according to [`OrphanVlanPolicy`].
+//!   Deleted VLANs are cascaded in turn;
+//! - its aliases, named `<name>:<label>`, are deleted, since they only add
+//!   addresses to it;
+//! - VLAN interfaces whose `vlan-id` it carried through `bridge-access` are
+//!   kept and flagged.
+//!
+//! Every secondary change is recorded in the returned [`CascadeReport`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::cascade::CascadeAction;
+//! use interface_rs::interface::Interface;
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let mut net_ifaces = NetworkInterfaces::from_interfaces([
+//!     Interface::builder("swp3").build(),
+//!     Interface::builder("br_default").with_option("bridge-ports", "swp1 swp3").build(),
+//! ]);
+//! let report = net_ifaces.delete_interface_cascade("swp3");
+//! assert_eq!(report.removed[0].name, "swp3");
+//! assert_eq!(report.changes[0].action, CascadeAction::RemovedBridgePort);
+//! assert_eq!(
+//!     net_ifaces.get_interface("br_default").unwrap().get_option("bridge-ports"),
+//!     Some("swp1")
+//! );
+//! ```
+
+use crate::graph::{self, DependencyKind, VxlanIndex};
+use crate::interface::option::keys_match;
+use crate::interface::Interface;
+use crate::network_interfaces::{EmptyListPolicy, NetworkInterfaces};
+use std::fmt;
+
+/// What to do with a VLAN whose raw device is deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OrphanVlanPolicy {
+    /// Keep the VLAN and report it as
+    /// [`OrphanedVlan`](CascadeAction::OrphanedVlan).
+    #[default]
+    Flag,
+    /// Delete the VLAN and the references to it.
+    Delete,
+}
+
+/// Options for [`NetworkInterfaces::delete_interface_cascade_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CascadeOptions {
+    /// What to do with VLANs on top of a deleted interface.
+    pub orphan_vlans: OrphanVlanPolicy,
+    /// What to do with `bridge-ports` or `bond-slaves` once their last
+    /// member is removed.
+    pub when_empty: EmptyListPolicy,
+}
+
+/// A secondary change made by a cascading delete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CascadeAction {
+    /// The deleted interface was removed from `bridge-ports`.
+    RemovedBridgePort,
+    /// The deleted interface was removed from `bond-slaves`.
+    RemovedBondSlave,
+    /// The `vrf` option naming the deleted interface was removed.
+    RemovedVrf,
+    /// The VLAN was deleted because its raw device was.
+    DeletedVlan,
+    /// The VLAN was kept although its raw device was deleted.
+    OrphanedVlan,
+    /// The alias was deleted because its parent was.
+    DeletedAlias,
+    /// The VLAN interface was kept although the VXLAN carrying its VLAN id
+    /// was deleted.
+    UnmappedVlan,
+}
+
+/// One secondary change made by a cascading delete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadeChange {
+    /// The interface that was changed, deleted, or flagged.
+    pub interface: String,
+    /// The deleted interface it referred to.
+    pub reference: String,
+    /// What was done.
+    pub action: CascadeAction,
+}
+
+impl fmt::Display for CascadeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.action {
+            CascadeAction::RemovedBridgePort => "removed bridge port",
+            CascadeAction::RemovedBondSlave => "removed bond slave",
+            CascadeAction::RemovedVrf => "removed from VRF",
+            CascadeAction::DeletedVlan => "deleted with raw device",
+            CascadeAction::OrphanedVlan => "left without raw device",
+            CascadeAction::DeletedAlias => "deleted with parent",
+            CascadeAction::UnmappedVlan => "left without VXLAN",
+        };
+        write!(f, "{}: {} {}", self.interface, action, self.reference)
+    }
+}
+
+/// The result of [`NetworkInterfaces::delete_interface_cascade`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CascadeReport {
+    /// The removed interfaces: the requested one first, if it existed, then
+    /// any VLANs and aliases deleted with it.
+    pub removed: Vec<Interface>,
+    /// Every change made to the remaining interfaces, in the order made.
+    pub changes: Vec<CascadeChange>,
+}
+
+impl CascadeReport {
+    /// Returns the VLANs kept although their raw device was deleted.
+    pub fn orphaned_vlans(&self) -> impl Iterator<Item = &str> {
+        self.changes
+            .iter()
+            .filter(|change| change.action == CascadeAction::OrphanedVlan)
+            .map(|change| change.interface.as_str())
+    }
+}
+
+impl NetworkInterfaces {
+    /// Deletes an interface and scrubs the references to it.
+    ///
+    /// Equivalent to
+    /// [`delete_interface_cascade_with`](NetworkInterfaces::delete_interface_cascade_with)
+    /// with default [`CascadeOptions`]: orphaned VLANs are flagged, and
+    /// emptied member lists are set to `none`.
+    pub fn delete_interface_cascade(&mut self, name: &str) -> CascadeReport {
+        self.delete_interface_cascade_with(name, &CascadeOptions::default())
+    }
+
+    /// Deletes an interface and scrubs the references to it, as described
+    /// in the [`cascade`](crate::cascade) module.
+    ///
+    /// References are scrubbed even if no interface has that name, for
+    /// example because it is defined in a sourced file; the report then has
+    /// no removed interfaces.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface to delete.
+    /// * `options` - See [`CascadeOptions`].
+    ///
+    /// # Returns
+    ///
+    /// A [`CascadeReport`] listing the removed interfaces and every
+    /// secondary change.
+    pub fn delete_interface_cascade_with(
+        &mut self,
+        name: &str,
+        options: &CascadeOptions,
+    ) -> CascadeReport {
+        let strict_keys = self.parser_options().strict_keys;
+        let mut report = CascadeReport::default();
+        let mut pending = vec![name.to_string()];
+        while let Some(name) = pending.pop() {
+            // Collect dependents first: a VXLAN is only indexed while it is defined
+            let vxlans = VxlanIndex::new(self.iter_by_name().map(|(_, iface)| iface), strict_keys);
+            let mut dependents: Vec<(String, DependencyKind)> = Vec::new();
+            for (other, iface) in self.iter_by_name() {
+                for dep in graph::dependencies(iface, &vxlans, strict_keys) {
+                    let entry = (other.clone(), dep.kind);
+                    if dep.to == name && !dependents.contains(&entry) {
+                        dependents.push(entry);
+                    }
+                }
+            }
+            report.removed.extend(self.delete_interface(&name));
+
+            for (other, kind) in dependents {
+                let Some(iface) = self.get_interface_mut(&other) else {
+                    continue;
+                };
+                let action = match kind {
+                    DependencyKind::BridgePort => {
+                        remove_member(iface, "bridge-ports", &name, options, strict_keys);
+                        CascadeAction::RemovedBridgePort
+                    }
+                    DependencyKind::BondSlave => {
+                        remove_member(iface, "bond-slaves", &name, options, strict_keys);
+                        CascadeAction::RemovedBondSlave
+                    }
+                    DependencyKind::VrfMember => {
+                        iface.options.retain(|(key, value)| {
+                            !keys_match(key, "vrf", strict_keys) || value.trim() != name
+                        });
+                        CascadeAction::RemovedVrf
+                    }
+                    DependencyKind::AliasParent => {
+                        pending.push(other.clone());
+                        CascadeAction::DeletedAlias
+                    }
+                    DependencyKind::VlanRawDevice => match options.orphan_vlans {
+                        OrphanVlanPolicy::Flag => CascadeAction::OrphanedVlan,
+                        OrphanVlanPolicy::Delete => {
+                            pending.push(other.clone());
+                            CascadeAction::DeletedVlan
+                        }
+                    },
+                    DependencyKind::VxlanAccess => CascadeAction::UnmappedVlan,
+                };
+                report.changes.push(CascadeChange {
+                    interface: other.clone(),
+                    reference: name.clone(),
+                    action,
+                });
+            }
+        }
+        report
+    }
+}
+
+/// Removes `member` from every `key` list of an interface, applying the
+/// empty-list policy of `options`.
+fn remove_member(
+    iface: &mut Interface,
+    key: &str,
+    member: &str,
+    options: &CascadeOptions,
+    strict_keys: bool,
+) {
+    let removes = options.when_empty.removes(iface, key, strict_keys);
+    iface.options.retain_mut(|(k, value)| {
+        if !keys_match(k, key, strict_keys) {
+            return true;
+        }
+        let remaining: Vec<&str> = value
+            .split_whitespace()
+            .filter(|m| *m != member && *m != "none")
+            .collect();
+        if !remaining.is_empty() {
+            *value = remaining.join(" ");
+            return true;
+        }
+        if removes {
+            return false;
+        }
+        *value = "none".to_string();
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topology() -> NetworkInterfaces {
+        NetworkInterfaces::from_interfaces([
+            Interface::builder("swp1").build(),
+            Interface::builder("swp3")
+                .with_option("vrf", "mgmt")
+                .build(),
+            Interface::builder("swp3.100").build(),
+            Interface::builder("vlan200")
+                .with_option("vlan-raw-device", "swp3")
+                .with_option("vlan-id", "200")
+                .build(),
+            Interface::builder("br_default")
+                .with_option("bridge-ports", "swp1 swp3 swp3.100")
+                .build(),
+            Interface::builder("bond0")
+                .with_option("bond-slaves", "swp3")
+                .build(),
+            Interface::builder("mgmt")
+                .with_option("vrf-table", "auto")
+                .build(),
+            Interface::builder("eth0")
+                .with_option("vrf", "mgmt")
+                .build(),
+        ])
+    }
+
+    fn option<'a>(net_ifaces: &'a NetworkInterfaces, name: &str, key: &str) -> Option<&'a str> {
+        net_ifaces.get_interface(name)?.get_option(key)
+    }
+
+    #[test]
+    fn test_cascade_flags_orphaned_vlans() {
+        let mut net_ifaces = topology();
+        let report = net_ifaces.delete_interface_cascade("swp3");
+
+        let removed: Vec<&str> = report.removed.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(removed, vec!["swp3"]);
+        let changes: Vec<String> = report.changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "bond0: removed bond slave swp3",
+                "br_default: removed bridge port swp3",
+                "swp3.100: left without raw device swp3",
+                "vlan200: left without raw device swp3",
+            ]
+        );
+        assert_eq!(
+            report.orphaned_vlans().collect::<Vec<_>>(),
+            vec!["swp3.100", "vlan200"]
+        );
+        assert_eq!(option(&net_ifaces, "bond0", "bond-slaves"), Some("none"));
+        assert_eq!(
+            option(&net_ifaces, "br_default", "bridge-ports"),
+            Some("swp1 swp3.100")
+        );
+        assert!(net_ifaces.get_interface("vlan200").is_some());
+    }
+
+    #[test]
+    fn test_cascade_deletes_orphaned_vlans() {
+        let mut net_ifaces = topology();
+        let options = CascadeOptions {
+            orphan_vlans: OrphanVlanPolicy::Delete,
+            when_empty: EmptyListPolicy::Remove,
+        };
+        let report = net_ifaces.delete_interface_cascade_with("swp3", &options);
+
+        let mut removed: Vec<&str> = report.removed.iter().map(|i| i.name.as_str()).collect();
+        removed.sort();
+        assert_eq!(removed, vec!["swp3", "swp3.100", "vlan200"]);
+        assert!(report.changes.contains(&CascadeChange {
+            interface: "br_default".to_string(),
+            reference: "swp3.100".to_string(),
+            action: CascadeAction::RemovedBridgePort,
+        }));
+        assert_eq!(report.orphaned_vlans().count(), 0);
+        assert_eq!(
+            option(&net_ifaces, "br_default", "bridge-ports"),
+            Some("swp1")
+        );
+        // The list that makes bond0 a bond is kept
+        assert_eq!(option(&net_ifaces, "bond0", "bond-slaves"), Some("none"));
+        assert_eq!(net_ifaces.len(), 5);
+    }
+
+    #[test]
+    fn test_cascade_flags_unmapped_vlans() {
+        let mut net_ifaces = NetworkInterfaces::from_interfaces([
+            Interface::builder("vni200")
+                .with_option("vxlan-id", "10200")
+                .with_option("bridge-access", "200")
+                .build(),
+            Interface::builder("br_default")
+                .with_option("bridge-ports", "vni200")
+                .build(),
+            Interface::builder("vlan200")
+                .with_option("vlan-raw-device", "br_default")
+                .with_option("vlan-id", "200")
+                .build(),
+        ]);
+        let report = net_ifaces.delete_interface_cascade("vni200");
+
+        let changes: Vec<String> = report.changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "br_default: removed bridge port vni200",
+                "vlan200: left without VXLAN vni200",
+            ]
+        );
+        assert_eq!(option(&net_ifaces, "vlan200", "vlan-id"), Some("200"));
+    }
+
+    #[test]
+    fn test_cascade_vrf_members() {
+        let mut net_ifaces = topology();
+        let report = net_ifaces.delete_interface_cascade("mgmt");
+
+        assert_eq!(report.removed.len(), 1);
+        let members: Vec<&str> = report
+            .changes
+            .iter()
+            .filter(|c| c.action == CascadeAction::RemovedVrf)
+            .map(|c| c.interface.as_str())
+            .collect();
+        assert_eq!(members, vec!["eth0", "swp3"]);
+        assert_eq!(option(&net_ifaces, "eth0", "vrf"), None);
+        assert_eq!(option(&net_ifaces, "swp3", "vrf"), None);
+
+        // References to an undefined interface are scrubbed too
+        let report = net_ifaces.delete_interface_cascade("swp1");
+        assert_eq!(report.changes.len(), 1);
+        let report = net_ifaces.delete_interface_cascade("swp9");
+        assert!(report.removed.is_empty());
+        assert!(report.changes.is_empty());
+    }
+}
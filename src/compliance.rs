@@ -0,0 +1,313 @@
+//! Compliance checks of a configuration against a desired specification.
+//!
+//! [`NetworkInterfaces::check_compliance`] compares the interface in the file
+//! with the name of a desired [`Interface`] and reports whether it is
+//! missing, compliant, or non-compliant, with every deviation as a
+//! [`FieldDiff`]. [`NetworkInterfaces::check_collection_compliance`] does the
+//! same for every interface of a desired collection and collects the results
+//! in a [`ComplianceReport`].
+//!
+//! Differences the specification does not ask about are *extras*: an option
+//! key the desired interface does not set at all, or a family, method, or
+//! mapping the file sets where the desired interface leaves it unset. By
+//! default extras are reported but do not make an interface non-compliant;
+//! with [`ComplianceOptions::strict`] they do. Every other difference is a
+//! violation.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::compliance::ComplianceResult;
+//! use interface_rs::{Interface, NetworkInterfaces};
+//!
+//! let file = NetworkInterfaces::from_interfaces([Interface::builder("swp1")
+//!     .with_option("mtu", "9216")
+//!     .with_option("link-speed", "10000")
+//!     .build()]);
+//! let desired = Interface::builder("swp1").with_option("mtu", "9216").build();
+//! match file.check_compliance(&desired) {
+//!     ComplianceResult::Compliant { extra } => {
+//!         assert_eq!(extra[0].to_string(), "link-speed: unexpected 10000");
+//!     }
+//!     other => panic!("unexpected result: {:?}", other),
+//! }
+//! ```
+
+use crate::helper::sort::natural;
+use crate::interface::{FieldDiff, Interface};
+use crate::network_interfaces::NetworkInterfaces;
+use std::fmt;
+
+/// Options for compliance checks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComplianceOptions {
+    /// Count extras, i.e. settings the specification leaves unset, as
+    /// violations. Defaults to `false`.
+    pub strict: bool,
+}
+
+/// The compliance of a single interface with its specification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComplianceResult {
+    /// The interface is not defined in the file.
+    Missing,
+    /// The interface matches the specification.
+    Compliant {
+        /// What the file sets beyond the specification; always empty in
+        /// strict mode.
+        extra: Vec<FieldDiff>,
+    },
+    /// The interface deviates from the specification.
+    NonCompliant {
+        /// The deviations from the specification.
+        violations: Vec<FieldDiff>,
+        /// What the file sets beyond the specification; always empty in
+        /// strict mode, where these count as violations.
+        extra: Vec<FieldDiff>,
+    },
+}
+
+impl ComplianceResult {
+    /// Returns `true` if the result is `Compliant`.
+    pub fn is_compliant(&self) -> bool {
+        matches!(self, ComplianceResult::Compliant { .. })
+    }
+}
+
+impl fmt::Display for ComplianceResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |diffs: &[FieldDiff]| {
+            diffs
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join("; ")
+        };
+        match self {
+            ComplianceResult::Missing => write!(f, "missing"),
+            ComplianceResult::Compliant { extra } if extra.is_empty() => write!(f, "compliant"),
+            ComplianceResult::Compliant { extra } => {
+                write!(f, "compliant (extra: {})", join(extra))
+            }
+            ComplianceResult::NonCompliant { violations, extra } if extra.is_empty() => {
+                write!(f, "non-compliant: {}", join(violations))
+            }
+            ComplianceResult::NonCompliant { violations, extra } => write!(
+                f,
+                "non-compliant: {} (extra: {})",
+                join(violations),
+                join(extra)
+            ),
+        }
+    }
+}
+
+/// The compliance of a collection with a desired collection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComplianceReport {
+    /// The result for each desired interface, ordered by interface name.
+    pub results: Vec<(String, ComplianceResult)>,
+}
+
+impl ComplianceReport {
+    /// Returns `true` if every desired interface is compliant.
+    pub fn is_compliant(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_compliant())
+    }
+
+    /// Returns the result for the interface named `name`, if it was checked.
+    pub fn get(&self, name: &str) -> Option<&ComplianceResult> {
+        self.results
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, result)| result)
+    }
+
+    /// Returns the number of compliant interfaces.
+    pub fn compliant(&self) -> usize {
+        self.count(|r| matches!(r, ComplianceResult::Compliant { .. }))
+    }
+
+    /// Returns the number of non-compliant interfaces.
+    pub fn non_compliant(&self) -> usize {
+        self.count(|r| matches!(r, ComplianceResult::NonCompliant { .. }))
+    }
+
+    /// Returns the number of missing interfaces.
+    pub fn missing(&self) -> usize {
+        self.count(|r| matches!(r, ComplianceResult::Missing))
+    }
+
+    fn count(&self, pred: impl Fn(&ComplianceResult) -> bool) -> usize {
+        self.results.iter().filter(|(_, r)| pred(r)).count()
+    }
+}
+
+impl fmt::Display for ComplianceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, result) in &self.results {
+            writeln!(f, "{}: {}", name, result)?;
+        }
+        writeln!(
+            f,
+            "{} compliant, {} non-compliant, {} missing",
+            self.compliant(),
+            self.non_compliant(),
+            self.missing()
+        )
+    }
+}
+
+impl NetworkInterfaces {
+    /// Checks the interface named like `desired` against it, counting extras
+    /// as compliant.
+    ///
+    /// See [`check_compliance_with`](Self::check_compliance_with).
+    pub fn check_compliance(&self, desired: &Interface) -> ComplianceResult {
+        self.check_compliance_with(desired, &ComplianceOptions::default())
+    }
+
+    /// Checks the interface named like `desired` against it.
+    ///
+    /// See the [module documentation](crate::compliance) for what counts as
+    /// an extra.
+    ///
+    /// # Arguments
+    ///
+    /// * `desired` - The specification of the interface.
+    /// * `options` - Whether extras count as violations.
+    ///
+    /// # Returns
+    ///
+    /// `Missing` if no interface has that name, otherwise `Compliant` or
+    /// `NonCompliant` with the deviations as found by [`Interface::diff`].
+    pub fn check_compliance_with(
+        &self,
+        desired: &Interface,
+        options: &ComplianceOptions,
+    ) -> ComplianceResult {
+        let Some(actual) = self.get_interface(&desired.name) else {
+            return ComplianceResult::Missing;
+        };
+        let (extra, violations): (Vec<_>, Vec<_>) = desired
+            .diff(actual)
+            .into_iter()
+            .partition(|diff| !options.strict && is_extra(diff));
+        if violations.is_empty() {
+            ComplianceResult::Compliant { extra }
+        } else {
+            ComplianceResult::NonCompliant { violations, extra }
+        }
+    }
+
+    /// Checks every interface of `desired` against this collection.
+    ///
+    /// Interfaces defined here but not in `desired` are not reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `desired` - The specification of the interfaces.
+    /// * `options` - Whether extras count as violations.
+    ///
+    /// # Returns
+    ///
+    /// A `ComplianceReport` with one result per desired interface.
+    pub fn check_collection_compliance(
+        &self,
+        desired: &NetworkInterfaces,
+        options: &ComplianceOptions,
+    ) -> ComplianceReport {
+        let mut desired: Vec<&Interface> = desired.iter().map(|(_, iface)| iface).collect();
+        desired.sort_by(|a, b| natural(&a.name, &b.name));
+        let results = desired
+            .into_iter()
+            .map(|iface| {
+                let result = self.check_compliance_with(iface, options);
+                (iface.name.clone(), result)
+            })
+            .collect();
+        ComplianceReport { results }
+    }
+}
+
+/// Returns `true` if `diff` is about something the specification leaves
+/// unset.
+fn is_extra(diff: &FieldDiff) -> bool {
+    match diff {
+        FieldDiff::Option { expected, .. } => expected.is_empty(),
+        FieldDiff::Family { expected, .. } => expected.is_none(),
+        FieldDiff::Method { expected, .. } => expected.is_none(),
+        FieldDiff::Mapping { expected, .. } => expected.is_none(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compliance() {
+        let file = NetworkInterfaces::from_interfaces([
+            Interface::builder("swp1")
+                .with_auto(true)
+                .with_option("mtu", "9216")
+                .with_option("link-speed", "10000")
+                .build(),
+            Interface::builder("swp2")
+                .with_method("manual")
+                .with_option("mtu", "1500")
+                .build(),
+        ]);
+        let desired = NetworkInterfaces::from_interfaces([
+            Interface::builder("swp1")
+                .with_auto(true)
+                .with_option("mtu", "9216")
+                .build(),
+            Interface::builder("swp2")
+                .with_option("mtu", "9216")
+                .build(),
+            Interface::builder("swp10").build(),
+        ]);
+
+        let report = file.check_collection_compliance(&desired, &ComplianceOptions::default());
+        assert_eq!(
+            report.to_string(),
+            "swp1: compliant (extra: link-speed: unexpected 10000)\n\
+             swp2: non-compliant: mtu: expected 9216, found 1500 \
+             (extra: method: expected none, found manual)\n\
+             swp10: missing\n\
+             1 compliant, 1 non-compliant, 1 missing\n"
+        );
+        assert!(!report.is_compliant());
+
+        let strict = ComplianceOptions { strict: true };
+        let swp1 = desired.get_interface("swp1").unwrap();
+        match file.check_compliance_with(swp1, &strict) {
+            ComplianceResult::NonCompliant { violations, extra } => {
+                assert_eq!(
+                    violations,
+                    vec![FieldDiff::Option {
+                        key: "link-speed".to_string(),
+                        expected: Vec::new(),
+                        actual: vec!["10000".to_string()],
+                    }]
+                );
+                assert!(extra.is_empty());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // A missing option is a violation even in lenient mode
+        let desired = Interface::builder("swp1")
+            .with_auto(true)
+            .with_option("mtu", "9216")
+            .with_option("link-speed", "10000")
+            .with_option("link-autoneg", "off")
+            .build();
+        assert_eq!(
+            file.check_compliance(&desired).to_string(),
+            "non-compliant: link-autoneg: missing, expected off"
+        );
+    }
+}
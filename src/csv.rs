@@ -0,0 +1,135 @@
+//! CSV export of interface summaries.
+//!
+//! [`NetworkInterfaces::to_csv`] writes one row per interface with the
+//! columns in [`HEADER`]. Multi-valued columns (`addresses`, `gateway`) are
+//! joined with `;`, and `master` is the bridge or bond that lists the
+//! interface as a port or slave. Fields containing commas, quotes, or line
+//! breaks are quoted as described in RFC 4180; rows end with `\n`.
+//!
+//! For streaming writers, [`HEADER`] and [`Interface::to_csv_row`] can be
+//! used directly.
+
+use crate::graph;
+use crate::interface::Interface;
+use crate::network_interfaces::NetworkInterfaces;
+use std::borrow::Cow;
+
+/// The header row written by [`NetworkInterfaces::to_csv`], without a line ending.
+pub const HEADER: &str = "name,kind,family,method,addresses,gateway,mtu,vrf,master,auto";
+
+impl Interface {
+    /// Renders a CSV row summarizing this interface, without a line ending.
+    ///
+    /// The columns are those of [`HEADER`]. The `master` column cannot be
+    /// derived from the interface alone, so it is passed in.
+    ///
+    /// # Arguments
+    ///
+    /// * `master` - The bridge or bond this interface is a member of, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, Interface};
+    ///
+    /// let iface = Interface::builder("swp1")
+    ///     .with_auto(true)
+    ///     .with_family(Family::Inet)
+    ///     .with_method("static")
+    ///     .with_option("address", "10.0.0.1/24")
+    ///     .with_option("address", "10.0.1.1/24")
+    ///     .build();
+    /// assert_eq!(
+    ///     iface.to_csv_row(Some("br0")),
+    ///     "swp1,physical,inet,static,10.0.0.1/24;10.0.1.1/24,,,,br0,true"
+    /// );
+    /// ```
+    pub fn to_csv_row(&self, master: Option<&str>) -> String {
+        let joined = |key| self.get_options(key).collect::<Vec<_>>().join(";");
+        let family = self.family.as_ref().map(ToString::to_string);
+        let fields = [
+            Cow::from(self.name.as_str()),
+            Cow::from(self.kind().as_str()),
+            Cow::from(family.unwrap_or_default()),
+            Cow::from(self.method_str().unwrap_or_default()),
+            Cow::from(joined("address")),
+            Cow::from(joined("gateway")),
+            Cow::from(self.get_option("mtu").unwrap_or_default()),
+            Cow::from(self.get_option("vrf").unwrap_or_default()),
+            Cow::from(master.unwrap_or_default()),
+            Cow::from(if self.auto { "true" } else { "false" }),
+        ];
+        fields
+            .iter()
+            .map(|field| escape(field))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl NetworkInterfaces {
+    /// Renders a CSV summary of the collection, one row per interface after
+    /// the [`HEADER`] row.
+    ///
+    /// Rows follow the configured [`SortOrder`](crate::SortOrder). See the
+    /// [module documentation](crate::csv) for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// std::fs::write("interfaces.csv", net_ifaces.to_csv()).unwrap();
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let strict_keys = self.parser_options().strict_keys;
+        let masters = graph::masters(self.iter().map(|(_, iface)| iface), strict_keys);
+        let mut csv = String::from(HEADER);
+        csv.push('\n');
+        for iface in self.sorted_iter() {
+            let master = masters.get(&iface.name).copied();
+            csv.push_str(&iface.to_csv_row(master));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quotes a field if it contains a comma, quote, or line break.
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_golden() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let net_ifaces = NetworkInterfaces::load(format!("{}/tests/interfaces", dir)).unwrap();
+        let golden = std::fs::read_to_string(format!("{}/tests/interfaces.csv", dir)).unwrap();
+        assert_eq!(net_ifaces.to_csv(), golden);
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a,b"), "\"a,b\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape("two\nlines"), "\"two\nlines\"");
+
+        let iface = Interface::builder("odd,name")
+            .with_option("vrf", "\"quoted\"")
+            .build();
+        assert_eq!(
+            iface.to_csv_row(None),
+            "\"odd,name\",physical,,,,,,\"\"\"quoted\"\"\",,false"
+        );
+    }
+}
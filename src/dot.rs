@@ -0,0 +1,137 @@
+//! Graphviz DOT export of the dependency graph.
+
+use crate::graph::{self, DependencyKind, VxlanIndex};
+use crate::interface::{Interface, InterfaceKind};
+use crate::network_interfaces::NetworkInterfaces;
+use std::fmt::Write;
+
+impl NetworkInterfaces {
+    /// Renders the interfaces and the dependencies between them as a
+    /// Graphviz DOT digraph.
+    ///
+    /// Every interface is a node, shaped and colored by its
+    /// [`kind`](Interface::kind) and labeled with its name, `vlan-id`,
+    /// `vxlan-id`, `bridge-access`, and `mtu` where set. Edges point from an
+    /// interface to what it depends on:
+    ///
+    /// | Edge | Style |
+    /// |------|-------|
+    /// | bridge → port (`bridge-ports`) | solid |
+    /// | bond → slave (`bond-slaves`) | bold |
+    /// | VLAN → raw device (`vlan-raw-device` or name) | dashed |
+    /// | member → VRF (`vrf`) | dotted |
+    /// | VLAN → VXLAN with its `vlan-id` as `bridge-access` | dashed, blue |
+    ///
+    /// Ports, slaves, and devices that are referenced but not defined appear
+    /// as plain nodes. Nodes and edges are written in natural order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// std::fs::write("topology.dot", net_ifaces.to_dot()).unwrap();
+    /// // dot -Tsvg topology.dot -o topology.svg
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let strict_keys = self.parser_options().strict_keys;
+        let mut dot =
+            String::from("digraph interfaces {\n    rankdir=LR;\n    node [style=filled];\n");
+        for (name, iface) in self.iter_by_name() {
+            let (shape, color) = node_style(iface.kind());
+            writeln!(
+                dot,
+                "    {} [label={}, shape={}, fillcolor={}];",
+                quote(name),
+                quote(&label(iface)),
+                shape,
+                color
+            )
+            .unwrap();
+        }
+        let vxlans = VxlanIndex::new(self.iter_by_name().map(|(_, iface)| iface), strict_keys);
+        for (name, iface) in self.iter_by_name() {
+            for dep in graph::dependencies(iface, &vxlans, strict_keys) {
+                let style = match dep.kind {
+                    DependencyKind::BridgePort => "solid",
+                    DependencyKind::BondSlave => "bold",
+                    DependencyKind::VlanRawDevice => "dashed",
+                    DependencyKind::VrfMember => "dotted",
+                    DependencyKind::AliasParent => "tapered",
+                    DependencyKind::VxlanAccess => "dashed, color=blue",
+                };
+                writeln!(
+                    dot,
+                    "    {} -> {} [style={}];",
+                    quote(name),
+                    quote(&dep.to),
+                    style
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Returns the node shape and fill color for an interface kind.
+fn node_style(kind: InterfaceKind) -> (&'static str, &'static str) {
+    match kind {
+        InterfaceKind::Loopback => ("circle", "gray90"),
+        InterfaceKind::Vrf => ("hexagon", "gold"),
+        InterfaceKind::Bridge => ("box3d", "lightblue"),
+        InterfaceKind::Bond => ("octagon", "palegreen"),
+        InterfaceKind::Vxlan => ("cds", "plum"),
+        InterfaceKind::Vlan => ("ellipse", "lightsalmon"),
+        InterfaceKind::Alias => ("note", "lightyellow"),
+        InterfaceKind::Physical => ("box", "white"),
+    }
+}
+
+/// Builds the multi-line node label for an interface.
+fn label(iface: &Interface) -> String {
+    let mut lines = vec![iface.name.clone()];
+    let attributes = [
+        ("vlan-id", "VLAN"),
+        ("vxlan-id", "VNI"),
+        ("bridge-access", "access VLAN"),
+        ("mtu", "MTU"),
+    ];
+    for (key, caption) in attributes {
+        if let Some(value) = iface.get_option(key) {
+            lines.push(format!("{} {}", caption, value));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Quotes a DOT identifier, escaping quotes and backslashes and turning line
+/// breaks into `\n` escapes.
+fn quote(id: &str) -> String {
+    let escaped = id
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_golden() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let net_ifaces = NetworkInterfaces::load(format!("{}/tests/evpn/interfaces", dir)).unwrap();
+        let golden = std::fs::read_to_string(format!("{}/tests/evpn/interfaces.dot", dir)).unwrap();
+        assert_eq!(net_ifaces.to_dot(), golden);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("eth0"), "\"eth0\"");
+        assert_eq!(quote("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+}
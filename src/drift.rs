@@ -0,0 +1,426 @@
+//! Comparison of declared configuration against running state.
+//!
+//! [`NetworkInterfaces::drift`] compares a loaded `interfaces(5)` file with a
+//! view of the running system, typically built with
+//! `NetworkInterfaces::from_ip_json` (`json` feature), and reports every
+//! difference as a [`DriftIssue`]. Like validation, it never modifies
+//! anything.
+//!
+//! | Code | Severity | Meaning |
+//! |------|----------|---------|
+//! | `interface-missing` | error (warning if not `auto`) | declared, but not present |
+//! | `interface-undeclared` | warning | present, but not declared |
+//! | `address-missing` | error | a declared address is not assigned |
+//! | `address-unexpected` | warning | an assigned address is not declared |
+//! | `mtu-mismatch` | error | the declared `mtu` differs from the running one |
+//! | `master-mismatch` | error | the interface is in a different bridge, bond, or VRF |
+//!
+//! Interfaces configured with a method that obtains addresses on its own
+//! (`dhcp`, `auto`, `loopback`, and the like) are not checked for
+//! `address-unexpected`, since the addresses they are assigned cannot be
+//! known from the file. For a dual-stack interface this is decided per
+//! address family, by the method of the stanza for that family.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let declared = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+//! # #[cfg(feature = "json")]
+//! # {
+//! let output = std::process::Command::new("ip")
+//!     .args(["-d", "-j", "addr", "show"])
+//!     .output()
+//!     .unwrap();
+//! let running =
+//!     NetworkInterfaces::from_ip_json(&String::from_utf8_lossy(&output.stdout)).unwrap();
+//! let report = declared.drift(&running);
+//! print!("{}", report);
+//! # }
+//! ```
+
+use crate::graph;
+use crate::helper::sort::natural;
+use crate::interface::option::keys_match;
+use crate::interface::{Address, Family, Interface, Method};
+use crate::network_interfaces::NetworkInterfaces;
+use crate::validation::Severity;
+use std::fmt;
+
+/// A single difference between declared and running state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftIssue {
+    /// How serious the difference is.
+    pub severity: Severity,
+    /// A stable, machine-readable identifier for the check that fired
+    /// (e.g., `"mtu-mismatch"`).
+    pub code: &'static str,
+    /// The interface the difference applies to.
+    pub interface: String,
+    /// A human-readable description of the difference.
+    pub message: String,
+}
+
+impl fmt::Display for DriftIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}: {}",
+            self.severity, self.code, self.interface, self.message
+        )
+    }
+}
+
+/// The result of comparing declared configuration with running state.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DriftReport {
+    /// All differences, ordered by interface name.
+    pub issues: Vec<DriftIssue>,
+}
+
+impl DriftReport {
+    /// Returns `true` if no differences were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns an iterator over the `Error`-severity issues.
+    pub fn errors(&self) -> impl Iterator<Item = &DriftIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    /// Returns an iterator over the `Warning`-severity issues.
+    pub fn warnings(&self) -> impl Iterator<Item = &DriftIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+    }
+
+    fn push(&mut self, severity: Severity, code: &'static str, name: &str, message: String) {
+        self.issues.push(DriftIssue {
+            severity,
+            code,
+            interface: name.to_string(),
+            message,
+        });
+    }
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl NetworkInterfaces {
+    /// Compares this (declared) configuration with the running state.
+    ///
+    /// See the [module documentation](crate::drift) for the checks performed.
+    ///
+    /// # Arguments
+    ///
+    /// * `running` - The observed state, e.g. from
+    ///   `from_ip_json` (`json` feature).
+    pub fn drift(&self, running: &NetworkInterfaces) -> DriftReport {
+        let strict_keys = self.parser_options().strict_keys;
+        let declared_masters = graph::masters(self.iter().map(|(_, i)| i), strict_keys);
+        let running_masters = graph::masters(
+            running.iter().map(|(_, i)| i),
+            running.parser_options().strict_keys,
+        );
+
+        let mut report = DriftReport::default();
+        let mut names: Vec<&String> = self.iter().map(|(name, _)| name).collect();
+        names.extend(
+            running
+                .iter()
+                .map(|(name, _)| name)
+                .filter(|name| self.get_interface(name).is_none()),
+        );
+        names.sort_by(|a, b| natural(a, b));
+
+        for name in names {
+            match (self.get_interface(name), running.get_interface(name)) {
+                (Some(declared), Some(actual)) => {
+                    check_addresses(declared, actual, &mut report);
+                    check_mtu(declared, actual, &mut report);
+                    let masters = [
+                        (
+                            declared_masters.get(name).copied(),
+                            running_masters.get(name).copied(),
+                            "bridge or bond",
+                        ),
+                        (declared.get_option("vrf"), actual.get_option("vrf"), "VRF"),
+                    ];
+                    for (expected, found, what) in masters {
+                        if expected != found {
+                            report.push(
+                                Severity::Error,
+                                "master-mismatch",
+                                name,
+                                format!(
+                                    "{} is {}, expected {}",
+                                    what,
+                                    found.unwrap_or("none"),
+                                    expected.unwrap_or("none")
+                                ),
+                            );
+                        }
+                    }
+                }
+                (Some(declared), None) => {
+                    let severity = if declared.auto {
+                        Severity::Error
+                    } else {
+                        Severity::Warning
+                    };
+                    report.push(
+                        severity,
+                        "interface-missing",
+                        name,
+                        "Declared interface is not present".to_string(),
+                    );
+                }
+                (None, Some(_)) => report.push(
+                    Severity::Warning,
+                    "interface-undeclared",
+                    name,
+                    "Interface is present but not declared".to_string(),
+                ),
+                (None, None) => unreachable!("name comes from one of the collections"),
+            }
+        }
+        report
+    }
+}
+
+/// Reports declared addresses that are not assigned, and assigned addresses
+/// that are not declared unless the method assigns addresses dynamically.
+fn check_addresses(declared: &Interface, actual: &Interface, report: &mut DriftReport) {
+    let name = &declared.name;
+    let declared_options = declared.options.iter().chain(
+        declared
+            .family_stanzas
+            .iter()
+            .flat_map(|stanza| &stanza.options),
+    );
+    let expected: Vec<(&str, Option<Address>)> = declared_options
+        .filter(|(key, _)| keys_match(key, "address", false))
+        .map(|(_, a)| (a.as_str(), a.parse().ok()))
+        .collect();
+    let found: Vec<(&str, Option<Address>)> = actual
+        .get_options("address")
+        .map(|a| (a, a.parse().ok()))
+        .collect();
+
+    for (text, addr) in &expected {
+        if !found.iter().any(|(t, a)| addresses_match(text, addr, t, a)) {
+            report.push(
+                Severity::Error,
+                "address-missing",
+                name,
+                format!("Address {} is not assigned", text),
+            );
+        }
+    }
+    for (text, addr) in &found {
+        if method_for(declared, addr).is_some_and(is_dynamic) {
+            continue;
+        }
+        if !expected
+            .iter()
+            .any(|(t, a)| addresses_match(t, a, text, addr))
+        {
+            report.push(
+                Severity::Warning,
+                "address-unexpected",
+                name,
+                format!("Address {} is assigned but not declared", text),
+            );
+        }
+    }
+}
+
+/// Reports a declared `mtu` that differs from the running one.
+fn check_mtu(declared: &Interface, actual: &Interface, report: &mut DriftReport) {
+    let Some(expected) = declared.get_option("mtu") else {
+        return;
+    };
+    let found = actual.get_option("mtu");
+    if found != Some(expected) {
+        report.push(
+            Severity::Error,
+            "mtu-mismatch",
+            &declared.name,
+            format!(
+                "MTU is {}, expected {}",
+                found.unwrap_or("unknown"),
+                expected
+            ),
+        );
+    }
+}
+
+/// Returns the method of the stanza of `declared` for the family of `addr`,
+/// or of the first stanza if there is none for that family.
+fn method_for<'a>(declared: &'a Interface, addr: &Option<Address>) -> Option<&'a Method> {
+    let family = match addr {
+        Some(addr) if addr.ip.is_ipv6() => Family::Inet6,
+        Some(_) => Family::Inet,
+        None => return declared.method.as_ref(),
+    };
+    declared
+        .family_stanzas
+        .iter()
+        .find(|stanza| stanza.family.as_ref() == Some(&family))
+        .map_or(declared.method.as_ref(), |stanza| stanza.method.as_ref())
+}
+
+/// Returns `true` for methods that obtain addresses without them being
+/// listed in the stanza.
+fn is_dynamic(method: &Method) -> bool {
+    matches!(
+        method,
+        Method::Loopback
+            | Method::Dhcp
+            | Method::Bootp
+            | Method::Ppp
+            | Method::Wvdial
+            | Method::Ipv4ll
+            | Method::Auto
+            | Method::Ipv6ll
+            | Method::Dhcp6
+    )
+}
+
+/// Compares a declared and an assigned address semantically, so that
+/// `2001:DB8::1/64` matches `2001:db8:0::1/64`. A declared address
+/// without a prefix length matches any prefix length; unparseable
+/// addresses are compared as text.
+fn addresses_match(
+    declared_text: &str,
+    declared: &Option<Address>,
+    found_text: &str,
+    found: &Option<Address>,
+) -> bool {
+    match (declared, found) {
+        (Some(d), Some(f)) => d.ip == f.ip && (d.prefix.is_none() || d.prefix == f.prefix),
+        _ => declared_text == found_text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift_codes() {
+        let declared = NetworkInterfaces::from_interfaces(vec![
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1 swp2")
+                .build(),
+            Interface::builder("swp1").with_auto(true).build(),
+            Interface::builder("swp2").build(),
+            Interface::builder("swp3").with_auto(true).build(),
+            Interface::builder("eth0")
+                .with_method("static")
+                .with_option("address", "2001:DB8::1/64")
+                .with_option("address", "10.0.0.1")
+                .with_option("mtu", "9000")
+                .with_option("vrf", "mgmt")
+                .build(),
+        ]);
+        let running = NetworkInterfaces::from_interfaces(vec![
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1")
+                .build(),
+            Interface::builder("swp1").build(),
+            Interface::builder("eth0")
+                .with_option("address", "2001:db8:0::1/64")
+                .with_option("address", "10.0.0.1/24")
+                .with_option("address", "10.0.0.2/24")
+                .with_option("mtu", "1500")
+                .build(),
+            Interface::builder("eth9").build(),
+        ]);
+
+        let report = declared.drift(&running);
+        let issues: Vec<(&str, &str, Severity)> = report
+            .issues
+            .iter()
+            .map(|i| (i.interface.as_str(), i.code, i.severity))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("eth0", "address-unexpected", Severity::Warning),
+                ("eth0", "mtu-mismatch", Severity::Error),
+                ("eth0", "master-mismatch", Severity::Error),
+                ("eth9", "interface-undeclared", Severity::Warning),
+                ("swp2", "interface-missing", Severity::Warning),
+                ("swp3", "interface-missing", Severity::Error),
+            ]
+        );
+        assert_eq!(
+            report.issues[2].to_string(),
+            "error [master-mismatch] eth0: VRF is none, expected mgmt"
+        );
+        assert_eq!(report.errors().count(), 3);
+    }
+
+    #[test]
+    fn test_drift_dhcp_addresses() {
+        let declared = NetworkInterfaces::from_interfaces(vec![Interface::builder("eth0")
+            .with_method("dhcp")
+            .with_option("address", "192.0.2.10/24")
+            .build()]);
+        let running = NetworkInterfaces::from_interfaces(vec![Interface::builder("eth0")
+            .with_option("address", "192.168.0.15/24")
+            .build()]);
+        let report = declared.drift(&running);
+        let codes: Vec<&str> = report.issues.iter().map(|i| i.code).collect();
+        assert_eq!(codes, vec!["address-missing"]);
+
+        assert!(running.drift(&running).is_clean());
+
+        // Each address is checked against the stanza for its family
+        let dual_stack: Interface = "iface eth0 inet static\n    address 192.0.2.10/24\n\n\
+                                     iface eth0 inet6 auto\n"
+            .parse()
+            .unwrap();
+        let running = NetworkInterfaces::from_interfaces(vec![Interface::builder("eth0")
+            .with_option("address", "192.0.2.10/24")
+            .with_option("address", "2001:db8::5/64")
+            .with_option("address", "198.51.100.1/24")
+            .build()]);
+        let report = NetworkInterfaces::from_interfaces(vec![dual_stack]).drift(&running);
+        let issues: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            ["warning [address-unexpected] eth0: Address 198.51.100.1/24 is assigned but not declared"]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_drift_fixture() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let declared = NetworkInterfaces::load(format!("{}/tests/interfaces", dir)).unwrap();
+        let json = std::fs::read_to_string(format!("{}/tests/ip-addr.json", dir)).unwrap();
+        let running = NetworkInterfaces::from_ip_json(&json).unwrap();
+
+        let report = declared.drift(&running);
+        let issues: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            vec![
+                "error [interface-missing] swp10: Declared interface is not present",
+                "error [interface-missing] vni100100: Declared interface is not present",
+            ]
+        );
+    }
+}
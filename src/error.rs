@@ -1,11 +1,27 @@
+//! Error types returned by the crate.
+
+use crate::interface::{NameError, OptionParseError, OptionRangeError};
+use crate::validation::ValidationReport;
 use std::error::Error;
 use std::fmt;
 use std::io;
+use std::path::{Path, PathBuf};
 
 /// The main error type for the `NetworkInterfaces` library.
 ///
 /// This enum encapsulates all possible errors that can occur within the library.
+///
+/// Errors that relate to a particular file are wrapped in
+/// [`WithPath`](NetworkInterfacesError::WithPath), and I/O errors in
+/// [`WithOp`](NetworkInterfacesError::WithOp) naming the failed operation.
+/// Use [`inner`](NetworkInterfacesError::inner) to match on the underlying
+/// error regardless of any context, and [`path`](NetworkInterfacesError::path)
+/// or [`io_op`](NetworkInterfacesError::io_op) to retrieve the context.
+///
+/// To branch on the broad category of an error, use
+/// [`kind`](NetworkInterfacesError::kind) instead of matching variants.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum NetworkInterfacesError {
     /// An I/O error occurred.
     Io(io::Error),
@@ -13,12 +29,310 @@ pub enum NetworkInterfacesError {
     Parser(ParserError),
     /// An error occurred while parsing the `Family` enum.
     FamilyParse(FamilyParseError),
-    /// An error occurred while parsing the `Method` enum.
-    MethodParse(MethodParseError),
     /// The interfaces file has been modified on disk since it was last loaded.
     FileModified,
+    /// Interfaces could not be ordered by their dependencies because they
+    /// depend on each other. Holds the names forming the cycle.
+    DependencyCycle(Vec<String>),
+    /// Validation found `Error`-severity issues. Holds the full report,
+    /// including any warnings.
+    Validation(ValidationReport),
+    /// An interface with the given name already exists.
+    Conflict {
+        /// The name of the existing interface.
+        name: String,
+    },
+    /// The same interface name was given more than once.
+    DuplicateInterfaces {
+        /// The repeated names, each listed once in natural order.
+        names: Vec<String>,
+    },
+    /// An interface is defined in more than one file.
+    DuplicateDefinition {
+        /// The name of the interface.
+        name: String,
+        /// The file it was first defined in.
+        first: PathBuf,
+        /// The file defining it again.
+        second: PathBuf,
+    },
+    /// Several interfaces would be saved to the same file.
+    FileConflict {
+        /// The file the interfaces would share.
+        path: PathBuf,
+        /// The names of the interfaces, in natural order.
+        names: Vec<String>,
+    },
+    /// No interface with the given name exists.
+    NotFound {
+        /// The name that was looked up.
+        name: String,
+    },
+    /// The interface exists but is not of the kind the operation requires.
+    WrongKind {
+        /// The name of the interface.
+        name: String,
+        /// A description of the required kind (e.g., `"VLAN-aware bridge"`).
+        expected: &'static str,
+    },
+    /// Every VLAN id in the requested range is already in use.
+    NoFreeVlan {
+        /// The first id of the range.
+        start: u16,
+        /// The last id of the range.
+        end: u16,
+    },
+    /// An existing option value could not be parsed.
+    OptionParse(OptionParseError),
+    /// A value is outside the range allowed for an option.
+    OptionRange(OptionRangeError),
+    /// A string is not a valid interface name.
+    InvalidName(NameError),
+    /// The input exceeds a limit set in
+    /// [`ParserOptions`](crate::ParserOptions).
+    LimitExceeded(LimitExceeded),
+    /// Placeholders in option values could not be substituted.
+    Substitution(SubstitutionError),
+    /// The files were saved, but the hook set with
+    /// [`set_post_save_hook`](crate::NetworkInterfaces::set_post_save_hook)
+    /// failed. Holds the message returned by the hook.
+    PostSaveHook(String),
+    /// An error occurred while reading or writing JSON.
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    /// An error occurred while reading TOML.
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
     /// A catch-all for other errors.
     Other(String),
+    /// An error that occurred during a specific I/O operation.
+    WithOp {
+        /// The operation that failed.
+        op: IoOp,
+        /// The underlying error.
+        source: Box<NetworkInterfacesError>,
+    },
+    /// An error that occurred while working with a specific file.
+    WithPath {
+        /// The file the error relates to.
+        path: PathBuf,
+        /// The underlying error.
+        source: Box<NetworkInterfacesError>,
+    },
+}
+
+/// The file operation during which an I/O error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IoOp {
+    /// Reading file metadata, such as the modification time.
+    ReadMetadata,
+    /// Reading the contents of a file.
+    ReadFile,
+    /// Creating a temporary file to write to.
+    CreateTemp,
+    /// Creating or writing the contents of a file.
+    Write,
+    /// Flushing written data to disk.
+    Fsync,
+    /// Renaming a file into place.
+    Rename,
+    /// Removing a file.
+    Remove,
+    /// Creating a directory.
+    CreateDir,
+    /// Listing the entries of a directory.
+    ReadDir,
+    /// Copying a file to its backup.
+    Backup,
+}
+
+impl fmt::Display for IoOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op_str = match self {
+            IoOp::ReadMetadata => "read metadata",
+            IoOp::ReadFile => "read file",
+            IoOp::CreateTemp => "create temporary file",
+            IoOp::Write => "write",
+            IoOp::Fsync => "fsync",
+            IoOp::Rename => "rename",
+            IoOp::Remove => "remove file",
+            IoOp::CreateDir => "create directory",
+            IoOp::ReadDir => "read directory",
+            IoOp::Backup => "create backup",
+        };
+        write!(f, "{}", op_str)
+    }
+}
+
+/// Tags the error of an I/O result with the operation that failed.
+pub(crate) trait IoResultExt<T> {
+    fn op(self, op: IoOp) -> Result<T, NetworkInterfacesError>;
+}
+
+impl<T> IoResultExt<T> for io::Result<T> {
+    fn op(self, op: IoOp) -> Result<T, NetworkInterfacesError> {
+        self.map_err(|err| NetworkInterfacesError::WithOp {
+            op,
+            source: Box::new(NetworkInterfacesError::Io(err)),
+        })
+    }
+}
+
+/// The broad category of a [`NetworkInterfacesError`].
+///
+/// See [`NetworkInterfacesError::kind`] for how variants map to kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Reading or writing a file failed.
+    Io,
+    /// The input could not be parsed.
+    Parse,
+    /// The operation conflicts with existing state.
+    Conflict,
+    /// The file was modified on disk since it was loaded.
+    FileModified,
+    /// The configuration is semantically invalid.
+    Validation,
+    /// A requested item does not exist.
+    NotFound,
+    /// The input exceeds a configured limit.
+    LimitExceeded,
+    /// An argument does not fit the target of the operation.
+    InvalidInput,
+    /// Any other error.
+    Other,
+}
+
+impl NetworkInterfacesError {
+    /// Returns the broad category of the error.
+    ///
+    /// Context is looked through, so a
+    /// [`WithPath`](NetworkInterfacesError::WithPath) or
+    /// [`WithOp`](NetworkInterfacesError::WithOp) error has the kind of the
+    /// error it wraps. The mapping is:
+    ///
+    /// | Variant | Kind |
+    /// |---------|------|
+    /// | `Io` | [`ErrorKind::Io`] |
+    /// | `Parser`, `FamilyParse`, `OptionParse`, `Json`, `Toml` | [`ErrorKind::Parse`] |
+    /// | `FileModified` | [`ErrorKind::FileModified`] |
+    /// | `DependencyCycle`, `Validation` | [`ErrorKind::Validation`] |
+    /// | `Conflict`, `DuplicateInterfaces`, `DuplicateDefinition`, `FileConflict`, `NoFreeVlan` | [`ErrorKind::Conflict`] |
+    /// | `NotFound` | [`ErrorKind::NotFound`] |
+    /// | `LimitExceeded` | [`ErrorKind::LimitExceeded`] |
+    /// | `WrongKind`, `OptionRange`, `InvalidName`, `Substitution` | [`ErrorKind::InvalidInput`] |
+    /// | `PostSaveHook`, `Other` | [`ErrorKind::Other`] |
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::error::ErrorKind;
+    ///
+    /// let err = NetworkInterfaces::load("/nonexistent/interfaces").unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Io);
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            NetworkInterfacesError::Io(_) => ErrorKind::Io,
+            NetworkInterfacesError::Parser(_)
+            | NetworkInterfacesError::FamilyParse(_)
+            | NetworkInterfacesError::OptionParse(_) => ErrorKind::Parse,
+            NetworkInterfacesError::FileModified => ErrorKind::FileModified,
+            NetworkInterfacesError::DependencyCycle(_) | NetworkInterfacesError::Validation(_) => {
+                ErrorKind::Validation
+            }
+            NetworkInterfacesError::Conflict { .. }
+            | NetworkInterfacesError::DuplicateInterfaces { .. }
+            | NetworkInterfacesError::DuplicateDefinition { .. }
+            | NetworkInterfacesError::FileConflict { .. }
+            | NetworkInterfacesError::NoFreeVlan { .. } => ErrorKind::Conflict,
+            NetworkInterfacesError::NotFound { .. } => ErrorKind::NotFound,
+            NetworkInterfacesError::LimitExceeded(_) => ErrorKind::LimitExceeded,
+            NetworkInterfacesError::WrongKind { .. }
+            | NetworkInterfacesError::OptionRange(_)
+            | NetworkInterfacesError::InvalidName(_)
+            | NetworkInterfacesError::Substitution(_) => ErrorKind::InvalidInput,
+            #[cfg(feature = "json")]
+            NetworkInterfacesError::Json(_) => ErrorKind::Parse,
+            #[cfg(feature = "toml")]
+            NetworkInterfacesError::Toml(_) => ErrorKind::Parse,
+            NetworkInterfacesError::PostSaveHook(_) | NetworkInterfacesError::Other(_) => {
+                ErrorKind::Other
+            }
+            NetworkInterfacesError::WithOp { source, .. }
+            | NetworkInterfacesError::WithPath { source, .. } => source.kind(),
+        }
+    }
+
+    /// Returns the path of the file the error relates to, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            NetworkInterfacesError::WithPath { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the validation report carried by a
+    /// [`Validation`](NetworkInterfacesError::Validation) error.
+    pub fn validation_report(&self) -> Option<&ValidationReport> {
+        match self.inner() {
+            NetworkInterfacesError::Validation(report) => Some(report),
+            _ => None,
+        }
+    }
+
+    /// Returns the I/O operation that failed, if known.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::error::IoOp;
+    ///
+    /// let err = NetworkInterfaces::load("/nonexistent/interfaces").unwrap_err();
+    /// assert_eq!(err.io_op(), Some(IoOp::ReadMetadata));
+    /// ```
+    pub fn io_op(&self) -> Option<IoOp> {
+        match self {
+            NetworkInterfacesError::WithOp { op, .. } => Some(*op),
+            NetworkInterfacesError::WithPath { source, .. } => source.io_op(),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error, without any path or operation context.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::{NetworkInterfaces, NetworkInterfacesError};
+    ///
+    /// let err = NetworkInterfaces::load("/nonexistent/interfaces").unwrap_err();
+    /// assert!(matches!(err.inner(), NetworkInterfacesError::Io(_)));
+    /// assert_eq!(err.path().unwrap().to_str(), Some("/nonexistent/interfaces"));
+    /// ```
+    pub fn inner(&self) -> &NetworkInterfacesError {
+        match self {
+            NetworkInterfacesError::WithOp { source, .. }
+            | NetworkInterfacesError::WithPath { source, .. } => source.inner(),
+            err => err,
+        }
+    }
+
+    /// Attaches path context to the error, replacing any existing path.
+    pub(crate) fn with_path(self, path: &Path) -> Self {
+        let source = match self {
+            NetworkInterfacesError::WithPath { source, .. } => source,
+            err => Box::new(err),
+        };
+        NetworkInterfacesError::WithPath {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
 }
 
 impl fmt::Display for NetworkInterfacesError {
@@ -26,13 +340,65 @@ impl fmt::Display for NetworkInterfacesError {
         match self {
             NetworkInterfacesError::Io(err) => write!(f, "I/O error: {}", err),
             NetworkInterfacesError::Parser(err) => write!(f, "Parser error: {}", err),
+            #[cfg(feature = "json")]
+            NetworkInterfacesError::Json(err) => write!(f, "JSON error: {}", err),
+            #[cfg(feature = "toml")]
+            NetworkInterfacesError::Toml(err) => write!(f, "TOML error: {}", err),
             NetworkInterfacesError::FamilyParse(err) => write!(f, "Family parse error: {}", err),
-            NetworkInterfacesError::MethodParse(err) => write!(f, "Method parse error: {}", err),
             NetworkInterfacesError::FileModified => write!(
                 f,
                 "The interfaces file has been modified on disk since it was last loaded."
             ),
+            NetworkInterfacesError::DependencyCycle(names) => write!(
+                f,
+                "Dependency cycle between interfaces: {} -> {}",
+                names.join(" -> "),
+                names.first().map(String::as_str).unwrap_or_default()
+            ),
+            NetworkInterfacesError::Validation(report) => write!(
+                f,
+                "Validation failed with {} error(s)",
+                report.errors().count()
+            ),
+            NetworkInterfacesError::Conflict { name } => {
+                write!(f, "Interface {} already exists", name)
+            }
+            NetworkInterfacesError::DuplicateInterfaces { names } => {
+                write!(f, "Duplicate interfaces: {}", names.join(", "))
+            }
+            NetworkInterfacesError::DuplicateDefinition { name, first, second } => write!(
+                f,
+                "Interface {} is defined in both {} and {}",
+                name,
+                first.display(),
+                second.display()
+            ),
+            NetworkInterfacesError::FileConflict { path, names } => write!(
+                f,
+                "Interfaces {} would share the file {}",
+                names.join(", "),
+                path.display()
+            ),
+            NetworkInterfacesError::NotFound { name } => write!(f, "Interface {} not found", name),
+            NetworkInterfacesError::WrongKind { name, expected } => {
+                write!(f, "Interface {} is not a {}", name, expected)
+            }
+            NetworkInterfacesError::NoFreeVlan { start, end } => {
+                write!(f, "No free VLAN in range {}-{}", start, end)
+            }
+            NetworkInterfacesError::OptionParse(err) => write!(f, "{}", err),
+            NetworkInterfacesError::OptionRange(err) => write!(f, "{}", err),
+            NetworkInterfacesError::InvalidName(err) => write!(f, "{}", err),
+            NetworkInterfacesError::LimitExceeded(err) => write!(f, "{}", err),
+            NetworkInterfacesError::Substitution(err) => write!(f, "{}", err),
+            NetworkInterfacesError::PostSaveHook(msg) => {
+                write!(f, "Saved, but the post-save hook failed: {}", msg)
+            }
             NetworkInterfacesError::Other(msg) => write!(f, "Error: {}", msg),
+            NetworkInterfacesError::WithOp { op, source } => write!(f, "{} failed: {}", op, source),
+            NetworkInterfacesError::WithPath { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
         }
     }
 }
@@ -43,9 +409,29 @@ impl Error for NetworkInterfacesError {
             NetworkInterfacesError::Io(err) => Some(err),
             NetworkInterfacesError::Parser(err) => Some(err),
             NetworkInterfacesError::FamilyParse(err) => Some(err),
-            NetworkInterfacesError::MethodParse(err) => Some(err),
             NetworkInterfacesError::FileModified => None,
+            NetworkInterfacesError::DependencyCycle(_) => None,
+            NetworkInterfacesError::Validation(_) => None,
+            NetworkInterfacesError::Conflict { .. } => None,
+            NetworkInterfacesError::DuplicateInterfaces { .. } => None,
+            NetworkInterfacesError::DuplicateDefinition { .. } => None,
+            NetworkInterfacesError::FileConflict { .. } => None,
+            NetworkInterfacesError::NotFound { .. } => None,
+            NetworkInterfacesError::WrongKind { .. } => None,
+            NetworkInterfacesError::NoFreeVlan { .. } => None,
+            NetworkInterfacesError::OptionParse(err) => Some(err),
+            NetworkInterfacesError::OptionRange(err) => Some(err),
+            NetworkInterfacesError::InvalidName(err) => Some(err),
+            NetworkInterfacesError::LimitExceeded(err) => Some(err),
+            NetworkInterfacesError::Substitution(err) => Some(err),
+            NetworkInterfacesError::PostSaveHook(_) => None,
+            #[cfg(feature = "json")]
+            NetworkInterfacesError::Json(err) => Some(err),
+            #[cfg(feature = "toml")]
+            NetworkInterfacesError::Toml(err) => Some(err),
             NetworkInterfacesError::Other(_) => None,
+            NetworkInterfacesError::WithOp { source, .. }
+            | NetworkInterfacesError::WithPath { source, .. } => Some(source.as_ref()),
         }
     }
 }
@@ -57,45 +443,255 @@ impl From<io::Error> for NetworkInterfacesError {
     }
 }
 
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for NetworkInterfacesError {
+    fn from(err: serde_json::Error) -> Self {
+        NetworkInterfacesError::Json(err)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for NetworkInterfacesError {
+    fn from(err: toml::de::Error) -> Self {
+        NetworkInterfacesError::Toml(err)
+    }
+}
+
 impl From<ParserError> for NetworkInterfacesError {
     fn from(err: ParserError) -> Self {
         NetworkInterfacesError::Parser(err)
     }
 }
 
-impl From<FamilyParseError> for NetworkInterfacesError {
-    fn from(err: FamilyParseError) -> Self {
-        NetworkInterfacesError::FamilyParse(err)
+impl From<LimitExceeded> for NetworkInterfacesError {
+    fn from(err: LimitExceeded) -> Self {
+        NetworkInterfacesError::LimitExceeded(err)
+    }
+}
+
+impl From<SubstitutionError> for NetworkInterfacesError {
+    fn from(err: SubstitutionError) -> Self {
+        NetworkInterfacesError::Substitution(err)
+    }
+}
+
+impl From<NameError> for NetworkInterfacesError {
+    fn from(err: NameError) -> Self {
+        NetworkInterfacesError::InvalidName(err)
     }
 }
 
-impl From<MethodParseError> for NetworkInterfacesError {
-    fn from(err: MethodParseError) -> Self {
-        NetworkInterfacesError::MethodParse(err)
+impl From<FamilyParseError> for NetworkInterfacesError {
+    fn from(err: FamilyParseError) -> Self {
+        NetworkInterfacesError::FamilyParse(err)
     }
 }
 
 /// Represents errors that can occur during parsing of the interfaces file.
+///
+/// When the error relates to a specific line, `Display` renders a compact
+/// excerpt:
+///
+/// ```text
+/// Parser error on line 4 in stanza br0: Missing interface name in 'iface' stanza
+///     4 | iface
+/// ```
 #[derive(Debug, Clone)]
 pub struct ParserError {
     /// A message describing the parsing error.
     pub message: String,
     /// Optional line number where the error occurred.
     pub line: Option<usize>,
+    /// The stanza being processed when the error occurred: the name of the
+    /// enclosing `iface` stanza, or the keyword of the offending line when no
+    /// stanza is open.
+    pub stanza: Option<String>,
+    /// The offending line, as written.
+    pub raw_line: Option<String>,
 }
 
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Parser error")?;
         if let Some(line) = self.line {
-            write!(f, "Parser error on line {}: {}", line, self.message)
-        } else {
-            write!(f, "Parser error: {}", self.message)
+            write!(f, " on line {}", line)?;
+        }
+        if let Some(stanza) = &self.stanza {
+            write!(f, " in stanza {}", stanza)?;
         }
+        write!(f, ": {}", self.message)?;
+        if let Some(raw_line) = &self.raw_line {
+            match self.line {
+                Some(line) => write!(f, "\n{:>5} | {}", line, raw_line)?,
+                None => write!(f, "\n      | {}", raw_line)?,
+            }
+        }
+        Ok(())
     }
 }
 
 impl Error for ParserError {}
 
+/// A limit set in [`ParserOptions`](crate::ParserOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Limit {
+    /// The size of the input, in bytes.
+    InputSize,
+    /// The length of a line, in bytes.
+    LineLength,
+    /// The number of interfaces.
+    Interfaces,
+    /// The number of options of one interface.
+    OptionsPerInterface,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit_str = match self {
+            Limit::InputSize => "input size",
+            Limit::LineLength => "line length",
+            Limit::Interfaces => "number of interfaces",
+            Limit::OptionsPerInterface => "number of options per interface",
+        };
+        f.write_str(limit_str)
+    }
+}
+
+/// The input exceeds a limit set in [`ParserOptions`](crate::ParserOptions).
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::error::Limit;
+/// use interface_rs::{NetworkInterfacesError, Parser, ParserOptions};
+///
+/// let options = ParserOptions {
+///     max_line_length: Some(16),
+///     ..Default::default()
+/// };
+/// let err = Parser::new()
+///     .with_options(options)
+///     .parse_bytes(b"auto lo\niface lo inet loopback\n")
+///     .unwrap_err();
+/// let NetworkInterfacesError::LimitExceeded(err) = err else { panic!() };
+/// assert_eq!(err.limit, Limit::LineLength);
+/// assert_eq!((err.max, err.actual, err.line), (16, Some(22), Some(2)));
+/// assert_eq!(
+///     err.to_string(),
+///     "Input exceeds the line length limit of 16 on line 2 (found 22)"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// The limit that was exceeded.
+    pub limit: Limit,
+    /// The configured maximum.
+    pub max: usize,
+    /// The observed value, if known. Counts are not known, since parsing
+    /// stops as soon as they exceed the limit.
+    pub actual: Option<usize>,
+    /// The 1-based line where the limit was exceeded, if any.
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Input exceeds the {} limit of {}", self.limit, self.max)?;
+        if let Some(line) = self.line {
+            write!(f, " on line {}", line)?;
+        }
+        if let Some(actual) = self.actual {
+            write!(f, " (found {})", actual)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for LimitExceeded {}
+
+/// The interfaces forming a dependency cycle, in dependency order, which
+/// prevent them from being ordered.
+///
+/// Returned by [`NetworkInterfaces::bringup_order`](crate::NetworkInterfaces::bringup_order)
+/// and converted into [`NetworkInterfacesError::DependencyCycle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError(pub Vec<String>);
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Dependency cycle between interfaces: {} -> {}",
+            self.0.join(" -> "),
+            self.0.first().map(String::as_str).unwrap_or_default()
+        )
+    }
+}
+
+impl Error for CycleError {}
+
+impl From<CycleError> for NetworkInterfacesError {
+    fn from(err: CycleError) -> Self {
+        NetworkInterfacesError::DependencyCycle(err.0)
+    }
+}
+
+/// Represents errors that can occur when converting from another
+/// configuration format.
+#[derive(Debug, Clone)]
+pub struct ConversionError(pub String);
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Conversion error: {}", self.0)
+    }
+}
+
+impl Error for ConversionError {}
+
+/// Errors from substituting `{{ name }}` placeholders with
+/// [`NetworkInterfaces::substitute`](crate::NetworkInterfaces::substitute).
+///
+/// Nothing is substituted when an error is returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubstitutionError {
+    /// A `{{` does not start a well-formed placeholder.
+    InvalidPlaceholder {
+        /// Where the placeholder is, such as `swp1 address` or
+        /// `header comment`.
+        location: String,
+        /// The placeholder text, from the `{{` to the matching `}}` or the
+        /// end of the value.
+        text: String,
+    },
+    /// Placeholders name variables that were not given. Holds every missing
+    /// name once, sorted.
+    UndefinedVariables(Vec<String>),
+    /// The value of a variable spans several lines, which would break the
+    /// file apart. Holds the variable name.
+    MultilineValue(String),
+}
+
+impl fmt::Display for SubstitutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubstitutionError::InvalidPlaceholder { location, text } => {
+                write!(f, "Invalid placeholder in {}: {}", location, text)
+            }
+            SubstitutionError::UndefinedVariables(names) => {
+                write!(f, "Undefined variables: {}", names.join(", "))
+            }
+            SubstitutionError::MultilineValue(name) => {
+                write!(f, "The value of variable {} spans several lines", name)
+            }
+        }
+    }
+}
+
+impl Error for SubstitutionError {}
+
 /// Represents errors that can occur when parsing the `Family` enum.
 #[derive(Debug, Clone)]
 pub struct FamilyParseError(pub String);
@@ -108,14 +704,87 @@ impl fmt::Display for FamilyParseError {
 
 impl Error for FamilyParseError {}
 
-/// Represents errors that can occur when parsing the `Method` enum.
-#[derive(Debug, Clone)]
-pub struct MethodParseError(pub String);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl fmt::Display for MethodParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Invalid method: {}", self.0)
+    #[test]
+    fn test_error_kinds() {
+        let parser_error = ParserError {
+            message: "bad".to_string(),
+            line: Some(1),
+            stanza: None,
+            raw_line: None,
+        };
+        let cases = [
+            (
+                NetworkInterfacesError::Io(io::Error::from(io::ErrorKind::PermissionDenied)),
+                ErrorKind::Io,
+            ),
+            (
+                NetworkInterfacesError::Parser(parser_error),
+                ErrorKind::Parse,
+            ),
+            (
+                NetworkInterfacesError::FamilyParse(FamilyParseError("x".to_string())),
+                ErrorKind::Parse,
+            ),
+            (
+                NetworkInterfacesError::FileModified,
+                ErrorKind::FileModified,
+            ),
+            (
+                NetworkInterfacesError::DependencyCycle(vec!["br0".to_string()]),
+                ErrorKind::Validation,
+            ),
+            (
+                NetworkInterfacesError::Validation(ValidationReport::default()),
+                ErrorKind::Validation,
+            ),
+            (
+                NetworkInterfacesError::Conflict {
+                    name: "eth0".to_string(),
+                },
+                ErrorKind::Conflict,
+            ),
+            (
+                NetworkInterfacesError::FileConflict {
+                    path: PathBuf::from("/etc/network/interfaces.d/eth0_1"),
+                    names: vec!["eth0.1".to_string(), "eth0_1".to_string()],
+                },
+                ErrorKind::Conflict,
+            ),
+            (
+                NetworkInterfacesError::NotFound {
+                    name: "eth0".to_string(),
+                },
+                ErrorKind::NotFound,
+            ),
+            (
+                NetworkInterfacesError::WrongKind {
+                    name: "eth0".to_string(),
+                    expected: "bridge",
+                },
+                ErrorKind::InvalidInput,
+            ),
+            (
+                NetworkInterfacesError::InvalidName(NameError::Empty),
+                ErrorKind::InvalidInput,
+            ),
+            (
+                NetworkInterfacesError::Other("x".to_string()),
+                ErrorKind::Other,
+            ),
+        ];
+        for (err, kind) in cases {
+            assert_eq!(err.kind(), kind, "{}", err);
+            let err = NetworkInterfacesError::WithOp {
+                op: IoOp::Write,
+                source: Box::new(err),
+            };
+            assert_eq!(err.kind(), kind, "{}", err);
+            let err = err.with_path(Path::new("/etc/network/interfaces"));
+            assert_eq!(err.kind(), kind, "{}", err);
+        }
     }
 }
-
-impl Error for MethodParseError {}
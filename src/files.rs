@@ -0,0 +1,572 @@
+//! Saving a collection spread over several files.
+//!
+//! Every interface remembers the file it is saved to: the file it was loaded
+//! from, or the one assigned with
+//! [`NetworkInterfaces::set_file`](crate::NetworkInterfaces::set_file).
+//! Interfaces created in memory are saved to the
+//! [`SaveTarget`] set with
+//! [`NetworkInterfaces::set_default_target`](crate::NetworkInterfaces::set_default_target).
+//!
+//! [`NetworkInterfaces::save`](crate::NetworkInterfaces::save) renders every
+//! file before touching the disk, then creates or updates each file that
+//! changed and deletes included files that no longer hold any interface.
+//! Each file is replaced atomically: its new contents are written to a
+//! temporary file next to it, flushed to disk, and renamed over it, so a
+//! crash or a full disk never leaves a truncated file behind.
+//! [`NetworkInterfaces::plan_save`](crate::NetworkInterfaces::plan_save)
+//! performs the same steps without writing anything.
+//! [`NetworkInterfaces::save_as`](crate::NetworkInterfaces::save_as) instead
+//! writes the whole collection to a single new file and saves it there from
+//! then on; [`NetworkInterfaces::save_copy`](crate::NetworkInterfaces::save_copy)
+//! writes the same file but keeps the layout.
+//!
+//! With a [`Backup`] set with
+//! [`NetworkInterfaces::set_backup`](crate::NetworkInterfaces::set_backup),
+//! every file a save overwrites or deletes is first copied next to it.
+//!
+//! A hook set with
+//! [`NetworkInterfaces::set_post_save_hook`](crate::NetworkInterfaces::set_post_save_hook)
+//! runs after a save that changed any file, for example to run
+//! `ifreload -a`. The crate never runs commands itself.
+//!
+//! [`NetworkInterfaces::save_split`](crate::NetworkInterfaces::save_split)
+//! moves every interface to a file of its own, named with
+//! [`file_name_for`].
+//! [`NetworkInterfaces::load_dir`](crate::NetworkInterfaces::load_dir)
+//! reads such a directory back without a main file.
+//!
+//! [`NetworkInterfaces::load_managed`](crate::NetworkInterfaces::load_managed)
+//! owns only a marked block of the main file; the text around the block is
+//! written back unchanged.
+
+use crate::error::{IoOp, IoResultExt, NetworkInterfacesError, ParserError};
+use crate::graph;
+use crate::interface::{Family, IfaceName};
+use crate::lint::LintWarning;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+/// Where interfaces without a recorded file are saved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SaveTarget {
+    /// The main file the collection was loaded from (the default).
+    #[default]
+    Main,
+    /// A separate file, typically under `/etc/network/interfaces.d`.
+    File(PathBuf),
+}
+
+/// How a save keeps the previous contents of the files it overwrites or
+/// deletes; see
+/// [`NetworkInterfaces::set_backup`](crate::NetworkInterfaces::set_backup).
+///
+/// Backups are written next to the files they copy, keeping their
+/// permissions. Backups of files other than the main file, such as those in
+/// `interfaces.d`, also get a leading `.`, as in `.uplinks.bak`, so that
+/// neither a `source interfaces.d/*` glob nor `source-directory` reads them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backup {
+    /// Copies each file to its path with the suffix appended, such as
+    /// `interfaces.bak`, replacing the previous backup. The suffix must not
+    /// be empty or contain a `/`.
+    Suffix(String),
+    /// Copies each file to its path with the UTC time of the save appended,
+    /// such as `interfaces.20261016T093000.123456Z`.
+    Timestamped {
+        /// How many backups of each file to keep, oldest removed first, or
+        /// `None` to keep them all. The backup just made is always kept.
+        keep: Option<usize>,
+    },
+}
+
+impl Default for Backup {
+    /// Returns [`Backup::Suffix`] with `.bak`.
+    fn default() -> Self {
+        Backup::Suffix(".bak".to_string())
+    }
+}
+
+impl Backup {
+    /// Checks that backups are named apart from the files they copy.
+    pub(crate) fn validate(&self) -> Result<(), NetworkInterfacesError> {
+        match self {
+            Backup::Suffix(suffix) if suffix.is_empty() || suffix.contains('/') => Err(
+                NetworkInterfacesError::Other(format!("Invalid backup suffix: {:?}", suffix)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Copies the file of `planned` to its backup if saving overwrites or
+    /// deletes it, then removes backups beyond the number to keep. The backup
+    /// of a `hidden` file gets a leading `.`.
+    pub(crate) fn create(
+        &self,
+        planned: &PlannedFile,
+        hidden: bool,
+        now: SystemTime,
+    ) -> Result<(), NetworkInterfacesError> {
+        if !matches!(planned.action, FileAction::Update | FileAction::Delete) {
+            return Ok(());
+        }
+        let prefix = backup_prefix(&planned.path, hidden);
+        let suffix = match self {
+            Backup::Suffix(suffix) => suffix.clone(),
+            Backup::Timestamped { .. } => format!(".{}", backup_timestamp(now)),
+        };
+        let backup = planned.path.with_file_name(format!("{}{}", prefix, suffix));
+        fs::copy(&planned.path, &backup).op(IoOp::Backup)?;
+        if let Backup::Timestamped { keep: Some(keep) } = self {
+            prune_backups(&planned.path, hidden, (*keep).max(1))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the start of the names of the backups of `path`: its file name,
+/// after a `.` if the backups are `hidden`.
+fn backup_prefix(path: &Path, hidden: bool) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if hidden && !name.starts_with('.') {
+        format!(".{}", name)
+    } else {
+        name.into_owned()
+    }
+}
+
+/// Formats `time` as the UTC timestamp of a [`Backup::Timestamped`] backup,
+/// which sorts by time.
+pub(crate) fn backup_timestamp(time: SystemTime) -> String {
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // The proleptic Gregorian calendar, counted in 400-year eras from March
+    let days = days + 719_468;
+    let (era, day_of_era) = (days / 146_097, days % 146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}.{:06}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_micros()
+    )
+}
+
+/// Returns `true` if `suffix` is a timestamp made by [`backup_timestamp`].
+fn is_backup_timestamp(suffix: &str) -> bool {
+    let bytes = suffix.as_bytes();
+    bytes.len() == 23
+        && bytes.iter().enumerate().all(|(i, &b)| match i {
+            8 => b == b'T',
+            15 => b == b'.',
+            22 => b == b'Z',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// Removes the oldest timestamped backups of `path` until `keep` are left.
+fn prune_backups(path: &Path, hidden: bool, keep: usize) -> Result<(), NetworkInterfacesError> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let prefix = format!("{}.", backup_prefix(path, hidden));
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir).op(IoOp::ReadDir)? {
+        let name = entry.op(IoOp::ReadDir)?.file_name();
+        let is_backup = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(&prefix))
+            .is_some_and(is_backup_timestamp);
+        if is_backup {
+            backups.push(dir.join(name));
+        }
+    }
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep);
+    for backup in &backups[..excess] {
+        fs::remove_file(backup).op(IoOp::Remove)?;
+    }
+    Ok(())
+}
+
+/// The signature of a hook run after a successful save.
+///
+/// The hook receives the path of the main file and returns an error message
+/// if it fails; see
+/// [`NetworkInterfaces::set_post_save_hook`](crate::NetworkInterfaces::set_post_save_hook).
+pub type PostSaveHookFn = dyn Fn(&Path) -> Result<(), String> + Send;
+
+/// A post-save hook, shared between clones of a collection.
+#[derive(Clone)]
+pub(crate) struct SaveHook(Arc<Mutex<Box<PostSaveHookFn>>>);
+
+impl SaveHook {
+    /// Wraps a hook.
+    pub(crate) fn new(hook: Box<PostSaveHookFn>) -> Self {
+        SaveHook(Arc::new(Mutex::new(hook)))
+    }
+
+    /// Runs the hook for `path`.
+    pub(crate) fn run(&self, path: &Path) -> Result<(), String> {
+        // A hook that panicked before still gets to run
+        let hook = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        hook(path)
+    }
+}
+
+impl fmt::Debug for SaveHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SaveHook(..)")
+    }
+}
+
+/// What saving does to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileAction {
+    /// The file does not exist yet and will be created.
+    Create,
+    /// The file exists and its contents will be replaced.
+    Update,
+    /// The file already has the rendered contents and is left alone.
+    Unchanged,
+    /// The file no longer holds any interface and will be removed.
+    Delete,
+}
+
+/// One file touched by a save, as reported by
+/// [`NetworkInterfaces::plan_save`](crate::NetworkInterfaces::plan_save).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// What saving does to the file.
+    pub action: FileAction,
+    /// The rendered contents, or `None` for [`FileAction::Delete`].
+    pub contents: Option<String>,
+}
+
+/// The per-file state of a collection.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FileMap {
+    /// The file each interface is saved to. Interfaces without an entry go
+    /// to `default_target`.
+    pub(crate) origins: BTreeMap<IfaceName, PathBuf>,
+    /// The file of each family stanza read from another file than the rest
+    /// of its interface, such as `iface eth0 inet6` in `interfaces.d/eth0`.
+    pub(crate) stanza_origins: BTreeMap<IfaceName, Vec<(Option<Family>, PathBuf)>>,
+    /// Files other than the main file that belong to the collection.
+    pub(crate) included: BTreeMap<PathBuf, IncludedFile>,
+    /// Where interfaces without an entry in `origins` are saved.
+    pub(crate) default_target: SaveTarget,
+    /// Style warnings found in the text of the files when they were read;
+    /// see [`NetworkInterfaces::lint`](crate::NetworkInterfaces::lint).
+    pub(crate) style: Vec<LintWarning>,
+    /// The text around the block of the main file the collection owns, if it
+    /// was loaded with `load_managed`.
+    pub(crate) managed: Option<ManagedBlock>,
+    /// How to back up files before a save overwrites or deletes them.
+    pub(crate) backup: Option<Backup>,
+}
+
+/// The parts of a main file outside its managed block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ManagedBlock {
+    /// The name after `# BEGIN` and `# END`.
+    pub(crate) marker: String,
+    /// Everything up to and including the `# BEGIN` line.
+    pub(crate) head: String,
+    /// Everything from the `# END` line on.
+    pub(crate) tail: String,
+}
+
+impl ManagedBlock {
+    /// Splits `content` around the block marked with `marker`.
+    ///
+    /// Returns the text outside the block and the block's contents with every
+    /// line before it blanked, so that line numbers found by parsing it match
+    /// the file. Without a block, the whole file is kept as the head and the
+    /// markers are added at its end.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a `# BEGIN` line has no matching `# END` line or the other
+    /// way around, or if the block is nested in itself or appears twice.
+    pub(crate) fn split(content: &str, marker: &str) -> Result<(Self, String), ParserError> {
+        let begin = format!("# BEGIN {}", marker);
+        let end = format!("# END {}", marker);
+        let error = |message: String, line: usize, raw_line: &str| ParserError {
+            message,
+            line: Some(line),
+            stanza: None,
+            raw_line: Some(raw_line.to_string()),
+        };
+
+        // Byte offsets of the start of the BEGIN line, the line after it, and
+        // the start of the END line
+        let mut block: Option<(usize, usize, usize)> = None;
+        let mut open: Option<(usize, usize, &str)> = None;
+        let mut offset = 0;
+        for (index, raw_line) in content.split_inclusive('\n').enumerate() {
+            let line = raw_line.trim_end_matches(['\n', '\r']);
+            let line_number = index + 1;
+            let next = offset + raw_line.len();
+            if line.trim() == begin {
+                if open.is_some() {
+                    let message = format!("Managed block '{}' is nested in itself", marker);
+                    return Err(error(message, line_number, line));
+                }
+                if block.is_some() {
+                    let message = format!("Managed block '{}' appears more than once", marker);
+                    return Err(error(message, line_number, line));
+                }
+                open = Some((offset, next, line));
+            } else if line.trim() == end {
+                match open.take() {
+                    Some((start, inner, _)) => block = Some((start, inner, offset)),
+                    None => {
+                        let message = format!("'{}' without a matching '{}'", end, begin);
+                        return Err(error(message, line_number, line));
+                    }
+                }
+            }
+            offset = next;
+        }
+        if let Some((start, _, line)) = open {
+            let line_number = content[..start].matches('\n').count() + 1;
+            let message = format!("Missing '{}' for managed block", end);
+            return Err(error(message, line_number, line));
+        }
+
+        Ok(match block {
+            Some((_, inner, tail)) => {
+                let head = &content[..inner];
+                let blanked = "\n".repeat(head.matches('\n').count());
+                let managed = ManagedBlock {
+                    marker: marker.to_string(),
+                    head: head.to_string(),
+                    tail: content[tail..].to_string(),
+                };
+                (managed, blanked + &content[inner..tail])
+            }
+            None => {
+                let mut head = content.to_string();
+                if !head.is_empty() && !head.ends_with('\n') {
+                    head.push('\n');
+                }
+                head.push_str(&begin);
+                head.push('\n');
+                let managed = ManagedBlock {
+                    marker: marker.to_string(),
+                    head,
+                    tail: end + "\n",
+                };
+                (managed, String::new())
+            }
+        })
+    }
+
+    /// Returns the file with `block` as the contents of the managed block.
+    pub(crate) fn wrap(&self, block: &str) -> String {
+        let mut contents = String::with_capacity(self.head.len() + block.len() + self.tail.len());
+        contents.push_str(&self.head);
+        contents.push_str(block);
+        contents.push_str(&self.tail);
+        contents
+    }
+}
+
+/// A file other than the main file that belongs to a collection.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IncludedFile {
+    /// Comments written at the top of the file.
+    pub(crate) comments: Vec<String>,
+    /// Source directives written after the comments.
+    pub(crate) sources: Vec<String>,
+    /// The modification time when the file was last read or written, if it
+    /// has been.
+    pub(crate) last_modified: Option<SystemTime>,
+}
+
+/// Returns the name of the file
+/// [`NetworkInterfaces::save_split`](crate::NetworkInterfaces::save_split)
+/// writes an interface to.
+///
+/// `source-directory` and other `run-parts`-style includes only read files
+/// whose names consist of ASCII letters, digits, underscores, and hyphens, so
+/// every other character is replaced with an underscore.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::files::file_name_for;
+///
+/// assert_eq!(file_name_for("swp1"), "swp1");
+/// assert_eq!(file_name_for("eth0.100"), "eth0_100");
+/// ```
+pub fn file_name_for(name: &str) -> String {
+    name.chars()
+        .map(|c| if is_file_name_char(c) { c } else { '_' })
+        .collect()
+}
+
+/// Returns `true` if `run-parts` would read a file with this name.
+pub(crate) fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_file_name_char)
+}
+
+fn is_file_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Returns the files in `dir` that `run-parts` would read, as
+/// `source-directory` includes them, in path order.
+///
+/// # Errors
+///
+/// Returns the error from reading the directory, including
+/// [`io::ErrorKind::NotFound`] if it does not exist.
+pub(crate) fn directory_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let eligible = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(is_valid_file_name);
+        if eligible && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Returns the files matching the shell glob `pattern`, in path order, as
+/// `source` would include them.
+///
+/// Any component of the pattern may contain `*`, matching any run of
+/// characters, and `?`, matching one character. Neither matches a leading
+/// `.`, so hidden files are skipped. Directories that do not exist or cannot
+/// be read simply match nothing, and a pattern without wildcards matches the
+/// file it names if it exists.
+pub(crate) fn expand_glob(pattern: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let component = component.as_os_str();
+        let glob = component.to_str().filter(|text| graph::is_glob(text));
+        let Some(glob) = glob else {
+            for path in &mut paths {
+                path.push(component);
+            }
+            continue;
+        };
+        let mut matches = Vec::new();
+        for dir in &paths {
+            let listed = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir.as_path()
+            };
+            let Ok(entries) = fs::read_dir(listed) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let Some(name) = name.to_str() else {
+                    continue;
+                };
+                if !name.starts_with('.') && graph::slave_matches(glob, name) {
+                    matches.push(dir.join(name));
+                }
+            }
+        }
+        paths = matches;
+    }
+    let mut files: Vec<PathBuf> = paths.into_iter().filter(|path| path.is_file()).collect();
+    files.sort();
+    files
+}
+
+/// Returns the temporary file that [`write_atomic`] writes `path` to.
+///
+/// The name starts with a `.` and contains another, so neither `source`
+/// globs nor `source-directory` ever pick up a file left behind by a crash.
+pub(crate) fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+/// Replaces the contents of `path` with `contents` without ever leaving a
+/// partly written file behind.
+///
+/// The contents are written to a temporary file in the same directory,
+/// flushed to disk, and renamed over `path`, so that readers, and the next
+/// boot after a crash or a full disk, see either the old file or the new
+/// one. A symbolic link is followed and the file it points to replaced. An
+/// existing file keeps its permissions.
+///
+/// # Errors
+///
+/// Returns an error tagged with the [`IoOp`] that failed. The temporary
+/// file is removed and `path` left untouched.
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> Result<(), NetworkInterfacesError> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let temp = temp_path_for(&target);
+    let result = write_and_rename(&target, &temp, contents);
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_and_rename(
+    target: &Path,
+    temp: &Path,
+    contents: &str,
+) -> Result<(), NetworkInterfacesError> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp)
+        .op(IoOp::CreateTemp)?;
+    file.write_all(contents.as_bytes()).op(IoOp::Write)?;
+    if let Ok(metadata) = fs::metadata(target) {
+        file.set_permissions(metadata.permissions())
+            .op(IoOp::Write)?;
+    }
+    file.sync_all().op(IoOp::Fsync)?;
+    drop(file);
+    fs::rename(temp, target).op(IoOp::Rename)?;
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Some(dir) = target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .op(IoOp::Fsync)?;
+    }
+    Ok(())
+}
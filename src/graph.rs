@@ -0,0 +1,528 @@
+//! Dependencies between interfaces.
+//!
+//! Some interfaces can only be brought up once others exist: a bridge needs
+//! its ports, a bond its slaves, a VLAN its raw device, an alias its parent,
+//! and a VRF member its VRF device. This module extracts those relationships from the options of
+//! each interface and orders a collection so that every interface comes after
+//! the interfaces it depends on.
+//!
+//! One relationship is not named by an option: in an EVPN layout, a VLAN
+//! interface depends on the VXLAN that maps its `vlan-id` through
+//! `bridge-access`. [`dependencies`] adds it using a [`VxlanIndex`] of the
+//! whole collection.
+//!
+//! The DOT export, dependency-ordered output, bring-up and teardown orders,
+//! and the `ip` command export all share this graph.
+
+use crate::helper::sort::natural;
+use crate::interface::option::keys_match;
+use crate::interface::{Interface, InterfaceKind};
+use std::collections::{HashMap, HashSet};
+
+/// The relationship a [`Dependency`] represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DependencyKind {
+    /// A bridge depends on a port listed in `bridge-ports`.
+    BridgePort,
+    /// A bond depends on a slave listed in `bond-slaves`.
+    BondSlave,
+    /// A VLAN depends on its `vlan-raw-device`, or on the part of its name
+    /// before the last `.` (e.g., `eth0.100` on `eth0`).
+    VlanRawDevice,
+    /// An interface depends on the VRF named by its `vrf` option.
+    VrfMember,
+    /// An alias depends on the part of its name before the `:` (e.g.,
+    /// `eth0:1` on `eth0`).
+    AliasParent,
+    /// A VLAN interface depends on a VXLAN whose `bridge-access` matches its
+    /// `vlan-id`.
+    VxlanAccess,
+}
+
+/// A dependency of one interface on another.
+///
+/// The interface named by `to` must be brought up before `from`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Dependency {
+    /// The dependent interface.
+    pub from: String,
+    /// The interface `from` depends on.
+    pub to: String,
+    /// The relationship between the two.
+    pub kind: DependencyKind,
+}
+
+/// Returns the dependencies declared by a single interface, in option order.
+///
+/// Names are returned as written, whether or not they are defined anywhere.
+/// When `strict_keys` is set, option keys are matched exactly.
+pub(crate) fn interface_dependencies(iface: &Interface, strict_keys: bool) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let mut push = |to: &str, kind| {
+        deps.push(Dependency {
+            from: iface.name.clone(),
+            to: to.to_string(),
+            kind,
+        })
+    };
+
+    let mut has_raw_device = false;
+    for (key, value) in &iface.options {
+        if keys_match(key, "bridge-ports", strict_keys) {
+            for port in value.split_whitespace().filter(|p| *p != "none") {
+                push(port, DependencyKind::BridgePort);
+            }
+        } else if keys_match(key, "bond-slaves", strict_keys) {
+            for slave in value.split_whitespace().filter(|s| *s != "none") {
+                push(slave, DependencyKind::BondSlave);
+            }
+        } else if keys_match(key, "vlan-raw-device", strict_keys) {
+            has_raw_device = true;
+            push(value.trim(), DependencyKind::VlanRawDevice);
+        } else if keys_match(key, "vrf", strict_keys) {
+            push(value.trim(), DependencyKind::VrfMember);
+        }
+    }
+    if let Some(parent) = iface.alias_parent() {
+        push(parent, DependencyKind::AliasParent);
+    } else if !has_raw_device {
+        if let Some((raw_device, _)) = iface.name.rsplit_once('.') {
+            if !raw_device.is_empty() {
+                push(raw_device, DependencyKind::VlanRawDevice);
+            }
+        }
+    }
+    deps
+}
+
+/// The VXLAN interfaces of a collection, keyed by their `bridge-access` VLAN
+/// id.
+pub(crate) struct VxlanIndex<'a> {
+    by_access: HashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> VxlanIndex<'a> {
+    /// Indexes the VXLAN interfaces among `interfaces`. Each id maps to its
+    /// VXLANs in natural order.
+    pub(crate) fn new(
+        interfaces: impl IntoIterator<Item = &'a Interface>,
+        strict_keys: bool,
+    ) -> Self {
+        let mut by_access: HashMap<&str, Vec<&str>> = HashMap::new();
+        for iface in interfaces {
+            if iface.kind() != InterfaceKind::Vxlan {
+                continue;
+            }
+            if let Some(access) = option(iface, "bridge-access", strict_keys) {
+                by_access.entry(access).or_default().push(&iface.name);
+            }
+        }
+        for vxlans in by_access.values_mut() {
+            vxlans.sort_by(|a, b| natural(a, b));
+        }
+        VxlanIndex { by_access }
+    }
+}
+
+/// Returns the dependencies of an interface within a collection: those
+/// declared by its options, then one on each VXLAN in `vxlans` that carries
+/// its `vlan-id`.
+pub(crate) fn dependencies(
+    iface: &Interface,
+    vxlans: &VxlanIndex,
+    strict_keys: bool,
+) -> Vec<Dependency> {
+    let mut deps = interface_dependencies(iface, strict_keys);
+    if iface.kind() != InterfaceKind::Vlan {
+        return deps;
+    }
+    let Some(vlan_id) = option(iface, "vlan-id", strict_keys) else {
+        return deps;
+    };
+    for vxlan in vxlans.by_access.get(vlan_id).into_iter().flatten() {
+        deps.push(Dependency {
+            from: iface.name.clone(),
+            to: vxlan.to_string(),
+            kind: DependencyKind::VxlanAccess,
+        });
+    }
+    deps
+}
+
+/// Returns the trimmed value of the first `key` option of `iface`.
+fn option<'a>(iface: &'a Interface, key: &str, strict_keys: bool) -> Option<&'a str> {
+    iface
+        .options
+        .iter()
+        .find(|(k, _)| keys_match(k, key, strict_keys))
+        .map(|(_, value)| value.trim())
+}
+
+/// Maps each bridge port and bond slave to the bridge or bond that lists it.
+///
+/// If several interfaces list the same member, the first one in iteration
+/// order wins. Members are returned whether or not they are defined anywhere.
+pub(crate) fn masters<'a>(
+    interfaces: impl IntoIterator<Item = &'a Interface>,
+    strict_keys: bool,
+) -> HashMap<String, &'a str> {
+    let mut masters = HashMap::new();
+    for iface in interfaces {
+        for dep in interface_dependencies(iface, strict_keys) {
+            if matches!(
+                dep.kind,
+                DependencyKind::BridgePort | DependencyKind::BondSlave
+            ) {
+                masters.entry(dep.to).or_insert(iface.name.as_str());
+            }
+        }
+    }
+    masters
+}
+
+/// Returns the entries of the `bond-slaves` options of `iface`, without the
+/// `none` placeholder. Entries may be [globs](slave_matches).
+pub(crate) fn bond_slave_entries(iface: &Interface, strict_keys: bool) -> Vec<&str> {
+    iface
+        .options
+        .iter()
+        .filter(|(key, _)| keys_match(key, "bond-slaves", strict_keys))
+        .flat_map(|(_, value)| value.split_whitespace())
+        .filter(|entry| *entry != "none")
+        .collect()
+}
+
+/// Returns the bond named by the `bond-master` option of `iface`, the
+/// per-port alternative to listing the port in `bond-slaves`.
+pub(crate) fn bond_master(iface: &Interface, strict_keys: bool) -> Option<&str> {
+    iface
+        .options
+        .iter()
+        .find(|(key, _)| keys_match(key, "bond-master", strict_keys))
+        .map(|(_, value)| value.trim())
+}
+
+/// Returns `true` if a `bond-slaves` entry contains glob wildcards.
+pub(crate) fn is_glob(entry: &str) -> bool {
+    entry.contains(['*', '?'])
+}
+
+/// Returns `true` if `name` matches a `bond-slaves` entry, in which `*`
+/// matches any run of characters and `?` any single character.
+pub(crate) fn slave_matches(entry: &str, name: &str) -> bool {
+    let (entry, name): (Vec<char>, Vec<char>) = (entry.chars().collect(), name.chars().collect());
+    // Position after the last `*`, and the name position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+    let (mut e, mut n) = (0, 0);
+    while n < name.len() {
+        if e < entry.len() && (entry[e] == '?' || entry[e] == name[n]) {
+            e += 1;
+            n += 1;
+        } else if e < entry.len() && entry[e] == '*' {
+            star = Some((e + 1, n));
+            e += 1;
+        } else if let Some((after, tried)) = star {
+            // Let the last `*` swallow one more character
+            star = Some((after, tried + 1));
+            e = after;
+            n = tried + 1;
+        } else {
+            return false;
+        }
+    }
+    entry[e..].iter().all(|c| *c == '*')
+}
+
+/// Orders interfaces so that each one comes after everything it depends on.
+///
+/// Interfaces are placed in tiers: the first tier holds interfaces without
+/// dependencies in the collection, and each following tier holds interfaces
+/// whose dependencies are all in earlier tiers. Each tier is sorted in
+/// natural order, except that the loopback interface leads the first tier.
+/// Dependencies on interfaces outside `interfaces` are ignored.
+///
+/// # Errors
+///
+/// Returns the names of the interfaces forming a cycle, in dependency order,
+/// if the interfaces cannot be ordered.
+pub(crate) fn dependency_order<'a>(
+    interfaces: impl IntoIterator<Item = &'a Interface>,
+    strict_keys: bool,
+) -> Result<Vec<&'a Interface>, Vec<String>> {
+    let interfaces: HashMap<&str, &Interface> = interfaces
+        .into_iter()
+        .map(|iface| (iface.name.as_str(), iface))
+        .collect();
+    let vxlans = VxlanIndex::new(interfaces.values().copied(), strict_keys);
+    let deps: HashMap<&str, Vec<String>> = interfaces
+        .iter()
+        .map(|(name, iface)| {
+            let to = dependencies(iface, &vxlans, strict_keys)
+                .into_iter()
+                .map(|dep| dep.to)
+                .filter(|to| interfaces.contains_key(to.as_str()))
+                .collect();
+            (*name, to)
+        })
+        .collect();
+
+    let mut remaining: HashSet<&str> = deps.keys().copied().collect();
+    let mut ordered = Vec::with_capacity(interfaces.len());
+    while !remaining.is_empty() {
+        let mut tier: Vec<&str> = remaining
+            .iter()
+            .copied()
+            .filter(|name| deps[name].iter().all(|to| !remaining.contains(to.as_str())))
+            .collect();
+        if tier.is_empty() {
+            return Err(find_cycle(&deps, &remaining));
+        }
+        let is_loopback = |name: &str| interfaces[name].kind() == InterfaceKind::Loopback;
+        tier.sort_by(|a, b| is_loopback(b).cmp(&is_loopback(a)).then(natural(a, b)));
+        for name in tier {
+            remaining.remove(name);
+            ordered.push(interfaces[name]);
+        }
+    }
+    Ok(ordered)
+}
+
+/// Follows dependencies among `remaining` interfaces, each of which has at
+/// least one dependency in `remaining`, until a name repeats.
+fn find_cycle(deps: &HashMap<&str, Vec<String>>, remaining: &HashSet<&str>) -> Vec<String> {
+    let mut current = remaining
+        .iter()
+        .copied()
+        .min_by(|a, b| natural(a, b))
+        .expect("remaining is not empty");
+    let mut path: Vec<&str> = Vec::new();
+    loop {
+        if let Some(start) = path.iter().position(|name| *name == current) {
+            return path[start..].iter().map(|name| name.to_string()).collect();
+        }
+        path.push(current);
+        current = deps[current]
+            .iter()
+            .map(String::as_str)
+            .find(|to| remaining.contains(to))
+            .expect("interface in a cycle has a remaining dependency");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface_dependencies() {
+        let bridge = Interface::builder("br0")
+            .with_option("bridge-ports", "swp1 bond0")
+            .with_option("vrf", "mgmt")
+            .build();
+        let deps = interface_dependencies(&bridge, false);
+        let deps: Vec<(&str, DependencyKind)> =
+            deps.iter().map(|d| (d.to.as_str(), d.kind)).collect();
+        assert_eq!(
+            deps,
+            vec![
+                ("swp1", DependencyKind::BridgePort),
+                ("bond0", DependencyKind::BridgePort),
+                ("mgmt", DependencyKind::VrfMember),
+            ]
+        );
+
+        let vlan = Interface::builder("eth0.100").build();
+        let deps = interface_dependencies(&vlan, false);
+        assert_eq!(deps[0].to, "eth0");
+        assert_eq!(deps[0].kind, DependencyKind::VlanRawDevice);
+
+        let vlan = Interface::builder("vlan100")
+            .with_option("vlan_raw_device", "bond0")
+            .build();
+        let deps = interface_dependencies(&vlan, false);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].to, "bond0");
+        assert!(interface_dependencies(&vlan, true).is_empty());
+    }
+
+    #[test]
+    fn test_qinq_dependencies() {
+        // swp1.100.200 is tagged on swp1.100, which is tagged on swp1
+        let interfaces = vec![
+            Interface::builder("swp1.100.200").build(),
+            Interface::builder("swp1.100")
+                .with_option("vlan-protocol", "802.1ad")
+                .build(),
+            Interface::builder("swp1").build(),
+        ];
+        let deps = interface_dependencies(&interfaces[0], false);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].to, "swp1.100");
+        assert_eq!(deps[0].kind, DependencyKind::VlanRawDevice);
+        assert_eq!(interface_dependencies(&interfaces[1], false)[0].to, "swp1");
+
+        let names: Vec<&str> = dependency_order(&interfaces, false)
+            .unwrap()
+            .into_iter()
+            .map(|iface| iface.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["swp1", "swp1.100", "swp1.100.200"]);
+    }
+
+    #[test]
+    fn test_vxlan_dependencies() {
+        let interfaces = vec![
+            Interface::builder("br0")
+                .with_option("bridge-ports", "vni10 vni20")
+                .with_option("bridge-vlan-aware", "yes")
+                .build(),
+            Interface::builder("vni10")
+                .with_option("vxlan-id", "10010")
+                .with_option("bridge-access", "10")
+                .build(),
+            Interface::builder("vni20")
+                .with_option("vxlan-id", "10020")
+                .with_option("bridge-access", "20")
+                .build(),
+            Interface::builder("vlan10")
+                .with_option("vlan-id", "10")
+                .with_option("vlan-raw-device", "br0")
+                .build(),
+            Interface::builder("vlan30")
+                .with_option("vlan-id", "30")
+                .with_option("vlan-raw-device", "br0")
+                .build(),
+        ];
+        let vxlans = VxlanIndex::new(&interfaces, false);
+        let deps = dependencies(&interfaces[3], &vxlans, false);
+        let deps: Vec<(&str, DependencyKind)> =
+            deps.iter().map(|d| (d.to.as_str(), d.kind)).collect();
+        assert_eq!(
+            deps,
+            vec![
+                ("br0", DependencyKind::VlanRawDevice),
+                ("vni10", DependencyKind::VxlanAccess),
+            ]
+        );
+        assert_eq!(dependencies(&interfaces[4], &vxlans, false).len(), 1);
+        assert_eq!(dependencies(&interfaces[1], &vxlans, false).len(), 0);
+
+        // The bridge carrying the VXLANs does not form a cycle with its VLANs
+        let names: Vec<&str> = dependency_order(&interfaces, false)
+            .unwrap()
+            .into_iter()
+            .map(|iface| iface.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["vni10", "vni20", "br0", "vlan10", "vlan30"]);
+    }
+
+    #[test]
+    fn test_masters() {
+        let interfaces = vec![
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1 bond0")
+                .build(),
+            Interface::builder("bond0")
+                .with_option("bond-slaves", "swp2 swp3")
+                .with_option("vrf", "mgmt")
+                .build(),
+            Interface::builder("br1")
+                .with_option("bridge-ports", "swp1")
+                .build(),
+        ];
+        let masters = masters(&interfaces, false);
+        assert_eq!(masters.len(), 4);
+        assert_eq!(masters["swp1"], "br0");
+        assert_eq!(masters["bond0"], "br0");
+        assert_eq!(masters["swp3"], "bond0");
+        assert!(!masters.contains_key("mgmt"));
+    }
+
+    #[test]
+    fn test_bond_slaves() {
+        let bond = Interface::builder("bond0")
+            .with_option("bond-slaves", "none")
+            .with_option("bond_slaves", "swp1 swp[2]")
+            .build();
+        assert_eq!(bond_slave_entries(&bond, false), vec!["swp1", "swp[2]"]);
+        assert!(bond_slave_entries(&bond, true).is_empty());
+        let port = Interface::builder("swp3")
+            .with_option("bond-master", "bond0")
+            .build();
+        assert_eq!(bond_master(&port, false), Some("bond0"));
+        assert_eq!(bond_master(&bond, false), None);
+
+        assert!(slave_matches("swp*", "swp12"));
+        assert!(slave_matches("swp*", "swp"));
+        assert!(slave_matches("sw?1*", "swp1s0"));
+        assert!(slave_matches("*s0", "swp1s0"));
+        assert!(slave_matches("swp1", "swp1"));
+        assert!(!slave_matches("swp1", "swp10"));
+        assert!(!slave_matches("swp?", "swp10"));
+        assert!(!slave_matches("*s1", "swp1s0"));
+        assert!(is_glob("swp*") && is_glob("swp?") && !is_glob("swp1"));
+    }
+
+    #[test]
+    fn test_dependency_order() {
+        let interfaces = vec![
+            Interface::builder("vlan10")
+                .with_option("vlan-raw-device", "br0")
+                .with_option("vrf", "red")
+                .build(),
+            Interface::builder("br0")
+                .with_option("bridge-ports", "bond0 swp3")
+                .build(),
+            Interface::builder("bond0")
+                .with_option("bond-slaves", "swp10 swp2")
+                .build(),
+            Interface::builder("red").build(),
+            Interface::builder("swp10").build(),
+            Interface::builder("swp2").build(),
+            Interface::builder("swp3").build(),
+            Interface::builder("lo").build(),
+        ];
+        let names: Vec<&str> = dependency_order(&interfaces, false)
+            .unwrap()
+            .into_iter()
+            .map(|iface| iface.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["lo", "red", "swp2", "swp3", "swp10", "bond0", "br0", "vlan10"]
+        );
+
+        // The loopback interface comes first even when it does not sort first
+        let interfaces = vec![
+            Interface::builder("eth0").build(),
+            Interface::builder("loop0").with_method("loopback").build(),
+            Interface::builder("bond0")
+                .with_option("bond-slaves", "eth0")
+                .build(),
+        ];
+        let names: Vec<&str> = dependency_order(&interfaces, false)
+            .unwrap()
+            .into_iter()
+            .map(|iface| iface.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["loop0", "eth0", "bond0"]);
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        let interfaces = vec![
+            Interface::builder("swp1").build(),
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1 bond0")
+                .build(),
+            Interface::builder("bond0")
+                .with_option("bond-slaves", "br0")
+                .build(),
+            Interface::builder("vlan5")
+                .with_option("vlan-raw-device", "br0")
+                .build(),
+        ];
+        let cycle = dependency_order(&interfaces, false).unwrap_err();
+        assert_eq!(cycle, vec!["bond0", "br0"]);
+    }
+}
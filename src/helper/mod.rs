@@ -1 +1,4 @@
-pub mod sort;
\ No newline at end of file
+//! Helpers shared by the rest of the crate.
+
+pub(crate) mod ordered_map;
+pub mod sort;
@@ -0,0 +1,203 @@
+//! The insertion-ordered map a collection stores its interfaces in.
+//!
+//! With the default `indexmap` feature this is [`indexmap::IndexMap`].
+//! Without it, a small map of its own keeps the entries in a `Vec` and their
+//! positions in a `HashMap`, so that the crate builds without dependencies.
+//! Both iterate in insertion order and look keys up in constant time.
+
+#[cfg(feature = "indexmap")]
+pub(crate) use indexmap::IndexMap as OrderedMap;
+
+#[cfg(not(feature = "indexmap"))]
+pub(crate) use fallback::OrderedMap;
+
+#[cfg(not(feature = "indexmap"))]
+mod fallback {
+    use std::borrow::Borrow;
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::hash::Hash;
+
+    /// A map iterating in insertion order, with the subset of the
+    /// `IndexMap` API the crate uses.
+    #[derive(Clone)]
+    pub(crate) struct OrderedMap<K, V> {
+        /// The entries, in insertion order.
+        entries: Vec<(K, V)>,
+        /// The position of each key in `entries`.
+        index: HashMap<K, usize>,
+    }
+
+    impl<K: Hash + Eq + Clone, V> OrderedMap<K, V> {
+        /// Creates an empty map.
+        pub(crate) fn new() -> Self {
+            OrderedMap {
+                entries: Vec::new(),
+                index: HashMap::new(),
+            }
+        }
+
+        /// Returns the number of entries.
+        pub(crate) fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        /// Returns `true` if the map has no entries.
+        pub(crate) fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        /// Returns `true` if the map has an entry for `key`.
+        pub(crate) fn contains_key<Q>(&self, key: &Q) -> bool
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            self.index.contains_key(key)
+        }
+
+        /// Returns the value for `key`.
+        pub(crate) fn get<Q>(&self, key: &Q) -> Option<&V>
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            let &i = self.index.get(key)?;
+            Some(&self.entries[i].1)
+        }
+
+        /// Returns the value for `key` mutably.
+        pub(crate) fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            let &i = self.index.get(key)?;
+            Some(&mut self.entries[i].1)
+        }
+
+        /// Inserts a value, returning the previous one for the key. A
+        /// replaced entry keeps its position; a new one goes last.
+        pub(crate) fn insert(&mut self, key: K, value: V) -> Option<V> {
+            if let Some(&i) = self.index.get(&key) {
+                return Some(std::mem::replace(&mut self.entries[i].1, value));
+            }
+            self.index.insert(key.clone(), self.entries.len());
+            self.entries.push((key, value));
+            None
+        }
+
+        /// Removes the entry for `key`, shifting the entries after it so the
+        /// others keep their order.
+        pub(crate) fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            let i = self.index.remove(key)?;
+            let (_, value) = self.entries.remove(i);
+            for (position, (moved, _)) in self.entries.iter().enumerate().skip(i) {
+                if let Some(slot) = self.index.get_mut::<K>(moved) {
+                    *slot = position;
+                }
+            }
+            Some(value)
+        }
+
+        /// Returns an iterator over the entries in insertion order.
+        pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+            self.entries.iter().map(|(key, value)| (key, value))
+        }
+
+        /// Returns an iterator over the keys in insertion order.
+        pub(crate) fn keys(&self) -> impl Iterator<Item = &K> {
+            self.entries.iter().map(|(key, _)| key)
+        }
+
+        /// Returns an iterator over the values in insertion order.
+        pub(crate) fn values(&self) -> impl Iterator<Item = &V> {
+            self.entries.iter().map(|(_, value)| value)
+        }
+
+        /// Returns a mutable iterator over the values in insertion order.
+        pub(crate) fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+            self.entries.iter_mut().map(|(_, value)| value)
+        }
+
+        /// Returns the values in insertion order.
+        pub(crate) fn into_values(self) -> impl Iterator<Item = V> {
+            self.entries.into_iter().map(|(_, value)| value)
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V> Default for OrderedMap<K, V> {
+        fn default() -> Self {
+            OrderedMap::new()
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+        fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+            let mut map = OrderedMap::new();
+            for (key, value) in iter {
+                map.insert(key, value);
+            }
+            map
+        }
+    }
+
+    /// Maps are equal if they have the same entries, in any order, as with
+    /// `IndexMap`.
+    impl<K: Hash + Eq + Clone, V: PartialEq> PartialEq for OrderedMap<K, V> {
+        fn eq(&self, other: &Self) -> bool {
+            self.len() == other.len()
+                && self
+                    .iter()
+                    .all(|(key, value)| other.get(key) == Some(value))
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V: Eq> Eq for OrderedMap<K, V> {}
+
+    impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OrderedMap<K, V> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_map()
+                .entries(self.entries.iter().map(|(k, v)| (k, v)))
+                .finish()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_ordered_map() {
+            let mut map: OrderedMap<String, u32> = ["c", "a", "b"]
+                .iter()
+                .zip(1..)
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+            assert_eq!(map.insert("a".to_string(), 10), Some(2));
+            assert_eq!(map.insert("d".to_string(), 4), None);
+            let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+            assert_eq!(keys, ["c", "a", "b", "d"]);
+            assert_eq!(map.get("a"), Some(&10));
+
+            assert_eq!(map.shift_remove("a"), Some(10));
+            assert_eq!(map.shift_remove("a"), None);
+            let entries: Vec<(&str, u32)> = map.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+            assert_eq!(entries, [("c", 1), ("b", 3), ("d", 4)]);
+            // Positions after the removed entry were updated
+            *map.get_mut("d").unwrap() += 1;
+            assert_eq!(map.get("d"), Some(&5));
+            assert!(!map.contains_key("a"));
+
+            // Equality ignores the order
+            let mut pairs: Vec<(String, u32)> = map.iter().map(|(k, v)| (k.clone(), *v)).collect();
+            pairs.reverse();
+            let reversed: OrderedMap<String, u32> = pairs.into_iter().collect();
+            assert_eq!(map, reversed);
+        }
+    }
+}
@@ -1,3 +1,30 @@
+//! Natural ordering of interface names.
+//!
+//! This is the order the crate writes interfaces in by default, exposed so
+//! that tools displaying interface names can use the same order. It is part
+//! of the public API and will not change between compatible releases.
+//!
+//! The rules of [`natural`] are:
+//!
+//! - Runs of ASCII digits are compared by numeric value, so `swp2` sorts
+//!   before `swp10`. Runs may be arbitrarily long.
+//! - Leading zeros are ignored, so `swp01` and `swp1` compare equal.
+//! - Everything else is compared character by character, which is the same
+//!   as comparing the UTF-8 bytes; in particular uppercase letters sort
+//!   before lowercase ones (use [`natural_ci`] to ignore case).
+//! - A string that is a prefix of another sorts first.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::sort::sort_interface_names;
+//!
+//! let mut names = vec!["swp10", "swp2", "bond0", "swp1s1"];
+//! sort_interface_names(&mut names);
+//! assert_eq!(names, vec!["bond0", "swp1s1", "swp2", "swp10"]);
+//! ```
+
+use crate::interface::Interface;
 use std::cmp::Ordering;
 
 /// Compares two strings using natural sort order.
@@ -11,17 +38,57 @@ use std::cmp::Ordering;
 /// # Returns
 /// An `Ordering` indicating the result of the comparison.
 pub fn natural(a: &str, b: &str) -> Ordering {
+    compare(a, b, false)
+}
+
+/// Compares two strings using natural sort order, ignoring letter case.
+/// Numbers are compared as in [`natural`], while other characters are
+/// compared after lowercasing, so "vlan2" < "VLAN10" < "Vlan100".
+///
+/// # Arguments
+/// - `a`: The first string to compare.
+/// - `b`: The second string to compare.
+///
+/// # Returns
+/// An `Ordering` indicating the result of the comparison. Strings that only
+/// differ in case compare as equal.
+pub fn natural_ci(a: &str, b: &str) -> Ordering {
+    compare(a, b, true)
+}
+
+/// Sorts a slice of interface names in place using [`natural`].
+///
+/// The sort is stable, so names that compare equal (such as `swp01` and
+/// `swp1`) keep their relative order.
+///
+/// # Arguments
+/// - `names`: The names to sort.
+pub fn sort_interface_names<S: AsRef<str>>(names: &mut [S]) {
+    names.sort_by(|a, b| natural(a.as_ref(), b.as_ref()));
+}
+
+/// Sorts a slice of interfaces in place by name using [`natural`].
+///
+/// The sort is stable.
+///
+/// # Arguments
+/// - `interfaces`: The interfaces to sort.
+pub fn sort_interfaces(interfaces: &mut [Interface]) {
+    interfaces.sort_by(|a, b| natural(&a.name, &b.name));
+}
+
+fn compare(a: &str, b: &str, fold_case: bool) -> Ordering {
     let mut a_iter = a.chars().peekable();
     let mut b_iter = b.chars().peekable();
 
     loop {
         match (a_iter.peek(), b_iter.peek()) {
             (Some(a_c), Some(b_c)) => {
-                if a_c.is_digit(10) && b_c.is_digit(10) {
+                if a_c.is_ascii_digit() && b_c.is_ascii_digit() {
                     // Extract full numbers
                     let mut a_num = String::new();
                     while let Some(c) = a_iter.peek() {
-                        if c.is_digit(10) {
+                        if c.is_ascii_digit() {
                             a_num.push(*c);
                             a_iter.next();
                         } else {
@@ -31,7 +98,7 @@ pub fn natural(a: &str, b: &str) -> Ordering {
 
                     let mut b_num = String::new();
                     while let Some(c) = b_iter.peek() {
-                        if c.is_digit(10) {
+                        if c.is_ascii_digit() {
                             b_num.push(*c);
                             b_iter.next();
                         } else {
@@ -39,16 +106,18 @@ pub fn natural(a: &str, b: &str) -> Ordering {
                         }
                     }
 
-                    let a_int = a_num.parse::<u64>().unwrap();
-                    let b_int = b_num.parse::<u64>().unwrap();
-
-                    match a_int.cmp(&b_int) {
+                    match compare_digit_runs(&a_num, &b_num) {
                         Ordering::Equal => continue,
                         other => return other,
                     }
                 } else {
                     // Compare characters
-                    match a_c.cmp(b_c) {
+                    let ordering = if fold_case {
+                        a_c.to_lowercase().cmp(b_c.to_lowercase())
+                    } else {
+                        a_c.cmp(b_c)
+                    };
+                    match ordering {
                         Ordering::Equal => {
                             a_iter.next();
                             b_iter.next();
@@ -65,6 +134,16 @@ pub fn natural(a: &str, b: &str) -> Ordering {
     }
 }
 
+/// Compares two runs of ASCII digits by numeric value without parsing them,
+/// so runs of any length are supported.
+fn compare_digit_runs(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    // With leading zeros removed, a longer run is a larger number; runs of
+    // equal length compare lexically.
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,4 +158,108 @@ mod tests {
         assert_eq!(natural("swp10s1", "swp10s2"), Ordering::Less);
         assert_eq!(natural("swp10", "swp9s1"), Ordering::Greater);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sort_natural_documented_rules() {
+        // Digit runs compare numerically
+        assert_eq!(natural("eth9", "eth10"), Ordering::Less);
+        assert_eq!(natural("1", "a"), Ordering::Less);
+        // Leading zeros are ignored
+        assert_eq!(natural("swp001", "swp1"), Ordering::Equal);
+        assert_eq!(natural("swp007", "swp10"), Ordering::Less);
+        // Everything else is compared byte-wise
+        assert_eq!(natural("Eth0", "eth0"), Ordering::Less);
+        assert_eq!(natural("br-a", "br_a"), Ordering::Less);
+        assert_eq!(natural("br.1", "br1"), Ordering::Less);
+        assert_eq!(natural("vlan\u{e9}", "vlanz"), Ordering::Greater);
+        // Prefixes sort first
+        assert_eq!(natural("swp1", "swp1s0"), Ordering::Less);
+        assert_eq!(natural("", "a"), Ordering::Less);
+
+        let mut names = vec![
+            "vlan100", "swp10", "eth0.100", "swp2", "bond0", "eth0", "lo", "swp1s1", "swp1",
+            "Mgmt", "eth0.20",
+        ];
+        sort_interface_names(&mut names);
+        assert_eq!(
+            names,
+            vec![
+                "Mgmt", "bond0", "eth0", "eth0.20", "eth0.100", "lo", "swp1", "swp1s1", "swp2",
+                "swp10", "vlan100",
+            ]
+        );
+
+        // Stable for names that compare equal
+        let mut names = vec![String::from("swp1"), String::from("swp01")];
+        sort_interface_names(&mut names);
+        assert_eq!(names, vec!["swp1", "swp01"]);
+
+        let mut interfaces: Vec<Interface> = ["swp10", "swp9", "bond1"]
+            .into_iter()
+            .map(|name| Interface::builder(name).build())
+            .collect();
+        sort_interfaces(&mut interfaces);
+        let names: Vec<&str> = interfaces.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["bond1", "swp9", "swp10"]);
+    }
+
+    #[test]
+    fn test_sort_natural_case_insensitive() {
+        assert_eq!(natural_ci("vlan2", "VLAN10"), Ordering::Less);
+        assert_eq!(natural_ci("VLAN10", "Vlan100"), Ordering::Less);
+        assert_eq!(natural_ci("Vlan100", "vlan2"), Ordering::Greater);
+        assert_eq!(natural_ci("Vlan100", "vlan100"), Ordering::Equal);
+        assert_eq!(natural("Vlan100", "vlan2"), Ordering::Less);
+
+        let mut names = vec!["Vlan100", "vlan2", "VLAN10", "bond0", "PortChannel1"];
+        names.sort_by(|a, b| natural_ci(a, b));
+        assert_eq!(
+            names,
+            vec!["bond0", "PortChannel1", "vlan2", "VLAN10", "Vlan100"]
+        );
+
+        assert_eq!(natural_ci("VLAN1", "vlan1"), Ordering::Equal);
+        assert_eq!(natural("VLAN1", "vlan1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_sort_natural_long_digit_runs() {
+        let big = "vni1234567890123456789012345";
+        let bigger = "vni1234567890123456789012346";
+        assert_eq!(natural(big, bigger), Ordering::Less);
+        assert_eq!(natural(bigger, big), Ordering::Greater);
+        assert_eq!(natural(big, big), Ordering::Equal);
+
+        // Mixed-length runs compare by magnitude
+        assert_eq!(
+            natural("if99999999999999999999999", "if100000000000000000000000"),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural("if2", "if10000000000000000000000000"),
+            Ordering::Less
+        );
+        assert_eq!(
+            natural("if00000000000000000000000002", "if3"),
+            Ordering::Less
+        );
+        assert_eq!(natural("swp01", "swp1"), Ordering::Equal);
+
+        let mut names = vec![
+            "x100000000000000000000000000",
+            "x9",
+            "x18446744073709551616",
+            "x18446744073709551615",
+        ];
+        names.sort_by(|a, b| natural(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "x9",
+                "x18446744073709551615",
+                "x18446744073709551616",
+                "x100000000000000000000000000"
+            ]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Addresses given with the `address` option.
+
+use crate::addr::parse_address_spec;
+use std::error::Error;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Represents an IP address with an optional prefix length, as configured by
+/// an `address` option.
+///
+/// `Address` accepts the forms an `address` value can take: a bare address
+/// (`10.0.0.1`), CIDR notation (`10.0.0.1/24`, `2001:db8::1/64`), and an IPv4
+/// address with a dotted netmask (`10.0.0.1/255.255.255.0`). It displays
+/// itself in CIDR notation. A separate `netmask` option is applied by
+/// [`Interface::addresses`](super::Interface::addresses).
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::Address;
+///
+/// let addr: Address = "10.0.0.1/255.255.255.0".parse().unwrap();
+/// assert_eq!(addr.prefix, Some(24));
+/// assert_eq!(addr.to_string(), "10.0.0.1/24");
+/// assert!(addr.in_subnet("10.0.0.0".parse().unwrap(), 16));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address {
+    /// The IP address.
+    pub ip: IpAddr,
+    /// The prefix length, if one was given.
+    pub prefix: Option<u8>,
+}
+
+impl Address {
+    /// Creates an `Address` from its parts.
+    pub fn new(ip: IpAddr, prefix: Option<u8>) -> Self {
+        Address { ip, prefix }
+    }
+
+    /// Returns `true` if the address lies within `network/prefix`.
+    ///
+    /// Only the IP address is compared; the prefix length of the address
+    /// itself is not. An address of the other family never matches, nor
+    /// does a prefix longer than the family allows.
+    pub fn in_subnet(&self, network: IpAddr, prefix: u8) -> bool {
+        in_subnet(self.ip, network, prefix)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.prefix {
+            Some(prefix) => write!(f, "{}/{}", self.ip, prefix),
+            None => write!(f, "{}", self.ip),
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressParseError;
+
+    /// Parses an `Address` from a string slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `AddressParseError` if the input is not an address in one
+    /// of the supported forms, or if the prefix length or netmask does not
+    /// fit the address family; see [`parse_address_spec`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ip, prefix) = parse_address_spec(s).map_err(|_| AddressParseError(s.to_string()))?;
+        Ok(Address { ip, prefix })
+    }
+}
+
+/// An error that occurs when parsing an `Address` from a string.
+#[derive(Debug, Clone)]
+pub struct AddressParseError(pub String);
+
+impl fmt::Display for AddressParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid address: {}", self.0)
+    }
+}
+
+impl Error for AddressParseError {}
+
+/// Returns `true` if `ip` lies within `network/prefix`.
+pub(crate) fn in_subnet(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forms() {
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        assert_eq!(
+            "10.0.0.1".parse::<Address>().unwrap(),
+            Address::new(ip, None)
+        );
+        assert_eq!(
+            "10.0.0.1/24".parse::<Address>().unwrap(),
+            Address::new(ip, Some(24))
+        );
+        assert_eq!(
+            "10.0.0.1/255.255.255.0".parse::<Address>().unwrap(),
+            Address::new(ip, Some(24))
+        );
+        assert_eq!(
+            "2001:db8::1/64".parse::<Address>().unwrap().to_string(),
+            "2001:db8::1/64"
+        );
+
+        assert!("10.0.0.1/33".parse::<Address>().is_err());
+        assert!("10.0.0.1/255.0.255.0".parse::<Address>().is_err());
+        assert!("2001:db8::1/255.255.255.0".parse::<Address>().is_err());
+        assert!("10.0.0.1/".parse::<Address>().is_err());
+        assert!("host.example".parse::<Address>().is_err());
+    }
+
+    #[test]
+    fn test_in_subnet() {
+        let addr: Address = "10.1.2.3/32".parse().unwrap();
+        assert!(addr.in_subnet("10.1.0.0".parse().unwrap(), 16));
+        assert!(addr.in_subnet("0.0.0.0".parse().unwrap(), 0));
+        assert!(addr.in_subnet("10.1.2.3".parse().unwrap(), 32));
+        assert!(!addr.in_subnet("10.1.2.2".parse().unwrap(), 32));
+        assert!(!addr.in_subnet("10.1.0.0".parse().unwrap(), 33));
+        assert!(!addr.in_subnet("::".parse().unwrap(), 0));
+
+        let addr: Address = "2001:db8:0:1::5".parse().unwrap();
+        assert!(addr.in_subnet("2001:db8::".parse().unwrap(), 48));
+        assert!(!addr.in_subnet("2001:db8::".parse().unwrap(), 64));
+    }
+}
@@ -0,0 +1,282 @@
+//! Field-by-field differences between two interfaces.
+
+use super::option::normalize_key;
+use super::{Family, Interface, Mapping, Method};
+use std::fmt;
+
+/// A single difference between two interfaces, as found by
+/// [`Interface::diff`].
+///
+/// `expected` holds the value of the interface `diff` was called on and
+/// `actual` the value of the one it was compared with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldDiff {
+    /// The `auto` flags differ.
+    Auto {
+        /// Whether the expected interface is brought up automatically.
+        expected: bool,
+        /// Whether the actual interface is.
+        actual: bool,
+    },
+    /// The `allow-*` classes differ.
+    Allow {
+        /// The `allow-*` classes of the expected interface.
+        expected: Vec<String>,
+        /// The `allow-*` classes of the actual interface.
+        actual: Vec<String>,
+    },
+    /// The families differ.
+    Family {
+        /// The family of the expected interface.
+        expected: Option<Family>,
+        /// The family of the actual interface.
+        actual: Option<Family>,
+    },
+    /// The methods differ.
+    Method {
+        /// The method of the expected interface.
+        expected: Option<Method>,
+        /// The method of the actual interface.
+        actual: Option<Method>,
+    },
+    /// The `mapping` stanzas differ.
+    Mapping {
+        /// The `mapping` stanza of the expected interface.
+        expected: Option<Mapping>,
+        /// The `mapping` stanza of the actual interface.
+        actual: Option<Mapping>,
+    },
+    /// One interface is disabled and the other is not.
+    Disabled {
+        /// Whether the expected interface is disabled.
+        expected: bool,
+        /// Whether the actual interface is.
+        actual: bool,
+    },
+    /// The values of an option differ. An empty `expected` means the option
+    /// is only set on the actual interface, and an empty `actual` that it is
+    /// missing there.
+    Option {
+        /// The key as written on the expected interface, or on the actual
+        /// one if the expected interface does not set it.
+        key: String,
+        /// The values on the expected interface, in order.
+        expected: Vec<String>,
+        /// The values on the actual interface, in order.
+        actual: Vec<String>,
+    },
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn or_none<T: fmt::Display>(value: &Option<T>) -> String {
+            value.as_ref().map_or("none".to_string(), T::to_string)
+        }
+        fn mapping(mapping: &Option<Mapping>) -> String {
+            mapping
+                .as_ref()
+                .map_or("none".to_string(), |m| m.script.clone())
+        }
+        match self {
+            FieldDiff::Auto { expected, actual } => {
+                write!(f, "auto: expected {}, found {}", expected, actual)
+            }
+            FieldDiff::Allow { expected, actual } => write!(
+                f,
+                "allow: expected [{}], found [{}]",
+                expected.join(", "),
+                actual.join(", ")
+            ),
+            FieldDiff::Family { expected, actual } => write!(
+                f,
+                "family: expected {}, found {}",
+                or_none(expected),
+                or_none(actual)
+            ),
+            FieldDiff::Method { expected, actual } => write!(
+                f,
+                "method: expected {}, found {}",
+                or_none(expected),
+                or_none(actual)
+            ),
+            FieldDiff::Mapping { expected, actual } => write!(
+                f,
+                "mapping: expected {}, found {}",
+                mapping(expected),
+                mapping(actual)
+            ),
+            FieldDiff::Disabled { expected, actual } => {
+                write!(f, "disabled: expected {}, found {}", expected, actual)
+            }
+            FieldDiff::Option {
+                key,
+                expected,
+                actual,
+            } if actual.is_empty() => {
+                write!(f, "{}: missing, expected {}", key, expected.join(", "))
+            }
+            FieldDiff::Option {
+                key,
+                expected,
+                actual,
+            } if expected.is_empty() => write!(f, "{}: unexpected {}", key, actual.join(", ")),
+            FieldDiff::Option {
+                key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}: expected {}, found {}",
+                key,
+                expected.join(", "),
+                actual.join(", ")
+            ),
+        }
+    }
+}
+
+impl Interface {
+    /// Lists the differences between this interface and `other`, field by
+    /// field.
+    ///
+    /// This interface supplies the `expected` side of each [`FieldDiff`] and
+    /// `other` the `actual` side. Options are compared per key, with keys
+    /// matched as by [`normalize_key`] and the values of repeated keys
+    /// compared in order; options with different keys may appear in any
+    /// order. Names, tags, and origins are not compared.
+    ///
+    /// # Returns
+    ///
+    /// The differences, with fields first and then options in the order their
+    /// keys first appear on this interface and then on `other`. The `Vec` is
+    /// empty if the interfaces configure the same thing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{FieldDiff, Interface};
+    ///
+    /// let expected: Interface = "auto swp1\niface swp1\n    mtu 9216".parse().unwrap();
+    /// let actual: Interface = "iface swp1\n    mtu 1500\n    link-speed 10000".parse().unwrap();
+    /// let diff = expected.diff(&actual);
+    /// assert_eq!(diff[0], FieldDiff::Auto { expected: true, actual: false });
+    /// assert_eq!(diff[1].to_string(), "mtu: expected 9216, found 1500");
+    /// assert_eq!(diff[2].to_string(), "link-speed: unexpected 10000");
+    /// ```
+    pub fn diff(&self, other: &Interface) -> Vec<FieldDiff> {
+        let mut diffs = Vec::new();
+        if self.auto != other.auto {
+            diffs.push(FieldDiff::Auto {
+                expected: self.auto,
+                actual: other.auto,
+            });
+        }
+        if self.allow != other.allow {
+            diffs.push(FieldDiff::Allow {
+                expected: self.allow.clone(),
+                actual: other.allow.clone(),
+            });
+        }
+        if self.family != other.family {
+            diffs.push(FieldDiff::Family {
+                expected: self.family.clone(),
+                actual: other.family.clone(),
+            });
+        }
+        if self.method != other.method {
+            diffs.push(FieldDiff::Method {
+                expected: self.method.clone(),
+                actual: other.method.clone(),
+            });
+        }
+        if self.mapping != other.mapping {
+            diffs.push(FieldDiff::Mapping {
+                expected: self.mapping.clone(),
+                actual: other.mapping.clone(),
+            });
+        }
+        if self.disabled != other.disabled {
+            diffs.push(FieldDiff::Disabled {
+                expected: self.disabled,
+                actual: other.disabled,
+            });
+        }
+
+        // Keys as written, with their normalized form, in order of first use
+        let mut keys: Vec<(&str, String)> = Vec::new();
+        for (key, _) in self.options.iter().chain(&other.options) {
+            let normalized = normalize_key(key);
+            if !keys.iter().any(|(_, seen)| *seen == normalized) {
+                keys.push((key, normalized.into_owned()));
+            }
+        }
+        let values = |iface: &Interface, normalized: &str| -> Vec<String> {
+            iface
+                .options
+                .iter()
+                .filter(|(key, _)| normalize_key(key) == normalized)
+                .map(|(_, value)| value.clone())
+                .collect()
+        };
+        for (key, normalized) in keys {
+            let expected = values(self, &normalized);
+            let actual = values(other, &normalized);
+            if expected != actual {
+                diffs.push(FieldDiff::Option {
+                    key: key.to_string(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+        diffs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let expected: Interface = "auto br0\niface br0 inet static\n    \
+            address 10.0.0.1/24\n    address 10.0.1.1/24\n    bridge_ports swp1\n    mtu 9000"
+            .parse()
+            .unwrap();
+        let same: Interface = "auto br0\niface br0 inet static\n    \
+            mtu 9000\n    address 10.0.0.1/24\n    bridge-ports swp1\n    address 10.0.1.1/24"
+            .parse()
+            .unwrap();
+        assert!(expected.diff(&same).is_empty());
+
+        let actual: Interface = "allow-hotplug br0\niface br0 inet dhcp\n    \
+            address 10.0.1.1/24\n    address 10.0.0.1/24\n    hwaddress 52:54:00:00:00:01"
+            .parse()
+            .unwrap();
+        let diffs: Vec<String> = expected
+            .diff(&actual)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            diffs,
+            vec![
+                "auto: expected true, found false",
+                "allow: expected [], found [hotplug]",
+                "method: expected static, found dhcp",
+                "address: expected 10.0.0.1/24, 10.0.1.1/24, found 10.0.1.1/24, 10.0.0.1/24",
+                "bridge_ports: missing, expected swp1",
+                "mtu: missing, expected 9000",
+                "hwaddress: unexpected 52:54:00:00:00:01",
+            ]
+        );
+        assert_eq!(
+            expected.diff(&actual)[4],
+            FieldDiff::Option {
+                key: "bridge_ports".to_string(),
+                expected: vec!["swp1".to_string()],
+                actual: Vec::new(),
+            }
+        );
+    }
+}
@@ -1,3 +1,6 @@
+//! Address families of `iface` stanzas.
+
+use super::Method;
 use std::error::Error;
 use std::fmt;
 use std::str::FromStr;
@@ -25,7 +28,7 @@ use std::str::FromStr;
 /// let family = Family::from_str("inet").unwrap();
 /// assert_eq!(family, Family::Inet);
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Family {
     /// The `inet` address family (IPv4).
     Inet,
@@ -37,15 +40,75 @@ pub enum Family {
     Can,
 }
 
-impl fmt::Display for Family {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let family_str = match self {
+impl Family {
+    /// Returns the family keyword as written in an `iface` stanza.
+    pub fn as_str(&self) -> &'static str {
+        match self {
             Family::Inet => "inet",
             Family::Inet6 => "inet6",
             Family::IpX => "ipx",
             Family::Can => "can",
-        };
-        write!(f, "{}", family_str)
+        }
+    }
+
+    /// Returns the methods `interfaces(5)` defines for this address family.
+    ///
+    /// | Family  | Methods                                                                     |
+    /// |---------|-----------------------------------------------------------------------------|
+    /// | `inet`  | loopback, static, manual, dhcp, bootp, tunnel, ppp, wvdial, ipv4ll          |
+    /// | `inet6` | auto, loopback, static, manual, dhcp, v4tunnel, 6to4                        |
+    /// | `ipx`   | static                                                                      |
+    /// | `can`   | static                                                                      |
+    ///
+    /// The table goes beyond the basic one of `(family, method)` pairs in two
+    /// places, both documented by `interfaces(5)`: `inet` also takes `ipv4ll`,
+    /// and `ipx` takes `static` rather than being skipped. Its `dynamic`
+    /// method has no variant and is accepted as [`Method::Other`].
+    ///
+    /// Vendors extend this list (for example ifupdown2's `ipv6ll`), so a method
+    /// missing from the table is suspicious rather than necessarily wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, Method};
+    ///
+    /// assert!(Family::Inet6.valid_methods().contains(&Method::Auto));
+    /// assert!(!Family::Can.valid_methods().contains(&Method::Dhcp));
+    /// ```
+    pub fn valid_methods(&self) -> &'static [Method] {
+        const INET: &[Method] = &[
+            Method::Loopback,
+            Method::Static,
+            Method::Manual,
+            Method::Dhcp,
+            Method::Bootp,
+            Method::Tunnel,
+            Method::Ppp,
+            Method::Wvdial,
+            Method::Ipv4ll,
+        ];
+        const INET6: &[Method] = &[
+            Method::Auto,
+            Method::Loopback,
+            Method::Static,
+            Method::Manual,
+            Method::Dhcp,
+            Method::V4Tunnel,
+            Method::SixToFour,
+        ];
+        const STATIC_ONLY: &[Method] = &[Method::Static];
+        match self {
+            Family::Inet => INET,
+            Family::Inet6 => INET6,
+            Family::IpX | Family::Can => STATIC_ONLY,
+        }
+    }
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
@@ -68,6 +131,21 @@ impl FromStr for Family {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Family {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Family {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// An error that occurs when parsing a `Family` from a string.
 ///
 /// This error is returned when the input string does not correspond to any
@@ -82,3 +160,36 @@ impl fmt::Display for FamilyParseError {
 }
 
 impl Error for FamilyParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_methods() {
+        use Method::*;
+        assert_eq!(
+            Family::Inet.valid_methods(),
+            [Loopback, Static, Manual, Dhcp, Bootp, Tunnel, Ppp, Wvdial, Ipv4ll]
+        );
+        assert_eq!(
+            Family::Inet6.valid_methods(),
+            [Auto, Loopback, Static, Manual, Dhcp, V4Tunnel, SixToFour]
+        );
+        assert_eq!(Family::IpX.valid_methods(), [Static]);
+        assert_eq!(Family::Can.valid_methods(), [Static]);
+        assert!(Ipv4ll.valid_for(&Family::Inet));
+        assert!(Method::from("dynamic").valid_for(&Family::IpX));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        for family in [Family::Inet, Family::Inet6, Family::IpX, Family::Can] {
+            let json = serde_json::to_string(&family).unwrap();
+            assert_eq!(json, format!("\"{}\"", family));
+            assert_eq!(serde_json::from_str::<Family>(&json).unwrap(), family);
+        }
+        assert!(serde_json::from_str::<Family>("\"inet7\"").is_err());
+    }
+}
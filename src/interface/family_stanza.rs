@@ -0,0 +1,69 @@
+//! Further `iface` stanzas of an interface for other address families.
+
+use super::{Family, Method, OptionKey};
+
+/// A further `iface` stanza of an interface, for another address family.
+///
+/// A dual-stack interface is configured with one stanza per family:
+///
+/// ```text
+/// iface eth0 inet static
+///     address 192.0.2.10/24
+///
+/// iface eth0 inet6 static
+///     address 2001:db8::10/64
+/// ```
+///
+/// The first stanza is held by the [`Interface`](super::Interface) itself,
+/// in its `family`, `method`, and `options` fields. Each later stanza with a
+/// different family is kept as a `FamilyStanza` in
+/// [`Interface::family_stanzas`](super::Interface::family_stanzas), and is
+/// written right after the first one.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::{Family, FamilyStanza, Interface, Method};
+///
+/// let iface = Interface::builder("eth0")
+///     .with_family(Family::Inet)
+///     .with_method("dhcp")
+///     .with_family_stanza(FamilyStanza {
+///         family: Some(Family::Inet6),
+///         method: Some(Method::Auto),
+///         options: vec![("privext".into(), "2".to_string())],
+///     })
+///     .build();
+/// assert_eq!(
+///     iface.to_string(),
+///     "iface eth0 inet dhcp\n\niface eth0 inet6 auto\n    privext 2\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FamilyStanza {
+    /// The address family of the stanza.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub family: Option<Family>,
+    /// The method of configuration.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub method: Option<Method>,
+    /// The options of the stanza, in file order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub options: Vec<(OptionKey, String)>,
+}
+
+/// One `iface` stanza of an interface, as returned by
+/// [`Interface::stanzas`](super::Interface::stanzas).
+///
+/// The first stanza is made of the fields of the interface itself; the
+/// others borrow its [`FamilyStanza`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StanzaRef<'a> {
+    /// The address family of the stanza.
+    pub family: Option<&'a Family>,
+    /// The method of configuration.
+    pub method: Option<&'a Method>,
+    /// The options of the stanza, in file order.
+    pub options: &'a [(OptionKey, String)],
+}
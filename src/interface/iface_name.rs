@@ -0,0 +1,303 @@
+//! Interface names: validation and the key type of a collection.
+
+use crate::helper::sort::natural;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::Deref;
+
+/// The name of an interface, ordered naturally.
+///
+/// `IfaceName` orders names with [`natural`], so `swp2` sorts before
+/// `swp10`. Names that compare equal naturally but are spelled differently
+/// (such as `swp01` and `swp1`) are ordered byte-wise, keeping the order
+/// consistent with equality so `IfaceName` can be used as a map key.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::IfaceName;
+///
+/// let mut names: Vec<IfaceName> = ["swp10", "swp2", "swp1"].map(IfaceName::from).to_vec();
+/// names.sort();
+/// assert_eq!(names, ["swp1", "swp2", "swp10"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IfaceName(pub(crate) String);
+
+impl IfaceName {
+    /// Returns the name as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the underlying `String`.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl Ord for IfaceName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        natural(&self.0, &other.0).then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for IfaceName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Deref for IfaceName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Lets hash maps keyed by `IfaceName` be looked up with a `&str`, as the
+/// derived `Eq` and `Hash` are those of the name.
+///
+/// The natural [`Ord`] differs from that of `str`, so ordered maps must still
+/// be looked up with an `IfaceName`.
+impl Borrow<str> for IfaceName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for IfaceName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for IfaceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for IfaceName {
+    fn from(name: String) -> Self {
+        IfaceName(name)
+    }
+}
+
+impl From<&str> for IfaceName {
+    fn from(name: &str) -> Self {
+        IfaceName(name.to_string())
+    }
+}
+
+impl From<IfaceName> for String {
+    fn from(name: IfaceName) -> Self {
+        name.0
+    }
+}
+
+impl PartialEq<str> for IfaceName {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for IfaceName {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// The longest interface name Linux accepts, in bytes (`IFNAMSIZ` minus the
+/// terminating NUL).
+pub const MAX_NAME_LEN: usize = 15;
+
+/// Returns the parent of an alias name of the form `<parent>:<label>`, such
+/// as `eth0` for `eth0:1`, or `None` if `name` is not one.
+pub(crate) fn alias_parent(name: &str) -> Option<&str> {
+    let (parent, label) = name.split_once(':')?;
+    (!parent.is_empty() && !label.is_empty() && !label.contains(':')).then_some(parent)
+}
+
+/// Splits a dotted VLAN name into its raw device and VLAN id, such as `eth0`
+/// and 100 for `eth0.100`, or returns `None` if the part after the last `.`
+/// is not a number. A double-tagged name like `swp1.100.200` splits into the
+/// outer VLAN `swp1.100` and 200.
+pub(crate) fn vlan_split(name: &str) -> Option<(&str, u16)> {
+    let (raw_device, id) = name.rsplit_once('.')?;
+    if raw_device.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((raw_device, id.parse().ok()?))
+}
+
+/// Checks that `name` is usable as a Linux interface name.
+///
+/// A name must be 1 to [`MAX_NAME_LEN`] bytes long, must not contain
+/// whitespace or `/`, and must not be `.` or `..`. VLAN names such as
+/// `eth0.100` are valid, as are alias names such as `eth0:1`; a name with a
+/// `:` must have this `<parent>:<label>` form.
+///
+/// # Errors
+///
+/// Returns a [`NameError`] describing the first rule the name violates.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::{validate_interface_name, NameError};
+///
+/// assert!(validate_interface_name("swp1.100").is_ok());
+/// assert_eq!(
+///     validate_interface_name("swp1 "),
+///     Err(NameError::Whitespace("swp1 ".to_string()))
+/// );
+/// ```
+pub fn validate_interface_name(name: &str) -> Result<(), NameError> {
+    if name.is_empty() {
+        Err(NameError::Empty)
+    } else if name == "." || name == ".." {
+        Err(NameError::Reserved(name.to_string()))
+    } else if name.chars().any(char::is_whitespace) {
+        Err(NameError::Whitespace(name.to_string()))
+    } else if name.contains('/') {
+        Err(NameError::Slash(name.to_string()))
+    } else if name.contains(':') && alias_parent(name).is_none() {
+        Err(NameError::Alias(name.to_string()))
+    } else if name.len() > MAX_NAME_LEN {
+        Err(NameError::TooLong(name.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// An error describing why a string is not a valid interface name; see
+/// [`validate_interface_name`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    /// The name is empty.
+    Empty,
+    /// The name is longer than [`MAX_NAME_LEN`] bytes.
+    TooLong(String),
+    /// The name contains whitespace.
+    Whitespace(String),
+    /// The name contains `/`.
+    Slash(String),
+    /// The name is `.` or `..`.
+    Reserved(String),
+    /// The name contains `:` but is not an alias name of the form
+    /// `<parent>:<label>`.
+    Alias(String),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "Interface name is empty"),
+            NameError::TooLong(name) => write!(
+                f,
+                "Interface name {:?} is {} bytes long, longer than {}",
+                name,
+                name.len(),
+                MAX_NAME_LEN
+            ),
+            NameError::Whitespace(name) => {
+                write!(f, "Interface name {:?} contains whitespace", name)
+            }
+            NameError::Slash(name) => write!(f, "Interface name {:?} contains '/'", name),
+            NameError::Reserved(name) => write!(f, "Interface name {:?} is reserved", name),
+            NameError::Alias(name) => write!(
+                f,
+                "Interface name {:?} is not an alias of the form <parent>:<label>",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for NameError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashSet};
+
+    #[test]
+    fn test_iface_name_order() {
+        let name = |s: &str| IfaceName::from(s);
+        assert_eq!(name("swp2").cmp(&name("swp10")), Ordering::Less);
+        assert_eq!(
+            name("vni99999999999999999999").cmp(&name("vni100000000000000000000")),
+            Ordering::Less
+        );
+
+        // Naturally equal names stay distinct keys
+        let names: BTreeSet<IfaceName> = ["swp1", "swp01", "swp1"].map(IfaceName::from).into();
+        assert_eq!(names.len(), 2);
+        let names: Vec<&str> = names.iter().map(IfaceName::as_str).collect();
+        assert_eq!(names, vec!["swp01", "swp1"]);
+    }
+
+    #[test]
+    fn test_iface_name_hash_lookup_by_str() {
+        let names: HashSet<IfaceName> = ["swp1", "swp01"].map(IfaceName::from).into();
+        assert!(names.contains("swp01"));
+        assert!(names.contains("swp1"));
+        assert!(!names.contains("swp001"));
+    }
+
+    #[test]
+    fn test_validate_interface_name() {
+        let good = [
+            "lo",
+            "eth0",
+            "swp1",
+            "eth0:1",
+            "eth0.100:web",
+            "eth0.100",
+            "bond0.100.200",
+            "vlan-4094",
+            "br_default",
+            "a",
+            "vni-16777215xyz",
+            "...",
+        ];
+        for name in good {
+            assert_eq!(validate_interface_name(name), Ok(()), "{}", name);
+        }
+
+        let bad = [
+            ("", NameError::Empty),
+            (".", NameError::Reserved(".".to_string())),
+            ("..", NameError::Reserved("..".to_string())),
+            ("swp1 ", NameError::Whitespace("swp1 ".to_string())),
+            (" swp1", NameError::Whitespace(" swp1".to_string())),
+            ("swp\t1", NameError::Whitespace("swp\t1".to_string())),
+            ("eth0/1", NameError::Slash("eth0/1".to_string())),
+            ("eth0:", NameError::Alias("eth0:".to_string())),
+            (":1", NameError::Alias(":1".to_string())),
+            ("eth0:1:2", NameError::Alias("eth0:1:2".to_string())),
+            (
+                "vni-16777215-xyz",
+                NameError::TooLong("vni-16777215-xyz".to_string()),
+            ),
+        ];
+        for (name, err) in bad {
+            assert_eq!(validate_interface_name(name), Err(err), "{:?}", name);
+        }
+
+        assert_eq!(
+            validate_interface_name("swp1 ").unwrap_err().to_string(),
+            "Interface name \"swp1 \" contains whitespace"
+        );
+        assert_eq!(
+            validate_interface_name("abcdefghijklmnop")
+                .unwrap_err()
+                .to_string(),
+            "Interface name \"abcdefghijklmnop\" is 16 bytes long, longer than 15"
+        );
+    }
+}
@@ -1,4 +1,11 @@
-use super::{Family, Interface, Mapping};
+//! The builder for [`Interface`].
+
+use super::option::{intern_key, remove_matching, remove_matching_value, set_single};
+use super::{
+    validate_interface_name, Family, FamilyStanza, Interface, Mapping, Method, NameError,
+    OptionKey, Origin,
+};
+use std::collections::BTreeMap;
 
 /// A builder for constructing [`Interface`] instances.
 ///
@@ -25,9 +32,14 @@ pub struct InterfaceBuilder {
     pub(crate) auto: bool,
     pub(crate) allow: Vec<String>,
     pub(crate) family: Option<Family>,
-    pub(crate) method: Option<String>,
-    pub(crate) options: Vec<(String, String)>,
+    pub(crate) method: Option<Method>,
+    pub(crate) options: Vec<(OptionKey, String)>,
+    pub(crate) family_stanzas: Vec<FamilyStanza>,
     pub(crate) mapping: Option<Mapping>,
+    pub(crate) disabled: bool,
+    pub(crate) tags: BTreeMap<String, String>,
+    pub(crate) origin: Option<Origin>,
+    pub(crate) placeholder: bool,
 }
 
 impl InterfaceBuilder {
@@ -52,7 +64,12 @@ impl InterfaceBuilder {
             family: None,
             method: None,
             options: Vec::new(),
+            family_stanzas: Vec::new(),
             mapping: None,
+            disabled: false,
+            tags: BTreeMap::new(),
+            origin: None,
+            placeholder: false,
         }
     }
 
@@ -114,17 +131,21 @@ impl InterfaceBuilder {
     ///
     /// # Arguments
     ///
-    /// * `method` - A string representing the method (e.g., `"static"`, `"dhcp"`).
+    /// * `method` - A string representing the method (e.g., `"static"`, `"dhcp"`),
+    ///   or a [`Method`]. Strings are converted with [`Method::from`], so unknown
+    ///   methods are kept as [`Method::Other`].
     ///
     /// # Examples
     ///
     /// ```rust
-    /// # use interface_rs::interface::Interface;
+    /// # use interface_rs::interface::{Interface, Method};
     /// let builder = Interface::builder("eth0")
     ///     .with_method("dhcp");
+    /// let builder = Interface::builder("eth1")
+    ///     .with_method(Method::Static);
     /// ```
     pub fn with_method(mut self, method: impl Into<String>) -> Self {
-        self.method = Some(method.into());
+        self.method = Some(Method::from(method.into()));
         self
     }
 
@@ -143,7 +164,56 @@ impl InterfaceBuilder {
     ///     .with_option("address", "192.168.1.100");
     /// ```
     pub fn with_option(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
-        self.options.push((key.into(), value.into()));
+        self.options.push((intern_key(key.into()), value.into()));
+        self
+    }
+
+    /// Sets an option to a single value, replacing earlier lines for the key.
+    ///
+    /// This behaves like [`Interface::set_option`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The option name (e.g., `"mtu"`).
+    /// * `value` - The option value (e.g., `"9000"`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use interface_rs::interface::Interface;
+    /// let iface = Interface::builder("eth0")
+    ///     .with_option("mtu", "1500")
+    ///     .set_option("mtu", "9000")
+    ///     .build();
+    /// assert_eq!(iface.get_option("mtu"), Some("9000"));
+    /// ```
+    pub fn set_option(mut self, key: &str, value: &str) -> Self {
+        set_single(&mut self.options, key, value, false);
+        self
+    }
+
+    /// Adds a further `iface` stanza for another address family.
+    ///
+    /// # Arguments
+    ///
+    /// * `stanza` - The [`FamilyStanza`], written after the first stanza and
+    ///   any added before it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, FamilyStanza, Interface, Method};
+    /// let builder = Interface::builder("eth0")
+    ///     .with_family(Family::Inet)
+    ///     .with_method("dhcp")
+    ///     .with_family_stanza(FamilyStanza {
+    ///         family: Some(Family::Inet6),
+    ///         method: Some(Method::Dhcp),
+    ///         options: Vec::new(),
+    ///     });
+    /// ```
+    pub fn with_family_stanza(mut self, stanza: FamilyStanza) -> Self {
+        self.family_stanzas.push(stanza);
         self
     }
 
@@ -169,10 +239,50 @@ impl InterfaceBuilder {
         self
     }
 
+    /// Sets whether the stanza is commented out.
+    ///
+    /// # Arguments
+    ///
+    /// * `disabled` - A boolean indicating if `ifup` should ignore the interface.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use interface_rs::interface::Interface;
+    /// let iface = Interface::builder("eth0").with_disabled(true).build();
+    /// assert_eq!(iface.to_string(), "#disabled# iface eth0\n");
+    /// ```
+    pub fn with_disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Sets a tag, replacing any previous value for the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The tag key (e.g., `"tenant"`).
+    /// * `value` - The tag value (e.g., `"acme"`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use interface_rs::interface::Interface;
+    /// let iface = Interface::builder("swp1")
+    ///     .with_tag("tier", "prod")
+    ///     .with_tag("tenant", "acme")
+    ///     .build();
+    /// assert_eq!(iface.to_string(), "# tags: tenant=acme, tier=prod\niface swp1\n");
+    /// ```
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
     /// Removes all options with the specified key from the interface configuration.
     ///
     /// This method removes all key-value pairs in the options where the key matches
-    /// the specified `key`.
+    /// the specified `key`, folding keys like [`Interface::remove_option`].
     ///
     /// # Arguments
     ///
@@ -195,14 +305,15 @@ impl InterfaceBuilder {
     /// // The builder no longer contains any "address" options.
     /// ```
     pub fn remove_option(mut self, key: &str) -> Self {
-        self.options.retain(|(k, _)| k != key);
+        remove_matching(&mut self.options, key, false);
         self
     }
 
     /// Removes a specific option by its key and value from the interface configuration.
     ///
     /// This method removes only the key-value pair in the options where both the key
-    /// matches the specified `key` and the value matches the specified `value`.
+    /// matches the specified `key` and the value matches the specified `value`, like
+    /// [`Interface::remove_option_value`].
     ///
     /// # Arguments
     ///
@@ -227,7 +338,7 @@ impl InterfaceBuilder {
     /// // but the pair ("address", "192.168.1.100") is removed.
     /// ```
     pub fn remove_option_value(mut self, key: &str, value: &str) -> Self {
-        self.options.retain(|(k, v)| !(k == key && v == value));
+        remove_matching_value(&mut self.options, key, value, false);
         self
     }
 
@@ -255,9 +366,35 @@ impl InterfaceBuilder {
             family: self.family,
             method: self.method,
             options: self.options,
+            family_stanzas: self.family_stanzas,
             mapping: self.mapping,
+            disabled: self.disabled,
+            tags: self.tags,
+            origin: self.origin,
+            placeholder: self.placeholder,
         }
     }
+
+    /// Builds the [`Interface`] instance after checking its name with
+    /// [`validate_interface_name`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NameError`] if the name is not a valid Linux interface
+    /// name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// assert!(Interface::builder("swp1").try_build().is_ok());
+    /// assert!(Interface::builder("swp1 ").try_build().is_err());
+    /// ```
+    pub fn try_build(self) -> Result<Interface, NameError> {
+        validate_interface_name(&self.name)?;
+        Ok(self.build())
+    }
 }
 
 #[cfg(test)]
@@ -276,7 +413,7 @@ mod tests {
         assert_eq!(iface.options.len(), 1);
         assert_eq!(
             iface.options[0],
-            ("netmask".to_string(), "255.255.255.0".to_string())
+            ("netmask".into(), "255.255.255.0".to_string())
         );
     }
 
@@ -293,12 +430,48 @@ mod tests {
         assert_eq!(iface.options.len(), 3);
         assert!(iface
             .options
-            .contains(&("netmask".to_string(), "255.255.255.0".to_string())));
+            .contains(&("netmask".into(), "255.255.255.0".to_string())));
         assert!(iface
             .options
-            .contains(&("address".to_string(), "192.168.1.51".to_string())));
+            .contains(&("address".into(), "192.168.1.51".to_string())));
         assert!(iface
             .options
-            .contains(&("address".to_string(), "192.168.1.52".to_string())));
+            .contains(&("address".into(), "192.168.1.52".to_string())));
+    }
+
+    #[test]
+    fn test_in_place_edits_match_builder() {
+        let built = Interface::builder("eth0")
+            .with_option("address", "192.168.1.50")
+            .with_option("mtu", "1500")
+            .with_option("address", "192.168.1.51")
+            .with_option("post_up", "/usr/local/bin/a")
+            .with_option("mtu", "9000")
+            .remove_option_value("address", "192.168.1.50")
+            .remove_option("post-up")
+            .set_option("MTU", "9216")
+            .build();
+
+        let mut edited = Interface::builder("eth0").build();
+        edited.add_option("address", "192.168.1.50");
+        edited.add_option("mtu", "1500");
+        edited.add_option("address", "192.168.1.51");
+        edited.add_option("post_up", "/usr/local/bin/a");
+        edited.add_option("mtu", "9000");
+        assert!(edited.remove_option_value("address", "192.168.1.50"));
+        assert!(!edited.remove_option_value("address", "192.168.1.50"));
+        assert_eq!(edited.remove_option("post-up"), 1);
+        assert!(edited.set_option("MTU", "9216"));
+        assert!(!edited.set_option("mtu", "9216"));
+
+        assert_eq!(edited, built);
+        // The first mtu line keeps its position and spelling
+        assert_eq!(
+            built.options,
+            vec![
+                ("mtu".into(), "9216".to_string()),
+                ("address".into(), "192.168.1.51".to_string()),
+            ]
+        );
     }
 }
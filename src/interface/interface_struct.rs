@@ -1,5 +1,45 @@
-use super::{Family, InterfaceBuilder, Mapping};
+//! The [`Interface`] struct, one interface of an `interfaces(5)` file.
+
+use super::iface_name;
+use super::option::{
+    intern_key, keys_match, normalize_key, remove_matching, remove_matching_value, set_single,
+    DHCP_KEYS,
+};
+use super::{
+    Address, Family, FamilyStanza, InterfaceBuilder, InterfaceKind, InterfaceOption, Mapping,
+    Method, OptionKey, Origin, ScriptPhase, StanzaRef, VlanProtocol,
+};
+use crate::addr::{canonical_ipv6, parse_prefix};
+use crate::error::ParserError;
+use crate::parser::{Parser, ParserOptions};
+use crate::write_options::{ValueAlignment, WriteOptions};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Static addressing options, stripped by [`Interface::to_dhcp`] and
+/// replaced by [`Interface::to_static`].
+const STATIC_OPTIONS: &[&str] = &[
+    "address",
+    "netmask",
+    "broadcast",
+    "gateway",
+    "pointopoint",
+    "scope",
+    "dns-nameservers",
+];
+
+/// Options whose IPv6 addresses are rewritten when
+/// [`WriteOptions::canonical_ipv6`] is set.
+const IPV6_OPTIONS: &[&str] = &[
+    "address",
+    "gateway",
+    "dns-nameservers",
+    "vxlan-local-tunnelip",
+];
 
 /// Represents a network interface configuration in an `interfaces(5)` file.
 ///
@@ -26,22 +66,101 @@ use std::fmt;
 ///     .with_option("mtu", "1500")
 ///     .build();
 /// ```
+///
+/// Parsing an `Interface` from a stanza:
+///
+/// ```rust
+/// use interface_rs::interface::{Interface, Method};
+///
+/// let iface: Interface = "auto eth0\niface eth0 inet dhcp\n    mtu 1500".parse().unwrap();
+/// assert!(iface.auto);
+/// assert_eq!(iface.method, Some(Method::Dhcp));
+/// ```
+///
+/// # Equality and hashing
+///
+/// `Interface` implements `PartialEq`, `Eq`, and `Hash` in their strict,
+/// order-sensitive form: two interfaces are equal only if every public field
+/// is equal, including the order of `allow` entries and `options`. The
+/// [`origin`](Interface::origin) is metadata and is ignored. Hashing is
+/// consistent with this equality, so interfaces can be used as keys in
+/// `HashMap`s and `HashSet`s for deduplication.
+///
+/// # Serialization
+///
+/// With the `serde` feature, an `Interface` serializes as a struct with the
+/// fields below. `family` and `method` are strings as written in the file,
+/// and `options` is an array of `[key, value]` pairs in file order. Only
+/// `name` is required when deserializing. The origin is not serialized, and
+/// neither are empty [`family_stanzas`](Interface::family_stanzas).
+///
+/// ```json
+/// {
+///   "name": "eth0",
+///   "auto": true,
+///   "allow": ["hotplug"],
+///   "family": "inet",
+///   "method": "static",
+///   "options": [["address", "192.168.1.10/24"], ["mtu", "1500"]],
+///   "mapping": null,
+///   "disabled": false,
+///   "tags": {"tier": "prod"}
+/// }
+/// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Interface {
     /// The name of the interface (e.g., `"eth0"`).
     pub name: String,
     /// Indicates if the interface is set to start automatically.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub auto: bool,
     /// A list of `allow-*` directives associated with the interface.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub allow: Vec<String>,
     /// The address family (e.g., `inet`).
+    #[cfg_attr(feature = "serde", serde(default))]
     pub family: Option<Family>,
-    /// The method of configuration (e.g., `"static"`, `"dhcp"`).
-    pub method: Option<String>,
+    /// The method of configuration (e.g., `static`, `dhcp`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub method: Option<Method>,
     /// A list of options specified under the `iface` stanza.
-    pub options: Vec<(String, String)>,
+    ///
+    /// Keys are [`OptionKey`]s, so well-known keys share storage; compare
+    /// them with `==` against a `&str` or borrow them with `as_ref()`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub options: Vec<(OptionKey, String)>,
+    /// The further `iface` stanzas of the interface for other address
+    /// families, in file order, such as the `inet6` stanza of a dual-stack
+    /// interface.
+    ///
+    /// `family`, `method`, and `options` hold the first stanza; option
+    /// lookups like [`get_option`](Interface::get_option) only see that
+    /// one. Use [`for_family`](Interface::for_family) to look at another.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub family_stanzas: Vec<FamilyStanza>,
     /// Optional mapping configuration for the interface.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub mapping: Option<Mapping>,
+    /// Indicates if the stanza is commented out, so `ifup` ignores it; see
+    /// [`NetworkInterfaces::disable_interface`](crate::NetworkInterfaces::disable_interface).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub disabled: bool,
+    /// Key-value tags, read from and written as a magic comment such as
+    /// `# tags: tenant=acme, tier=prod` on the lines before the stanza; see
+    /// [`ParserOptions::tag_prefix`](crate::ParserOptions::tag_prefix).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: BTreeMap<String, String>,
+    /// Where the interface was parsed from.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) origin: Option<Origin>,
+    /// Whether the parser created the interface for a name on an `auto` or
+    /// `allow-*` line and has not seen an `iface` or `mapping` stanza for it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) placeholder: bool,
 }
 
 impl Interface {
@@ -62,6 +181,43 @@ impl Interface {
         InterfaceBuilder::new(name)
     }
 
+    /// Parses a single stanza into an `Interface`.
+    ///
+    /// The snippet is run through the same parser as a full `interfaces(5)`
+    /// file, so `auto`/`allow-*` lines, comments, and options behave exactly
+    /// as they do on load. This is also available through [`FromStr`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParserError` if the snippet cannot be parsed, or if it does
+    /// not define exactly one interface.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let iface = Interface::parse_stanza(
+    ///     "auto swp1\niface swp1 inet static\n    address 10.0.0.1/24",
+    /// ).unwrap();
+    /// assert_eq!(iface.name, "swp1");
+    /// assert_eq!(iface.get_option("address"), Some("10.0.0.1/24"));
+    ///
+    /// assert!(Interface::parse_stanza("iface a\niface b").is_err());
+    /// ```
+    pub fn parse_stanza(s: &str) -> Result<Interface, ParserError> {
+        let (interfaces, _comments, _sources) = Parser::new().parse(s)?;
+        if interfaces.len() != 1 {
+            return Err(ParserError {
+                message: format!("Expected exactly one interface, found {}", interfaces.len()),
+                line: None,
+                stanza: None,
+                raw_line: None,
+            });
+        }
+        Ok(interfaces.into_values().next().unwrap())
+    }
+
     /// Creates a new [`InterfaceBuilder`] initialized with this `Interface`'s data.
     ///
     /// This method allows you to modify an existing `Interface` using the builder pattern.
@@ -69,13 +225,9 @@ impl Interface {
     /// # Examples
     ///
     /// ```rust
-    /// use interface_rs::interface::{Interface, Family};
+    /// use interface_rs::interface::Interface;
     ///
-    /// let iface = Interface::builder("eth0")
-    ///     .with_auto(true)
-    ///     .with_family(Family::Inet)
-    ///     .with_method("dhcp")
-    ///     .build();
+    /// let iface: Interface = "auto eth0\niface eth0 inet dhcp".parse().unwrap();
     ///
     /// // Modify the existing interface
     /// let modified_iface = iface.edit()
@@ -91,40 +243,1533 @@ impl Interface {
             family: self.family.clone(),
             method: self.method.clone(),
             options: self.options.clone(),
+            family_stanzas: self.family_stanzas.clone(),
             mapping: self.mapping.clone(),
+            disabled: self.disabled,
+            tags: self.tags.clone(),
+            origin: self.origin.clone(),
+            placeholder: self.placeholder,
+        }
+    }
+
+    /// Returns a copy of this interface rewritten to use the `static` method
+    /// with the given addressing.
+    ///
+    /// The family is set from `addr` (`inet` or `inet6`). DHCP-only options
+    /// (the [`DHCP_KEYS`], such as `hostname`,
+    /// `client`, and `leasetime`) are dropped, as is any
+    /// previous static addressing. `address`, `gateway`, and
+    /// `dns-nameservers` are then inserted, in that order, ahead of the
+    /// remaining options.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to assign.
+    /// * `prefix` - The prefix length, written as `address <addr>/<prefix>`.
+    /// * `gateway` - The default gateway, if any.
+    /// * `dns` - Name servers for `dns-nameservers`; omitted if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Interface, Method};
+    ///
+    /// let iface: Interface = "iface eth0 inet dhcp\n    hostname box".parse().unwrap();
+    /// let iface = iface.to_static("192.0.2.10".parse().unwrap(), 24, None, &[]);
+    /// assert_eq!(iface.method, Some(Method::Static));
+    /// assert_eq!(iface.get_option("address"), Some("192.0.2.10/24"));
+    /// assert_eq!(iface.get_option("hostname"), None);
+    /// ```
+    pub fn to_static(
+        &self,
+        addr: IpAddr,
+        prefix: u8,
+        gateway: Option<IpAddr>,
+        dns: &[IpAddr],
+    ) -> Interface {
+        let family = if addr.is_ipv4() {
+            Family::Inet
+        } else {
+            Family::Inet6
+        };
+        let mut options: Vec<(OptionKey, String)> =
+            vec![("address".into(), format!("{}/{}", addr, prefix))];
+        if let Some(gateway) = gateway {
+            options.push(("gateway".into(), gateway.to_string()));
+        }
+        if !dns.is_empty() {
+            let servers: Vec<String> = dns.iter().map(IpAddr::to_string).collect();
+            options.push(("dns-nameservers".into(), servers.join(" ")));
         }
+        options.extend(self.options_without(&[DHCP_KEYS, STATIC_OPTIONS]));
+
+        let mut builder = self.edit().with_family(family).with_method("static");
+        builder.options = options;
+        builder.build()
+    }
+
+    /// Returns a copy of this interface rewritten to use the `dhcp` method.
+    ///
+    /// Static addressing options (`address`, `netmask`, `gateway`,
+    /// `dns-nameservers`, ...) are dropped; everything else is kept in order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Interface, Method};
+    ///
+    /// let iface: Interface = "iface eth0 inet static\n    address 192.0.2.10/24\n    mtu 9000"
+    ///     .parse()
+    ///     .unwrap();
+    /// let iface = iface.to_dhcp();
+    /// assert_eq!(iface.to_string(), "iface eth0 inet dhcp\n    mtu 9000\n");
+    /// ```
+    pub fn to_dhcp(&self) -> Interface {
+        let mut builder = self.edit().with_method("dhcp");
+        builder.options = self.options_without(&[STATIC_OPTIONS]);
+        builder.build()
+    }
+
+    /// Returns the options whose keys are not in any of `lists`.
+    fn options_without(&self, lists: &[&[&str]]) -> Vec<(OptionKey, String)> {
+        self.options
+            .iter()
+            .filter(|(k, _)| {
+                !lists
+                    .iter()
+                    .flat_map(|list| list.iter())
+                    .any(|key| keys_match(k, key, false))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns where the interface was parsed from, or `None` if it was built
+    /// in memory.
+    ///
+    /// The origin survives [`edit`](Interface::edit), so an interface that is
+    /// modified and re-added keeps pointing at its definition.
+    pub fn origin(&self) -> Option<&Origin> {
+        self.origin.as_ref()
+    }
+
+    /// Returns the lines of the first `iface` block of the interface.
+    ///
+    /// # Returns
+    ///
+    /// The 1-based, half-open range from the `iface` line to the last option
+    /// line, or `None` if the interface was built in memory or has no stanza.
+    /// The `auto` and `allow-*` lines are not part of the span; see
+    /// [`Origin::directive_lines`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let iface: Interface = "iface eth0 inet static\n    address 10.0.0.1/24\n    mtu 9000"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(iface.source_span(), Some(1..4));
+    /// ```
+    pub fn source_span(&self) -> Option<Range<usize>> {
+        self.origin.as_ref().and_then(|origin| origin.span.clone())
+    }
+
+    /// Returns `true` if the interface has an `iface` or `mapping` stanza.
+    ///
+    /// The parser creates a placeholder `Interface` for a name that only
+    /// appears on `auto` or `allow-*` lines, with no stanza. Such
+    /// placeholders are counted by
+    /// [`NetworkInterfaces::len`](crate::NetworkInterfaces::len) like any
+    /// other interface but are not declared. A placeholder becomes declared
+    /// once it is given a family, method, options, or mapping. Interfaces
+    /// built in memory are always declared.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let placeholder: Interface = "auto eth0".parse().unwrap();
+    /// assert!(!placeholder.is_declared());
+    /// let bare: Interface = "auto eth0\niface eth0".parse().unwrap();
+    /// assert!(bare.is_declared());
+    /// assert!(Interface::builder("eth0").build().is_declared());
+    /// ```
+    pub fn is_declared(&self) -> bool {
+        !self.placeholder
+            || self.family.is_some()
+            || self.method.is_some()
+            || !self.options.is_empty()
+            || self.mapping.is_some()
+    }
+
+    /// Returns the method of configuration as written in the file, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let iface: Interface = "iface eth0 inet dhcp".parse().unwrap();
+    /// assert_eq!(iface.method_str(), Some("dhcp"));
+    /// ```
+    pub fn method_str(&self) -> Option<&str> {
+        self.method.as_ref().map(Method::as_str)
+    }
+
+    /// Returns the address families the interface has a stanza for: the
+    /// family of the first stanza, then those of the
+    /// [`family_stanzas`](Interface::family_stanzas).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, Interface};
+    ///
+    /// let content = "iface eth0 inet dhcp\n\niface eth0 inet6 auto";
+    /// let iface = content.parse::<Interface>().unwrap();
+    /// let families: Vec<&Family> = iface.families().collect();
+    /// assert_eq!(families, [&Family::Inet, &Family::Inet6]);
+    /// ```
+    pub fn families(&self) -> impl Iterator<Item = &Family> {
+        self.stanzas().filter_map(|stanza| stanza.family)
+    }
+
+    /// Returns every `iface` stanza of the interface: the first one, then
+    /// its [`family_stanzas`](Interface::family_stanzas).
+    ///
+    /// Code that looks at options should go through this rather than
+    /// [`options`](Interface::options), so that the `inet6` stanza of a
+    /// dual-stack interface is not missed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, Interface};
+    ///
+    /// let content = "\
+    /// iface eth0 inet dhcp
+    ///
+    /// iface eth0 inet6 static
+    ///     address 2001:db8::10/64";
+    /// let iface = content.parse::<Interface>().unwrap();
+    /// let stanzas: Vec<_> = iface.stanzas().collect();
+    /// assert_eq!(stanzas.len(), 2);
+    /// assert_eq!(stanzas[1].family, Some(&Family::Inet6));
+    /// assert_eq!(stanzas[1].options[0].1, "2001:db8::10/64");
+    /// ```
+    pub fn stanzas(&self) -> impl Iterator<Item = StanzaRef<'_>> {
+        let first = StanzaRef {
+            family: self.family.as_ref(),
+            method: self.method.as_ref(),
+            options: &self.options,
+        };
+        std::iter::once(first).chain(self.family_stanzas.iter().map(|stanza| StanzaRef {
+            family: stanza.family.as_ref(),
+            method: stanza.method.as_ref(),
+            options: &stanza.options,
+        }))
+    }
+
+    /// Returns the options of every stanza for editing, in the order of
+    /// [`stanzas`](Interface::stanzas).
+    pub fn stanza_options_mut(&mut self) -> impl Iterator<Item = &mut Vec<(OptionKey, String)>> {
+        std::iter::once(&mut self.options).chain(
+            self.family_stanzas
+                .iter_mut()
+                .map(|stanza| &mut stanza.options),
+        )
+    }
+
+    /// Returns a copy of the interface with only its stanza for `family`.
+    ///
+    /// The copy keeps the `auto` and `allow-*` lines, mapping, tags, and
+    /// disabled state, and takes the method and options of the stanza, so
+    /// that every accessor of `Interface` applies to that family alone.
+    ///
+    /// # Returns
+    ///
+    /// The copy, or `None` if the interface has no stanza for `family`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, Interface};
+    ///
+    /// let content = "\
+    /// iface eth0 inet static
+    ///     address 192.0.2.10/24
+    ///
+    /// iface eth0 inet6 static
+    ///     address 2001:db8::10/64";
+    /// let iface = content.parse::<Interface>().unwrap();
+    /// let inet6 = iface.for_family(Family::Inet6).unwrap();
+    /// assert_eq!(inet6.get_option("address"), Some("2001:db8::10/64"));
+    /// assert!(inet6.family_stanzas.is_empty());
+    /// assert!(iface.for_family(Family::IpX).is_none());
+    /// ```
+    pub fn for_family(&self, family: Family) -> Option<Interface> {
+        let mut copy = self.clone();
+        copy.family_stanzas = Vec::new();
+        if self.family.as_ref() == Some(&family) {
+            return Some(copy);
+        }
+        let stanza = self
+            .family_stanzas
+            .iter()
+            .find(|stanza| stanza.family.as_ref() == Some(&family))?;
+        copy.family = stanza.family.clone();
+        copy.method = stanza.method.clone();
+        copy.options = stanza.options.clone();
+        Some(copy)
+    }
+
+    /// Returns the value of the first option matching `key`.
+    ///
+    /// Keys are compared after folding with [`normalize_key`], so
+    /// `get_option("post_up")` finds a `post-up` line and vice versa.
+    ///
+    /// An `Interface` does not know the options it was parsed with, so this
+    /// and the other option accessors fold keys even under
+    /// [`ParserOptions::strict_keys`]. For an exact lookup, compare against
+    /// the keys of [`options`](Interface::options) directly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let iface: Interface = "iface eth0\n    dns-nameserver 192.0.2.53".parse().unwrap();
+    /// assert_eq!(iface.get_option("dns-nameservers"), Some("192.0.2.53"));
+    /// assert_eq!(iface.get_option("mtu"), None);
+    /// ```
+    pub fn get_option(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(k, _)| keys_match(k, key, false))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the values of all options matching `key`, in the order they
+    /// were defined.
+    ///
+    /// Keys are folded as in [`get_option`](Interface::get_option).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let iface: Interface = "iface eth0\n    address 10.0.0.1/24\n    address fd00::1/64"
+    ///     .parse()
+    ///     .unwrap();
+    /// let addresses: Vec<&str> = iface.get_options("address").collect();
+    /// assert_eq!(addresses, vec!["10.0.0.1/24", "fd00::1/64"]);
+    /// ```
+    pub fn get_options<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.options
+            .iter()
+            .filter(move |(k, _)| keys_match(k, key, false))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Appends an option line, keeping any existing lines for the same key.
+    ///
+    /// This is the in-place counterpart of
+    /// [`InterfaceBuilder::with_option`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The option name (e.g., `"address"`).
+    /// * `value` - The option value (e.g., `"192.168.1.100"`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let mut iface: Interface = "iface eth0 inet static\n    address 10.0.0.1/24"
+    ///     .parse()
+    ///     .unwrap();
+    /// iface.add_option("address", "fd00::1/64");
+    /// let addresses: Vec<&str> = iface.get_options("address").collect();
+    /// assert_eq!(addresses, vec!["10.0.0.1/24", "fd00::1/64"]);
+    /// ```
+    pub fn add_option(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.options.push((intern_key(key.into()), value.into()));
+    }
+
+    /// Sets an option to a single value.
+    ///
+    /// The first line matching `key` keeps its position and takes the new
+    /// value, and any later lines for the key are removed. If there is no such
+    /// line, one is appended. Keys are folded as in
+    /// [`get_option`](Interface::get_option).
+    ///
+    /// # Returns
+    ///
+    /// `true` if the options changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let mut iface: Interface = "iface eth0\n    mtu 1500\n    mtu 9000".parse().unwrap();
+    /// assert!(iface.set_option("mtu", "9216"));
+    /// assert!(!iface.set_option("mtu", "9216"));
+    /// assert_eq!(iface.to_string(), "iface eth0\n    mtu 9216\n");
+    /// ```
+    pub fn set_option(&mut self, key: &str, value: &str) -> bool {
+        set_single(&mut self.options, key, value, false)
+    }
+
+    /// Removes every option line matching `key`.
+    ///
+    /// Keys are folded as in [`get_option`](Interface::get_option).
+    ///
+    /// # Returns
+    ///
+    /// The number of lines removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let mut iface: Interface = "iface eth0\n    post-up a\n    post_up b\n    mtu 9000"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(iface.remove_option("post-up"), 2);
+    /// assert_eq!(iface.remove_option("post-up"), 0);
+    /// ```
+    pub fn remove_option(&mut self, key: &str) -> usize {
+        remove_matching(&mut self.options, key, false)
+    }
+
+    /// Removes every `key value` line, keeping other values of the key.
+    ///
+    /// Keys are folded as in [`get_option`](Interface::get_option); values
+    /// must match exactly.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a line was removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let mut iface: Interface = "iface eth0\n    address 10.0.0.1/24\n    address fd00::1/64"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(iface.remove_option_value("address", "10.0.0.1/24"));
+    /// assert_eq!(iface.get_option("address"), Some("fd00::1/64"));
+    /// ```
+    pub fn remove_option_value(&mut self, key: &str, value: &str) -> bool {
+        remove_matching_value(&mut self.options, key, value, false)
+    }
+
+    /// Returns the commands run in `phase`, in the order they run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Interface, ScriptPhase};
+    ///
+    /// let iface: Interface = "iface eth0\n    post-up ip link set eth0 promisc on\n    mtu 9000"
+    ///     .parse()
+    ///     .unwrap();
+    /// let commands: Vec<&str> = iface.commands(ScriptPhase::PostUp).collect();
+    /// assert_eq!(commands, vec!["ip link set eth0 promisc on"]);
+    /// ```
+    pub fn commands(&self, phase: ScriptPhase) -> impl Iterator<Item = &str> {
+        self.get_options(phase.key())
+    }
+
+    /// Appends a command to `phase`.
+    ///
+    /// The command is placed directly after the last existing command of the
+    /// phase, so it runs after them and the position of the phase among the
+    /// other options is kept. The new line is spelled like its neighbour
+    /// (e.g., `post_up`). If the phase has no commands yet, it is appended
+    /// after all options.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Interface, ScriptPhase};
+    ///
+    /// let mut iface: Interface = "iface eth0\n    post-up /usr/local/bin/a\n    mtu 9000"
+    ///     .parse()
+    ///     .unwrap();
+    /// iface.add_command(ScriptPhase::PostUp, "/usr/local/bin/b");
+    /// let keys: Vec<&str> = iface.options.iter().map(|(key, _)| key.as_ref()).collect();
+    /// assert_eq!(keys, vec!["post-up", "post-up", "mtu"]);
+    /// ```
+    pub fn add_command(&mut self, phase: ScriptPhase, cmd: impl Into<String>) {
+        let positions = self.command_positions(phase);
+        self.insert_command_at(&positions, positions.len(), phase, cmd.into());
+    }
+
+    /// Appends a `pre-up` command; see [`add_command`](Interface::add_command).
+    pub fn add_pre_up(&mut self, cmd: impl Into<String>) {
+        self.add_command(ScriptPhase::PreUp, cmd);
+    }
+
+    /// Appends an `up` command; see [`add_command`](Interface::add_command).
+    pub fn add_up(&mut self, cmd: impl Into<String>) {
+        self.add_command(ScriptPhase::Up, cmd);
+    }
+
+    /// Appends a `post-up` command; see [`add_command`](Interface::add_command).
+    pub fn add_post_up(&mut self, cmd: impl Into<String>) {
+        self.add_command(ScriptPhase::PostUp, cmd);
+    }
+
+    /// Appends a `pre-down` command; see [`add_command`](Interface::add_command).
+    pub fn add_pre_down(&mut self, cmd: impl Into<String>) {
+        self.add_command(ScriptPhase::PreDown, cmd);
+    }
+
+    /// Appends a `down` command; see [`add_command`](Interface::add_command).
+    pub fn add_down(&mut self, cmd: impl Into<String>) {
+        self.add_command(ScriptPhase::Down, cmd);
+    }
+
+    /// Appends a `post-down` command; see [`add_command`](Interface::add_command).
+    pub fn add_post_down(&mut self, cmd: impl Into<String>) {
+        self.add_command(ScriptPhase::PostDown, cmd);
+    }
+
+    /// Inserts a command into `phase` so that it becomes the command at
+    /// `index` among the commands of the phase.
+    ///
+    /// The command takes the place of the current command at `index`, which
+    /// moves down together with the rest. An `index` past the last command
+    /// appends like [`add_command`](Interface::add_command).
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The phase to insert into.
+    /// * `index` - The position among the commands of `phase`.
+    /// * `cmd` - The command.
+    pub fn insert_command(&mut self, phase: ScriptPhase, index: usize, cmd: impl Into<String>) {
+        let positions = self.command_positions(phase);
+        self.insert_command_at(&positions, index, phase, cmd.into());
+    }
+
+    /// Moves a command of `phase` from position `from` to position `to`
+    /// among the commands of the phase.
+    ///
+    /// Only the order of the commands within the phase changes: the option
+    /// lines they occupy, including the spelling of their keys, stay where
+    /// they are relative to other options. A `to`
+    /// past the last command moves the command to the end.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the phase has no command at `from`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Interface, ScriptPhase};
+    ///
+    /// let mut iface: Interface = "iface eth0\n    up a\n    mtu 9000\n    up b"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(iface.move_command(ScriptPhase::Up, 1, 0));
+    /// let commands: Vec<&str> = iface.commands(ScriptPhase::Up).collect();
+    /// assert_eq!(commands, vec!["b", "a"]);
+    /// assert_eq!(iface.options[1].0, "mtu");
+    /// ```
+    pub fn move_command(&mut self, phase: ScriptPhase, from: usize, to: usize) -> bool {
+        let positions = self.command_positions(phase);
+        if from >= positions.len() {
+            return false;
+        }
+        let mut commands: Vec<String> = positions
+            .iter()
+            .map(|&i| std::mem::take(&mut self.options[i].1))
+            .collect();
+        let command = commands.remove(from);
+        commands.insert(to.min(commands.len()), command);
+        for (&i, command) in positions.iter().zip(commands) {
+            self.options[i].1 = command;
+        }
+        true
+    }
+
+    /// Returns the indices in `options` of the commands of `phase`.
+    fn command_positions(&self, phase: ScriptPhase) -> Vec<usize> {
+        self.options
+            .iter()
+            .enumerate()
+            .filter(|(_, (key, _))| keys_match(key, phase.key(), false))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Inserts a command before the `index`-th of `positions`, or after the
+    /// last of them.
+    fn insert_command_at(
+        &mut self,
+        positions: &[usize],
+        index: usize,
+        phase: ScriptPhase,
+        cmd: String,
+    ) {
+        let at = match positions.get(index) {
+            Some(&i) => i,
+            None => positions.last().map_or(self.options.len(), |&i| i + 1),
+        };
+        // Reuse the spelling of a neighbouring command so the stanza reads
+        // consistently
+        let key = match positions.get(index).or(positions.last()) {
+            Some(&i) => self.options[i].0.clone(),
+            None => intern_key(phase.key()),
+        };
+        self.options.insert(at, (key, cmd));
+    }
+
+    /// Returns the addresses configured by the `address` options.
+    ///
+    /// All forms of an address are understood: CIDR notation, a dotted
+    /// netmask after the slash, and a bare address combined with the
+    /// `netmask` option of the stanza. Values that are not IP addresses are
+    /// skipped. The addresses of the
+    /// [`family_stanzas`](Interface::family_stanzas) follow those of the
+    /// first stanza.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let iface: Interface = "iface eth0 inet static\n    address 10.0.0.1\n    netmask 255.255.255.0"
+    ///     .parse()
+    ///     .unwrap();
+    /// let addresses: Vec<String> = iface.addresses().iter().map(|a| a.to_string()).collect();
+    /// assert_eq!(addresses, vec!["10.0.0.1/24"]);
+    /// ```
+    pub fn addresses(&self) -> Vec<Address> {
+        self.stanzas()
+            .flat_map(|stanza| stanza_addresses(stanza.options))
+            .collect()
+    }
+
+    /// Returns `true` if this stanza configures an IPv4 alias, named
+    /// `<parent>:<label>` like `eth0:1`.
+    ///
+    /// Aliases are a legacy way to add addresses to an interface; they are
+    /// not devices of their own.
+    pub fn is_alias(&self) -> bool {
+        self.alias_parent().is_some()
+    }
+
+    /// Returns the interface this alias belongs to, the part of its name
+    /// before the `:`, or `None` if this is not an alias.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// assert_eq!(Interface::builder("eth0:1").build().alias_parent(), Some("eth0"));
+    /// assert_eq!(Interface::builder("eth0.100:web").build().alias_parent(), Some("eth0.100"));
+    /// assert_eq!(Interface::builder("eth0").build().alias_parent(), None);
+    /// ```
+    pub fn alias_parent(&self) -> Option<&str> {
+        iface_name::alias_parent(&self.name)
+    }
+
+    /// Returns the device this VLAN is stacked on: the `vlan-raw-device`
+    /// option, or else the part of a dotted name before the last `.`.
+    ///
+    /// For a double-tagged name like `swp1.100.200` this is the outer VLAN
+    /// `swp1.100`, not the physical port.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// assert_eq!(Interface::builder("swp1.100").build().vlan_raw_device(), Some("swp1"));
+    /// assert_eq!(Interface::builder("swp1.100.200").build().vlan_raw_device(), Some("swp1.100"));
+    /// let svi = Interface::builder("vlan10").with_option("vlan-raw-device", "br0").build();
+    /// assert_eq!(svi.vlan_raw_device(), Some("br0"));
+    /// ```
+    pub fn vlan_raw_device(&self) -> Option<&str> {
+        if let Some(raw_device) = self.get_option("vlan-raw-device") {
+            return Some(raw_device.trim());
+        }
+        if self.is_alias() {
+            return None;
+        }
+        self.name
+            .rsplit_once('.')
+            .map(|(raw_device, _)| raw_device)
+            .filter(|raw_device| !raw_device.is_empty())
+    }
+
+    /// Returns the VLAN id this interface tags with: the `vlan-id` option, or
+    /// else the number after the last `.` of a dotted name, or after `vlan`
+    /// in a name like `vlan100`.
+    ///
+    /// For a double-tagged interface this is the inner tag, the same as
+    /// [`inner_vlan_id`](Interface::inner_vlan_id).
+    pub fn vlan_id(&self) -> Option<u16> {
+        if let Some(id) = self.get_option("vlan-id") {
+            return id.trim().parse().ok();
+        }
+        if self.is_alias() {
+            return None;
+        }
+        iface_name::vlan_split(&self.name)
+            .map(|(_, id)| id)
+            .or_else(|| {
+                self.name
+                    .strip_prefix("vlan")
+                    .filter(|id| id.bytes().all(|b| b.is_ascii_digit()))
+                    .and_then(|id| id.parse().ok())
+            })
+    }
+
+    /// Returns the outer (service) tag of a double-tagged (QinQ) VLAN, or
+    /// `None` if the interface is not one.
+    ///
+    /// A VLAN is double-tagged when its [raw device](Interface::vlan_raw_device)
+    /// is itself a dotted VLAN, as in `swp1.100.200` or a stanza with
+    /// `vlan-raw-device swp1.100`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let qinq = Interface::builder("swp1.100.200").build();
+    /// assert_eq!(qinq.outer_vlan_id(), Some(100));
+    /// assert_eq!(qinq.inner_vlan_id(), Some(200));
+    ///
+    /// let single = Interface::builder("swp1.100").build();
+    /// assert_eq!(single.vlan_id(), Some(100));
+    /// assert_eq!(single.outer_vlan_id(), None);
+    /// ```
+    pub fn outer_vlan_id(&self) -> Option<u16> {
+        self.vlan_id()?;
+        iface_name::vlan_split(self.vlan_raw_device()?).map(|(_, id)| id)
+    }
+
+    /// Returns the inner (customer) tag of a double-tagged (QinQ) VLAN, or
+    /// `None` if the interface is not one. See
+    /// [`outer_vlan_id`](Interface::outer_vlan_id).
+    pub fn inner_vlan_id(&self) -> Option<u16> {
+        self.outer_vlan_id().and(self.vlan_id())
+    }
+
+    /// Returns the tag protocol set by the `vlan-protocol` option, or `None`
+    /// if it is unset or not a known protocol. The kernel defaults to
+    /// [`VlanProtocol::Dot1Q`].
+    pub fn vlan_protocol(&self) -> Option<VlanProtocol> {
+        self.get_option("vlan-protocol")?.trim().parse().ok()
+    }
+
+    /// Infers the kind of device this stanza configures from its name,
+    /// method, and options.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Interface, InterfaceKind};
+    ///
+    /// let br = Interface::builder("br0").with_option("bridge-ports", "swp1").build();
+    /// assert_eq!(br.kind(), InterfaceKind::Bridge);
+    /// assert_eq!(Interface::builder("eth0.100").build().kind(), InterfaceKind::Vlan);
+    /// assert_eq!(Interface::builder("swp1.100.200").build().kind(), InterfaceKind::Vlan);
+    /// assert_eq!(Interface::builder("eth0:1").build().kind(), InterfaceKind::Alias);
+    /// assert_eq!(Interface::builder("swp1").build().kind(), InterfaceKind::Physical);
+    /// ```
+    pub fn kind(&self) -> InterfaceKind {
+        let has = |key: &str| self.get_option(key).is_some();
+        let vlan_name = self.name.contains('.')
+            || self
+                .name
+                .strip_prefix("vlan")
+                .is_some_and(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()));
+        if self.name == "lo" || self.method == Some(Method::Loopback) {
+            InterfaceKind::Loopback
+        } else if self.is_alias() {
+            InterfaceKind::Alias
+        } else if has("vrf-table") {
+            InterfaceKind::Vrf
+        } else if has("bridge-ports") || has("bridge-vlan-aware") {
+            InterfaceKind::Bridge
+        } else if has("bond-slaves") {
+            InterfaceKind::Bond
+        } else if has("vxlan-id") {
+            InterfaceKind::Vxlan
+        } else if has("vlan-id") || has("vlan-raw-device") || has("vlan-protocol") || vlan_name {
+            InterfaceKind::Vlan
+        } else {
+            InterfaceKind::Physical
+        }
+    }
+
+    /// Returns the options of this interface classified as [`InterfaceOption`]s.
+    ///
+    /// Options are returned in the order they were defined.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Interface, InterfaceOption};
+    ///
+    /// let iface: Interface = "iface eth0\n    mtu 9000".parse().unwrap();
+    /// assert_eq!(iface.typed_options(), vec![InterfaceOption::Mtu(9000)]);
+    /// ```
+    pub fn typed_options(&self) -> Vec<InterfaceOption> {
+        self.options
+            .iter()
+            .map(|(key, value)| InterfaceOption::from_key_value(key, value))
+            .collect()
+    }
+}
+
+#[cfg(feature = "json")]
+impl Interface {
+    /// Serializes the interface to JSON, using the representation documented
+    /// on [`Interface`].
+    ///
+    /// # Arguments
+    ///
+    /// * `pretty` - Whether to indent the output.
+    pub fn to_json_string(&self, pretty: bool) -> String {
+        let json = if pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        };
+        json.expect("Interface always serializes to JSON")
+    }
+
+    /// Deserializes an interface from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `serde_json::Error` if the input is not valid JSON or does
+    /// not match the documented representation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::Interface;
+    ///
+    /// let iface = Interface::from_json_str(r#"{"name": "eth0", "method": "dhcp"}"#).unwrap();
+    /// assert_eq!(iface.to_string(), "iface eth0 dhcp\n");
+    /// ```
+    pub fn from_json_str(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
+impl PartialEq for Interface {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.auto == other.auto
+            && self.allow == other.allow
+            && self.family == other.family
+            && self.method == other.method
+            && self.options == other.options
+            && self.family_stanzas == other.family_stanzas
+            && self.mapping == other.mapping
+            && self.disabled == other.disabled
+            && self.tags == other.tags
+    }
+}
+
+impl Eq for Interface {}
+
+impl Hash for Interface {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.auto.hash(state);
+        self.allow.hash(state);
+        self.family.hash(state);
+        self.method.hash(state);
+        self.options.hash(state);
+        self.family_stanzas.hash(state);
+        self.mapping.hash(state);
+        self.disabled.hash(state);
+        self.tags.hash(state);
+    }
+}
+
+impl FromStr for Interface {
+    type Err = ParserError;
+
+    /// Parses a single stanza into an `Interface`; see [`Interface::parse_stanza`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Interface::parse_stanza(s)
     }
 }
 
 impl fmt::Display for Interface {
+    /// Writes the stanza. A [disabled](Interface::disabled) stanza has every
+    /// line prefixed with
+    /// [`DEFAULT_DISABLED_MARKER`](crate::ParserOptions::DEFAULT_DISABLED_MARKER),
+    /// and [tags](Interface::tags) are written after
+    /// [`DEFAULT_TAG_PREFIX`](crate::ParserOptions::DEFAULT_TAG_PREFIX).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_stanza(
+            f,
+            ParserOptions::DEFAULT_DISABLED_MARKER,
+            ParserOptions::DEFAULT_TAG_PREFIX,
+            &WriteOptions::default(),
+        )
+    }
+}
+
+impl Interface {
+    /// Writes the stanza like `Display`, laid out according to `options`.
+    ///
+    /// Only the [`alignment`](WriteOptions::alignment) and
+    /// [`canonical_ipv6`](WriteOptions::canonical_ipv6) settings of `options`
+    /// apply to a single stanza; see [`ValueAlignment`] for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `f` fails.
+    pub fn write_to(&self, f: &mut impl fmt::Write, options: &WriteOptions) -> fmt::Result {
+        self.write_stanza(
+            f,
+            ParserOptions::DEFAULT_DISABLED_MARKER,
+            ParserOptions::DEFAULT_TAG_PREFIX,
+            options,
+        )
+    }
+
+    /// Writes the stanza, prefixing every line with `marker` if the
+    /// interface is disabled, writing the tags after `tag_prefix`, and
+    /// writing option values per `options`.
+    pub(crate) fn write_stanza(
+        &self,
+        f: &mut impl fmt::Write,
+        marker: &str,
+        tag_prefix: &str,
+        options: &WriteOptions,
+    ) -> fmt::Result {
+        let prefix = if self.disabled { marker } else { "" };
+        let tag_prefix = tag_prefix.trim_end();
+        if !self.tags.is_empty() && !tag_prefix.is_empty() {
+            write_line(f, &[prefix, tag_prefix, " ", &format_tags(&self.tags)])?;
+        }
         if self.auto {
-            writeln!(f, "auto {}", self.name)?;
+            write_line(f, &[prefix, "auto ", &self.name])?;
         }
         for allow_type in &self.allow {
-            writeln!(f, "allow-{} {}", allow_type, self.name)?;
+            write_line(f, &[prefix, "allow-", allow_type, " ", &self.name])?;
         }
         if let Some(mapping) = &self.mapping {
-            writeln!(f, "mapping {}", self.name)?;
-            writeln!(f, "    script {}", mapping.script)?;
+            write_line(f, &[prefix, "mapping ", &self.name])?;
+            write_line(f, &[prefix, "    script ", &mapping.script])?;
             for map in &mapping.maps {
-                writeln!(f, "    map {}", map)?;
+                write_line(f, &[prefix, "    map ", map])?;
             }
         }
-        write!(f, "iface {}", self.name)?;
-        if let Some(family) = &self.family {
-            write!(f, " {}", family)?;
+        self.write_iface_line(f, prefix, self.family.as_ref(), self.method.as_ref())?;
+        write_options(f, prefix, &self.options, options)?;
+        for stanza in &self.family_stanzas {
+            for _ in 0..options.blank_lines {
+                f.write_str("\n")?;
+            }
+            self.write_iface_line(f, prefix, stanza.family.as_ref(), stanza.method.as_ref())?;
+            write_options(f, prefix, &stanza.options, options)?;
         }
-        if let Some(method) = &self.method {
-            write!(f, " {}", method)?;
+        Ok(())
+    }
+
+    /// Writes the `iface` line of a stanza with `family` and `method`.
+    fn write_iface_line(
+        &self,
+        f: &mut impl fmt::Write,
+        prefix: &str,
+        family: Option<&Family>,
+        method: Option<&Method>,
+    ) -> fmt::Result {
+        f.write_str(prefix)?;
+        f.write_str("iface ")?;
+        f.write_str(&self.name)?;
+        if let Some(family) = family {
+            f.write_str(" ")?;
+            f.write_str(family.as_str())?;
         }
-        writeln!(f)?;
-        // Sort options before printing
-        let mut sorted_options = self.options.clone();
-        sorted_options.sort_by(|a, b| a.0.cmp(&b.0));
-        for (option_name, option_value) in &sorted_options {
-            writeln!(f, "    {} {}", option_name, option_value)?;
+        if let Some(method) = method {
+            f.write_str(" ")?;
+            f.write_str(method.as_str())?;
         }
-        Ok(())
+        f.write_str("\n")
+    }
+}
+
+/// Writes the option lines of a stanza, each prefixed with `prefix`.
+fn write_options(
+    f: &mut impl fmt::Write,
+    prefix: &str,
+    stanza_options: &[(OptionKey, String)],
+    options: &WriteOptions,
+) -> fmt::Result {
+    let longest = match options.alignment {
+        ValueAlignment::Auto => stanza_options
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or(0),
+        _ => 0,
+    };
+    let write = |option| write_option(f, prefix, option, longest, options);
+    if options.sort_options {
+        let mut sorted: Vec<&(OptionKey, String)> = stanza_options.iter().collect();
+        // Spellings of one key sort together, so hook commands keep their order
+        sorted.sort_by_cached_key(|(key, _)| normalize_key(key).into_owned());
+        sorted.into_iter().try_for_each(write)
+    } else {
+        stanza_options.iter().try_for_each(write)
+    }
+}
+
+/// Writes one option line, with its value after `longest` as configured by
+/// `options`.
+fn write_option(
+    f: &mut impl fmt::Write,
+    prefix: &str,
+    (option_name, option_value): &(OptionKey, String),
+    longest: usize,
+    options: &WriteOptions,
+) -> fmt::Result {
+    f.write_str(prefix)?;
+    f.write_str("    ")?;
+    f.write_str(option_name)?;
+    for _ in 0..options.alignment.padding(option_name, longest) {
+        f.write_char(' ')?;
+    }
+    let canonical =
+        options.canonical_ipv6 && IPV6_OPTIONS.contains(&normalize_key(option_name).as_ref());
+    if canonical {
+        write_line(f, &[&canonical_ipv6(option_value)])
+    } else {
+        write_line(f, &[option_value])
+    }
+}
+
+/// Returns the addresses of the `address` options of one stanza, as
+/// [`Interface::addresses`] does.
+fn stanza_addresses(options: &[(OptionKey, String)]) -> Vec<Address> {
+    let values = |key: &'static str| {
+        options
+            .iter()
+            .filter(move |(k, _)| keys_match(k, key, false))
+            .map(|(_, value)| value.as_str())
+    };
+    let netmask = values("netmask").next();
+    values("address")
+        .filter_map(|value| value.parse::<Address>().ok())
+        .map(|mut addr| {
+            if addr.prefix.is_none() {
+                addr.prefix = netmask.and_then(|mask| parse_prefix(mask, addr.ip).ok());
+            }
+            addr
+        })
+        .collect()
+}
+
+/// Formats tags as the body of their magic comment, `key=value` pairs
+/// separated by commas. A tag with an empty value is written as its key.
+fn format_tags(tags: &BTreeMap<String, String>) -> String {
+    let pairs: Vec<String> = tags
+        .iter()
+        .map(|(key, value)| {
+            if value.is_empty() {
+                key.clone()
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect();
+    pairs.join(", ")
+}
+
+/// Writes `parts` followed by a newline, without going through the
+/// formatting machinery.
+fn write_line(f: &mut impl fmt::Write, parts: &[&str]) -> fmt::Result {
+    for part in parts {
+        f.write_str(part)?;
+    }
+    f.write_str("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_parse_stanza() {
+        let iface: Interface = r#"
+# uplink
+auto swp1
+allow-hotplug swp1
+iface swp1 inet static
+    address 10.0.0.1/24
+    post-up /bin/true
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(iface.name, "swp1");
+        assert!(iface.auto);
+        assert_eq!(iface.allow, vec!["hotplug"]);
+        assert_eq!(iface.family, Some(Family::Inet));
+        assert_eq!(iface.method, Some(Method::Static));
+        assert_eq!(iface.options.len(), 2);
+
+        assert!("".parse::<Interface>().is_err());
+        assert!("# nothing here".parse::<Interface>().is_err());
+        let err = "iface a\niface b".parse::<Interface>().unwrap_err();
+        assert!(err.message.contains("found 2"));
+    }
+
+    #[test]
+    fn test_edit_keeps_duplicate_options() {
+        let iface: Interface = "\
+iface eth0 inet static
+    address 192.0.2.1/24
+    post-up ip route add 198.51.100.0/24 via 192.0.2.254
+    address 192.0.2.2/24
+    post-up ip route add 203.0.113.0/24 via 192.0.2.254
+    post-up /usr/local/bin/notify up
+"
+        .parse()
+        .unwrap();
+        let edited = iface.edit().with_option("mtu", "9000").build();
+
+        let addresses: Vec<&str> = edited.get_options("address").collect();
+        assert_eq!(addresses, ["192.0.2.1/24", "192.0.2.2/24"]);
+        let post_up: Vec<&str> = edited.get_options("post-up").collect();
+        assert_eq!(
+            post_up,
+            [
+                "ip route add 198.51.100.0/24 via 192.0.2.254",
+                "ip route add 203.0.113.0/24 via 192.0.2.254",
+                "/usr/local/bin/notify up",
+            ]
+        );
+        assert_eq!(edited.options[..5], iface.options[..]);
+        assert_eq!(edited.options.len(), 6);
+
+        // Editing without changes gives back the same interface
+        assert_eq!(iface.edit().build(), iface);
+    }
+
+    #[test]
+    fn test_display_layout() {
+        let mut iface = Interface::builder("eth0")
+            .with_auto(true)
+            .with_allow("hotplug")
+            .with_family(Family::Inet)
+            .with_method("static")
+            .with_option("mtu", "9000")
+            .with_option("address", "192.0.2.1/24")
+            .with_option("post-up", "/bin/b")
+            .with_option("address", "192.0.2.2/24")
+            .with_option("post-up", "/bin/a")
+            .build();
+        iface.mapping = Some(Mapping {
+            script: "/usr/bin/guess".to_string(),
+            maps: vec!["home eth0-home".to_string()],
+        });
+        assert_eq!(
+            iface.to_string(),
+            "auto eth0\n\
+             allow-hotplug eth0\n\
+             mapping eth0\n    script /usr/bin/guess\n    map home eth0-home\n\
+             iface eth0 inet static\n\
+             \x20   mtu 9000\n    address 192.0.2.1/24\n    post-up /bin/b\n\
+             \x20   address 192.0.2.2/24\n    post-up /bin/a\n"
+        );
+
+        // Sorting options by key keeps repeated keys in order
+        let options = WriteOptions {
+            sort_options: true,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        iface.write_to(&mut out, &options).unwrap();
+        assert!(out.ends_with(
+            "iface eth0 inet static\n\
+             \x20   address 192.0.2.1/24\n    address 192.0.2.2/24\n\
+             \x20   mtu 9000\n    post-up /bin/b\n    post-up /bin/a\n"
+        ));
+    }
+
+    #[test]
+    fn test_partial_iface_line_round_trip() {
+        let cases = [
+            ("iface eth0\n", None, None),
+            ("iface eth0 inet6\n", Some(Family::Inet6), None),
+            (
+                "iface eth0 inet static\n",
+                Some(Family::Inet),
+                Some(Method::Static),
+            ),
+            ("iface eth0 manual\n", None, Some(Method::Manual)),
+        ];
+        for (line, family, method) in cases {
+            let text = format!("{}    address 192.0.2.1/24\n    mtu 9000\n", line);
+            let iface: Interface = text.parse().unwrap();
+            assert_eq!(
+                (&iface.family, &iface.method),
+                (&family, &method),
+                "{}",
+                line
+            );
+            assert_eq!(iface.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_value_alignment() {
+        let iface = Interface::builder("swp1")
+            .with_family(Family::Inet)
+            .with_method("static")
+            .with_option("address", "10.0.0.1/31")
+            .with_option("mtu", "9216")
+            .with_option("post-up", "echo \"a b\"")
+            .build();
+        let render = |alignment: ValueAlignment| -> String {
+            let options = WriteOptions {
+                alignment,
+                ..Default::default()
+            };
+            let mut out = String::new();
+            iface.write_to(&mut out, &options).unwrap();
+            out
+        };
+
+        assert_eq!(render(ValueAlignment::Off), iface.to_string());
+        assert_eq!(
+            render(ValueAlignment::Off),
+            "iface swp1 inet static\n\
+             \x20   address 10.0.0.1/31\n\
+             \x20   mtu 9216\n\
+             \x20   post-up echo \"a b\"\n"
+        );
+        assert_eq!(
+            render(ValueAlignment::Auto),
+            "iface swp1 inet static\n\
+             \x20   address 10.0.0.1/31\n\
+             \x20   mtu     9216\n\
+             \x20   post-up echo \"a b\"\n"
+        );
+        assert_eq!(
+            render(ValueAlignment::Column(13)),
+            "iface swp1 inet static\n\
+             \x20   address      10.0.0.1/31\n\
+             \x20   mtu          9216\n\
+             \x20   post-up      echo \"a b\"\n"
+        );
+        // Keys longer than the column still get one space
+        assert_eq!(
+            render(ValueAlignment::Column(4)),
+            "iface swp1 inet static\n\
+             \x20   address 10.0.0.1/31\n\
+             \x20   mtu 9216\n\
+             \x20   post-up echo \"a b\"\n"
+        );
+
+        // Alignment does not change what is parsed
+        for alignment in [ValueAlignment::Auto, ValueAlignment::Column(13)] {
+            let reparsed: Interface = render(alignment).parse().unwrap();
+            assert_eq!(reparsed, iface);
+        }
+    }
+
+    #[test]
+    fn test_canonical_ipv6_output() {
+        let iface: Interface = "\
+iface swp1 inet6 static
+    address 2001:0DB8:0000:0000:0000:0000:0000:0001/64
+    address 10.0.0.1/31
+    gateway FE80:0:0:0:0:0:0:1%swp1
+    dns-nameservers 2001:DB8::53 192.0.2.53
+    vxlan-local-tunnelip 2001:db8:0:0::0100
+    post-up ip -6 route add 2001:DB8:1::/48 dev swp1
+    hwaddress 00:00:5E:00:53:01
+"
+        .parse()
+        .unwrap();
+        let options = WriteOptions {
+            canonical_ipv6: true,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        iface.write_to(&mut out, &options).unwrap();
+        assert_eq!(
+            out,
+            "iface swp1 inet6 static\n\
+             \x20   address 2001:db8::1/64\n\
+             \x20   address 10.0.0.1/31\n\
+             \x20   gateway fe80::1%swp1\n\
+             \x20   dns-nameservers 2001:db8::53 192.0.2.53\n\
+             \x20   vxlan-local-tunnelip 2001:db8::100\n\
+             \x20   post-up ip -6 route add 2001:DB8:1::/48 dev swp1\n\
+             \x20   hwaddress 00:00:5E:00:53:01\n"
+        );
+
+        // Canonical output is written the same way again
+        let reparsed: Interface = out.parse().unwrap();
+        let mut again = String::new();
+        reparsed.write_to(&mut again, &options).unwrap();
+        assert_eq!(again, out);
+        assert_eq!(reparsed.to_string(), out);
+
+        // Values are kept as written by default
+        assert!(iface.to_string().contains("address 2001:0DB8:0000"));
+    }
+
+    #[test]
+    fn test_to_static_and_back() {
+        let dhcp: Interface = r#"
+auto eth0
+iface eth0 inet dhcp
+    hostname box
+    mtu 9000
+    client box-id
+    leasetime 3600
+    address 198.51.100.7/24
+    post-up /bin/true
+"#
+        .parse()
+        .unwrap();
+        let gateway = "192.0.2.1".parse().unwrap();
+        let dns = [
+            "192.0.2.53".parse().unwrap(),
+            "2001:db8::53".parse().unwrap(),
+        ];
+        let iface = dhcp.to_static("192.0.2.10".parse().unwrap(), 24, Some(gateway), &dns);
+        assert!(iface.auto);
+        assert_eq!(iface.family, Some(Family::Inet));
+        assert_eq!(iface.method, Some(Method::Static));
+        assert_eq!(
+            iface.options,
+            vec![
+                ("address".into(), "192.0.2.10/24".to_string()),
+                ("gateway".into(), "192.0.2.1".to_string()),
+                (
+                    "dns-nameservers".into(),
+                    "192.0.2.53 2001:db8::53".to_string()
+                ),
+                ("mtu".into(), "9000".to_string()),
+                ("post-up".into(), "/bin/true".to_string()),
+            ]
+        );
+
+        let iface = iface.to_dhcp();
+        assert_eq!(iface.method, Some(Method::Dhcp));
+        assert_eq!(
+            iface.options,
+            vec![
+                ("mtu".into(), "9000".to_string()),
+                ("post-up".into(), "/bin/true".to_string()),
+            ]
+        );
+
+        let v6 = dhcp.to_static("2001:db8::10".parse().unwrap(), 64, None, &[]);
+        assert_eq!(v6.family, Some(Family::Inet6));
+        assert_eq!(
+            v6.get_options("address").collect::<Vec<_>>(),
+            vec!["2001:db8::10/64"]
+        );
+        assert_eq!(v6.get_option("gateway"), None);
+        assert_eq!(v6.get_option("dns-nameservers"), None);
+    }
+
+    #[test]
+    fn test_interface_hash_is_order_sensitive() {
+        let a = Interface::builder("eth0")
+            .with_family(Family::Inet)
+            .with_method("static")
+            .with_option("address", "192.168.1.10/24")
+            .with_option("gateway", "192.168.1.1")
+            .build();
+        let b = a.clone();
+        let reordered = Interface::builder("eth0")
+            .with_family(Family::Inet)
+            .with_method("static")
+            .with_option("gateway", "192.168.1.1")
+            .with_option("address", "192.168.1.10/24")
+            .build();
+
+        let set: HashSet<Interface> = vec![a.clone(), b, reordered.clone()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&a));
+        assert!(set.contains(&reordered));
+        assert_ne!(a, reordered);
+    }
+
+    #[test]
+    fn test_script_commands() {
+        let mut iface: Interface = "iface eth0 inet static\n\
+                                    pre-up /bin/a\n\
+                                    address 10.0.0.1/24\n\
+                                    post-up /bin/b\n\
+                                    pre-down /bin/c\n\
+                                    post_up /bin/d\n\
+                                    mtu 9000"
+            .parse()
+            .unwrap();
+        let lines = |iface: &Interface| -> Vec<String> {
+            iface
+                .options
+                .iter()
+                .map(|(key, value)| format!("{} {}", key, value))
+                .collect()
+        };
+
+        iface.add_post_up("/bin/e");
+        iface.add_pre_up("/bin/f");
+        iface.add_down("/bin/g");
+        iface.insert_command(ScriptPhase::PostUp, 1, "/bin/h");
+        iface.insert_command(ScriptPhase::PreDown, 0, "/bin/i");
+        assert_eq!(
+            lines(&iface),
+            vec![
+                "pre-up /bin/a",
+                "pre-up /bin/f",
+                "address 10.0.0.1/24",
+                "post-up /bin/b",
+                "pre-down /bin/i",
+                "pre-down /bin/c",
+                "post_up /bin/h",
+                "post_up /bin/d",
+                "post_up /bin/e",
+                "mtu 9000",
+                "down /bin/g",
+            ]
+        );
+
+        // Commands trade places; the lines they occupy stay put, so the
+        // spelling of each line is kept
+        assert!(iface.move_command(ScriptPhase::PostUp, 3, 0));
+        assert!(iface.move_command(ScriptPhase::PostUp, 1, 99));
+        assert!(!iface.move_command(ScriptPhase::Up, 0, 1));
+        let commands: Vec<&str> = iface.commands(ScriptPhase::PostUp).collect();
+        assert_eq!(commands, vec!["/bin/e", "/bin/h", "/bin/d", "/bin/b"]);
+        assert_eq!(
+            lines(&iface),
+            vec![
+                "pre-up /bin/a",
+                "pre-up /bin/f",
+                "address 10.0.0.1/24",
+                "post-up /bin/e",
+                "pre-down /bin/i",
+                "pre-down /bin/c",
+                "post_up /bin/h",
+                "post_up /bin/d",
+                "post_up /bin/b",
+                "mtu 9000",
+                "down /bin/g",
+            ]
+        );
+
+        // The sequence survives editing
+        let edited = iface.edit().build();
+        assert_eq!(lines(&edited), lines(&iface));
+
+        // Positions among the other options are written as they are
+        let rendered = iface.to_string();
+        let expected: String = lines(&iface)
+            .iter()
+            .map(|line| format!("    {}\n", line))
+            .collect();
+        assert_eq!(rendered, format!("iface eth0 inet static\n{}", expected));
+
+        // Sorting by key keeps every spelling of a phase in execution order
+        let options = WriteOptions {
+            sort_options: true,
+            ..Default::default()
+        };
+        let mut sorted = String::new();
+        iface.write_to(&mut sorted, &options).unwrap();
+        assert_eq!(
+            sorted,
+            "iface eth0 inet static\n    address 10.0.0.1/24\n    down /bin/g\n    mtu 9000\n\
+             \x20   post-up /bin/e\n    post_up /bin/h\n    post_up /bin/d\n    post_up /bin/b\n\
+             \x20   pre-down /bin/i\n    pre-down /bin/c\n    pre-up /bin/a\n    pre-up /bin/f\n"
+        );
+        let mixed: Interface = "iface eth0\n    post_up a\n    post-up b".parse().unwrap();
+        let mut out = String::new();
+        mixed.write_to(&mut out, &options).unwrap();
+        assert_eq!(out, "iface eth0\n    post_up a\n    post-up b\n");
+    }
+
+    #[test]
+    fn test_vlan_tags() {
+        use crate::interface::VlanBuilder;
+
+        // Single-tagged, by name and by options
+        let single = VlanBuilder::new("swp1", 100).build();
+        assert_eq!(single.len(), 1);
+        let single = &single[0];
+        assert_eq!(single.name, "swp1.100");
+        assert_eq!(single.kind(), InterfaceKind::Vlan);
+        assert_eq!(single.vlan_raw_device(), Some("swp1"));
+        assert_eq!(single.vlan_id(), Some(100));
+        assert_eq!(single.outer_vlan_id(), None);
+        assert_eq!(single.inner_vlan_id(), None);
+        assert_eq!(single.vlan_protocol(), None);
+        let svi = Interface::builder("vlan10")
+            .with_option("vlan-raw-device", "br0")
+            .build();
+        assert_eq!(svi.vlan_raw_device(), Some("br0"));
+        assert_eq!(svi.vlan_id(), Some(10));
+        assert_eq!(svi.outer_vlan_id(), None);
+
+        // Double-tagged, by name and by options
+        let stacked = VlanBuilder::stacked("swp1", 100, 200).build();
+        let text: Vec<String> = stacked.iter().map(|vlan| vlan.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "auto swp1.100\niface swp1.100\n    vlan-protocol 802.1ad\n",
+                "auto swp1.100.200\niface swp1.100.200\n",
+            ]
+        );
+        let (outer, inner) = (&stacked[0], &stacked[1]);
+        assert_eq!(outer.vlan_protocol(), Some(VlanProtocol::Dot1Ad));
+        assert_eq!(outer.outer_vlan_id(), None);
+        assert_eq!(inner.kind(), InterfaceKind::Vlan);
+        assert_eq!(inner.vlan_raw_device(), Some("swp1.100"));
+        assert_eq!(inner.vlan_id(), Some(200));
+        assert_eq!(inner.outer_vlan_id(), Some(100));
+        assert_eq!(inner.inner_vlan_id(), Some(200));
+        let named: Interface = "iface cust200\n    vlan-raw-device swp1.100\n    vlan-id 200"
+            .parse()
+            .unwrap();
+        assert_eq!(named.outer_vlan_id(), Some(100));
+        assert_eq!(named.inner_vlan_id(), Some(200));
+
+        let dot1q = VlanBuilder::stacked("swp1", 100, 200)
+            .protocol(VlanProtocol::Dot1Q)
+            .build();
+        assert_eq!(dot1q[0].get_option("vlan-protocol"), Some("802.1Q"));
+
+        // Neither aliases nor other dotted names have tags
+        let alias = Interface::builder("swp1.100:1").build();
+        assert_eq!(alias.vlan_raw_device(), None);
+        assert_eq!(alias.vlan_id(), None);
+        assert_eq!(Interface::builder("eth0.web").build().vlan_id(), None);
     }
 }
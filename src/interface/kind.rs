@@ -0,0 +1,48 @@
+//! The kind of device an interface configures.
+
+use std::fmt;
+
+/// The kind of device an interface stanza configures, as inferred by
+/// [`Interface::kind`](super::Interface::kind).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InterfaceKind {
+    /// The loopback interface.
+    Loopback,
+    /// A VRF device (has `vrf-table`).
+    Vrf,
+    /// A bridge (has `bridge-ports` or `bridge-vlan-aware`).
+    Bridge,
+    /// A bond (has `bond-slaves`).
+    Bond,
+    /// A VXLAN tunnel endpoint (has `vxlan-id`).
+    Vxlan,
+    /// A VLAN (has `vlan-id`, `vlan-raw-device`, or `vlan-protocol`, or is
+    /// named like `eth0.100`, `swp1.100.200`, or `vlan100`).
+    Vlan,
+    /// An IPv4 alias of another interface, named like `eth0:1`.
+    Alias,
+    /// Anything else, usually a physical port.
+    Physical,
+}
+
+impl InterfaceKind {
+    /// Returns the kind as a lowercase string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InterfaceKind::Loopback => "loopback",
+            InterfaceKind::Vrf => "vrf",
+            InterfaceKind::Bridge => "bridge",
+            InterfaceKind::Bond => "bond",
+            InterfaceKind::Vxlan => "vxlan",
+            InterfaceKind::Vlan => "vlan",
+            InterfaceKind::Alias => "alias",
+            InterfaceKind::Physical => "physical",
+        }
+    }
+}
+
+impl fmt::Display for InterfaceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
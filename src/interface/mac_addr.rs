@@ -0,0 +1,118 @@
+//! MAC addresses.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Represents a 48-bit Ethernet MAC address.
+///
+/// `MacAddr` accepts the common textual notations (`aa:bb:cc:dd:ee:ff`,
+/// `aa-bb-cc-dd-ee-ff` and `aabb.ccdd.eeff`) in any letter case, and always
+/// displays itself in lowercase, colon-separated form.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::MacAddr;
+///
+/// let mac: MacAddr = "AA-BB-CC-DD-EE-FF".parse().unwrap();
+/// assert_eq!(mac.to_string(), "aa:bb:cc:dd:ee:ff");
+/// assert_eq!(mac, "aa:bb:cc:dd:ee:ff".parse().unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl MacAddr {
+    /// Returns the six octets of the address.
+    pub fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let o = self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            o[0], o[1], o[2], o[3], o[4], o[5]
+        )
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = MacAddrParseError;
+
+    /// Parses a `MacAddr` from a string slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MacAddrParseError` if the input is not a MAC address in one
+    /// of the supported notations.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || MacAddrParseError(s.to_string());
+
+        // Split into groups on the first separator found
+        let groups: Vec<&str> = if s.contains(':') {
+            s.split(':').collect()
+        } else if s.contains('-') {
+            s.split('-').collect()
+        } else if s.contains('.') {
+            s.split('.').collect()
+        } else {
+            return Err(err());
+        };
+
+        let digits: String = match groups.len() {
+            6 if groups.iter().all(|g| g.len() == 2) => groups.concat(),
+            3 if groups.iter().all(|g| g.len() == 4) => groups.concat(),
+            _ => return Err(err()),
+        };
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(err());
+        }
+
+        let mut octets = [0u8; 6];
+        for (i, octet) in octets.iter_mut().enumerate() {
+            *octet = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).map_err(|_| err())?;
+        }
+        Ok(MacAddr(octets))
+    }
+}
+
+/// An error that occurs when parsing a `MacAddr` from a string.
+#[derive(Debug, Clone)]
+pub struct MacAddrParseError(pub String);
+
+impl fmt::Display for MacAddrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid MAC address: {}", self.0)
+    }
+}
+
+impl Error for MacAddrParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notations() {
+        let expected = MacAddr([0xaa, 0xbb, 0xcc, 0x01, 0x02, 0x03]);
+        assert_eq!("aa:bb:cc:01:02:03".parse::<MacAddr>().unwrap(), expected);
+        assert_eq!("AA:BB:CC:01:02:03".parse::<MacAddr>().unwrap(), expected);
+        assert_eq!("aa-bb-cc-01-02-03".parse::<MacAddr>().unwrap(), expected);
+        assert_eq!("aabb.cc01.0203".parse::<MacAddr>().unwrap(), expected);
+        assert_eq!(expected.to_string(), "aa:bb:cc:01:02:03");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("".parse::<MacAddr>().is_err());
+        assert!("aa:bb:cc:dd:ee".parse::<MacAddr>().is_err());
+        assert!("aa:bb:cc:dd:ee:gg".parse::<MacAddr>().is_err());
+        assert!("aa:bb:cc:dd:ee:f".parse::<MacAddr>().is_err());
+        assert!("ether aa:bb:cc:dd:ee:ff".parse::<MacAddr>().is_err());
+        assert!("+a:bb:cc:dd:ee:ff".parse::<MacAddr>().is_err());
+    }
+}
@@ -1,3 +1,5 @@
+//! `mapping` stanzas.
+
 /// Represents a `mapping` stanza in the `/etc/network/interfaces` file.
 ///
 /// The `Mapping` struct holds the script and map entries associated with a
@@ -15,7 +17,8 @@
 ///     maps: vec!["eth0".to_string(), "eth1".to_string()],
 /// };
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mapping {
     /// The script to be used for mapping.
     pub script: String,
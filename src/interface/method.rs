@@ -0,0 +1,256 @@
+//! Configuration methods of `iface` stanzas.
+
+use super::Family;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+/// Represents the configuration method of an `iface` stanza.
+///
+/// Well-known methods have dedicated variants; anything else is preserved
+/// verbatim in [`Method::Other`], so parsing a `Method` never fails.
+///
+/// # Variants
+///
+/// - `Loopback`: The `loopback` method.
+/// - `Static`: The `static` method.
+/// - `Manual`: The `manual` method.
+/// - `Dhcp`: The `dhcp` method.
+/// - `Bootp`: The `bootp` method (`inet`).
+/// - `Tunnel`: The `tunnel` method (`inet`).
+/// - `Ppp`: The `ppp` method (`inet`).
+/// - `Wvdial`: The `wvdial` method (`inet`).
+/// - `Ipv4ll`: The `ipv4ll` method (`inet`).
+/// - `Auto`: The `auto` method, stateless autoconfiguration (`inet6`).
+/// - `SixToFour`: The `6to4` method (`inet6`).
+/// - `V4Tunnel`: The `v4tunnel` method (`inet6`).
+/// - `Ipv6ll`: The `ipv6ll` method, link-local only (ifupdown2).
+/// - `Dhcp6`: The `dhcp6` method used by some platforms.
+/// - `Other`: Any other method, stored as written.
+///
+/// # Examples
+///
+/// Parsing a `Method` from a string:
+///
+/// ```rust
+/// use interface_rs::interface::Method;
+///
+/// let method: Method = "dhcp".parse().unwrap();
+/// assert_eq!(method, Method::Dhcp);
+///
+/// let method = Method::from("vendor-magic");
+/// assert_eq!(method, Method::Other("vendor-magic".to_string()));
+/// assert_eq!(method.to_string(), "vendor-magic");
+/// ```
+///
+/// More methods may get their own variant in future releases, and will then
+/// no longer parse as [`Method::Other`], so matches need a wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Method {
+    /// The `loopback` method.
+    Loopback,
+    /// The `static` method.
+    Static,
+    /// The `manual` method.
+    Manual,
+    /// The `dhcp` method.
+    Dhcp,
+    /// The `bootp` method.
+    Bootp,
+    /// The `tunnel` method.
+    Tunnel,
+    /// The `ppp` method.
+    Ppp,
+    /// The `wvdial` method.
+    Wvdial,
+    /// The `ipv4ll` method.
+    Ipv4ll,
+    /// The `auto` method (stateless autoconfiguration).
+    Auto,
+    /// The `6to4` method.
+    SixToFour,
+    /// The `v4tunnel` method.
+    V4Tunnel,
+    /// The `ipv6ll` method.
+    Ipv6ll,
+    /// The `dhcp6` method.
+    Dhcp6,
+    /// Any other method, stored as written.
+    Other(String),
+}
+
+impl Method {
+    /// Returns the method as written in an `interfaces(5)` file.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Loopback => "loopback",
+            Method::Static => "static",
+            Method::Manual => "manual",
+            Method::Dhcp => "dhcp",
+            Method::Bootp => "bootp",
+            Method::Tunnel => "tunnel",
+            Method::Ppp => "ppp",
+            Method::Wvdial => "wvdial",
+            Method::Ipv4ll => "ipv4ll",
+            Method::Auto => "auto",
+            Method::SixToFour => "6to4",
+            Method::V4Tunnel => "v4tunnel",
+            Method::Ipv6ll => "ipv6ll",
+            Method::Dhcp6 => "dhcp6",
+            Method::Other(s) => s,
+        }
+    }
+
+    /// Returns `true` if this method may be used with `family`, according to
+    /// [`Family::valid_methods`].
+    ///
+    /// [`Method::Other`] is always considered valid, since the crate cannot
+    /// know what vendor-specific methods support.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, Method};
+    ///
+    /// assert!(Method::Dhcp.valid_for(&Family::Inet));
+    /// assert!(!Method::Loopback.valid_for(&Family::Can));
+    /// assert!(Method::Other("vendor".to_string()).valid_for(&Family::Can));
+    /// ```
+    pub fn valid_for(&self, family: &Family) -> bool {
+        matches!(self, Method::Other(_)) || family.valid_methods().contains(self)
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Method {
+    type Err = Infallible;
+
+    /// Parses a `Method` from a string slice.
+    ///
+    /// Unknown methods are returned as [`Method::Other`], so this never fails.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Method::from(s))
+    }
+}
+
+impl From<&str> for Method {
+    fn from(s: &str) -> Self {
+        match s {
+            "loopback" => Method::Loopback,
+            "static" => Method::Static,
+            "manual" => Method::Manual,
+            "dhcp" => Method::Dhcp,
+            "bootp" => Method::Bootp,
+            "tunnel" => Method::Tunnel,
+            "ppp" => Method::Ppp,
+            "wvdial" => Method::Wvdial,
+            "ipv4ll" => Method::Ipv4ll,
+            "auto" => Method::Auto,
+            "6to4" => Method::SixToFour,
+            "v4tunnel" => Method::V4Tunnel,
+            "ipv6ll" => Method::Ipv6ll,
+            "dhcp6" => Method::Dhcp6,
+            _ => Method::Other(s.to_string()),
+        }
+    }
+}
+
+impl From<String> for Method {
+    fn from(s: String) -> Self {
+        match Method::from(s.as_str()) {
+            Method::Other(_) => Method::Other(s),
+            method => method,
+        }
+    }
+}
+
+impl From<Method> for String {
+    fn from(method: Method) -> Self {
+        match method {
+            Method::Other(s) => s,
+            method => method.as_str().to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Method {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Method {
+    /// Deserializes a `Method` from its textual form; unrecognized strings
+    /// become [`Method::Other`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Method::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_methods_round_trip() {
+        let table = [
+            ("bootp", Method::Bootp),
+            ("tunnel", Method::Tunnel),
+            ("ppp", Method::Ppp),
+            ("wvdial", Method::Wvdial),
+            ("ipv4ll", Method::Ipv4ll),
+            ("auto", Method::Auto),
+            ("6to4", Method::SixToFour),
+            ("v4tunnel", Method::V4Tunnel),
+            ("ipv6ll", Method::Ipv6ll),
+            ("dhcp6", Method::Dhcp6),
+        ];
+        for (s, expected) in table {
+            let method: Method = s.parse().unwrap();
+            assert_eq!(method, expected);
+            assert_eq!(method.to_string(), s);
+            assert_eq!(Method::from(String::from(method)), expected);
+        }
+    }
+
+    #[test]
+    fn test_method_round_trip() {
+        for s in ["loopback", "static", "manual", "dhcp", "vendor-magic"] {
+            let method: Method = s.parse().unwrap();
+            assert_eq!(method.to_string(), s);
+            assert_eq!(method.as_str(), s);
+        }
+        assert_eq!(Method::from("static"), Method::Static);
+        assert_eq!(String::from(Method::SixToFour), "6to4");
+        assert_eq!(
+            Method::from("Static".to_string()),
+            Method::Other("Static".to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        for method in [
+            Method::Dhcp,
+            Method::SixToFour,
+            Method::Other("vendor-magic".to_string()),
+        ] {
+            let json = serde_json::to_string(&method).unwrap();
+            assert_eq!(json, format!("\"{}\"", method));
+            assert_eq!(serde_json::from_str::<Method>(&json).unwrap(), method);
+        }
+        assert_eq!(
+            serde_json::from_str::<Method>("\"vendor-magic\"").unwrap(),
+            Method::Other("vendor-magic".to_string())
+        );
+    }
+}
@@ -1,18 +1,49 @@
 //! Module containing definitions related to network interfaces.
 //!
 //! This module provides the [`Interface`] struct and related enums and structs
-//! such as [`Family`], [`Mapping`], and the [`InterfaceBuilder`]. These types
-//! are used to represent and manipulate network interface configurations in an
+//! such as [`Family`], [`Method`], [`IfaceName`], [`Mapping`], [`InterfaceOption`],
+//! [`Address`], [`VlanRanges`], [`VlanProtocol`], [`Origin`], [`FieldDiff`],
+//! and the [`InterfaceBuilder`] and [`VlanBuilder`]. These types are used to
+//! represent and manipulate network interface configurations in an
 //! `interfaces(5)` file.
 //!
 //! Refer to the `interfaces(5)` manual page for details on the file format.
 
+pub mod address;
+pub mod diff;
 pub mod family;
+pub mod family_stanza;
+pub mod iface_name;
 pub mod interface_builder;
 pub mod interface_struct;
+pub mod kind;
+pub mod mac_addr;
 pub mod mapping;
+pub mod method;
+pub mod option;
+pub mod origin;
+pub mod view;
+pub mod vlan_builder;
+pub mod vlan_protocol;
+pub mod vlan_ranges;
 
+pub use address::{Address, AddressParseError};
+pub use diff::FieldDiff;
 pub use family::{Family, FamilyParseError};
+pub use family_stanza::{FamilyStanza, StanzaRef};
+pub use iface_name::{validate_interface_name, IfaceName, NameError, MAX_NAME_LEN};
 pub use interface_builder::InterfaceBuilder;
 pub use interface_struct::Interface;
+pub use kind::InterfaceKind;
+pub use mac_addr::{MacAddr, MacAddrParseError};
 pub use mapping::Mapping;
+pub use method::Method;
+pub use option::{
+    HwAddress, InterfaceOption, OptionCategory, OptionKey, OptionParseError, OptionRangeError,
+    ScriptPhase,
+};
+pub use origin::Origin;
+pub use view::InterfaceView;
+pub use vlan_builder::VlanBuilder;
+pub use vlan_protocol::{VlanProtocol, VlanProtocolParseError};
+pub use vlan_ranges::{VlanRanges, VlanRangesParseError};
@@ -0,0 +1,1176 @@
+//! Option keys and typed option values.
+
+use super::{Family, MacAddr, VlanProtocol, VlanRanges};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Represents a single option line under an `iface` stanza, classified by key.
+///
+/// Options are stored on an [`Interface`](super::Interface) as raw key/value
+/// pairs so that the file round-trips exactly; `InterfaceOption` is the typed
+/// view of such a pair. Well-known keys map to dedicated variants, while
+/// anything else (or a well-known key whose value cannot be parsed) is kept
+/// verbatim in [`InterfaceOption::Other`].
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::InterfaceOption;
+///
+/// let opt = InterfaceOption::from_key_value("mtu", "9216");
+/// assert_eq!(opt, InterfaceOption::Mtu(9216));
+/// assert_eq!(opt.key(), "mtu");
+/// assert_eq!(opt.value(), "9216");
+///
+/// let opt = InterfaceOption::from_key_value("mtu", "jumbo");
+/// assert_eq!(opt, InterfaceOption::Other("mtu".to_string(), "jumbo".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InterfaceOption {
+    /// `address` - an address, optionally with a prefix length or netmask.
+    Address(String),
+    /// `netmask` - the netmask or prefix length.
+    Netmask(String),
+    /// `gateway` - the default gateway.
+    Gateway(String),
+    /// `broadcast` - the broadcast address.
+    Broadcast(String),
+    /// `network` - the network address.
+    Network(String),
+    /// `pointopoint` - the address of the other end of a point-to-point link.
+    PointToPoint(String),
+    /// `hwaddress` - the link-layer address.
+    HwAddress(HwAddress),
+    /// `mtu` - the maximum transmission unit.
+    Mtu(u16),
+    /// `metric` - the metric of the default route.
+    Metric(u32),
+    /// `vlan-id` - the 802.1Q VLAN id.
+    VlanId(u16),
+    /// `vlan-raw-device` - the device a VLAN is stacked on.
+    VlanRawDevice(String),
+    /// `vlan-protocol` - the tag protocol, 802.1Q or 802.1ad.
+    VlanProtocol(VlanProtocol),
+    /// `bridge-ports` - the member ports of a bridge.
+    BridgePorts(String),
+    /// `bridge-access` - the access VLAN of a bridge port.
+    BridgeAccess(u16),
+    /// `bridge-pvid` - the native VLAN of a bridge or bridge port.
+    BridgePvid(u16),
+    /// `bridge-vids` - the VLANs carried by a bridge or bridge port.
+    BridgeVids(VlanRanges),
+    /// `bridge-vlan-aware` - whether the bridge is VLAN aware.
+    BridgeVlanAware(String),
+    /// `bond-slaves` - the member ports of a bond.
+    BondSlaves(String),
+    /// `bond-mode` - the bonding mode.
+    BondMode(String),
+    /// `vrf` - the VRF the interface is enslaved to.
+    Vrf(String),
+    /// `vrf-table` - the routing table of a VRF device.
+    VrfTable(String),
+    /// `dns-nameservers` - the DNS servers for the interface.
+    DnsNameservers(String),
+    /// `dns-search` - the DNS search domains for the interface.
+    DnsSearch(String),
+    /// `hostname` - the hostname the DHCP client requests.
+    DhcpHostname(String),
+    /// `client` - the client identifier the DHCP client sends.
+    DhcpClientId(String),
+    /// `leasehours` - the preferred lease time in hours.
+    DhcpLeaseHours(u32),
+    /// `leasetime` - the preferred lease time in seconds.
+    DhcpLeaseTime(u32),
+    /// `vendor` - the vendor class identifier the DHCP client sends.
+    DhcpVendor(String),
+    /// `request_prefix` - whether the DHCPv6 client requests a delegated
+    /// prefix.
+    DhcpRequestPrefix(String),
+    /// `pre-up` - a command run before bringing the interface up.
+    PreUp(String),
+    /// `up` - a command run when bringing the interface up.
+    Up(String),
+    /// `post-up` - a command run after bringing the interface up.
+    PostUp(String),
+    /// `pre-down` - a command run before taking the interface down.
+    PreDown(String),
+    /// `down` - a command run when taking the interface down.
+    Down(String),
+    /// `post-down` - a command run after taking the interface down.
+    PostDown(String),
+    /// Any other option, stored as its raw key and value.
+    Other(String, String),
+}
+
+impl InterfaceOption {
+    /// Classifies a key/value pair, falling back to [`InterfaceOption::Other`]
+    /// when the key is unknown or its value cannot be parsed.
+    ///
+    /// The key is folded with [`normalize_key`] before classification, so
+    /// `post_up`, `POST-UP`, and `post-up` all produce
+    /// [`InterfaceOption::PostUp`]. Use [`InterfaceOption::from_key_value_strict`]
+    /// to match keys exactly.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The option name (e.g., `"mtu"`).
+    /// * `value` - The option value (e.g., `"9216"`).
+    pub fn from_key_value(key: &str, value: &str) -> Self {
+        Self::classify(key, value, false)
+    }
+
+    /// Classifies a key/value pair like [`InterfaceOption::from_key_value`],
+    /// but without folding the key: only the canonical spelling of a
+    /// well-known key produces its typed variant.
+    pub fn from_key_value_strict(key: &str, value: &str) -> Self {
+        Self::classify(key, value, true)
+    }
+
+    /// Classifies a key/value pair, failing when a well-known key has a value
+    /// that cannot be parsed into its typed variant.
+    ///
+    /// Unknown keys are returned as [`InterfaceOption::Other`]. The key is
+    /// folded with [`normalize_key`] before classification.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `OptionParseError` if the value of a typed option is invalid.
+    pub fn try_from_key_value(key: &str, value: &str) -> Result<Self, OptionParseError> {
+        Self::try_classify(key, value, false)
+    }
+
+    fn classify(key: &str, value: &str, strict: bool) -> Self {
+        Self::try_classify(key, value, strict)
+            .unwrap_or_else(|_| InterfaceOption::Other(key.to_string(), value.to_string()))
+    }
+
+    pub(crate) fn try_classify(
+        key: &str,
+        value: &str,
+        strict: bool,
+    ) -> Result<Self, OptionParseError> {
+        let string = || value.to_string();
+        let invalid = || OptionParseError {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let normalized = if strict {
+            Cow::Borrowed(key)
+        } else {
+            normalize_key(key)
+        };
+        let opt = match normalized.as_ref() {
+            "address" => InterfaceOption::Address(string()),
+            "netmask" => InterfaceOption::Netmask(string()),
+            "gateway" => InterfaceOption::Gateway(string()),
+            "broadcast" => InterfaceOption::Broadcast(string()),
+            "network" => InterfaceOption::Network(string()),
+            "pointopoint" => InterfaceOption::PointToPoint(string()),
+            "hwaddress" => InterfaceOption::HwAddress(HwAddress::new(value)),
+            "mtu" => InterfaceOption::Mtu(value.parse().map_err(|_| invalid())?),
+            "metric" => InterfaceOption::Metric(value.parse().map_err(|_| invalid())?),
+            "vlan-id" => InterfaceOption::VlanId(value.parse().map_err(|_| invalid())?),
+            "vlan-raw-device" => InterfaceOption::VlanRawDevice(string()),
+            "vlan-protocol" => InterfaceOption::VlanProtocol(value.parse().map_err(|_| invalid())?),
+            "bridge-ports" => InterfaceOption::BridgePorts(string()),
+            "bridge-access" => InterfaceOption::BridgeAccess(value.parse().map_err(|_| invalid())?),
+            "bridge-pvid" => InterfaceOption::BridgePvid(value.parse().map_err(|_| invalid())?),
+            "bridge-vids" => InterfaceOption::BridgeVids(value.parse().map_err(|_| invalid())?),
+            "bridge-vlan-aware" => InterfaceOption::BridgeVlanAware(string()),
+            "bond-slaves" => InterfaceOption::BondSlaves(string()),
+            "bond-mode" => InterfaceOption::BondMode(string()),
+            "vrf" => InterfaceOption::Vrf(string()),
+            "vrf-table" => InterfaceOption::VrfTable(string()),
+            "dns-nameservers" => InterfaceOption::DnsNameservers(string()),
+            "dns-search" => InterfaceOption::DnsSearch(string()),
+            "hostname" => InterfaceOption::DhcpHostname(string()),
+            "client" => InterfaceOption::DhcpClientId(string()),
+            "leasehours" => InterfaceOption::DhcpLeaseHours(value.parse().map_err(|_| invalid())?),
+            "leasetime" => InterfaceOption::DhcpLeaseTime(value.parse().map_err(|_| invalid())?),
+            "vendor" => InterfaceOption::DhcpVendor(string()),
+            // The canonical spelling has an underscore, which folding replaces
+            "request_prefix" => InterfaceOption::DhcpRequestPrefix(string()),
+            "request-prefix" if !strict => InterfaceOption::DhcpRequestPrefix(string()),
+            "pre-up" => InterfaceOption::PreUp(string()),
+            "up" => InterfaceOption::Up(string()),
+            "post-up" => InterfaceOption::PostUp(string()),
+            "pre-down" => InterfaceOption::PreDown(string()),
+            "down" => InterfaceOption::Down(string()),
+            "post-down" => InterfaceOption::PostDown(string()),
+            _ => InterfaceOption::Other(key.to_string(), string()),
+        };
+        Ok(opt)
+    }
+
+    /// Returns the option name as written in an `interfaces(5)` file.
+    pub fn key(&self) -> &str {
+        match self {
+            InterfaceOption::Address(_) => "address",
+            InterfaceOption::Netmask(_) => "netmask",
+            InterfaceOption::Gateway(_) => "gateway",
+            InterfaceOption::Broadcast(_) => "broadcast",
+            InterfaceOption::Network(_) => "network",
+            InterfaceOption::PointToPoint(_) => "pointopoint",
+            InterfaceOption::HwAddress(_) => "hwaddress",
+            InterfaceOption::Mtu(_) => "mtu",
+            InterfaceOption::Metric(_) => "metric",
+            InterfaceOption::VlanId(_) => "vlan-id",
+            InterfaceOption::VlanRawDevice(_) => "vlan-raw-device",
+            InterfaceOption::VlanProtocol(_) => "vlan-protocol",
+            InterfaceOption::BridgePorts(_) => "bridge-ports",
+            InterfaceOption::BridgeAccess(_) => "bridge-access",
+            InterfaceOption::BridgePvid(_) => "bridge-pvid",
+            InterfaceOption::BridgeVids(_) => "bridge-vids",
+            InterfaceOption::BridgeVlanAware(_) => "bridge-vlan-aware",
+            InterfaceOption::BondSlaves(_) => "bond-slaves",
+            InterfaceOption::BondMode(_) => "bond-mode",
+            InterfaceOption::Vrf(_) => "vrf",
+            InterfaceOption::VrfTable(_) => "vrf-table",
+            InterfaceOption::DnsNameservers(_) => "dns-nameservers",
+            InterfaceOption::DnsSearch(_) => "dns-search",
+            InterfaceOption::DhcpHostname(_) => "hostname",
+            InterfaceOption::DhcpClientId(_) => "client",
+            InterfaceOption::DhcpLeaseHours(_) => "leasehours",
+            InterfaceOption::DhcpLeaseTime(_) => "leasetime",
+            InterfaceOption::DhcpVendor(_) => "vendor",
+            InterfaceOption::DhcpRequestPrefix(_) => "request_prefix",
+            InterfaceOption::PreUp(_) => "pre-up",
+            InterfaceOption::Up(_) => "up",
+            InterfaceOption::PostUp(_) => "post-up",
+            InterfaceOption::PreDown(_) => "pre-down",
+            InterfaceOption::Down(_) => "down",
+            InterfaceOption::PostDown(_) => "post-down",
+            InterfaceOption::Other(key, _) => key,
+        }
+    }
+
+    /// Returns the option value as written in an `interfaces(5)` file.
+    pub fn value(&self) -> String {
+        let mut value = String::new();
+        // Writing to a String cannot fail
+        let _ = self.write_value(&mut value);
+        value
+    }
+
+    /// Writes the option value without building an intermediate `String`.
+    fn write_value(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self {
+            InterfaceOption::Address(v)
+            | InterfaceOption::Netmask(v)
+            | InterfaceOption::Gateway(v)
+            | InterfaceOption::Broadcast(v)
+            | InterfaceOption::Network(v)
+            | InterfaceOption::PointToPoint(v)
+            | InterfaceOption::VlanRawDevice(v)
+            | InterfaceOption::BridgePorts(v)
+            | InterfaceOption::BridgeVlanAware(v)
+            | InterfaceOption::BondSlaves(v)
+            | InterfaceOption::BondMode(v)
+            | InterfaceOption::Vrf(v)
+            | InterfaceOption::VrfTable(v)
+            | InterfaceOption::DnsNameservers(v)
+            | InterfaceOption::DnsSearch(v)
+            | InterfaceOption::DhcpHostname(v)
+            | InterfaceOption::DhcpClientId(v)
+            | InterfaceOption::DhcpVendor(v)
+            | InterfaceOption::DhcpRequestPrefix(v)
+            | InterfaceOption::PreUp(v)
+            | InterfaceOption::Up(v)
+            | InterfaceOption::PostUp(v)
+            | InterfaceOption::PreDown(v)
+            | InterfaceOption::Down(v)
+            | InterfaceOption::PostDown(v)
+            | InterfaceOption::Other(_, v) => f.write_str(v),
+            InterfaceOption::HwAddress(hw) => f.write_str(hw.as_str()),
+            InterfaceOption::BridgeVids(vids) => write!(f, "{}", vids),
+            InterfaceOption::VlanProtocol(protocol) => f.write_str(protocol.as_str()),
+            InterfaceOption::Mtu(n)
+            | InterfaceOption::VlanId(n)
+            | InterfaceOption::BridgeAccess(n)
+            | InterfaceOption::BridgePvid(n) => write!(f, "{}", n),
+            InterfaceOption::Metric(n)
+            | InterfaceOption::DhcpLeaseHours(n)
+            | InterfaceOption::DhcpLeaseTime(n) => write!(f, "{}", n),
+        }
+    }
+
+    /// Checks that the value of a numeric option lies within the range
+    /// accepted by the kernel.
+    ///
+    /// The enforced bounds are:
+    ///
+    /// - `mtu`: 68–65535, or 1280–65535 when `family` is `inet6`.
+    /// - `vlan-id`, `bridge-access`, `bridge-pvid`: 1–4094.
+    ///
+    /// Non-numeric options always pass. Parsing stays lenient, so this check
+    /// is only applied when explicitly requested (for example by
+    /// [`NetworkInterfaces::validate`](crate::NetworkInterfaces::validate)).
+    ///
+    /// # Errors
+    ///
+    /// Returns an `OptionRangeError` naming the option and the allowed range.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::{Family, InterfaceOption};
+    ///
+    /// assert!(InterfaceOption::Mtu(1500).validate(None).is_ok());
+    /// assert!(InterfaceOption::Mtu(576).validate(Some(&Family::Inet6)).is_err());
+    /// assert!(InterfaceOption::VlanId(5000).validate(None).is_err());
+    /// ```
+    pub fn validate(&self, family: Option<&Family>) -> Result<(), OptionRangeError> {
+        let (value, min, max) = match self {
+            InterfaceOption::Mtu(n) => {
+                let min = if family == Some(&Family::Inet6) {
+                    1280
+                } else {
+                    68
+                };
+                (u64::from(*n), min, 65535)
+            }
+            InterfaceOption::VlanId(n)
+            | InterfaceOption::BridgeAccess(n)
+            | InterfaceOption::BridgePvid(n) => (u64::from(*n), 1, 4094),
+            _ => return Ok(()),
+        };
+        if value < min || value > max {
+            return Err(OptionRangeError {
+                key: self.key().to_string(),
+                value,
+                min,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the [`OptionCategory`] this option belongs to.
+    ///
+    /// Options without a dedicated variant are categorized by their key
+    /// prefix (`bridge-`, `bond-`, `vlan-`, `vrf`, `dns-`), or as
+    /// [`OptionCategory::Dhcp`] if they are one of the [`DHCP_KEYS`].
+    pub fn category(&self) -> OptionCategory {
+        match self {
+            InterfaceOption::Address(_)
+            | InterfaceOption::Netmask(_)
+            | InterfaceOption::Gateway(_)
+            | InterfaceOption::Broadcast(_)
+            | InterfaceOption::Network(_)
+            | InterfaceOption::PointToPoint(_) => OptionCategory::Addressing,
+            InterfaceOption::HwAddress(_)
+            | InterfaceOption::Mtu(_)
+            | InterfaceOption::Metric(_) => OptionCategory::Link,
+            InterfaceOption::VlanId(_)
+            | InterfaceOption::VlanRawDevice(_)
+            | InterfaceOption::VlanProtocol(_) => OptionCategory::Vlan,
+            InterfaceOption::BridgePorts(_)
+            | InterfaceOption::BridgeAccess(_)
+            | InterfaceOption::BridgePvid(_)
+            | InterfaceOption::BridgeVids(_)
+            | InterfaceOption::BridgeVlanAware(_) => OptionCategory::Bridge,
+            InterfaceOption::BondSlaves(_) | InterfaceOption::BondMode(_) => OptionCategory::Bond,
+            InterfaceOption::Vrf(_) | InterfaceOption::VrfTable(_) => OptionCategory::Vrf,
+            InterfaceOption::DnsNameservers(_) | InterfaceOption::DnsSearch(_) => {
+                OptionCategory::Dns
+            }
+            InterfaceOption::DhcpHostname(_)
+            | InterfaceOption::DhcpClientId(_)
+            | InterfaceOption::DhcpLeaseHours(_)
+            | InterfaceOption::DhcpLeaseTime(_)
+            | InterfaceOption::DhcpVendor(_)
+            | InterfaceOption::DhcpRequestPrefix(_) => OptionCategory::Dhcp,
+            InterfaceOption::PreUp(_)
+            | InterfaceOption::Up(_)
+            | InterfaceOption::PostUp(_)
+            | InterfaceOption::PreDown(_)
+            | InterfaceOption::Down(_)
+            | InterfaceOption::PostDown(_) => OptionCategory::Script,
+            InterfaceOption::Other(key, _) => {
+                let key = normalize_key(key);
+                if key.starts_with("bridge-") {
+                    OptionCategory::Bridge
+                } else if key.starts_with("bond-") {
+                    OptionCategory::Bond
+                } else if key.starts_with("vlan-") {
+                    OptionCategory::Vlan
+                } else if key.starts_with("vrf") {
+                    OptionCategory::Vrf
+                } else if key.starts_with("dns-") {
+                    OptionCategory::Dns
+                } else if DHCP_KEYS.iter().any(|dhcp| keys_match(&key, dhcp, false)) {
+                    OptionCategory::Dhcp
+                } else {
+                    OptionCategory::Other
+                }
+            }
+        }
+    }
+
+    /// Returns `true` for layer-3 addressing options (`address`, `netmask`,
+    /// `gateway`, `broadcast`, `network`, `pointopoint`).
+    pub fn is_addressing(&self) -> bool {
+        self.category() == OptionCategory::Addressing
+    }
+
+    /// Returns `true` for hook commands (`pre-up`, `up`, `post-up`,
+    /// `pre-down`, `down`, `post-down`).
+    pub fn is_script(&self) -> bool {
+        self.category() == OptionCategory::Script
+    }
+
+    /// Returns `true` for bridge options (`bridge-*`).
+    pub fn is_bridge(&self) -> bool {
+        self.category() == OptionCategory::Bridge
+    }
+
+    /// Returns `true` for bonding options (`bond-*`).
+    pub fn is_bond(&self) -> bool {
+        self.category() == OptionCategory::Bond
+    }
+
+    /// Returns `true` for options only meaningful to a DHCP client; see
+    /// [`DHCP_KEYS`].
+    pub fn is_dhcp(&self) -> bool {
+        self.category() == OptionCategory::Dhcp
+    }
+
+    /// Returns `true` if the option may legally appear more than once in a
+    /// stanza.
+    ///
+    /// This holds for `address`, the DNS options, and all hook commands.
+    /// Unknown options are conservatively treated as repeatable, since the
+    /// crate cannot tell whether merging them would lose information.
+    pub fn is_repeatable(&self) -> bool {
+        matches!(
+            self,
+            InterfaceOption::Address(_)
+                | InterfaceOption::DnsNameservers(_)
+                | InterfaceOption::DnsSearch(_)
+                | InterfaceOption::Other(..)
+        ) || self.is_script()
+    }
+}
+
+impl fmt::Display for InterfaceOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())?;
+        f.write_str(" ")?;
+        self.write_value(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for InterfaceOption {
+    /// Serializes the option as a `[key, value]` pair, the same form used for
+    /// [`Interface::options`](crate::Interface::options).
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.key(), self.value()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for InterfaceOption {
+    /// Deserializes a `[key, value]` pair with
+    /// [`from_key_value`](InterfaceOption::from_key_value), so unknown keys
+    /// and unparsable values are kept as [`InterfaceOption::Other`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (key, value) = <(String, String)>::deserialize(deserializer)?;
+        Ok(InterfaceOption::from_key_value(&key, &value))
+    }
+}
+
+/// The broad category of an [`InterfaceOption`].
+///
+/// Categories are used to group options when rendering, diffing, and
+/// validating, so that the classification lives in one place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OptionCategory {
+    /// Layer-3 addressing: `address`, `netmask`, `gateway`, `broadcast`,
+    /// `network`, `pointopoint`.
+    Addressing,
+    /// Link and interface-wide settings: `hwaddress`, `mtu`, `metric`.
+    Link,
+    /// VLAN settings: `vlan-*`.
+    Vlan,
+    /// Bridge settings: `bridge-*`.
+    Bridge,
+    /// Bonding settings: `bond-*`.
+    Bond,
+    /// VRF settings: `vrf`, `vrf-table`.
+    Vrf,
+    /// Resolver settings: `dns-*`.
+    Dns,
+    /// DHCP client settings: the [`DHCP_KEYS`].
+    Dhcp,
+    /// Hook commands: `pre-up`, `up`, `post-up`, `pre-down`, `down`, `post-down`.
+    Script,
+    /// Anything else.
+    Other,
+}
+
+/// The keys of options only meaningful to a DHCP client: `hostname`,
+/// `client`, `leasehours`, `leasetime`, `vendor`, `request_prefix`, and the
+/// link-local fallback settings `ll-attempts` and `ll-interval`.
+///
+/// [`Interface::to_static`](super::Interface::to_static) strips these options,
+/// and validation flags them on interfaces that do not use DHCP.
+pub const DHCP_KEYS: &[&str] = &[
+    "hostname",
+    "client",
+    "leasehours",
+    "leasetime",
+    "vendor",
+    "request_prefix",
+    "ll-attempts",
+    "ll-interval",
+];
+
+/// The phase of bringing an interface up or down in which a hook command
+/// runs.
+///
+/// Each phase corresponds to one of the script options of a stanza; see
+/// [`Interface::commands`](super::Interface::commands).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ScriptPhase {
+    /// `pre-up` - before the interface is brought up.
+    PreUp,
+    /// `up` - when the interface is brought up.
+    Up,
+    /// `post-up` - after the interface is brought up.
+    PostUp,
+    /// `pre-down` - before the interface is taken down.
+    PreDown,
+    /// `down` - when the interface is taken down.
+    Down,
+    /// `post-down` - after the interface is taken down.
+    PostDown,
+}
+
+impl ScriptPhase {
+    /// Every phase, in the order they run for an `ifup` followed by an
+    /// `ifdown`.
+    pub const ALL: [ScriptPhase; 6] = [
+        ScriptPhase::PreUp,
+        ScriptPhase::Up,
+        ScriptPhase::PostUp,
+        ScriptPhase::PreDown,
+        ScriptPhase::Down,
+        ScriptPhase::PostDown,
+    ];
+
+    /// Returns the option key of the phase (e.g., `"post-up"`).
+    pub fn key(&self) -> &'static str {
+        match self {
+            ScriptPhase::PreUp => "pre-up",
+            ScriptPhase::Up => "up",
+            ScriptPhase::PostUp => "post-up",
+            ScriptPhase::PreDown => "pre-down",
+            ScriptPhase::Down => "down",
+            ScriptPhase::PostDown => "post-down",
+        }
+    }
+}
+
+impl fmt::Display for ScriptPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+/// Folds an option key into its canonical spelling.
+///
+/// ifupdown is tolerant of how option names are written, and real-world
+/// files mix spellings. Normalization:
+///
+/// - lowercases the key;
+/// - replaces underscores with hyphens (`post_up` becomes `post-up`);
+/// - maps known aliases to their canonical name (`dns-nameserver` becomes
+///   `dns-nameservers`).
+///
+/// Normalization is only used to *match* keys; the original spelling is kept
+/// in the interface's options and written back unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::option::normalize_key;
+///
+/// assert_eq!(normalize_key("Post_Up"), "post-up");
+/// assert_eq!(normalize_key("dns-nameserver"), "dns-nameservers");
+/// assert_eq!(normalize_key("mtu"), "mtu");
+/// ```
+pub fn normalize_key(key: &str) -> Cow<'_, str> {
+    let folded: Cow<'_, str> = if key.bytes().any(|b| b == b'_' || b.is_ascii_uppercase()) {
+        Cow::Owned(key.to_ascii_lowercase().replace('_', "-"))
+    } else {
+        Cow::Borrowed(key)
+    };
+    match folded.as_ref() {
+        "dns-nameserver" => Cow::Borrowed("dns-nameservers"),
+        _ => folded,
+    }
+}
+
+/// Returns `true` if two option keys match, folding them with
+/// [`normalize_key`] unless `strict` is set.
+pub(crate) fn keys_match(a: &str, b: &str, strict: bool) -> bool {
+    if strict {
+        a == b
+    } else {
+        a == b || normalize_key(a) == normalize_key(b)
+    }
+}
+
+/// Removes every option whose key matches `key`, returning how many lines
+/// were removed.
+pub(crate) fn remove_matching(
+    options: &mut Vec<(OptionKey, String)>,
+    key: &str,
+    strict: bool,
+) -> usize {
+    let before = options.len();
+    options.retain(|(k, _)| !keys_match(k, key, strict));
+    before - options.len()
+}
+
+/// Removes every `key value` line, returning `true` if any was removed.
+pub(crate) fn remove_matching_value(
+    options: &mut Vec<(OptionKey, String)>,
+    key: &str,
+    value: &str,
+    strict: bool,
+) -> bool {
+    let before = options.len();
+    options.retain(|(k, v)| !(keys_match(k, key, strict) && v == value));
+    options.len() != before
+}
+
+/// Sets `key` to `value`, leaving exactly one line for the key.
+///
+/// The first matching line keeps its position and spelling and takes the
+/// new value; later lines for the key are removed. If no line matches, one is
+/// appended. Returns `true` if the options changed.
+pub(crate) fn set_single(
+    options: &mut Vec<(OptionKey, String)>,
+    key: &str,
+    value: &str,
+    strict: bool,
+) -> bool {
+    let mut changed = match options.iter_mut().find(|(k, _)| keys_match(k, key, strict)) {
+        Some((_, v)) if v == value => false,
+        Some((_, v)) => {
+            *v = value.to_string();
+            true
+        }
+        None => {
+            options.push((intern_key(key), value.to_string()));
+            true
+        }
+    };
+    let before = options.len();
+    let mut seen = false;
+    options.retain(|(k, _)| !keys_match(k, key, strict) || !std::mem::replace(&mut seen, true));
+    changed |= options.len() != before;
+    changed
+}
+
+/// The key of an option line, as stored in
+/// [`Interface::options`](super::Interface::options).
+///
+/// Well-known keys borrow a shared static string so that the thousands of
+/// `address` or `mtu` lines in a large file do not each allocate their own
+/// copy; other keys are owned. Use [`intern_key`] to build one, or
+/// `.into()` from a `String` or `&'static str`.
+pub type OptionKey = Cow<'static, str>;
+
+/// Defines the well-known keys shared by [`intern_key`].
+macro_rules! known_keys {
+    ($($key:literal,)*) => {
+        /// Every well-known key.
+        #[cfg(test)]
+        const KNOWN_KEYS: &[&str] = &[$($key),*];
+
+        /// Returns the static copy of `key` if it is well known.
+        fn known_key(key: &str) -> Option<&'static str> {
+            match key {
+                $($key => Some($key),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+known_keys! {
+    "address",
+    "alias",
+    "bond-downdelay",
+    "bond-lacp-bypass-allow",
+    "bond-lacp-rate",
+    "bond-miimon",
+    "bond-min-links",
+    "bond-mode",
+    "bond-slaves",
+    "bond-updelay",
+    "bond-xmit-hash-policy",
+    "bridge-access",
+    "bridge-arp-nd-suppress",
+    "bridge-learning",
+    "bridge-ports",
+    "bridge-pvid",
+    "bridge-stp",
+    "bridge-vids",
+    "bridge-vlan-aware",
+    "broadcast",
+    "dns-nameservers",
+    "dns-search",
+    "down",
+    "gateway",
+    "hwaddress",
+    "link-autoneg",
+    "link-duplex",
+    "link-speed",
+    "metric",
+    "mstpctl-bpduguard",
+    "mstpctl-portadminedge",
+    "mstpctl-portbpdufilter",
+    "mtu",
+    "netmask",
+    "network",
+    "pointopoint",
+    "post-down",
+    "post-up",
+    "pre-down",
+    "pre-up",
+    "up",
+    "vlan-id",
+    "vlan-protocol",
+    "vlan-raw-device",
+    "vrf",
+    "vrf-table",
+    "vxlan-id",
+    "vxlan-local-tunnelip",
+}
+
+/// Returns an [`OptionKey`] for `key`, sharing storage for well-known keys.
+///
+/// Owned keys that are not well known are kept without copying.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::option::intern_key;
+/// use std::borrow::Cow;
+///
+/// assert!(matches!(intern_key("mtu"), Cow::Borrowed("mtu")));
+/// assert!(matches!(intern_key("x-vendor-knob"), Cow::Owned(_)));
+/// ```
+pub fn intern_key<'a>(key: impl Into<Cow<'a, str>>) -> OptionKey {
+    let key = key.into();
+    match known_key(&key) {
+        Some(known) => Cow::Borrowed(known),
+        None => Cow::Owned(key.into_owned()),
+    }
+}
+
+/// The value of a `hwaddress` option.
+///
+/// `interfaces(5)` accepts both `hwaddress aa:bb:cc:dd:ee:ff` and the older
+/// `hwaddress ether aa:bb:cc:dd:ee:ff` form. `HwAddress` keeps the text exactly
+/// as it was read so that it is written back unchanged, and additionally holds
+/// the parsed [`MacAddr`] when the value is a recognizable MAC address.
+///
+/// Two `HwAddress` values compare equal when they carry the same MAC address,
+/// regardless of letter case, notation, or `ether` prefix. Values that could
+/// not be parsed compare by their raw text.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::HwAddress;
+///
+/// let a = HwAddress::new("ether AA:BB:CC:DD:EE:FF");
+/// let b = HwAddress::new("aa:bb:cc:dd:ee:ff");
+/// assert!(a.has_ether_prefix());
+/// assert_eq!(a, b);
+/// assert_eq!(a.as_str(), "ether AA:BB:CC:DD:EE:FF");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HwAddress {
+    raw: String,
+    mac: Option<MacAddr>,
+}
+
+impl HwAddress {
+    /// Creates a new `HwAddress` from the raw option value.
+    pub fn new(raw: impl Into<String>) -> Self {
+        let raw = raw.into();
+        let mut tokens = raw.split_whitespace();
+        let mac = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some("ether"), Some(address), None) | (Some(address), None, None) => {
+                address.parse().ok()
+            }
+            _ => None,
+        };
+        HwAddress { raw, mac }
+    }
+
+    /// Returns the parsed MAC address, if the value is one.
+    pub fn mac(&self) -> Option<MacAddr> {
+        self.mac
+    }
+
+    /// Returns `true` if the value was written with the `ether` prefix.
+    pub fn has_ether_prefix(&self) -> bool {
+        self.raw.split_whitespace().next() == Some("ether")
+    }
+
+    /// Returns the value exactly as it was read.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl From<MacAddr> for HwAddress {
+    fn from(mac: MacAddr) -> Self {
+        HwAddress {
+            raw: mac.to_string(),
+            mac: Some(mac),
+        }
+    }
+}
+
+impl PartialEq for HwAddress {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.mac, other.mac) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.raw == other.raw,
+        }
+    }
+}
+
+impl Eq for HwAddress {}
+
+impl Hash for HwAddress {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Consistent with `PartialEq`: parsed addresses hash by MAC, anything
+        // else by its raw text.
+        match self.mac {
+            Some(mac) => mac.hash(state),
+            None => self.raw.hash(state),
+        }
+    }
+}
+
+impl fmt::Display for HwAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// An error that occurs when a well-known option has a value that cannot be
+/// parsed into its typed [`InterfaceOption`] variant.
+#[derive(Debug, Clone)]
+pub struct OptionParseError {
+    /// The option name.
+    pub key: String,
+    /// The value that failed to parse.
+    pub value: String,
+}
+
+impl fmt::Display for OptionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid value for option {}: {}", self.key, self.value)
+    }
+}
+
+impl Error for OptionParseError {}
+
+/// An error that occurs when a numeric option is outside its allowed range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionRangeError {
+    /// The option name.
+    pub key: String,
+    /// The out-of-range value.
+    pub value: u64,
+    /// The smallest allowed value.
+    pub min: u64,
+    /// The largest allowed value.
+    pub max: u64,
+}
+
+impl fmt::Display for OptionRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Value {} for option {} is out of range ({}-{})",
+            self.value, self.key, self.min, self.max
+        )
+    }
+}
+
+impl Error for OptionRangeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_known_keys() {
+        for key in KNOWN_KEYS {
+            assert!(matches!(intern_key(key.to_string()), Cow::Borrowed(k) if k == *key));
+        }
+    }
+
+    #[test]
+    fn test_hwaddress_forms() {
+        let plain = InterfaceOption::from_key_value("hwaddress", "aa:bb:cc:dd:ee:ff");
+        let ether = InterfaceOption::from_key_value("hwaddress", "ether AA:BB:CC:DD:EE:FF");
+        assert_eq!(plain, ether);
+        assert_eq!(plain.value(), "aa:bb:cc:dd:ee:ff");
+        assert_eq!(ether.value(), "ether AA:BB:CC:DD:EE:FF");
+
+        match ether {
+            InterfaceOption::HwAddress(hw) => {
+                assert!(hw.has_ether_prefix());
+                assert_eq!(
+                    hw.mac(),
+                    Some(MacAddr([0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]))
+                );
+            }
+            other => panic!("unexpected option {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hwaddress_raw_fallback() {
+        let opt = InterfaceOption::from_key_value("hwaddress", "random");
+        match &opt {
+            InterfaceOption::HwAddress(hw) => assert_eq!(hw.mac(), None),
+            other => panic!("unexpected option {:?}", other),
+        }
+        assert_eq!(opt.to_string(), "hwaddress random");
+        assert_ne!(
+            opt,
+            InterfaceOption::from_key_value("hwaddress", "aa:bb:cc:dd:ee:ff")
+        );
+    }
+
+    #[test]
+    fn test_validate_ranges() {
+        let inet6 = Some(&Family::Inet6);
+        assert!(InterfaceOption::Mtu(68).validate(None).is_ok());
+        assert!(InterfaceOption::Mtu(67).validate(None).is_err());
+        assert!(InterfaceOption::Mtu(1280).validate(inet6).is_ok());
+        assert!(InterfaceOption::Mtu(1279).validate(inet6).is_err());
+        assert!(InterfaceOption::BridgePvid(1).validate(None).is_ok());
+        assert!(InterfaceOption::BridgeAccess(4094).validate(None).is_ok());
+        assert!(InterfaceOption::Metric(u32::MAX).validate(None).is_ok());
+
+        let err = InterfaceOption::BridgePvid(0).validate(None).unwrap_err();
+        assert_eq!(
+            err,
+            OptionRangeError {
+                key: "bridge-pvid".to_string(),
+                value: 0,
+                min: 1,
+                max: 4094,
+            }
+        );
+        assert_eq!(
+            InterfaceOption::VlanId(5000)
+                .validate(None)
+                .unwrap_err()
+                .to_string(),
+            "Value 5000 for option vlan-id is out of range (1-4094)"
+        );
+    }
+
+    #[test]
+    fn test_key_aliases_classify_alike() {
+        let pairs = [
+            ("post-up", "post_up"),
+            ("post-up", "POST-UP"),
+            ("dns-nameservers", "dns-nameserver"),
+            ("dns-nameservers", "DNS_Nameservers"),
+            ("bridge-vlan-aware", "bridge_vlan_aware"),
+            ("mtu", "MTU"),
+            ("request_prefix", "request-prefix"),
+            ("leasetime", "LeaseTime"),
+        ];
+        for (canonical, alias) in pairs {
+            let a = InterfaceOption::from_key_value(canonical, "1500");
+            let b = InterfaceOption::from_key_value(alias, "1500");
+            assert_eq!(a, b, "{} vs {}", canonical, alias);
+            assert!(!matches!(b, InterfaceOption::Other(..)));
+            assert_eq!(b.key(), canonical);
+
+            // Strict matching leaves the alias unclassified and as written
+            assert_eq!(
+                InterfaceOption::from_key_value_strict(alias, "1500"),
+                InterfaceOption::Other(alias.to_string(), "1500".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_categories() {
+        use OptionCategory::*;
+        // (key, value, category, repeatable)
+        let table = [
+            ("address", "10.0.0.1/24", Addressing, true),
+            ("netmask", "255.255.255.0", Addressing, false),
+            ("gateway", "10.0.0.254", Addressing, false),
+            ("broadcast", "10.0.0.255", Addressing, false),
+            ("network", "10.0.0.0", Addressing, false),
+            ("pointopoint", "10.0.0.2", Addressing, false),
+            ("hwaddress", "aa:bb:cc:dd:ee:ff", Link, false),
+            ("mtu", "9216", Link, false),
+            ("metric", "100", Link, false),
+            ("vlan-id", "100", Vlan, false),
+            ("vlan-raw-device", "bridge", Vlan, false),
+            ("vlan-protocol", "802.1ad", Vlan, false),
+            ("bridge-ports", "swp1 swp2", Bridge, false),
+            ("bridge-access", "100", Bridge, false),
+            ("bridge-pvid", "1", Bridge, false),
+            ("bridge-vids", "100-200", Bridge, false),
+            ("bridge-vlan-aware", "yes", Bridge, false),
+            ("bond-slaves", "swp1 swp2", Bond, false),
+            ("bond-mode", "802.3ad", Bond, false),
+            ("vrf", "mgmt", Vrf, false),
+            ("vrf-table", "auto", Vrf, false),
+            ("dns-nameservers", "192.0.2.53", Dns, true),
+            ("dns-search", "example.com", Dns, true),
+            ("hostname", "box", Dhcp, false),
+            ("client", "01:aa:bb:cc:dd:ee:ff", Dhcp, false),
+            ("leasehours", "12", Dhcp, false),
+            ("leasetime", "3600", Dhcp, false),
+            ("vendor", "acme", Dhcp, false),
+            ("request_prefix", "1", Dhcp, false),
+            ("ll-attempts", "3", Dhcp, true),
+            ("pre-up", "/bin/true", Script, true),
+            ("up", "/bin/true", Script, true),
+            ("post-up", "/bin/true", Script, true),
+            ("pre-down", "/bin/true", Script, true),
+            ("down", "/bin/true", Script, true),
+            ("post-down", "/bin/true", Script, true),
+            ("bridge-stp", "on", Bridge, true),
+            ("bond-miimon", "100", Bond, true),
+            ("vlan-protocol", "802.1x", Vlan, true),
+            ("dns-domain", "example.com", Dns, true),
+            ("mstpctl-bpduguard", "yes", Other, true),
+        ];
+        for (key, value, category, repeatable) in table {
+            let opt = InterfaceOption::from_key_value(key, value);
+            assert_eq!(opt.category(), category, "{}", key);
+            assert_eq!(opt.is_repeatable(), repeatable, "{}", key);
+            assert_eq!(opt.is_addressing(), category == Addressing, "{}", key);
+            assert_eq!(opt.is_script(), category == Script, "{}", key);
+            assert_eq!(opt.is_bridge(), category == Bridge, "{}", key);
+            assert_eq!(opt.is_bond(), category == Bond, "{}", key);
+            assert_eq!(opt.is_dhcp(), category == Dhcp, "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_hashset_diff_matches_linear_diff() {
+        use std::collections::HashSet;
+
+        let make = |range: std::ops::Range<u16>| -> Vec<InterfaceOption> {
+            range
+                .flat_map(|i| {
+                    vec![
+                        InterfaceOption::from_key_value(
+                            "address",
+                            &format!("10.0.{}.1/24", i % 250),
+                        ),
+                        InterfaceOption::from_key_value("bridge-access", &i.to_string()),
+                        InterfaceOption::from_key_value(
+                            "hwaddress",
+                            &format!("ether AA:BB:CC:DD:{:02X}:{:02X}", i >> 8, i & 0xff),
+                        ),
+                        InterfaceOption::from_key_value("post-up", &format!("/bin/hook {}", i)),
+                    ]
+                })
+                .collect()
+        };
+        let old = make(0..1500);
+        let mut new = make(500..2000);
+        // Same MAC written differently must still be considered present
+        new.push(InterfaceOption::from_key_value(
+            "hwaddress",
+            "aa:bb:cc:dd:02:00",
+        ));
+
+        let linear_removed: Vec<&InterfaceOption> =
+            old.iter().filter(|o| !new.contains(o)).collect();
+        let linear_added: Vec<&InterfaceOption> = new.iter().filter(|o| !old.contains(o)).collect();
+
+        let old_set: HashSet<&InterfaceOption> = old.iter().collect();
+        let new_set: HashSet<&InterfaceOption> = new.iter().collect();
+        let set_removed: Vec<&InterfaceOption> =
+            old.iter().filter(|o| !new_set.contains(o)).collect();
+        let set_added: Vec<&InterfaceOption> =
+            new.iter().filter(|o| !old_set.contains(o)).collect();
+
+        assert_eq!(linear_removed, set_removed);
+        assert_eq!(linear_added, set_added);
+        assert!(!set_removed.is_empty());
+        assert!(!set_added.is_empty());
+    }
+
+    #[test]
+    fn test_bridge_vids_canonical() {
+        let opt = InterfaceOption::from_key_value("bridge-vids", "200-210 100 101");
+        assert_eq!(opt.value(), "100-101 200-210");
+        assert_eq!(opt, InterfaceOption::from_key_value("bridge-vids", "100-101 200-210"));
+        assert_eq!(
+            InterfaceOption::from_key_value("bridge-vids", "100 all"),
+            InterfaceOption::Other("bridge-vids".to_string(), "100 all".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dhcp_options() {
+        assert_eq!(
+            InterfaceOption::from_key_value("leasetime", "3600"),
+            InterfaceOption::DhcpLeaseTime(3600)
+        );
+        assert_eq!(
+            InterfaceOption::from_key_value("client", "box-1"),
+            InterfaceOption::DhcpClientId("box-1".to_string())
+        );
+        let opt = InterfaceOption::from_key_value("request-prefix", "1");
+        assert_eq!(opt, InterfaceOption::DhcpRequestPrefix("1".to_string()));
+        assert_eq!(opt.to_string(), "request_prefix 1");
+        assert!(InterfaceOption::try_from_key_value("leasehours", "a day").is_err());
+    }
+
+    #[test]
+    fn test_numeric_fallback() {
+        assert_eq!(
+            InterfaceOption::from_key_value("vlan-id", "101"),
+            InterfaceOption::VlanId(101)
+        );
+        assert!(InterfaceOption::try_from_key_value("vlan-id", "x").is_err());
+        assert_eq!(
+            InterfaceOption::from_key_value("vlan-id", "x"),
+            InterfaceOption::Other("vlan-id".to_string(), "x".to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let opt = InterfaceOption::Mtu(9000);
+        let json = serde_json::to_string(&opt).unwrap();
+        assert_eq!(json, r#"["mtu","9000"]"#);
+        assert_eq!(serde_json::from_str::<InterfaceOption>(&json).unwrap(), opt);
+        assert_eq!(
+            serde_json::from_str::<InterfaceOption>(r#"["x-vendor","a b"]"#).unwrap(),
+            InterfaceOption::Other("x-vendor".to_string(), "a b".to_string())
+        );
+    }
+}
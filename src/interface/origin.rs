@@ -0,0 +1,56 @@
+//! Where a parsed interface was defined.
+
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Where an [`Interface`](super::Interface) was defined.
+///
+/// The parser records the origin of every interface it creates. The line is
+/// that of the first `iface` stanza for the interface, or of the first
+/// `auto` or `allow-*` line naming it if it has no stanza. Interfaces built
+/// in memory have no origin.
+///
+/// Line numbers are 1-based. The [`span`](Origin::span) covers only the
+/// first `iface` block, from its header to its last option line; `auto` and
+/// `allow-*` lines can be far from the stanza, so they are listed separately
+/// in [`directive_lines`](Origin::directive_lines).
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::Interface;
+///
+/// let iface: Interface = "# uplink\nauto eth0\niface eth0 inet dhcp".parse().unwrap();
+/// let origin = iface.origin().unwrap();
+/// assert_eq!(origin.path, None);
+/// assert_eq!(origin.line, 3);
+/// assert_eq!(origin.to_string(), "line 3");
+/// assert_eq!(origin.span, Some(3..4));
+/// assert_eq!(origin.directive_lines, [2]);
+///
+/// assert!(Interface::builder("eth0").build().origin().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Origin {
+    /// The file the interface was loaded from, or `None` if it was parsed
+    /// from a string.
+    pub path: Option<PathBuf>,
+    /// The 1-based line number.
+    pub line: usize,
+    /// The half-open range of lines of the first `iface` block, or `None` if
+    /// the interface has no stanza.
+    pub span: Option<Range<usize>>,
+    /// The `auto` and `allow-*` lines naming the interface, in file order.
+    pub directive_lines: Vec<usize>,
+}
+
+impl fmt::Display for Origin {
+    /// Formats the origin as `path:line`, or `line N` without a path.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}:{}", path.display(), self.line),
+            None => write!(f, "line {}", self.line),
+        }
+    }
+}
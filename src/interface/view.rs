@@ -0,0 +1,102 @@
+//! Borrowed views of interfaces, for parsing without allocating.
+
+use super::option::intern_key;
+use super::{Family, Interface, Origin};
+
+/// A borrowed view of an interface, produced by
+/// [`Parser::parse_borrowed`](crate::Parser::parse_borrowed).
+///
+/// Every string is a slice of the parsed content, so building a view copies
+/// nothing but the small vectors holding the slices. Option values are the
+/// rest of the line as written, including any runs of inner whitespace;
+/// [`to_interface`](InterfaceView::to_interface) collapses them the way the
+/// owned parser does.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::Parser;
+///
+/// let content = "allow-hotplug eth1\niface eth1 inet6 auto\n    mtu 1500\n";
+/// let views = Parser::new().parse_borrowed(content).unwrap();
+/// let view = &views["eth1"];
+/// assert_eq!(view.allow, ["hotplug"]);
+/// assert_eq!(view.method, Some("auto"));
+/// assert_eq!(view.line, 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceView<'a> {
+    /// The name of the interface.
+    pub name: &'a str,
+    /// Whether the interface is named on an `auto` line.
+    pub auto: bool,
+    /// The `allow-*` classes naming the interface, without the prefix.
+    pub allow: Vec<&'a str>,
+    /// The address family.
+    pub family: Option<Family>,
+    /// The method, as written.
+    pub method: Option<&'a str>,
+    /// The option lines, as key/value pairs.
+    pub options: Vec<(&'a str, &'a str)>,
+    /// The 1-based line where the interface is defined; see [`Origin`].
+    pub line: usize,
+}
+
+impl<'a> InterfaceView<'a> {
+    /// Creates an empty view first seen on `line`.
+    pub(crate) fn new(name: &'a str, line: usize) -> Self {
+        InterfaceView {
+            name,
+            auto: false,
+            allow: Vec::new(),
+            family: None,
+            method: None,
+            options: Vec::new(),
+            line,
+        }
+    }
+
+    /// Returns the option value for the first line with `key`.
+    pub fn get_option(&self, key: &str) -> Option<&'a str> {
+        self.options
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| *value)
+    }
+
+    /// Copies the view into an owned [`Interface`], equal to the one
+    /// [`Parser::parse`](crate::Parser::parse) returns for the same content
+    /// except that it has no [`mapping`](Interface::mapping), and that the
+    /// stanzas of a dual-stack interface are merged into one, since a view
+    /// does not keep them apart.
+    ///
+    /// The origin of the interface has its line but no path, span, or
+    /// directive lines, since the view does not track them.
+    pub fn to_interface(&self) -> Interface {
+        let mut builder = Interface::builder(self.name).with_auto(self.auto);
+        for class in &self.allow {
+            builder = builder.with_allow(*class);
+        }
+        if let Some(family) = &self.family {
+            builder = builder.with_family(family.clone());
+        }
+        if let Some(method) = self.method {
+            builder = builder.with_method(method);
+        }
+        builder.options = self
+            .options
+            .iter()
+            .map(|(key, value)| {
+                let value = value.split_whitespace().collect::<Vec<&str>>().join(" ");
+                (intern_key(*key), value)
+            })
+            .collect();
+        builder.origin = Some(Origin {
+            path: None,
+            line: self.line,
+            span: None,
+            directive_lines: Vec::new(),
+        });
+        builder.build()
+    }
+}
@@ -0,0 +1,107 @@
+//! A builder for VLAN interfaces with dotted names.
+
+use super::{Interface, VlanProtocol};
+
+/// A builder for VLAN interfaces with dotted names, including stacked
+/// (QinQ) VLANs.
+///
+/// A single-tagged VLAN with id 100 on `swp1` is named `swp1.100`. A
+/// double-tagged VLAN adds an inner tag on top of an outer VLAN:
+/// [`stacked("swp1", 100, 200)`](VlanBuilder::stacked) builds the outer VLAN
+/// `swp1.100`, tagged with 802.1ad unless another
+/// [`protocol`](VlanBuilder::protocol) is set, and the inner VLAN
+/// `swp1.100.200` stacked on it.
+///
+/// The interfaces are marked `auto`. Their raw devices and ids follow from
+/// their names, so no `vlan-raw-device` or `vlan-id` options are written.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::{VlanBuilder, VlanProtocol};
+/// use interface_rs::NetworkInterfaces;
+///
+/// let vlans = VlanBuilder::stacked("swp1", 100, 200).build();
+/// let names: Vec<&str> = vlans.iter().map(|vlan| vlan.name.as_str()).collect();
+/// assert_eq!(names, ["swp1.100", "swp1.100.200"]);
+/// assert_eq!(vlans[0].vlan_protocol(), Some(VlanProtocol::Dot1Ad));
+/// assert_eq!(vlans[1].outer_vlan_id(), Some(100));
+///
+/// let net_ifaces = NetworkInterfaces::from_interfaces(vlans);
+/// assert_eq!(net_ifaces.vlans_on_device("swp1").len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VlanBuilder {
+    raw_device: String,
+    id: u16,
+    inner: Option<u16>,
+    protocol: Option<VlanProtocol>,
+}
+
+impl VlanBuilder {
+    /// Creates a builder for the single-tagged VLAN `id` on `raw_device`.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_device` - The device to tag on, such as `"swp1"`.
+    /// * `id` - The VLAN id.
+    pub fn new(raw_device: impl Into<String>, id: u16) -> Self {
+        VlanBuilder {
+            raw_device: raw_device.into(),
+            id,
+            inner: None,
+            protocol: None,
+        }
+    }
+
+    /// Creates a builder for the double-tagged VLAN `inner` stacked on the
+    /// VLAN `outer` of `raw_device`.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_device` - The device to tag on, such as `"swp1"`.
+    /// * `outer` - The outer (service) tag.
+    /// * `inner` - The inner (customer) tag.
+    pub fn stacked(raw_device: impl Into<String>, outer: u16, inner: u16) -> Self {
+        VlanBuilder {
+            inner: Some(inner),
+            ..VlanBuilder::new(raw_device, outer)
+        }
+    }
+
+    /// Sets the tag protocol of the outer VLAN, written as its
+    /// `vlan-protocol` option.
+    ///
+    /// Single-tagged VLANs have no `vlan-protocol` option unless one is set,
+    /// leaving the kernel default of 802.1Q. Stacked VLANs default to
+    /// [`VlanProtocol::Dot1Ad`].
+    pub fn protocol(mut self, protocol: VlanProtocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    /// Builds the VLAN interfaces.
+    ///
+    /// # Returns
+    ///
+    /// The single VLAN, or for a stacked VLAN the outer VLAN followed by the
+    /// inner one, so that adding them in order keeps every VLAN after its
+    /// raw device.
+    pub fn build(self) -> Vec<Interface> {
+        let outer_name = format!("{}.{}", self.raw_device, self.id);
+        let protocol = match self.inner {
+            Some(_) => Some(self.protocol.unwrap_or(VlanProtocol::Dot1Ad)),
+            None => self.protocol,
+        };
+        let mut outer = Interface::builder(outer_name.as_str()).with_auto(true);
+        if let Some(protocol) = protocol {
+            outer = outer.with_option("vlan-protocol", protocol.as_str());
+        }
+        let mut vlans = vec![outer.build()];
+        if let Some(inner) = self.inner {
+            let name = format!("{}.{}", outer_name, inner);
+            vlans.push(Interface::builder(name).with_auto(true).build());
+        }
+        vlans
+    }
+}
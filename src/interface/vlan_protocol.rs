@@ -0,0 +1,95 @@
+//! The `vlan-protocol` option of 802.1Q and 802.1ad VLANs.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The tag protocol of a VLAN, as set by the `vlan-protocol` option.
+///
+/// A provider bridge (QinQ) adds an outer 802.1ad service tag in front of the
+/// 802.1Q customer tag. With ifupdown2 the outer VLAN sets
+/// `vlan-protocol 802.1ad`, and the inner VLAN is stacked on it with a name
+/// like `swp1.100.200`.
+///
+/// Parsing ignores letter case; values are written as `802.1Q` and `802.1ad`.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::VlanProtocol;
+///
+/// assert_eq!("802.1ad".parse::<VlanProtocol>().unwrap(), VlanProtocol::Dot1Ad);
+/// assert_eq!("802.1q".parse::<VlanProtocol>().unwrap(), VlanProtocol::Dot1Q);
+/// assert_eq!(VlanProtocol::Dot1Q.to_string(), "802.1Q");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum VlanProtocol {
+    /// IEEE 802.1Q, the default tag protocol.
+    #[default]
+    Dot1Q,
+    /// IEEE 802.1ad, the service tag of a provider bridge.
+    Dot1Ad,
+}
+
+impl VlanProtocol {
+    /// Returns the protocol as written in a `vlan-protocol` option.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VlanProtocol::Dot1Q => "802.1Q",
+            VlanProtocol::Dot1Ad => "802.1ad",
+        }
+    }
+}
+
+impl fmt::Display for VlanProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for VlanProtocol {
+    type Err = VlanProtocolParseError;
+
+    /// Parses a `VlanProtocol` from a string slice, ignoring letter case.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VlanProtocolParseError` if the input is neither `802.1Q`
+    /// nor `802.1ad`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("802.1q") {
+            Ok(VlanProtocol::Dot1Q)
+        } else if s.eq_ignore_ascii_case("802.1ad") {
+            Ok(VlanProtocol::Dot1Ad)
+        } else {
+            Err(VlanProtocolParseError(s.to_string()))
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for VlanProtocol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VlanProtocol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// An error that occurs when parsing a `VlanProtocol` from a string.
+#[derive(Debug, Clone)]
+pub struct VlanProtocolParseError(pub String);
+
+impl fmt::Display for VlanProtocolParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid VLAN protocol: {}", self.0)
+    }
+}
+
+impl Error for VlanProtocolParseError {}
@@ -0,0 +1,261 @@
+//! VLAN id lists such as `bridge-vids`.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// A set of VLAN ids, as written in options such as `bridge-vids`.
+///
+/// The textual form is a whitespace-separated list of ids and inclusive
+/// ranges (`100 200-210`). `VlanRanges` always keeps its ranges sorted and
+/// merged, so it displays in canonical form: overlapping and adjacent ranges
+/// are joined, and single ids are written without a dash.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::VlanRanges;
+///
+/// let mut vids: VlanRanges = "1100 1000-1004".parse().unwrap();
+/// assert!(vids.insert(1005));
+/// assert!(!vids.insert(1002));
+/// assert_eq!(vids.to_string(), "1000-1005 1100");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VlanRanges(Vec<RangeInclusive<u16>>);
+
+impl VlanRanges {
+    /// The smallest valid VLAN id.
+    pub const MIN: u16 = 1;
+    /// The largest valid VLAN id.
+    pub const MAX: u16 = 4094;
+
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the canonical ranges, sorted and non-adjacent.
+    pub fn ranges(&self) -> &[RangeInclusive<u16>] {
+        &self.0
+    }
+
+    /// Returns `true` if the set contains no ids.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if `vid` is in the set.
+    pub fn contains(&self, vid: u16) -> bool {
+        self.0.iter().any(|range| range.contains(&vid))
+    }
+
+    /// Returns an iterator over every id in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        self.0.iter().flat_map(|range| range.clone())
+    }
+
+    /// Adds `vid` to the set, merging it with adjacent ranges.
+    ///
+    /// The id is not checked against [`VlanRanges::MIN`] and
+    /// [`VlanRanges::MAX`]; callers that accept ids from users should.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the id was not already present.
+    pub fn insert(&mut self, vid: u16) -> bool {
+        if self.contains(vid) {
+            return false;
+        }
+        self.0.push(vid..=vid);
+        self.normalize();
+        true
+    }
+
+    /// Removes `vid` from the set, splitting the range that contains it.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the id was present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::VlanRanges;
+    ///
+    /// let mut vids: VlanRanges = "1000-1004".parse().unwrap();
+    /// assert!(vids.remove(1002));
+    /// assert_eq!(vids.to_string(), "1000-1001 1003-1004");
+    /// ```
+    pub fn remove(&mut self, vid: u16) -> bool {
+        let Some(i) = self.0.iter().position(|range| range.contains(&vid)) else {
+            return false;
+        };
+        let (start, end) = self.0[i].clone().into_inner();
+        let mut pieces = Vec::with_capacity(2);
+        if start < vid {
+            pieces.push(start..=vid - 1);
+        }
+        if vid < end {
+            pieces.push(vid + 1..=end);
+        }
+        self.0.splice(i..=i, pieces);
+        true
+    }
+
+    /// Sorts the ranges and merges overlapping or adjacent ones.
+    fn normalize(&mut self) {
+        self.0.sort_by_key(|range| *range.start());
+        let mut merged: Vec<RangeInclusive<u16>> = Vec::with_capacity(self.0.len());
+        for range in self.0.drain(..) {
+            match merged.last_mut() {
+                Some(last) if u32::from(*range.start()) <= u32::from(*last.end()) + 1 => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+        self.0 = merged;
+    }
+}
+
+impl fmt::Display for VlanRanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for VlanRanges {
+    type Err = VlanRangesParseError;
+
+    /// Parses a whitespace-separated list of VLAN ids and ranges.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `VlanRangesParseError` if an entry is not a number or a
+    /// `low-high` range with `low <= high`, or lies outside
+    /// [`VlanRanges::MIN`]..=[`VlanRanges::MAX`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || VlanRangesParseError(s.to_string());
+        let vid = |n: &str| -> Result<u16, VlanRangesParseError> {
+            let vid: u16 = n.parse().map_err(|_| err())?;
+            if !(Self::MIN..=Self::MAX).contains(&vid) {
+                return Err(err());
+            }
+            Ok(vid)
+        };
+
+        let mut ranges = Vec::new();
+        for token in s.split_whitespace() {
+            let range = match token.split_once('-') {
+                Some((low, high)) => vid(low)?..=vid(high)?,
+                None => vid(token)?..=vid(token)?,
+            };
+            if range.is_empty() {
+                return Err(err());
+            }
+            ranges.push(range);
+        }
+        let mut vids = VlanRanges(ranges);
+        vids.normalize();
+        Ok(vids)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for VlanRanges {
+    /// Serializes the set in its canonical textual form, e.g. `"1 100-110"`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VlanRanges {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// An error that occurs when parsing `VlanRanges` from a string.
+#[derive(Debug, Clone)]
+pub struct VlanRangesParseError(pub String);
+
+impl fmt::Display for VlanRangesParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid VLAN list: {}", self.0)
+    }
+}
+
+impl Error for VlanRangesParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_canonicalizes() {
+        let cases = [
+            ("100", "100"),
+            ("200-210 100", "100 200-210"),
+            ("1-5 3-8 9", "1-9"),
+            ("10 11 12 20", "10-12 20"),
+            ("  4094\t1 ", "1 4094"),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            let vids: VlanRanges = input.parse().unwrap();
+            assert_eq!(vids.to_string(), expected, "{:?}", input);
+        }
+        for invalid in ["0", "4095", "10-5", "a", "1-", "-5", "1-2-3"] {
+            assert!(invalid.parse::<VlanRanges>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let mut vids: VlanRanges = "1000-1004 1006 1100".parse().unwrap();
+        assert!(vids.insert(1005));
+        assert_eq!(vids.to_string(), "1000-1006 1100");
+        assert!(vids.insert(1));
+        assert!(vids.insert(4094));
+        assert!(!vids.insert(1000));
+        assert_eq!(vids.to_string(), "1 1000-1006 1100 4094");
+        assert_eq!(vids.iter().count(), 10);
+        assert!(vids.contains(1003));
+        assert!(!vids.contains(1007));
+    }
+
+    #[test]
+    fn test_remove_splits() {
+        let mut vids: VlanRanges = "1000-1004 1100 2000-2001".parse().unwrap();
+        assert!(vids.remove(1002));
+        assert_eq!(vids.to_string(), "1000-1001 1003-1004 1100 2000-2001");
+        assert!(vids.remove(1000));
+        assert!(vids.remove(1004));
+        assert!(vids.remove(2001));
+        assert_eq!(vids.to_string(), "1001 1003 1100 2000");
+        assert!(vids.remove(1100));
+        assert!(!vids.remove(1100));
+        assert!(!vids.remove(3000));
+        assert_eq!(vids.to_string(), "1001 1003 2000");
+        for vid in [1001, 1003, 2000] {
+            vids.remove(vid);
+        }
+        assert!(vids.is_empty());
+    }
+}
@@ -0,0 +1,444 @@
+//! Export of the configuration as `ip` commands.
+
+use crate::addr::parse_prefix;
+use crate::error::ConversionError;
+use crate::graph::{self, DependencyKind};
+use crate::interface::option::keys_match;
+use crate::interface::{
+    Address, HwAddress, Interface, InterfaceKind, Method, OptionKey, VlanProtocol,
+};
+use crate::network_interfaces::NetworkInterfaces;
+use std::net::IpAddr;
+
+/// The option keys [`NetworkInterfaces::to_ip_commands`] translates.
+const SUPPORTED_KEYS: &[&str] = &[
+    "address",
+    "netmask",
+    "gateway",
+    "mtu",
+    "hwaddress",
+    "bridge-ports",
+    "bridge-vlan-aware",
+    "bond-slaves",
+    "bond-mode",
+    "bond-miimon",
+    "bond-lacp-rate",
+    "bond-xmit-hash-policy",
+    "vlan-id",
+    "vlan-protocol",
+    "vlan-raw-device",
+    "vrf",
+    "vrf-table",
+    "vxlan-id",
+    "vxlan-local-tunnelip",
+];
+
+/// The bond options passed to `ip link add`, with their `ip` names.
+const BOND_PARAMETERS: &[(&str, &str)] = &[
+    ("bond-mode", "mode"),
+    ("bond-miimon", "miimon"),
+    ("bond-lacp-rate", "lacp_rate"),
+    ("bond-xmit-hash-policy", "xmit_hash_policy"),
+];
+
+impl NetworkInterfaces {
+    /// Generates `ip` commands that apply the configuration to a running
+    /// system without ifupdown.
+    ///
+    /// Interfaces are handled in
+    /// [`bringup_order`](NetworkInterfaces::bringup_order), so a bridge comes
+    /// after its ports, a bond after its slaves, a VLAN after its raw device,
+    /// an alias after its parent, and a VRF member after its VRF. For each
+    /// interface, the commands:
+    ///
+    /// 1. create the device with `ip link add`, for bridges, bonds, VLANs,
+    ///    VRFs, and VXLANs;
+    /// 2. set its `mtu` and `hwaddress`;
+    /// 3. attach it to its VRF (`vrf`), or its ports or slaves to it
+    ///    (`bridge-ports`, `bond-slaves`);
+    /// 4. bring it up;
+    /// 5. add its addresses and a default route through its `gateway`.
+    ///
+    /// Aliases such as `eth0:1` only get step 5: their addresses are added to
+    /// the parent, labeled with the name of the alias.
+    ///
+    /// Disabled interfaces are left out. Nothing is run; the caller decides
+    /// what to do with the commands, such as writing them to a script.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConversionError` listing every construct that has no `ip`
+    /// equivalent: methods other than `static`, `manual`, and `loopback`
+    /// (such as `dhcp`), `mapping` stanzas, hook scripts such as `post-up`,
+    /// and any other option not listed above. It is also returned if the
+    /// dependencies form a cycle.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("br0").with_option("bridge-ports", "swp1").build(),
+    ///     Interface::builder("swp1").with_option("mtu", "9000").build(),
+    /// ]);
+    /// assert_eq!(
+    ///     net_ifaces.to_ip_commands().unwrap(),
+    ///     [
+    ///         "ip link set swp1 mtu 9000",
+    ///         "ip link set swp1 up",
+    ///         "ip link add br0 type bridge",
+    ///         "ip link set swp1 master br0",
+    ///         "ip link set br0 up",
+    ///     ]
+    /// );
+    ///
+    /// let dhcp = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("eth0").with_method("dhcp").build(),
+    /// ]);
+    /// assert!(dhcp.to_ip_commands().is_err());
+    /// ```
+    pub fn to_ip_commands(&self) -> Result<Vec<String>, ConversionError> {
+        let strict_keys = self.parser_options().strict_keys;
+        let ordered = self
+            .bringup_order()
+            .map_err(|err| ConversionError(err.to_string()))?;
+
+        let mut commands = Vec::new();
+        let mut unsupported = Vec::new();
+        for iface in ordered {
+            let mut link = Link {
+                iface,
+                strict_keys,
+                commands: &mut commands,
+                unsupported: &mut unsupported,
+            };
+            link.check_supported();
+            link.create();
+            link.configure();
+            link.attach();
+            link.up();
+            link.address();
+        }
+        if !unsupported.is_empty() {
+            return Err(ConversionError(format!(
+                "cannot express as ip commands: {}",
+                unsupported.join("; ")
+            )));
+        }
+        Ok(commands)
+    }
+}
+
+/// Collects the commands for one interface, and what cannot be expressed.
+struct Link<'a> {
+    iface: &'a Interface,
+    strict_keys: bool,
+    commands: &'a mut Vec<String>,
+    unsupported: &'a mut Vec<String>,
+}
+
+impl<'a> Link<'a> {
+    /// Returns the values of the options with key `key`.
+    fn options(&self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let strict_keys = self.strict_keys;
+        self.iface
+            .stanzas()
+            .flat_map(|stanza| stanza.options)
+            .filter(move |(k, _)| keys_match(k, key, strict_keys))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the first value of the option with key `key`.
+    fn option(&self, key: &'a str) -> Option<&'a str> {
+        self.options(key).next()
+    }
+
+    fn push(&mut self, command: String) {
+        self.commands.push(command);
+    }
+
+    fn report(&mut self, what: String) {
+        self.unsupported
+            .push(format!("{}: {}", self.iface.name, what));
+    }
+
+    /// Reports the method, mapping, and options that have no `ip` form.
+    fn check_supported(&mut self) {
+        let iface = self.iface;
+        for stanza in iface.stanzas() {
+            match stanza.method {
+                None | Some(Method::Static | Method::Manual | Method::Loopback) => {}
+                Some(method) => self.report(format!("method {}", method)),
+            }
+        }
+        if iface.mapping.is_some() {
+            self.report("mapping stanza".to_string());
+        }
+        let strict_keys = self.strict_keys;
+        for (key, _) in iface.stanzas().flat_map(|stanza| stanza.options) {
+            let supported = SUPPORTED_KEYS
+                .iter()
+                .any(|known| keys_match(key, known, strict_keys));
+            if !supported {
+                self.report(format!("option {}", key));
+            }
+        }
+    }
+
+    /// Creates virtual devices.
+    fn create(&mut self) {
+        let name = &self.iface.name;
+        match self.iface.kind() {
+            InterfaceKind::Vrf => {
+                let table = self.option("vrf-table").unwrap_or_default();
+                if table.parse::<u32>().is_ok() {
+                    self.push(format!("ip link add {} type vrf table {}", name, table));
+                } else {
+                    self.report(format!("vrf-table {}", table));
+                }
+            }
+            InterfaceKind::Bridge => {
+                let mut command = format!("ip link add {} type bridge", name);
+                if self.option("bridge-vlan-aware") == Some("yes") {
+                    command.push_str(" vlan_filtering 1");
+                }
+                self.push(command);
+            }
+            InterfaceKind::Bond => {
+                let mut command = format!("ip link add {} type bond", name);
+                for (key, parameter) in BOND_PARAMETERS {
+                    if let Some(value) = self.option(key) {
+                        command.push_str(&format!(" {} {}", parameter, value));
+                    }
+                }
+                self.push(command);
+            }
+            InterfaceKind::Vxlan => {
+                let id = self.option("vxlan-id").unwrap_or_default();
+                let mut command = format!("ip link add {} type vxlan id {}", name, id);
+                if let Some(local) = self.option("vxlan-local-tunnelip") {
+                    command.push_str(&format!(" local {}", local));
+                }
+                command.push_str(" dstport 4789");
+                self.push(command);
+            }
+            InterfaceKind::Vlan => {
+                let (raw_device, id) = vlan_parts(self.iface, self.strict_keys);
+                let protocol = match self.option("vlan-protocol") {
+                    Some(value) => match value.parse::<VlanProtocol>() {
+                        Ok(protocol) => format!(" protocol {}", protocol),
+                        Err(_) => {
+                            self.report(format!("vlan-protocol {}", value));
+                            String::new()
+                        }
+                    },
+                    None => String::new(),
+                };
+                match (raw_device, id) {
+                    (Some(raw_device), Some(id)) => self.push(format!(
+                        "ip link add link {} name {} type vlan{} id {}",
+                        raw_device, name, protocol, id
+                    )),
+                    (None, _) => self.report("VLAN without a raw device".to_string()),
+                    (_, None) => self.report("VLAN without an id".to_string()),
+                }
+            }
+            InterfaceKind::Loopback | InterfaceKind::Alias | InterfaceKind::Physical => {}
+        }
+    }
+
+    /// Sets the MTU and MAC address.
+    fn configure(&mut self) {
+        let name = &self.iface.name;
+        if self.iface.is_alias() {
+            for key in ["mtu", "hwaddress"] {
+                if self.option(key).is_some() {
+                    self.report(format!("{} on an alias", key));
+                }
+            }
+            return;
+        }
+        if let Some(mtu) = self.option("mtu") {
+            self.push(format!("ip link set {} mtu {}", name, mtu));
+        }
+        if let Some(hwaddress) = self.option("hwaddress") {
+            match HwAddress::new(hwaddress).mac() {
+                Some(mac) => self.push(format!("ip link set {} address {}", name, mac)),
+                None => self.report(format!("hwaddress {}", hwaddress)),
+            }
+        }
+    }
+
+    /// Attaches the interface to its VRF, and its ports or slaves to it.
+    fn attach(&mut self) {
+        let name = &self.iface.name;
+        for dep in graph::interface_dependencies(self.iface, self.strict_keys) {
+            match dep.kind {
+                DependencyKind::VrfMember => {
+                    self.push(format!("ip link set {} master {}", name, dep.to));
+                }
+                DependencyKind::BridgePort => {
+                    self.push(format!("ip link set {} master {}", dep.to, name));
+                }
+                DependencyKind::BondSlave => {
+                    // A slave must be down to be enslaved
+                    self.push(format!("ip link set {} down", dep.to));
+                    self.push(format!("ip link set {} master {}", dep.to, name));
+                }
+                DependencyKind::VlanRawDevice
+                | DependencyKind::AliasParent
+                | DependencyKind::VxlanAccess => {}
+            }
+        }
+    }
+
+    fn up(&mut self) {
+        if self.iface.is_alias() {
+            return;
+        }
+        let command = format!("ip link set {} up", self.iface.name);
+        self.push(command);
+    }
+
+    /// Adds the addresses and the default routes of every stanza. The
+    /// addresses of an alias are added to its parent, labeled with the name
+    /// of the alias.
+    fn address(&mut self) {
+        let iface = self.iface;
+        for stanza in iface.stanzas() {
+            self.stanza_address(stanza.options);
+        }
+    }
+
+    /// Adds the addresses and the default route of one stanza; a `netmask`
+    /// only applies to the addresses of its own stanza.
+    fn stanza_address(&mut self, options: &'a [(OptionKey, String)]) {
+        let name = self.iface.alias_parent().unwrap_or(&self.iface.name);
+        let label = if self.iface.is_alias() {
+            format!(" label {}", self.iface.name)
+        } else {
+            String::new()
+        };
+        let strict_keys = self.strict_keys;
+        let values = |key: &'a str| {
+            options
+                .iter()
+                .filter(move |(k, _)| keys_match(k, key, strict_keys))
+                .map(|(_, v)| v.as_str())
+        };
+        let netmask = values("netmask").next();
+        for value in values("address") {
+            match value.parse::<Address>() {
+                Ok(mut addr) => {
+                    if addr.prefix.is_none() {
+                        addr.prefix = netmask.and_then(|mask| parse_prefix(mask, addr.ip).ok());
+                    }
+                    self.push(format!("ip addr add {} dev {}{}", addr, name, label));
+                }
+                Err(_) => self.report(format!("address {}", value)),
+            }
+        }
+        let vrf = self.option("vrf");
+        for gateway in values("gateway") {
+            let Ok(ip) = gateway.parse::<IpAddr>() else {
+                self.report(format!("gateway {}", gateway));
+                continue;
+            };
+            let family = if ip.is_ipv6() { " -6" } else { "" };
+            let mut command = format!("ip{} route add default via {} dev {}", family, ip, name);
+            if let Some(vrf) = vrf {
+                command.push_str(&format!(" vrf {}", vrf));
+            }
+            self.push(command);
+        }
+    }
+}
+
+/// Returns the raw device and id of a VLAN, from its options or its name
+/// (`eth0.100`, or `vlan100` for the id).
+fn vlan_parts(iface: &Interface, strict_keys: bool) -> (Option<&str>, Option<&str>) {
+    let option = |key: &str| {
+        iface
+            .stanzas()
+            .flat_map(|stanza| stanza.options)
+            .find(|(k, _)| keys_match(k, key, strict_keys))
+            .map(|(_, v)| v.as_str())
+    };
+    let dotted = iface.name.rsplit_once('.');
+    let raw_device = option("vlan-raw-device").or(dotted.map(|(raw, _)| raw));
+    let id = option("vlan-id")
+        .or(dotted.map(|(_, id)| id))
+        .or_else(|| iface.name.strip_prefix("vlan"))
+        .filter(|id| id.parse::<u16>().is_ok());
+    (raw_device, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_commands_golden() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let net_ifaces = NetworkInterfaces::load(format!("{}/tests/ip/interfaces", dir)).unwrap();
+        let golden = std::fs::read_to_string(format!("{}/tests/ip/commands", dir)).unwrap();
+        let commands = net_ifaces.to_ip_commands().unwrap();
+        assert_eq!(commands.join("\n") + "\n", golden);
+    }
+
+    #[test]
+    fn test_ip_commands_unsupported() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let net_ifaces = NetworkInterfaces::load(format!("{}/tests/interfaces", dir)).unwrap();
+        let err = net_ifaces.to_ip_commands().unwrap_err();
+        let message = err.to_string();
+        for what in [
+            "eth0: method dhcp",
+            "mgmt: vrf-table auto",
+            "vlan100: option post-up",
+            "vlan100: option dns-nameservers",
+            "eth1: method auto",
+            "br_default: option bridge-vids",
+        ] {
+            assert!(message.contains(what), "{} not in {}", what, message);
+        }
+
+        let mapped = NetworkInterfaces::from_interfaces([Interface::builder("eth0")
+            .with_mapping(crate::interface::Mapping {
+                script: "/usr/local/bin/map".to_string(),
+                maps: Vec::new(),
+            })
+            .build()]);
+        assert_eq!(
+            mapped.to_ip_commands().unwrap_err().to_string(),
+            "Conversion error: cannot express as ip commands: eth0: mapping stanza"
+        );
+    }
+
+    #[test]
+    fn test_ip_commands_dual_stack() {
+        let net_ifaces = NetworkInterfaces::parse_str(
+            "iface eth0 inet static\n    address 192.0.2.10\n    netmask 255.255.255.0\n\
+             iface eth0 inet6 static\n    address 2001:db8::10/64\n    gateway 2001:db8::1\n",
+        )
+        .unwrap();
+        assert_eq!(
+            net_ifaces.to_ip_commands().unwrap(),
+            [
+                "ip link set eth0 up",
+                "ip addr add 192.0.2.10/24 dev eth0",
+                "ip addr add 2001:db8::10/64 dev eth0",
+                "ip -6 route add default via 2001:db8::1 dev eth0",
+            ]
+        );
+
+        let dhcp6 = NetworkInterfaces::parse_str("iface eth0 inet static\niface eth0 inet6 dhcp\n")
+            .unwrap();
+        assert_eq!(
+            dhcp6.to_ip_commands().unwrap_err().to_string(),
+            "Conversion error: cannot express as ip commands: eth0: method dhcp"
+        );
+    }
+}
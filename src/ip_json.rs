@@ -0,0 +1,393 @@
+//! Conversion from `ip -j` JSON.
+//!
+//! Enabled with the `json` feature, [`NetworkInterfaces::from_ip_json`]
+//! builds a view of the *running* system from the output of
+//! `ip -j addr show`, so that it can be compared with a loaded
+//! `interfaces(5)` file. The crate never runs `ip` itself; callers capture
+//! the output and pass it in. Adding `-d` (`ip -d -j addr show`) includes the
+//! link details used to infer each device's kind.
+//!
+//! Every link becomes an `iface <name> <family> manual` stanza:
+//!
+//! | `ip -j` | `interfaces(5)` |
+//! |---------|-----------------|
+//! | `UP` in `flags` | `auto` |
+//! | `addr_info` entries (except `scope link`) | one `address` per entry; the family is `inet6` if all addresses are IPv6, `inet` otherwise |
+//! | `address` (when `link_type` is `ether`) | `hwaddress` |
+//! | `mtu` | `mtu` |
+//! | `linkinfo.info_kind: vlan` with `info_data.id`, `link` | `vlan-id`, `vlan-raw-device` |
+//! | `linkinfo.info_kind: vxlan` with `info_data.id` | `vxlan-id` |
+//! | `linkinfo.info_kind: vrf` with `info_data.table` | `vrf-table` |
+//! | `linkinfo.info_kind: bond` with `info_data.mode` | `bond-mode` |
+//! | `linkinfo.info_kind: bridge` with `info_data.vlan_filtering: 1` | `bridge-vlan-aware yes` |
+//! | `master` | `vrf` on the member, or the member is listed in the master's `bridge-ports` / `bond-slaves` |
+//!
+//! The master's type is taken from the member's `info_slave_kind`, then the
+//! master's own `info_kind`. Without `-d`, a member flagged `SLAVE` is taken
+//! to be a bond slave and any other member a bridge port.
+//!
+//! Link-local addresses are skipped because they are assigned by the kernel
+//! rather than configured. Addresses learned over DHCP (`dynamic`) are kept;
+//! it is up to the caller to decide whether they are expected.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::interface::InterfaceKind;
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let running = NetworkInterfaces::from_ip_json(
+//!     r#"[{"ifname": "eth0.100", "flags": ["UP"], "mtu": 1500, "link": "eth0",
+//!          "linkinfo": {"info_kind": "vlan", "info_data": {"id": 100}},
+//!          "addr_info": [{"family": "inet", "local": "10.0.0.2", "prefixlen": 24}]}]"#,
+//! )
+//! .unwrap();
+//! let vlan = running.get_interface("eth0.100").unwrap();
+//! assert_eq!(vlan.kind(), InterfaceKind::Vlan);
+//! assert_eq!(vlan.get_option("address"), Some("10.0.0.2/24"));
+//! assert_eq!(vlan.get_option("vlan-raw-device"), Some("eth0"));
+//! ```
+
+use crate::error::ConversionError;
+use crate::interface::option::intern_key;
+use crate::interface::{Family, Interface};
+use crate::network_interfaces::NetworkInterfaces;
+use crate::parser::ParserOptions;
+use serde_json::Value;
+use std::collections::HashMap;
+
+impl NetworkInterfaces {
+    /// Builds a collection describing the running system from the JSON
+    /// printed by `ip -j addr show`.
+    ///
+    /// See the [module documentation](crate::ip_json) for how links are
+    /// mapped to stanzas.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr_json` - The output of `ip -j addr show` (or `ip -d -j addr show`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConversionError` if the input is not a JSON array of links,
+    /// or if a link has no `ifname`.
+    pub fn from_ip_json(addr_json: &str) -> Result<Self, ConversionError> {
+        Self::from_ip_json_with_link(addr_json, "[]")
+    }
+
+    /// Like [`from_ip_json`](NetworkInterfaces::from_ip_json), but also takes
+    /// the output of `ip -j link show`.
+    ///
+    /// Links are matched by `ifname`. Fields missing from the address output
+    /// (typically `linkinfo`, when the addresses were captured without `-d`)
+    /// are taken from the link output, and links that only appear in the
+    /// link output are included as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr_json` - The output of `ip -j addr show`.
+    /// * `link_json` - The output of `ip -j link show` (or `ip -d -j link show`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConversionError` if either input is not a JSON array of
+    /// links, or if a link has no `ifname`.
+    pub fn from_ip_json_with_link(
+        addr_json: &str,
+        link_json: &str,
+    ) -> Result<Self, ConversionError> {
+        let mut links = parse_links(addr_json)?;
+        for extra in parse_links(link_json)? {
+            match links.iter_mut().find(|link| link.name == extra.name) {
+                Some(link) => {
+                    for (key, value) in extra.fields {
+                        link.fields.entry(key).or_insert(value);
+                    }
+                }
+                None => links.push(extra),
+            }
+        }
+
+        let kinds: HashMap<&str, &str> = links
+            .iter()
+            .filter_map(|link| Some((link.name.as_str(), link.info_kind()?)))
+            .collect();
+        let mut interfaces: HashMap<String, Interface> = links
+            .iter()
+            .map(|link| (link.name.clone(), link.to_interface()))
+            .collect();
+
+        // Membership is attached once every link has a stanza, so that
+        // members are listed in the order `ip` printed them.
+        for link in &links {
+            let Some(master) = link.str("master") else {
+                continue;
+            };
+            let master_kind = link
+                .field("linkinfo")
+                .and_then(|info| info.get("info_slave_kind"))
+                .and_then(Value::as_str)
+                .or_else(|| kinds.get(master).copied())
+                .unwrap_or(if link.has_flag("SLAVE") {
+                    "bond"
+                } else {
+                    "bridge"
+                });
+            match master_kind {
+                "vrf" => {
+                    if let Some(member) = interfaces.get_mut(&link.name) {
+                        member.options.push(("vrf".into(), master.to_string()));
+                    }
+                }
+                "bridge" | "bond" => {
+                    let key = if master_kind == "bond" {
+                        "bond-slaves"
+                    } else {
+                        "bridge-ports"
+                    };
+                    let master_iface = interfaces.entry(master.to_string()).or_insert_with(|| {
+                        Interface::builder(master)
+                            .with_family(Family::Inet)
+                            .with_method("manual")
+                            .build()
+                    });
+                    match master_iface.options.iter_mut().find(|(k, _)| k == key) {
+                        Some((_, members)) => {
+                            members.push(' ');
+                            members.push_str(&link.name);
+                        }
+                        None => master_iface
+                            .options
+                            .push((intern_key(key), link.name.clone())),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(NetworkInterfaces::from_parts(
+            interfaces,
+            Vec::new(),
+            Vec::new(),
+            None,
+            None,
+            ParserOptions::default(),
+        ))
+    }
+}
+
+/// One element of the top-level `ip -j` array.
+struct Link {
+    name: String,
+    fields: serde_json::Map<String, Value>,
+}
+
+impl Link {
+    fn field(&self, key: &str) -> Option<&Value> {
+        self.fields.get(key)
+    }
+
+    fn str(&self, key: &str) -> Option<&str> {
+        self.field(key).and_then(Value::as_str)
+    }
+
+    fn has_flag(&self, flag: &str) -> bool {
+        self.field("flags")
+            .and_then(Value::as_array)
+            .is_some_and(|flags| flags.iter().any(|f| f.as_str() == Some(flag)))
+    }
+
+    fn info_kind(&self) -> Option<&str> {
+        self.field("linkinfo")?.get("info_kind")?.as_str()
+    }
+
+    /// Returns `linkinfo.info_data.<key>` rendered as a string.
+    fn info_data(&self, key: &str) -> Option<String> {
+        let value = self.field("linkinfo")?.get("info_data")?.get(key)?;
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Converts the link to a stanza, without membership options.
+    fn to_interface(&self) -> Interface {
+        let mut addresses = Vec::new();
+        let mut all_inet6 = true;
+        let addr_info = self.field("addr_info").and_then(Value::as_array);
+        for addr in addr_info.into_iter().flatten() {
+            if addr.get("scope").and_then(Value::as_str) == Some("link") {
+                continue;
+            }
+            let (Some(local), Some(prefixlen)) = (
+                addr.get("local").and_then(Value::as_str),
+                addr.get("prefixlen").and_then(Value::as_u64),
+            ) else {
+                continue;
+            };
+            all_inet6 &= addr.get("family").and_then(Value::as_str) == Some("inet6");
+            addresses.push(format!("{}/{}", local, prefixlen));
+        }
+        let family = if !addresses.is_empty() && all_inet6 {
+            Family::Inet6
+        } else {
+            Family::Inet
+        };
+
+        let mut builder = Interface::builder(self.name.as_str())
+            .with_auto(self.has_flag("UP"))
+            .with_family(family)
+            .with_method("manual");
+        for address in addresses {
+            builder = builder.with_option("address", address);
+        }
+        if self.str("link_type") == Some("ether") {
+            if let Some(mac) = self.str("address") {
+                builder = builder.with_option("hwaddress", mac);
+            }
+        }
+        if let Some(mtu) = self.field("mtu").and_then(Value::as_u64) {
+            builder = builder.with_option("mtu", mtu.to_string());
+        }
+        match self.info_kind() {
+            Some("vlan") => {
+                if let Some(id) = self.info_data("id") {
+                    builder = builder.with_option("vlan-id", id);
+                }
+                if let Some(lower) = self.str("link") {
+                    builder = builder.with_option("vlan-raw-device", lower);
+                }
+            }
+            Some("vxlan") => {
+                if let Some(id) = self.info_data("id") {
+                    builder = builder.with_option("vxlan-id", id);
+                }
+            }
+            Some("vrf") => {
+                if let Some(table) = self.info_data("table") {
+                    builder = builder.with_option("vrf-table", table);
+                }
+            }
+            Some("bond") => {
+                if let Some(mode) = self.info_data("mode") {
+                    builder = builder.with_option("bond-mode", mode);
+                }
+            }
+            Some("bridge") if self.info_data("vlan_filtering").as_deref() == Some("1") => {
+                builder = builder.with_option("bridge-vlan-aware", "yes");
+            }
+            _ => {}
+        }
+        builder.build()
+    }
+}
+
+/// Parses the top-level array of an `ip -j` document.
+fn parse_links(json: &str) -> Result<Vec<Link>, ConversionError> {
+    let doc: Value = serde_json::from_str(json).map_err(|err| ConversionError(err.to_string()))?;
+    let Value::Array(entries) = doc else {
+        return Err(ConversionError(
+            "expected a JSON array of links".to_string(),
+        ));
+    };
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let Value::Object(fields) = entry else {
+                return Err(ConversionError(format!("link {} is not an object", i)));
+            };
+            let name = fields
+                .get("ifname")
+                .and_then(Value::as_str)
+                .ok_or_else(|| ConversionError(format!("link {} has no 'ifname'", i)))?
+                .to_string();
+            Ok(Link { name, fields })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::InterfaceKind;
+
+    fn fixture() -> String {
+        let path = format!("{}/tests/ip-addr.json", env!("CARGO_MANIFEST_DIR"));
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_from_ip_json_fixture() {
+        let running = NetworkInterfaces::from_ip_json(&fixture()).unwrap();
+        let kind = |name: &str| running.get_interface(name).unwrap().kind();
+        assert_eq!(running.len(), 9);
+        assert_eq!(kind("lo"), InterfaceKind::Loopback);
+        assert_eq!(kind("mgmt"), InterfaceKind::Vrf);
+        assert_eq!(kind("br_default"), InterfaceKind::Bridge);
+        assert_eq!(kind("bond0"), InterfaceKind::Bond);
+        assert_eq!(kind("vlan100"), InterfaceKind::Vlan);
+        assert_eq!(kind("swp1"), InterfaceKind::Physical);
+
+        let eth0 = running.get_interface("eth0").unwrap();
+        assert_eq!(
+            eth0.to_string(),
+            "auto eth0\n\
+             iface eth0 inet manual\n    \
+             address 192.168.0.15/24\n    \
+             hwaddress 52:54:00:12:34:56\n    \
+             mtu 1500\n    \
+             vrf mgmt\n"
+        );
+
+        let br = running.get_interface("br_default").unwrap();
+        assert_eq!(br.get_option("bridge-ports"), Some("swp1 bond0"));
+        assert_eq!(br.get_option("bridge-vlan-aware"), Some("yes"));
+        let bond = running.get_interface("bond0").unwrap();
+        assert_eq!(bond.get_option("bond-slaves"), Some("swp2"));
+        assert_eq!(bond.get_option("bond-mode"), Some("802.3ad"));
+        let vlan = running.get_interface("vlan100").unwrap();
+        assert_eq!(vlan.get_option("vlan-id"), Some("100"));
+        assert_eq!(vlan.get_option("vlan-raw-device"), Some("br_default"));
+
+        let lo = running.get_interface("lo").unwrap();
+        let addresses: Vec<&str> = lo.get_options("address").collect();
+        assert_eq!(addresses, vec!["127.0.0.1/8", "::1/128"]);
+        assert_eq!(lo.get_option("hwaddress"), None);
+        assert!(!running.get_interface("eth1").unwrap().auto);
+    }
+
+    #[test]
+    fn test_from_ip_json_with_link() {
+        let addr = r#"[
+            {"ifname": "swp1", "flags": ["UP"], "master": "br0", "addr_info": []},
+            {"ifname": "swp2", "flags": ["SLAVE", "UP"], "master": "bond0", "addr_info": []},
+            {"ifname": "v6only", "flags": ["UP"], "addr_info": [
+                {"family": "inet6", "local": "2001:db8::1", "prefixlen": 64, "scope": "global"}
+            ]}
+        ]"#;
+        let link = r#"[
+            {"ifname": "br0", "flags": ["UP"], "linkinfo": {"info_kind": "bridge"}}
+        ]"#;
+
+        let running = NetworkInterfaces::from_ip_json_with_link(addr, link).unwrap();
+        let br0 = running.get_interface("br0").unwrap();
+        assert_eq!(br0.get_option("bridge-ports"), Some("swp1"));
+        assert!(br0.auto);
+        // bond0 was not listed, so it is created from the SLAVE flag.
+        let bond0 = running.get_interface("bond0").unwrap();
+        assert_eq!(bond0.get_option("bond-slaves"), Some("swp2"));
+        let v6only = running.get_interface("v6only").unwrap();
+        assert_eq!(v6only.family, Some(Family::Inet6));
+    }
+
+    #[test]
+    fn test_from_ip_json_errors() {
+        assert!(NetworkInterfaces::from_ip_json("not json").is_err());
+        let err = NetworkInterfaces::from_ip_json("{}").unwrap_err();
+        assert!(err.to_string().contains("expected a JSON array"));
+        let err = NetworkInterfaces::from_ip_json(r#"[{"mtu": 1500}]"#).unwrap_err();
+        assert!(err.to_string().contains("no 'ifname'"));
+    }
+}
@@ -26,8 +26,10 @@
 //! use interface_rs::interface::{Interface, Family};
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     // Load interfaces (replace with an existing file path during tests)
-//!     let mut net_ifaces = NetworkInterfaces::load("tests/interfaces")?;
+//! #   let path = std::env::temp_dir().join("interface-rs-doc-modify");
+//! #   std::fs::copy("tests/interfaces", &path)?;
+//!     // Load interfaces
+//!     let mut net_ifaces = NetworkInterfaces::load(&path)?;
 //!
 //!     // Retrieve and modify an existing interface
 //!     if let Some(iface) = net_ifaces.get_interface("eth0") {
@@ -42,6 +44,7 @@
 //!
 //!     // Save changes
 //!     net_ifaces.save()?;
+//! #   std::fs::remove_file(&path)?;
 //!     Ok(())
 //! }
 //! ```
@@ -53,8 +56,10 @@
 //! use interface_rs::interface::{Interface, Family};
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     // Load interfaces (replace with an existing file path during tests)
-//!     let mut net_ifaces = NetworkInterfaces::load("tests/interfaces")?;
+//! #   let path = std::env::temp_dir().join("interface-rs-doc-add");
+//! #   std::fs::copy("tests/interfaces", &path)?;
+//!     // Load interfaces
+//!     let mut net_ifaces = NetworkInterfaces::load(&path)?;
 //!
 //!     // Create a new interface using the builder pattern
 //!     net_ifaces.add_interface(
@@ -70,6 +75,7 @@
 //!
 //!     // Save changes back to the file
 //!     net_ifaces.save()?;
+//! #   std::fs::remove_file(&path)?;
 //!
 //!     Ok(())
 //! }
@@ -81,14 +87,19 @@
 //! use interface_rs::NetworkInterfaces;
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     // Load interfaces (replace with an existing file path during tests)
-//!     let mut net_ifaces = NetworkInterfaces::load("tests/interfaces")?;
+//! #   let path = std::env::temp_dir().join("interface-rs-doc-delete");
+//! #   std::fs::copy("tests/interfaces", &path)?;
+//!     // Load interfaces
+//!     let mut net_ifaces = NetworkInterfaces::load(&path)?;
 //!
 //!     // Delete an interface by name
-//!     net_ifaces.delete_interface("eth0");
+//!     if let Some(removed) = net_ifaces.delete_interface("eth0") {
+//!         println!("Deleted {} ({} options)", removed.name, removed.options.len());
+//!     }
 //!
 //!     // Save changes back to the file
 //!     net_ifaces.save()?;
+//! #   std::fs::remove_file(&path)?;
 //!
 //!     Ok(())
 //! }
@@ -98,12 +109,48 @@
 //!
 //! This project is licensed under the MIT License.
 
+pub mod addr;
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+pub mod breakout;
+pub mod cascade;
+pub mod compliance;
+pub mod csv;
+mod dot;
+pub mod drift;
 pub mod error;
+pub mod files;
+mod graph;
+pub mod helper;
 pub mod interface;
+mod ip_commands;
+#[cfg(feature = "json")]
+pub mod ip_json;
+pub mod lint;
+#[cfg(feature = "netplan")]
+pub mod netplan;
 pub mod network_interfaces;
-pub mod helper;
+#[cfg(feature = "ipnet")]
+pub mod networks;
+pub mod normalize;
 mod parser;
+pub mod roundtrip;
+pub mod source;
+pub mod stats;
+pub mod substitute;
+pub mod validation;
+pub mod wireless;
+pub mod write_options;
 
-pub use error::NetworkInterfacesError;
-pub use interface::{Family, Interface, InterfaceBuilder, Mapping};
-pub use network_interfaces::NetworkInterfaces;
+pub use error::{ConversionError, CycleError, ErrorKind, IoOp, NetworkInterfacesError};
+pub use helper::sort;
+pub use interface::{
+    Address, Family, IfaceName, Interface, InterfaceBuilder, InterfaceOption, InterfaceView,
+    MacAddr, Mapping, Method, Origin,
+};
+pub use network_interfaces::{
+    EmptyListPolicy, NetworkInterfaces, ProvisionedVni, VniOptions, VrfOptions,
+};
+pub use parser::{Parser, ParserOptions};
+pub use source::{Source, SourceKind};
+pub use write_options::{CompareFn, Comparator, SortOrder, ValueAlignment, WriteOptions};
@@ -0,0 +1,376 @@
+//! Style feedback for `interfaces(5)` files.
+//!
+//! [`NetworkInterfaces::lint`] reports things that work but make a file
+//! harder to read and maintain. Unlike [validation](crate::validation), none
+//! of them would be rejected by `ifup`; unlike
+//! [normalization](crate::normalize), linting never modifies anything.
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | `trailing-whitespace` | a line ends in spaces or tabs |
+//! | `mixed-indentation` | an option line is indented with both tabs and spaces |
+//! | `inconsistent-indentation` | an option line is indented differently from most others in the file |
+//! | `auto-far-from-stanza` | an `auto` or `allow-*` line is separated from the stanza it names by other stanzas |
+//! | `reference-before-declaration` | an interface is declared after an interface that references it |
+//! | `non-canonical-boolean` | a yes/no option is spelled `on`, `true`, `1`, and the like |
+//!
+//! The first three are about the text of the file, so they are found while
+//! it is loaded and reflect the file as it was read; warnings for interfaces
+//! that have since been deleted are dropped. The others are computed from
+//! the collection and the [`Origin`] of each interface.
+//!
+//! Saving fixes the text warnings and `auto-far-from-stanza`, since every
+//! stanza is rewritten with its `auto` and `allow-*` lines. Use
+//! [`NetworkInterfaces::normalize`] to clean up the options themselves.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+//! for warning in net_ifaces.lint() {
+//!     println!("{}", warning);
+//! }
+//! ```
+
+use crate::graph;
+use crate::interface::option::keys_match;
+use crate::interface::{Interface, Origin};
+use crate::network_interfaces::NetworkInterfaces;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Options whose value is `yes` or `no`.
+const BOOLEAN_KEYS: &[&str] = &[
+    "bond-use-carrier",
+    "bridge-arp-nd-suppress",
+    "bridge-learning",
+    "bridge-multicast-flood",
+    "bridge-stp",
+    "bridge-unicast-flood",
+    "bridge-vlan-aware",
+    "link-autoneg",
+    "mstpctl-bpduguard",
+    "mstpctl-portadminedge",
+    "mstpctl-portautoedge",
+    "mstpctl-portbpdufilter",
+    "vxlan-learning",
+];
+
+/// A single style finding produced by [`NetworkInterfaces::lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    /// A stable, machine-readable identifier for the rule that fired
+    /// (e.g., `"trailing-whitespace"`).
+    pub code: &'static str,
+    /// The interface the finding applies to, if any.
+    pub interface: Option<String>,
+    /// A human-readable description of the finding.
+    pub message: String,
+    /// The line the finding applies to, or the definition of the interface
+    /// if there is no single line. `None` for interfaces built in memory.
+    pub origin: Option<Origin>,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.interface {
+            Some(name) => write!(f, "[{}] {}: {}", self.code, name, self.message),
+            None => write!(f, "[{}] {}", self.code, self.message),
+        }?;
+        match &self.origin {
+            Some(origin) => write!(f, " ({})", origin),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the origin of a single 1-based line.
+fn line_origin(path: Option<&Path>, line: usize) -> Origin {
+    Origin {
+        path: path.map(Path::to_path_buf),
+        line,
+        span: None,
+        directive_lines: Vec::new(),
+    }
+}
+
+/// Describes an indentation, e.g. "4 spaces" or "1 tab".
+fn describe_indent(indent: &str) -> String {
+    let count = |c: char| indent.chars().filter(|&i| i == c).count();
+    let part = |n: usize, what: &str| match n {
+        1 => format!("1 {}", what),
+        n => format!("{} {}s", n, what),
+    };
+    match (count(' '), count('\t')) {
+        (0, 0) => "no indentation".to_string(),
+        (spaces, 0) => part(spaces, "space"),
+        (0, tabs) => part(tabs, "tab"),
+        (spaces, tabs) => format!("{} and {}", part(spaces, "space"), part(tabs, "tab")),
+    }
+}
+
+/// Finds the text-level warnings in the content of a file.
+///
+/// This is run when a file is loaded, since the text is not kept.
+pub(crate) fn scan_text(content: &str, path: Option<&Path>) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    let mut push = |code, interface: Option<&str>, message: String, line| {
+        warnings.push(LintWarning {
+            code,
+            interface: interface.map(str::to_string),
+            message,
+            origin: Some(line_origin(path, line)),
+        });
+    };
+
+    // Name of the stanza whose lines are being read
+    let mut stanza: Option<&str> = None;
+    // The indentation of each option line, with its line number and stanza
+    let mut indents: Vec<(usize, &str, Option<&str>)> = Vec::new();
+    for (line_number, raw_line) in content.lines().enumerate() {
+        let line_number = line_number + 1;
+        let text = raw_line.trim_start();
+        if raw_line.trim_end().len() != raw_line.len() {
+            let message = "line ends in whitespace".to_string();
+            push("trailing-whitespace", stanza, message, line_number);
+        }
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let mut words = text.split_whitespace();
+        let keyword = words.next().unwrap_or_default();
+        if matches!(keyword, "iface" | "mapping") {
+            stanza = words.next();
+        } else if keyword == "auto"
+            || keyword.starts_with("allow-")
+            || keyword.starts_with("source")
+        {
+            stanza = None;
+        } else if stanza.is_some() {
+            let indent = &raw_line[..raw_line.len() - text.len()];
+            if indent.contains(' ') && indent.contains('\t') {
+                let message = format!("indented with {}", describe_indent(indent));
+                push("mixed-indentation", stanza, message, line_number);
+            } else {
+                indents.push((line_number, indent, stanza));
+            }
+        }
+    }
+
+    // The most common indentation is the house style; ties go to the first
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (position, (_, indent, _)) in indents.iter().enumerate() {
+        counts.entry(indent).or_insert((0, position)).0 += 1;
+    }
+    let house = counts
+        .iter()
+        .max_by(|a, b| a.1 .0.cmp(&b.1 .0).then(b.1 .1.cmp(&a.1 .1)))
+        .map(|(indent, _)| *indent);
+    for (line_number, indent, stanza) in indents {
+        if let Some(house) = house.filter(|house| *house != indent) {
+            let message = format!(
+                "indented with {} instead of {}",
+                describe_indent(indent),
+                describe_indent(house)
+            );
+            push("inconsistent-indentation", stanza, message, line_number);
+        }
+    }
+    warnings
+}
+
+/// Returns the canonical spelling of a boolean value, or `None` if `value`
+/// is not a boolean.
+fn canonical_boolean(value: &str) -> Option<&'static str> {
+    match value.to_ascii_lowercase().as_str() {
+        "yes" | "on" | "true" | "1" => Some("yes"),
+        "no" | "off" | "false" | "0" => Some("no"),
+        _ => None,
+    }
+}
+
+impl NetworkInterfaces {
+    /// Reports style problems in the collection.
+    ///
+    /// See the [module documentation](crate::lint) for the rules. Nothing
+    /// is modified.
+    ///
+    /// # Returns
+    ///
+    /// The warnings, ordered by file and line; warnings without a line come
+    /// last, ordered by interface.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let strict_keys = self.parser_options().strict_keys;
+        let mut warnings: Vec<LintWarning> = self
+            .style_warnings()
+            .iter()
+            .filter(|w| {
+                w.interface
+                    .as_ref()
+                    .is_none_or(|name| self.get_interface(name).is_some())
+            })
+            .cloned()
+            .collect();
+        let mut push = |code, iface: &Interface, message, origin| {
+            warnings.push(LintWarning {
+                code,
+                interface: Some(iface.name.clone()),
+                message,
+                origin,
+            });
+        };
+
+        // The first line of every stanza, by file
+        let mut starts: BTreeMap<Option<&PathBuf>, BTreeSet<usize>> = BTreeMap::new();
+        for (_, iface) in self.iter() {
+            if let Some(origin) = iface.origin().filter(|origin| origin.span.is_some()) {
+                starts
+                    .entry(origin.path.as_ref())
+                    .or_default()
+                    .insert(origin.line);
+            }
+        }
+
+        for (_, iface) in self.iter() {
+            if let Some((origin, span)) = iface.origin().and_then(|o| Some((o, o.span.as_ref()?))) {
+                let file_starts = &starts[&origin.path.as_ref()];
+                for &line in &origin.directive_lines {
+                    let (low, high) = (line.min(span.start), line.max(span.start));
+                    if file_starts.range(low + 1..high).next().is_some() {
+                        let message = format!(
+                            "named on line {}, separated from its stanza on line {} by other stanzas",
+                            line, span.start
+                        );
+                        let origin = line_origin(origin.path.as_deref(), line);
+                        push("auto-far-from-stanza", iface, message, Some(origin));
+                    }
+                }
+
+                for dep in graph::interface_dependencies(iface, strict_keys) {
+                    let Some(target) = self.get_interface(&dep.to) else {
+                        continue;
+                    };
+                    let Some(target_origin) = target.origin().filter(|o| o.span.is_some()) else {
+                        continue;
+                    };
+                    if target_origin.path == origin.path && target_origin.line > origin.line {
+                        let message = format!(
+                            "declared on line {}, after {} references it on line {}",
+                            target_origin.line, iface.name, origin.line
+                        );
+                        push(
+                            "reference-before-declaration",
+                            target,
+                            message,
+                            Some(target_origin.clone()),
+                        );
+                    }
+                }
+            }
+
+            for (key, value) in iface.stanzas().flat_map(|stanza| stanza.options) {
+                if !BOOLEAN_KEYS.iter().any(|k| keys_match(key, k, strict_keys)) {
+                    continue;
+                }
+                match canonical_boolean(value) {
+                    Some(canonical) if canonical != value => {
+                        let message =
+                            format!("{} {} is better written {} {}", key, value, key, canonical);
+                        push(
+                            "non-canonical-boolean",
+                            iface,
+                            message,
+                            iface.origin().cloned(),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        warnings.sort_by(|a, b| {
+            let key = |w: &LintWarning| {
+                let origin = w.origin.as_ref();
+                (
+                    origin.is_none(),
+                    origin.and_then(|o| o.path.clone()),
+                    origin.map(|o| o.line),
+                )
+            };
+            key(a).cmp(&key(b))
+        });
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_fixture() {
+        let mut net_ifaces = NetworkInterfaces::load("tests/lint/interfaces").unwrap();
+        let codes = |net_ifaces: &NetworkInterfaces| -> Vec<(&'static str, String, usize)> {
+            net_ifaces
+                .lint()
+                .into_iter()
+                .map(|w| (w.code, w.interface.unwrap(), w.origin.unwrap().line))
+                .collect()
+        };
+        let expected = [
+            ("auto-far-from-stanza", "br0", 1),
+            ("non-canonical-boolean", "br0", 4),
+            ("trailing-whitespace", "br0", 7),
+            ("inconsistent-indentation", "br0", 8),
+            ("reference-before-declaration", "swp1", 10),
+            ("non-canonical-boolean", "swp1", 10),
+            ("inconsistent-indentation", "swp1", 12),
+            ("mixed-indentation", "swp1", 13),
+            ("reference-before-declaration", "swp2", 15),
+            ("auto-far-from-stanza", "swp1", 18),
+        ];
+        let expected: Vec<(&str, String, usize)> = expected
+            .iter()
+            .map(|&(code, name, line)| (code, name.to_string(), line))
+            .collect();
+        assert_eq!(codes(&net_ifaces), expected);
+
+        let warnings = net_ifaces.lint();
+        assert_eq!(
+            warnings[3].to_string(),
+            "[inconsistent-indentation] br0: indented with 1 tab instead of 4 spaces \
+             (tests/lint/interfaces:8)"
+        );
+        assert_eq!(
+            warnings[5].message,
+            "mstpctl-bpduguard true is better written mstpctl-bpduguard yes"
+        );
+
+        // Warnings for deleted interfaces are dropped
+        net_ifaces.delete_interface("swp1");
+        let remaining: Vec<(&str, String, usize)> = expected
+            .into_iter()
+            .filter(|(_, name, _)| name != "swp1")
+            .collect();
+        assert_eq!(codes(&net_ifaces), remaining);
+    }
+
+    #[test]
+    fn test_lint_family_stanza() {
+        let net_ifaces = NetworkInterfaces::parse_str(
+            "iface br0 inet static\n    bridge-stp yes\n\
+             iface br0 inet6 static\n    bridge-learning on\n",
+        )
+        .unwrap();
+        let warnings = net_ifaces.lint();
+        let messages: Vec<&str> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            ["bridge-learning on is better written bridge-learning yes"]
+        );
+    }
+}
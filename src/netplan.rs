@@ -0,0 +1,504 @@
+//! Conversion from netplan YAML.
+//!
+//! Enabled with the `netplan` feature, [`from_netplan`] builds a
+//! [`NetworkInterfaces`] collection with stanzas equivalent to a netplan
+//! (version 2) document:
+//!
+//! | netplan | `interfaces(5)` |
+//! |---------|-----------------|
+//! | `ethernets`, `bridges`, `bonds`, `vlans` entries | one `iface` stanza each, marked `auto` (`allow-hotplug` when `optional: true`) |
+//! | `dhcp4: true` | `inet dhcp` |
+//! | `dhcp6: true` | `inet6 dhcp`, in a second stanza with `dhcp4` |
+//! | `addresses` | `inet static` (`inet6` if all addresses are IPv6) with one `address` per entry |
+//! | neither | `inet manual` |
+//! | `gateway4`, `gateway6`, default `routes` | `gateway` |
+//! | other `routes` | `post-up ip route add <to> via <via>` |
+//! | `nameservers.addresses` / `nameservers.search` | `dns-nameservers` / `dns-search` |
+//! | `mtu`, `macaddress` | `mtu`, `hwaddress` |
+//! | bridge `interfaces`, `parameters.stp`, `parameters.forward-delay` | `bridge-ports`, `bridge-stp`, `bridge-fd` |
+//! | bond `interfaces`, `parameters.mode`, `lacp-rate`, `mii-monitor-interval`, `primary`, `transmit-hash-policy` | `bond-slaves`, `bond-mode`, `bond-lacp-rate`, `bond-miimon`, `bond-primary`, `bond-xmit-hash-policy` |
+//! | vlan `id`, `link` | `vlan-id`, `vlan-raw-device` |
+//!
+//! A `lo` loopback stanza is added unless the document declares `lo`.
+//! Anything else is dropped and reported by [`from_netplan_with_warnings`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::netplan::from_netplan;
+//!
+//! let net_ifaces = from_netplan(
+//!     "network:
+//!   version: 2
+//!   ethernets:
+//!     eth0:
+//!       dhcp4: true
+//! ",
+//! )
+//! .unwrap();
+//! assert!(net_ifaces.to_string().contains("iface eth0 inet dhcp"));
+//! ```
+
+use crate::error::ConversionError;
+use crate::interface::{Family, FamilyStanza, Interface, InterfaceBuilder, Method, OptionKey};
+use crate::network_interfaces::NetworkInterfaces;
+use crate::parser::ParserOptions;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+
+/// Converts a netplan YAML document into a `NetworkInterfaces` collection.
+///
+/// Unsupported netplan settings are dropped; use
+/// [`from_netplan_with_warnings`] to find out which.
+///
+/// # Errors
+///
+/// Returns a `ConversionError` if the input is not YAML or is not a netplan
+/// `network` document.
+pub fn from_netplan(yaml: &str) -> Result<NetworkInterfaces, ConversionError> {
+    from_netplan_with_warnings(yaml).map(|(net_ifaces, _warnings)| net_ifaces)
+}
+
+/// Converts a netplan YAML document into a `NetworkInterfaces` collection,
+/// also returning a warning for every setting that was dropped.
+///
+/// # Errors
+///
+/// Returns a `ConversionError` if the input is not YAML or is not a netplan
+/// `network` document.
+pub fn from_netplan_with_warnings(
+    yaml: &str,
+) -> Result<(NetworkInterfaces, Vec<String>), ConversionError> {
+    let doc: Value = serde_yaml::from_str(yaml).map_err(|err| ConversionError(err.to_string()))?;
+    let network = doc
+        .get("network")
+        .and_then(Value::as_mapping)
+        .ok_or_else(|| ConversionError("missing 'network' mapping".to_string()))?;
+
+    let mut converter = Converter::default();
+    for (key, value) in network {
+        let section = key_str(key);
+        match section {
+            "version" => {
+                if value.as_u64() != Some(2) {
+                    return Err(ConversionError(format!(
+                        "unsupported netplan version {}",
+                        scalar(value)
+                    )));
+                }
+            }
+            "ethernets" | "bridges" | "bonds" | "vlans" => {
+                let devices = value.as_mapping().ok_or_else(|| {
+                    ConversionError(format!("'network.{}' is not a mapping", section))
+                })?;
+                for (name, config) in devices {
+                    converter.device(section, key_str(name), config)?;
+                }
+            }
+            _ => converter.warn(format!("network.{}", section), "is not supported"),
+        }
+    }
+
+    let Converter {
+        mut interfaces,
+        warnings,
+    } = converter;
+    interfaces.entry("lo".to_string()).or_insert_with(|| {
+        Interface::builder("lo")
+            .with_auto(true)
+            .with_family(Family::Inet)
+            .with_method("loopback")
+            .build()
+    });
+    let net_ifaces = NetworkInterfaces::from_parts(
+        interfaces,
+        Vec::new(),
+        Vec::new(),
+        None,
+        None,
+        ParserOptions::default(),
+    );
+    Ok((net_ifaces, warnings))
+}
+
+/// Accumulates converted interfaces and warnings.
+#[derive(Default)]
+struct Converter {
+    interfaces: HashMap<String, Interface>,
+    warnings: Vec<String>,
+}
+
+impl Converter {
+    fn warn(&mut self, path: String, message: &str) {
+        self.warnings.push(format!("{}: {}", path, message));
+    }
+
+    /// Converts one device of a `network.<section>` mapping.
+    fn device(&mut self, section: &str, name: &str, config: &Value) -> Result<(), ConversionError> {
+        let path = format!("network.{}.{}", section, name);
+        let empty = Mapping::new();
+        let config = match config {
+            Value::Null => &empty,
+            Value::Mapping(config) => config,
+            _ => return Err(ConversionError(format!("'{}' is not a mapping", path))),
+        };
+
+        let mut builder = Interface::builder(name);
+        let mut addresses: Vec<String> = Vec::new();
+        let mut dhcp4 = false;
+        let mut dhcp6 = false;
+        let mut optional = false;
+
+        for (key, value) in config {
+            let key = key_str(key);
+            let key_path = format!("{}.{}", path, key);
+            match key {
+                "dhcp4" => dhcp4 = value.as_bool().unwrap_or(false),
+                "dhcp6" => dhcp6 = value.as_bool().unwrap_or(false),
+                "optional" => optional = value.as_bool().unwrap_or(false),
+                "addresses" => addresses.extend(scalars(value)),
+                "gateway4" | "gateway6" => builder = builder.with_option("gateway", scalar(value)),
+                "mtu" => builder = builder.with_option("mtu", scalar(value)),
+                "macaddress" => builder = builder.with_option("hwaddress", scalar(value)),
+                "nameservers" => builder = self.nameservers(builder, &key_path, value),
+                "routes" => builder = self.routes(builder, &key_path, value),
+                "interfaces" if section == "bridges" => {
+                    let ports = scalars(value);
+                    let ports = if ports.is_empty() {
+                        "none".to_string()
+                    } else {
+                        ports.join(" ")
+                    };
+                    builder = builder.with_option("bridge-ports", ports);
+                }
+                "interfaces" if section == "bonds" => {
+                    builder = builder.with_option("bond-slaves", scalars(value).join(" "));
+                }
+                "parameters" if section == "bridges" || section == "bonds" => {
+                    builder = self.parameters(builder, section, &key_path, value);
+                }
+                "id" if section == "vlans" => {
+                    builder = builder.with_option("vlan-id", scalar(value))
+                }
+                "link" if section == "vlans" => {
+                    builder = builder.with_option("vlan-raw-device", scalar(value))
+                }
+                _ => self.warn(key_path, "is not supported"),
+            }
+        }
+
+        if dhcp4 {
+            builder = builder.with_family(Family::Inet).with_method("dhcp");
+            if dhcp6 {
+                builder = builder.with_family_stanza(FamilyStanza {
+                    family: Some(Family::Inet6),
+                    method: Some(Method::Dhcp),
+                    options: Vec::new(),
+                });
+            }
+        } else if dhcp6 {
+            builder = builder.with_family(Family::Inet6).with_method("dhcp");
+        } else if !addresses.is_empty() {
+            let family = if addresses.iter().all(|a| a.contains(':')) {
+                Family::Inet6
+            } else {
+                Family::Inet
+            };
+            builder = builder.with_family(family).with_method("static");
+        } else {
+            builder = builder.with_family(Family::Inet).with_method("manual");
+        }
+        if dhcp4 || dhcp6 {
+            // Static addresses alongside DHCP are kept, as ifupdown2 allows
+            for address in &addresses {
+                builder = builder.with_option("address", address);
+            }
+        } else {
+            // Addresses go first so the stanza reads naturally
+            let mut iface = builder.build();
+            let mut options: Vec<(OptionKey, String)> = addresses
+                .into_iter()
+                .map(|a| ("address".into(), a))
+                .collect();
+            options.append(&mut iface.options);
+            iface.options = options;
+            builder = iface.edit();
+        }
+        builder = if optional {
+            builder.with_allow("hotplug")
+        } else {
+            builder.with_auto(true)
+        };
+
+        if self.interfaces.contains_key(name) {
+            self.warn(path, "duplicate interface name; later definition dropped");
+        } else {
+            self.interfaces.insert(name.to_string(), builder.build());
+        }
+        Ok(())
+    }
+
+    fn nameservers(
+        &mut self,
+        mut builder: InterfaceBuilder,
+        path: &str,
+        value: &Value,
+    ) -> InterfaceBuilder {
+        let Some(config) = value.as_mapping() else {
+            self.warn(path.to_string(), "is not a mapping");
+            return builder;
+        };
+        for (key, value) in config {
+            match key_str(key) {
+                "addresses" => {
+                    builder = builder.with_option("dns-nameservers", scalars(value).join(" "))
+                }
+                "search" => builder = builder.with_option("dns-search", scalars(value).join(" ")),
+                key => self.warn(format!("{}.{}", path, key), "is not supported"),
+            }
+        }
+        builder
+    }
+
+    fn routes(
+        &mut self,
+        mut builder: InterfaceBuilder,
+        path: &str,
+        value: &Value,
+    ) -> InterfaceBuilder {
+        let routes = value.as_sequence().map(Vec::as_slice).unwrap_or_default();
+        for (i, route) in routes.iter().enumerate() {
+            let (Some(to), Some(via)) = (route.get("to"), route.get("via")) else {
+                self.warn(
+                    format!("{}[{}]", path, i),
+                    "route without 'to' and 'via' dropped",
+                );
+                continue;
+            };
+            let (to, via) = (scalar(to), scalar(via));
+            if let Some(extra) = route
+                .as_mapping()
+                .and_then(|r| r.keys().map(key_str).find(|k| *k != "to" && *k != "via"))
+            {
+                self.warn(format!("{}[{}].{}", path, i, extra), "is not supported");
+            }
+            if matches!(to.as_str(), "default" | "0.0.0.0/0" | "::/0") {
+                builder = builder.with_option("gateway", via);
+            } else {
+                builder =
+                    builder.with_option("post-up", format!("ip route add {} via {}", to, via));
+            }
+        }
+        builder
+    }
+
+    fn parameters(
+        &mut self,
+        mut builder: InterfaceBuilder,
+        section: &str,
+        path: &str,
+        value: &Value,
+    ) -> InterfaceBuilder {
+        let Some(config) = value.as_mapping() else {
+            self.warn(path.to_string(), "is not a mapping");
+            return builder;
+        };
+        for (key, value) in config {
+            let key = key_str(key);
+            let option = match (section, key) {
+                ("bridges", "stp") => {
+                    let on = if value.as_bool().unwrap_or(false) {
+                        "on"
+                    } else {
+                        "off"
+                    };
+                    builder = builder.with_option("bridge-stp", on);
+                    continue;
+                }
+                ("bridges", "forward-delay") => "bridge-fd",
+                ("bonds", "mode") => "bond-mode",
+                ("bonds", "lacp-rate") => "bond-lacp-rate",
+                ("bonds", "mii-monitor-interval") => "bond-miimon",
+                ("bonds", "primary") => "bond-primary",
+                ("bonds", "transmit-hash-policy") => "bond-xmit-hash-policy",
+                _ => {
+                    self.warn(format!("{}.{}", path, key), "is not supported");
+                    continue;
+                }
+            };
+            builder = builder.with_option(option, scalar(value));
+        }
+        builder
+    }
+}
+
+/// Returns a mapping key as a string, or `""` for non-string keys.
+fn key_str(key: &Value) -> &str {
+    key.as_str().unwrap_or_default()
+}
+
+/// Renders a scalar YAML value as it would be written in an interfaces file.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => String::new(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Renders a sequence of scalars; a single scalar is treated as a sequence of one.
+fn scalars(value: &Value) -> Vec<String> {
+    match value {
+        Value::Sequence(seq) => seq.iter().map(scalar).collect(),
+        Value::Null => Vec::new(),
+        other => vec![scalar(other)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    const NETPLAN: &str = "network:
+  version: 2
+  renderer: networkd
+  ethernets:
+    eno1:
+      dhcp4: true
+      dhcp6: true
+    eno2:
+      optional: true
+    eno3: {}
+    eno4:
+      mtu: 9000
+      wakeonlan: true
+  bonds:
+    bond0:
+      interfaces: [eno3, eno4]
+      parameters:
+        mode: 802.3ad
+        lacp-rate: fast
+        mii-monitor-interval: 100
+        gratuitous-arp: 5
+  bridges:
+    br0:
+      interfaces: [bond0]
+      addresses: [192.168.10.5/24, \"2001:db8::5/64\"]
+      routes:
+        - to: default
+          via: 192.168.10.1
+        - to: 10.0.0.0/8
+          via: 192.168.10.254
+          metric: 100
+      nameservers:
+        addresses: [192.168.10.1, 1.1.1.1]
+        search: [example.com]
+      macaddress: 52:54:00:12:34:56
+      parameters:
+        stp: false
+        forward-delay: 4
+  vlans:
+    vlan20:
+      id: 20
+      link: br0
+      addresses: [\"2001:db8:20::1/64\"]
+";
+
+    const EXPECTED: &str = "auto bond0
+iface bond0 inet manual
+    bond-slaves eno3 eno4
+    bond-mode 802.3ad
+    bond-lacp-rate fast
+    bond-miimon 100
+
+auto br0
+iface br0 inet static
+    address 192.168.10.5/24
+    address 2001:db8::5/64
+    bridge-ports bond0
+    gateway 192.168.10.1
+    post-up ip route add 10.0.0.0/8 via 192.168.10.254
+    dns-nameservers 192.168.10.1 1.1.1.1
+    dns-search example.com
+    hwaddress 52:54:00:12:34:56
+    bridge-stp off
+    bridge-fd 4
+
+auto eno1
+iface eno1 inet dhcp
+
+iface eno1 inet6 dhcp
+
+allow-hotplug eno2
+iface eno2 inet manual
+
+auto eno3
+iface eno3 inet manual
+
+auto eno4
+iface eno4 inet manual
+    mtu 9000
+
+auto lo
+iface lo inet loopback
+
+auto vlan20
+iface vlan20 inet6 static
+    address 2001:db8:20::1/64
+    vlan-id 20
+    vlan-raw-device br0
+";
+
+    #[test]
+    fn test_from_netplan() {
+        let (net_ifaces, warnings) = from_netplan_with_warnings(NETPLAN).unwrap();
+        assert_eq!(net_ifaces.to_string(), EXPECTED);
+        assert_eq!(
+            warnings,
+            vec![
+                "network.renderer: is not supported",
+                "network.ethernets.eno4.wakeonlan: is not supported",
+                "network.bonds.bond0.parameters.gratuitous-arp: is not supported",
+                "network.bridges.br0.routes[1].metric: is not supported",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_netplan_round_trip() {
+        // netplan -> interfaces -> model preserves every converted interface
+        let net_ifaces = from_netplan(NETPLAN).unwrap();
+        let (interfaces, _, _) = Parser::new().parse(&net_ifaces.to_string()).unwrap();
+        assert_eq!(interfaces.len(), net_ifaces.len());
+        for (name, iface) in net_ifaces.iter() {
+            assert_eq!(&interfaces[name], iface);
+        }
+        let br0 = net_ifaces.get_interface("br0").unwrap();
+        assert_eq!(
+            br0.options[0],
+            ("address".into(), "192.168.10.5/24".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_netplan_errors() {
+        assert!(from_netplan("network: [").is_err());
+        assert!(from_netplan("other: {}").is_err());
+        let err = from_netplan("network:\n  version: 1\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Conversion error: unsupported netplan version 1"
+        );
+        assert!(from_netplan("network:\n  ethernets: [eth0]\n").is_err());
+
+        let net_ifaces = from_netplan("network:\n  version: 2\n").unwrap();
+        assert_eq!(net_ifaces.len(), 1);
+        assert!(net_ifaces.get_interface("lo").is_some());
+    }
+}
@@ -1,14 +1,93 @@
-use crate::error::NetworkInterfacesError;
-use crate::interface::Interface;
-use crate::parser::Parser;
-use crate::helper::sort::natural;
-use std::collections::HashMap;
+//! The [`NetworkInterfaces`] collection: loading, querying, editing, and
+//! saving a whole `interfaces(5)` configuration.
+
+use crate::error::{
+    CycleError, IoOp, IoResultExt, Limit, LimitExceeded, NetworkInterfacesError,
+};
+use crate::files::{
+    self, Backup, FileAction, FileMap, IncludedFile, ManagedBlock, PlannedFile, PostSaveHookFn,
+    SaveHook, SaveTarget,
+};
+use crate::graph;
+use crate::helper::ordered_map::OrderedMap;
+use crate::lint::{self, LintWarning};
+use crate::interface::option::{keys_match, remove_matching, set_single};
+use crate::interface::{
+    validate_interface_name, Address, Family, FamilyStanza, IfaceName, Interface, InterfaceKind,
+    InterfaceOption, MacAddr, MacAddrParseError, OptionParseError, OptionRangeError, Origin,
+    VlanRanges,
+};
+use crate::parser::{self, Parser, ParserOptions};
+use crate::source::{Source, SourceKind, SourceParseError};
+use crate::validation::{self, DuplicateHwaddress, ValidationOptions, ValidationReport};
+use crate::write_options::{CompareFn, Comparator, SortOrder, WriteOptions};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// What to do with a member list option, such as `bridge-ports`, when its
+/// last member is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EmptyListPolicy {
+    /// Keep the option with the value `none`.
+    #[default]
+    WriteNone,
+    /// Remove the option, unless it is what makes the interface a bridge or
+    /// a bond. It is then kept with the value `none` too, so that a bridge
+    /// left without ports can still be given new ones.
+    Remove,
+}
+
+impl EmptyListPolicy {
+    /// Returns whether the emptied `key` list of `iface` is removed rather
+    /// than kept as `none`.
+    pub(crate) fn removes(self, iface: &Interface, key: &str, strict_keys: bool) -> bool {
+        if self == EmptyListPolicy::WriteNone {
+            return false;
+        }
+        let mut without = iface.clone();
+        without.options.retain(|(k, _)| !keys_match(k, key, strict_keys));
+        without.kind() == iface.kind()
+    }
+}
+
+/// Options for [`NetworkInterfaces::set_vrf_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VrfOptions {
+    /// Allow assigning a VRF that is not defined in the collection, for
+    /// example because it is defined in a sourced file.
+    pub allow_missing_vrf: bool,
+}
+
+/// Options for [`NetworkInterfaces::provision_vni`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VniOptions {
+    /// The `vxlan-local-tunnelip` to set on a newly created VNI interface.
+    pub local_tunnel_ip: Option<String>,
+    /// The `mtu` to set on a newly created VNI interface.
+    pub mtu: Option<u16>,
+}
+
+/// The result of [`NetworkInterfaces::provision_vni`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvisionedVni {
+    /// The VLAN the VNI is mapped to.
+    pub vlan_id: u16,
+    /// The name of the VNI interface (`vni<N>`).
+    pub vni_interface: String,
+    /// The name of the SVI (`vlan<X>`).
+    pub svi_interface: String,
+    /// The interfaces that were created, in creation order. Empty if
+    /// everything already existed.
+    pub created: Vec<String>,
+}
+
 /// Represents the collection of network interfaces defined in an `interfaces(5)` file.
 ///
 /// The `NetworkInterfaces` struct provides methods to load, manipulate, and save
@@ -20,23 +99,65 @@ use std::time::SystemTime;
 ///
 /// ```rust
 /// use interface_rs::NetworkInterfaces;
-/// use interface_rs::interface::Interface;
+/// use interface_rs::interface::{Interface, Method};
 ///
-/// let mut net_ifaces = NetworkInterfaces::load("tests/interfaces").unwrap();
+/// # let path = std::env::temp_dir().join("interface-rs-doc-network-interfaces");
+/// # std::fs::copy("tests/interfaces", &path).unwrap();
+/// let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
 ///
 /// // Modify an interface
 /// if let Some(iface) = net_ifaces.get_interface_mut("eth0") {
-///     iface.method = Some("static".to_string());
-///     iface.options.push(("address".to_string(), "192.168.1.100".to_string()));
+///     iface.method = Some(Method::Static);
+///     iface.add_option("address", "192.168.1.100");
 /// }
 ///
 /// // Save changes
 /// net_ifaces.save().unwrap();
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+///
+/// # Serialization
+///
+/// With the `serde` feature, a `NetworkInterfaces` serializes as a struct of
+/// the interfaces, keyed by name in natural order, and the top-of-file
+/// comments and source directives. The file path, modification time, and
+/// parser and write options are not serialized; a deserialized collection
+/// has no path and default options. Each interface uses the representation
+/// documented on [`Interface`]. The order of the stanzas in the file is not
+/// part of the representation, so a deserialized collection is written in
+/// natural order.
+///
+/// ```json
+/// {
+///   "interfaces": {
+///     "lo": { "name": "lo", "auto": true, "family": "inet", "method": "loopback", ... }
+///   },
+///   "comments": ["# The loopback network interface"],
+///   "sources": ["source /etc/network/interfaces.d/*"]
+/// }
 /// ```
-#[derive(Debug)]
+///
+/// Deserializing fails if a key does not match the `name` of its interface.
+/// The `json` and `toml` features add helpers for those formats; see
+/// `to_json_string` and `to_toml_string`.
+///
+/// # Cloning and equality
+///
+/// A clone is a full copy, including the path and modification time, so it
+/// saves to the same files and [`save`](NetworkInterfaces::save) still
+/// detects changes made on disk since the original was loaded.
+///
+/// Two collections are equal if they hold equal interfaces, compared as
+/// documented on [`Interface`], and the same header comments and source
+/// directives. Everything about where the content came from or how it is
+/// written is ignored: the path, modification time, files, insertion order,
+/// and parser and write options. Loading identical content from two paths
+/// gives equal collections.
+#[derive(Debug, Clone)]
 pub struct NetworkInterfaces {
-    /// A mapping of interface names to their configurations.
-    interfaces: HashMap<String, Interface>,
+    /// A mapping of interface names to their configurations, in the order
+    /// they were added.
+    interfaces: OrderedMap<IfaceName, Interface>,
     /// The path to the interfaces file.
     path: Option<PathBuf>,
     /// The last modified time of the interfaces file.
@@ -45,24 +166,187 @@ pub struct NetworkInterfaces {
     comments: Vec<String>,
     /// Source directives from the original file
     sources: Vec<String>,
+    /// Options the file was parsed with
+    parser_options: ParserOptions,
+    /// Options controlling how the collection is written
+    write_options: WriteOptions,
+    /// The file each interface is saved to
+    files: FileMap,
+    /// The hook run after a successful save
+    post_save_hook: Option<SaveHook>,
+}
+
+impl PartialEq for NetworkInterfaces {
+    fn eq(&self, other: &Self) -> bool {
+        self.interfaces == other.interfaces
+            && self.comments == other.comments
+            && self.sources == other.sources
+    }
 }
 
+impl Eq for NetworkInterfaces {}
+
 impl NetworkInterfaces {
-    /// Creates a new `NetworkInterfaces` instance.
-    fn new(
+    /// Creates an empty collection with no path.
+    ///
+    /// The collection has no header comments or sources either, and the
+    /// default [`ParserOptions`] and [`WriteOptions`]. Since it has no file,
+    /// [`save`](NetworkInterfaces::save) fails until one is given with
+    /// [`save_as`](NetworkInterfaces::save_as). The same is available through
+    /// [`Default`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::new();
+    /// assert!(net_ifaces.is_empty());
+    /// net_ifaces.add_interface(Interface::builder("eth0").with_auto(true).build());
+    /// assert_eq!(net_ifaces.to_string(), "auto eth0\niface eth0\n");
+    /// assert!(net_ifaces.save().is_err());
+    /// ```
+    pub fn new() -> Self {
+        NetworkInterfaces::from_parts(
+            HashMap::new(),
+            Vec::new(),
+            Vec::new(),
+            None,
+            None,
+            ParserOptions::default(),
+        )
+    }
+
+    /// Parses a collection from the contents of an `interfaces(5)` file,
+    /// such as one fetched over SSH.
+    ///
+    /// The collection has no path, so `source` directives are kept but not
+    /// followed, and [`save`](NetworkInterfaces::save) fails until a file is
+    /// given with [`save_as`](NetworkInterfaces::save_as).
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The contents of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkInterfacesError` if the content cannot be parsed or
+    /// exceeds the limits of the default [`ParserOptions`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let content = "auto lo\niface lo inet loopback\n";
+    /// let net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+    /// assert!(net_ifaces.get_interface("lo").unwrap().auto);
+    /// assert_eq!(net_ifaces.to_string(), content);
+    /// ```
+    pub fn parse_str(content: &str) -> Result<Self, NetworkInterfacesError> {
+        let options = ParserOptions::default();
+        let parser = Parser::new().with_options(options.clone());
+        let (interfaces, comments, sources) = parser.parse_limited(content)?;
+        let mut net_ifaces =
+            NetworkInterfaces::from_parts(interfaces, comments, sources, None, None, options);
+        net_ifaces.files.style = lint::scan_text(content, None);
+        Ok(net_ifaces)
+    }
+
+    /// Creates a collection from its parsed parts.
+    pub(crate) fn from_parts(
         interfaces: HashMap<String, Interface>,
         comments: Vec<String>,
         sources: Vec<String>,
         path: Option<PathBuf>,
         last_modified: Option<SystemTime>,
+        parser_options: ParserOptions,
     ) -> Self {
+        // Parsed interfaces are added in file order, others after them
+        let mut interfaces: Vec<(usize, IfaceName, Interface)> = interfaces
+            .into_iter()
+            .map(|(name, iface)| {
+                let line = iface.origin().map_or(usize::MAX, |o| o.line);
+                (line, IfaceName::from(name), iface)
+            })
+            .collect();
+        interfaces.sort_unstable_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        let interfaces = interfaces.into_iter().map(|(_, name, iface)| (name, iface)).collect();
         NetworkInterfaces {
             interfaces,
             comments,
             sources,
             path,
             last_modified,
+            parser_options,
+            write_options: WriteOptions::default(),
+            files: FileMap::default(),
+            post_save_hook: None,
+        }
+    }
+
+    /// Creates a collection from interfaces built in memory.
+    ///
+    /// The collection has no path, header comments, or sources. If several
+    /// interfaces share a name, the last one wins; use
+    /// [`try_from_interfaces`](NetworkInterfaces::try_from_interfaces) to
+    /// reject duplicates instead. The same is available through
+    /// [`FromIterator`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ifaces` - The interfaces to include.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let net_ifaces: NetworkInterfaces = (1..=4)
+    ///     .map(|n| Interface::builder(format!("swp{}", n)).with_option("mtu", "9216").build())
+    ///     .collect();
+    /// assert_eq!(net_ifaces.len(), 4);
+    /// ```
+    pub fn from_interfaces(ifaces: impl IntoIterator<Item = Interface>) -> Self {
+        let mut net_ifaces = NetworkInterfaces::new();
+        for iface in ifaces {
+            net_ifaces.add_interface(iface);
+        }
+        net_ifaces
+    }
+
+    /// Creates a collection from interfaces built in memory, rejecting
+    /// duplicate names.
+    ///
+    /// # Arguments
+    ///
+    /// * `ifaces` - The interfaces to include.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::DuplicateInterfaces`] listing every
+    /// name that occurs more than once.
+    pub fn try_from_interfaces(
+        ifaces: impl IntoIterator<Item = Interface>,
+    ) -> Result<Self, NetworkInterfacesError> {
+        let mut net_ifaces = NetworkInterfaces::from_interfaces(None);
+        let mut duplicates = BTreeSet::new();
+        for iface in ifaces {
+            let name = IfaceName::from(iface.name.as_str());
+            if net_ifaces.interfaces.contains_key(&name) {
+                duplicates.insert(name);
+            } else {
+                net_ifaces.interfaces.insert(name, iface);
+            }
+        }
+        if !duplicates.is_empty() {
+            return Err(NetworkInterfacesError::DuplicateInterfaces {
+                names: duplicates.into_iter().map(|name| name.0).collect(),
+            });
         }
+        Ok(net_ifaces)
     }
 
     /// Loads the `interfaces(5)` file into memory.
@@ -77,23 +361,340 @@ impl NetworkInterfaces {
     ///
     /// # Errors
     ///
-    /// Returns a `NetworkInterfacesError` if the file cannot be read or parsed.
+    /// Returns a `NetworkInterfacesError` if the file cannot be read or parsed,
+    /// or if it is larger than
+    /// [`DEFAULT_MAX_INPUT_SIZE`](ParserOptions::DEFAULT_MAX_INPUT_SIZE); use
+    /// [`load_with`](NetworkInterfaces::load_with) to change the limit.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, NetworkInterfacesError> {
-        let path_buf = path.as_ref().to_path_buf();
-        let metadata = fs::metadata(&path_buf)?;
-        let last_modified = metadata.modified()?;
+        NetworkInterfaces::load_with(path, ParserOptions::default())
+    }
+
+    /// Loads the `interfaces(5)` file into memory using the given parser options.
+    ///
+    /// The options are kept with the collection and also apply to later
+    /// lookups, validation, and [`reload`](NetworkInterfaces::reload).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the interfaces file.
+    /// * `options` - The [`ParserOptions`] to use.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkInterfacesError` if the file cannot be read or parsed,
+    /// or [`LimitExceeded`](NetworkInterfacesError::LimitExceeded) if it
+    /// exceeds a limit of `options`, such as
+    /// [`max_input_size`](ParserOptions::max_input_size). The error carries
+    /// the path of the file.
+    pub fn load_with<P: AsRef<Path>>(
+        path: P,
+        options: ParserOptions,
+    ) -> Result<Self, NetworkInterfacesError> {
+        let path = path.as_ref();
+        let follow_sources = options.follow_sources;
+        let mut net_ifaces =
+            NetworkInterfaces::read(path, options, None).map_err(|err| err.with_path(path))?;
+        if follow_sources {
+            net_ifaces.follow_sources()?;
+        }
+        Ok(net_ifaces)
+    }
+
+    /// Loads the `interfaces(5)` file together with the files it includes
+    /// with `source` and `source-directory` directives.
+    ///
+    /// The pattern of a `source` directive is expanded like a shell glob,
+    /// with `*` and `?` as wildcards. A `source-directory` directive includes
+    /// the files of a directory whose names consist of letters, digits, `_`
+    /// and `-`, the names `run-parts` accepts. Relative paths are resolved
+    /// against the directory of the file containing the directive. The files
+    /// are read in path order, and their own directives are followed in turn;
+    /// a file is read only once. As with `ifup`, a pattern that matches
+    /// nothing or a directory that does not exist is not an error.
+    ///
+    /// Every interface remembers the file it came from, available through
+    /// [`file_of`](NetworkInterfaces::file_of), and
+    /// [`save`](NetworkInterfaces::save) writes it back there. An `auto` or
+    /// `allow-*` line in one file for an interface defined in another is
+    /// merged into the definition, and written with it when saving. The
+    /// directives themselves are kept as written.
+    ///
+    /// This is [`load_with`](NetworkInterfaces::load_with) with
+    /// [`follow_sources`](ParserOptions::follow_sources) set.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the main interfaces file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::DuplicateDefinition`] naming both
+    /// files if two files define the same interface, or any error returned by
+    /// [`load`](NetworkInterfaces::load) for the main file or an included
+    /// file, carrying the path of that file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load_with_includes("/etc/network/interfaces").unwrap();
+    /// for (name, _) in net_ifaces.iter() {
+    ///     println!("{} in {}", name, net_ifaces.file_of(name).unwrap().display());
+    /// }
+    /// ```
+    pub fn load_with_includes<P: AsRef<Path>>(path: P) -> Result<Self, NetworkInterfacesError> {
+        let options = ParserOptions {
+            follow_sources: true,
+            ..Default::default()
+        };
+        NetworkInterfaces::load_with(path, options)
+    }
+
+    /// Reads the files included by the `source` and `source-directory`
+    /// directives of the main file, and recursively by those of the included
+    /// files.
+    fn follow_sources(&mut self) -> Result<(), NetworkInterfacesError> {
+        let Some(main) = self.path.clone() else {
+            return Ok(());
+        };
+        let canonical = fs::canonicalize(&main).unwrap_or_else(|_| main.clone());
+        let mut visited = BTreeSet::from([canonical]);
+        let sources = self.sources.clone();
+        self.include_sources(&main, &sources, &mut visited)
+    }
+
+    /// Reads the files matching `sources`, the directives of `file`, that are
+    /// not in `visited`, followed by the files they include.
+    fn include_sources(
+        &mut self,
+        file: &Path,
+        sources: &[String],
+        visited: &mut BTreeSet<PathBuf>,
+    ) -> Result<(), NetworkInterfacesError> {
+        let dir = file.parent().unwrap_or(Path::new(""));
+        for source in sources.iter().filter_map(|line| line.parse::<Source>().ok()) {
+            let pattern = dir.join(&source.path);
+            let paths = match source.kind {
+                SourceKind::Source => files::expand_glob(&pattern),
+                SourceKind::SourceDirectory => match files::directory_files(&pattern) {
+                    Ok(paths) => paths,
+                    // Like `ifup`, skip a directory that does not exist
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                    Err(err) => {
+                        return Err(err).op(IoOp::ReadDir).map_err(|err| err.with_path(&pattern))
+                    }
+                },
+            };
+            for path in paths {
+                let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                if !visited.insert(canonical) {
+                    continue;
+                }
+                let fragment = NetworkInterfaces::read(&path, self.parser_options.clone(), None)
+                    .map_err(|err| err.with_path(&path))?;
+                let nested = fragment.sources.clone();
+                self.merge_fragment(path.clone(), fragment)?;
+                self.include_sources(&path, &nested, visited)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the interfaces of `fragment`, read from `path`, recording the file
+    /// of each, and records `path` as an included file.
+    ///
+    /// An interface that only appears on `auto` or `allow-*` lines in one of
+    /// the files takes those flags to its definition in the other. Stanzas
+    /// for another address family of a defined interface are added to it, and
+    /// saved back to `path`.
+    fn merge_fragment(
+        &mut self,
+        path: PathBuf,
+        fragment: NetworkInterfaces,
+    ) -> Result<(), NetworkInterfacesError> {
+        // Keep the order of the fragment's stanzas
+        let mut interfaces: Vec<Interface> = fragment.interfaces.into_values().collect();
+        interfaces.sort_by_key(|iface| iface.origin().map_or(usize::MAX, |o| o.line));
+        for mut iface in interfaces {
+            let name = IfaceName::from(iface.name.as_str());
+            if let Some(existing) = self.interfaces.get_mut(&name) {
+                match (existing.is_declared(), iface.is_declared()) {
+                    // Another address family of the interface, kept in the
+                    // file it was read from
+                    (true, true) if !shares_family(existing, &iface) => {
+                        merge_directives(existing, &iface);
+                        let stanza_origins = self.files.stanza_origins.entry(name).or_default();
+                        for stanza in iface.stanzas() {
+                            stanza_origins.push((stanza.family.cloned(), path.clone()));
+                        }
+                        existing.tags.extend(iface.tags);
+                        let first = FamilyStanza {
+                            family: iface.family,
+                            method: iface.method,
+                            options: iface.options,
+                        };
+                        existing.family_stanzas.push(first);
+                        existing.family_stanzas.extend(iface.family_stanzas);
+                        continue;
+                    }
+                    (true, true) => {
+                        let first = self.files.origins.get(&name).cloned().unwrap_or_default();
+                        return Err(NetworkInterfacesError::DuplicateDefinition {
+                            name: name.0,
+                            first,
+                            second: path,
+                        });
+                    }
+                    (true, false) => {
+                        merge_directives(existing, &iface);
+                        continue;
+                    }
+                    (false, _) => merge_directives(&mut iface, existing),
+                }
+            }
+            self.files.origins.insert(name, path.clone());
+            self.add_interface(iface);
+        }
+        self.files.style.extend(fragment.files.style);
+        let included = IncludedFile {
+            comments: fragment.comments,
+            sources: fragment.sources,
+            last_modified: fragment.last_modified,
+        };
+        self.files.included.insert(path, included);
+        Ok(())
+    }
+
+    /// Loads only the block of a file between `# BEGIN <marker>` and
+    /// `# END <marker>`.
+    ///
+    /// The rest of the file is kept as opaque text: interfaces defined outside
+    /// the block are not visible through the API, and
+    /// [`save`](NetworkInterfaces::save) rewrites only the block, leaving the
+    /// text above and below it byte-for-byte unchanged. This lets a tool
+    /// manage part of a file that people also edit by hand. If the file has
+    /// no such block, it is loaded empty and the markers are added at its end
+    /// on the next save.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the interfaces file.
+    /// * `marker` - The name following `# BEGIN` and `# END` on the marker
+    ///   lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`Parser`](NetworkInterfacesError::Parser) error if the
+    /// block has no `# END` line or an `# END` line comes first, if the block
+    /// appears more than once, or if it is nested in itself, as well as any
+    /// error returned by [`load`](NetworkInterfaces::load).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let mut net_ifaces =
+    ///     NetworkInterfaces::load_managed("/etc/network/interfaces", "netd").unwrap();
+    /// net_ifaces.add_interface(Interface::builder("swp1").with_auto(true).build());
+    /// net_ifaces.save().unwrap();
+    /// ```
+    pub fn load_managed<P: AsRef<Path>>(
+        path: P,
+        marker: &str,
+    ) -> Result<Self, NetworkInterfacesError> {
+        let path = path.as_ref();
+        NetworkInterfaces::read(path, ParserOptions::default(), Some(marker))
+            .map_err(|err| err.with_path(path))
+    }
+
+    /// Loads every fragment in a directory, for systems without a main file.
+    ///
+    /// The files read are those `source-directory` would include: regular
+    /// files whose names consist only of ASCII letters, digits, underscores,
+    /// and hyphens, in name order. Each interface remembers the file it came
+    /// from, and [`save`](NetworkInterfaces::save) writes it back there.
+    ///
+    /// The collection has no main file, so new interfaces need a
+    /// [file](NetworkInterfaces::set_file) or
+    /// [default target](NetworkInterfaces::set_default_target) before saving.
+    /// Source directives in the fragments are kept in their files but not
+    /// followed.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to read, such as `/etc/network/interfaces.d`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::DuplicateDefinition`] naming both
+    /// files if two files define the same interface, or any error returned by
+    /// [`load`](NetworkInterfaces::load) for a fragment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load_dir("/etc/network/interfaces.d").unwrap();
+    /// for (name, _) in net_ifaces.iter() {
+    ///     println!("{} in {}", name, net_ifaces.file_of(name).unwrap().display());
+    /// }
+    /// ```
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Result<Self, NetworkInterfacesError> {
+        let dir = dir.as_ref();
+        let paths =
+            files::directory_files(dir).op(IoOp::ReadDir).map_err(|err| err.with_path(dir))?;
+        let mut net_ifaces = NetworkInterfaces::from_interfaces(None);
+        for path in paths {
+            let fragment = NetworkInterfaces::load(&path)?;
+            net_ifaces.merge_fragment(path, fragment)?;
+        }
+        Ok(net_ifaces)
+    }
+
+    /// Reads and parses the file at `path`, or only its block marked with
+    /// `managed`.
+    fn read(
+        path: &Path,
+        options: ParserOptions,
+        managed: Option<&str>,
+    ) -> Result<Self, NetworkInterfacesError> {
+        let metadata = fs::metadata(path).op(IoOp::ReadMetadata)?;
+        let last_modified = metadata.modified().op(IoOp::ReadMetadata)?;
 
-        let content = fs::read_to_string(&path_buf)?;
-        let parser = Parser::new();
-        let (interfaces, comments, sources) = parser.parse(&content)?;
+        let parser = Parser::for_file(path).with_options(options.clone());
+        parser.check_input_size(metadata.len())?;
+        let bytes = read_bounded(path, parser.max_input_size())?;
+        let content = parser::decode(&bytes)?;
+        let (managed, block) = match managed {
+            Some(marker) => {
+                let (managed, block) = ManagedBlock::split(content, marker)?;
+                (Some(managed), Cow::Owned(block))
+            }
+            None => (None, Cow::Borrowed(content)),
+        };
+        let content = &*block;
+        let (interfaces, comments, sources) = parser.parse_limited(content)?;
 
-        Ok(NetworkInterfaces::new(
+        let mut net_ifaces = NetworkInterfaces::from_parts(
             interfaces,
             comments,
             sources,
-            Some(path_buf),
+            Some(path.to_path_buf()),
             Some(last_modified),
-        ))
+            options,
+        );
+        net_ifaces.files.origins = net_ifaces
+            .interfaces
+            .keys()
+            .map(|name| (name.clone(), path.to_path_buf()))
+            .collect();
+        net_ifaces.files.style = lint::scan_text(content, Some(path));
+        net_ifaces.files.managed = managed;
+        Ok(net_ifaces)
     }
 
     /// Retrieves a reference to an interface by name.
@@ -109,6 +710,23 @@ impl NetworkInterfaces {
         self.interfaces.get(name)
     }
 
+    /// Retrieves the stanza of an interface for one address family, such as
+    /// the `inet6` stanza of a dual-stack interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface.
+    /// * `family` - The address family of the stanza.
+    ///
+    /// # Returns
+    ///
+    /// A copy of the interface with only that stanza, as from
+    /// [`Interface::for_family`], or `None` if the interface does not exist or
+    /// has no stanza for `family`.
+    pub fn get_interface_family(&self, name: &str, family: Family) -> Option<Interface> {
+        self.get_interface(name)?.for_family(family)
+    }
+
     /// Retrieves a mutable reference to an interface by name.
     ///
     /// # Arguments
@@ -122,283 +740,5733 @@ impl NetworkInterfaces {
         self.interfaces.get_mut(name)
     }
 
-    /// Adds or updates an interface in the collection.
+    /// Retrieves the interfaces whose `hwaddress` is `mac`, in any of their
+    /// stanzas.
+    ///
+    /// Values match regardless of letter case, notation, or `ether` prefix;
+    /// values that are not MAC addresses never match. More than one interface
+    /// can be returned, since cloned configurations often share an address (see
+    /// [`duplicate_hwaddresses`](NetworkInterfaces::duplicate_hwaddresses)).
     ///
     /// # Arguments
     ///
-    /// * `iface` - The `Interface` to add or update.
-    pub fn add_interface(&mut self, iface: Interface) {
-        self.interfaces.insert(iface.name.clone(), iface);
+    /// * `mac` - The MAC address to look for.
+    ///
+    /// # Returns
+    ///
+    /// The matching interfaces in natural order of their names.
+    pub fn get_interface_by_mac(&self, mac: &MacAddr) -> Vec<&Interface> {
+        let classify = if self.parser_options.strict_keys {
+            InterfaceOption::from_key_value_strict
+        } else {
+            InterfaceOption::from_key_value
+        };
+        self.by_name()
+            .filter(|iface| {
+                iface.stanzas().flat_map(|stanza| stanza.options).any(|(key, value)| {
+                    matches!(
+                        classify(key, value),
+                        InterfaceOption::HwAddress(hwaddress) if hwaddress.mac() == Some(*mac)
+                    )
+                })
+            })
+            .collect()
     }
 
-    /// Deletes an interface by name.
+    /// Retrieves the interfaces whose `hwaddress` is the MAC address in `mac`;
+    /// see [`get_interface_by_mac`](NetworkInterfaces::get_interface_by_mac).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `MacAddrParseError` if `mac` is not a MAC address.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("eth0")
+    ///         .with_option("hwaddress", "ether 52:54:00:AB:CD:01")
+    ///         .build(),
+    /// ]);
+    /// let found = net_ifaces.get_interface_by_mac_str("52-54-00-ab-cd-01").unwrap();
+    /// assert_eq!(found[0].name, "eth0");
+    /// assert!(net_ifaces.get_interface_by_mac_str("52:54:00").is_err());
+    /// ```
+    pub fn get_interface_by_mac_str(
+        &self,
+        mac: &str,
+    ) -> Result<Vec<&Interface>, MacAddrParseError> {
+        Ok(self.get_interface_by_mac(&mac.parse()?))
+    }
+
+    /// Finds the interfaces with the tag `key` set to `value`, in name order.
+    ///
+    /// Tags come from magic comments such as `# tags: tenant=acme`; see
+    /// [`Interface::tags`].
     ///
     /// # Arguments
     ///
-    /// * `name` - The name of the interface to delete.
-    pub fn delete_interface(&mut self, name: &str) {
-        self.interfaces.remove(name);
+    /// * `key` - The tag key (e.g., `"tenant"`).
+    /// * `value` - The value the tag must have (e.g., `"acme"`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("swp1").with_tag("tenant", "acme").build(),
+    ///     Interface::builder("swp2").with_tag("tenant", "globex").build(),
+    /// ]);
+    /// let found = net_ifaces.find_by_tag("tenant", "acme");
+    /// assert_eq!(found.len(), 1);
+    /// assert_eq!(found[0].name, "swp1");
+    /// ```
+    pub fn find_by_tag(&self, key: &str, value: &str) -> Vec<&Interface> {
+        self.by_name()
+            .filter(|iface| iface.tags.get(key).is_some_and(|v| v == value))
+            .collect()
     }
 
-    /// Returns the number of interfaces.
-    pub fn len(&self) -> usize {
-        self.interfaces.len()
+    /// Returns the tags of an interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface.
+    ///
+    /// # Returns
+    ///
+    /// The tags of the interface, which are empty if it has none, or `None`
+    /// if there is no such interface.
+    pub fn tags_of(&self, name: &str) -> Option<&BTreeMap<String, String>> {
+        self.get_interface(name).map(|iface| &iface.tags)
     }
 
-    /// Checks if the collection is empty.
-    pub fn is_empty(&self) -> bool {
-        self.interfaces.is_empty()
+    /// Adds or updates an interface in the collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `iface` - The `Interface` to add or update.
+    pub fn add_interface(&mut self, iface: Interface) {
+        let name = IfaceName::from(iface.name.as_str());
+        self.interfaces.insert(name, iface);
     }
 
-    /// Finds the next unused VLAN ID within a specified range.
+    /// Adds an interface, failing if one with the same name already exists
+    /// or its name is not a valid interface name.
     ///
     /// # Arguments
     ///
-    /// * `start` - The starting VLAN ID (inclusive).
-    /// * `end` - The ending VLAN ID (inclusive).
+    /// * `iface` - The `Interface` to add.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// * `Option<u16>` - The next unused VLAN ID, or `None` if all are used.
-    pub fn next_unused_vlan_in_range(&self, start: u16, end: u16) -> Option<u16> {
-        for vlan_id in start..=end {
-            let vlan_name = format!("vlan{}", vlan_id);
-            if !self.interfaces.contains_key(&vlan_name) {
-                return Some(vlan_id);
-            }
+    /// Returns [`NetworkInterfacesError::InvalidName`] if the name fails
+    /// [`validate_interface_name`], or [`NetworkInterfacesError::Conflict`] if
+    /// an interface with the same name exists. In both cases the collection
+    /// is left unchanged.
+    pub fn try_add_interface(&mut self, iface: Interface) -> Result<(), NetworkInterfacesError> {
+        validate_interface_name(&iface.name)?;
+        let name = IfaceName::from(iface.name.as_str());
+        if self.interfaces.contains_key(&name) {
+            return Err(NetworkInterfacesError::Conflict { name: name.0 });
         }
-        None // All VLAN IDs in the specified range are used
+        self.interfaces.insert(name, iface);
+        Ok(())
     }
 
-    /// Retrieves the VLAN associated with an existing VNI interface.
+    /// Deletes an interface by name.
+    ///
+    /// The file the interface was saved to no longer holds it on the next
+    /// [`save`](NetworkInterfaces::save). Other interfaces referring to it are
+    /// left alone; use
+    /// [`delete_interface_cascade`](NetworkInterfaces::delete_interface_cascade)
+    /// to remove those references too.
     ///
     /// # Arguments
     ///
-    /// * `vni_id` - The VNI ID to search for (e.g., `1347682`).
+    /// * `name` - The name of the interface to delete.
     ///
     /// # Returns
     ///
-    /// * `Option<u16>` - The VLAN ID specified in the `bridge-access` option, or `None` if
-    ///   the interface does not exist or the option is not present.
-    pub fn get_existing_vni_vlan(&self, vni_id: u32) -> Option<u16> {
-        let vni_name = format!("vni{}", vni_id);
-
-        // Check if the interface exists
-        let interface = self.interfaces.get(&vni_name)?;
-
-        // Look for the `bridge-access` option
-        for (key, value) in &interface.options {
-            if key == "bridge-access" {
-                // Try to parse the value as a u16
-                if let Ok(vlan_id) = value.parse::<u16>() {
-                    return Some(vlan_id);
-                }
-            }
-        }
-
-        None // No `bridge-access` option or invalid value
+    /// The removed `Interface`, or `None` if no interface has that name.
+    pub fn delete_interface(&mut self, name: &str) -> Option<Interface> {
+        let iface = self.interfaces.shift_remove(name)?;
+        let name = IfaceName::from(name);
+        self.files.origins.remove(&name);
+        self.files.stanza_origins.remove(&name);
+        Some(iface)
     }
 
-    /// Retrieves all port names that have a `bridge-access` option defined.
+    /// Deletes an interface by name, failing if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface to delete.
     ///
     /// # Returns
     ///
-    /// A `Vec<String>` containing the names of the ports with `bridge-access` defined.
-    pub fn get_bridge_interfaces(&self) -> Vec<String> {
-        self.interfaces
-            .iter()
-            .filter_map(|(name, iface)| {
-                for (key, _) in &iface.options {
-                    if key == "bridge-access" {
-                        return Some(name.clone());
-                    }
-                }
-                None
+    /// The removed `Interface`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::NotFound`] if no interface has that name.
+    pub fn try_delete_interface(&mut self, name: &str) -> Result<Interface, NetworkInterfacesError> {
+        self.delete_interface(name)
+            .ok_or_else(|| NetworkInterfacesError::NotFound {
+                name: name.to_string(),
             })
-            .collect()
     }
 
-    /// Saves changes back to the `interfaces(5)` file.
+    /// Disables an interface by commenting out its stanza.
+    ///
+    /// The interface stays in the collection with
+    /// [`disabled`](Interface::disabled) set, and every line of its stanza is
+    /// written prefixed with the
+    /// [disabled marker](ParserOptions::disabled_marker), so `ifup` ignores
+    /// it. Loading the file reads the stanza back as a disabled interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface to disable.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the interface was enabled, so callers can skip saving
+    /// otherwise.
     ///
     /// # Errors
     ///
-    /// Returns a `NetworkInterfacesError` if the file cannot be written or has been modified on disk.
-    pub fn save(&mut self) -> Result<(), NetworkInterfacesError> {
-        let path = match &self.path {
-            Some(p) => p.clone(),
+    /// Returns [`NetworkInterfacesError::NotFound`] if no interface has that name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("eth0").with_auto(true).with_method("dhcp").build(),
+    /// ]);
+    /// net_ifaces.disable_interface("eth0").unwrap();
+    /// assert_eq!(
+    ///     net_ifaces.to_string(),
+    ///     "#disabled# auto eth0\n#disabled# iface eth0 dhcp\n"
+    /// );
+    /// ```
+    pub fn disable_interface(&mut self, name: &str) -> Result<bool, NetworkInterfacesError> {
+        self.set_disabled(name, true)
+    }
+
+    /// Enables an interface disabled with
+    /// [`disable_interface`](NetworkInterfaces::disable_interface), so its
+    /// stanza is written without the marker again.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface to enable.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the interface was disabled, so callers can skip saving
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::NotFound`] if no interface has that name.
+    pub fn enable_interface(&mut self, name: &str) -> Result<bool, NetworkInterfacesError> {
+        self.set_disabled(name, false)
+    }
+
+    /// Sets the [`disabled`](Interface::disabled) flag of an interface,
+    /// returning whether it changed.
+    fn set_disabled(&mut self, name: &str, disabled: bool) -> Result<bool, NetworkInterfacesError> {
+        let iface = self
+            .get_interface_mut(name)
+            .ok_or_else(|| NetworkInterfacesError::NotFound {
+                name: name.to_string(),
+            })?;
+        Ok(std::mem::replace(&mut iface.disabled, disabled) != disabled)
+    }
+
+    /// Returns the number of interfaces.
+    ///
+    /// This includes placeholders for names that only appear on `auto` or
+    /// `allow-*` lines; see [`Interface::is_declared`] and
+    /// [`declared_len`](NetworkInterfaces::declared_len).
+    pub fn len(&self) -> usize {
+        self.interfaces.len()
+    }
+
+    /// Returns the number of declared interfaces, excluding placeholders.
+    ///
+    /// See [`Interface::is_declared`].
+    pub fn declared_len(&self) -> usize {
+        self.interfaces.values().filter(|iface| iface.is_declared()).count()
+    }
+
+    /// Checks if the collection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.interfaces.is_empty()
+    }
+
+    /// Sets an option on every interface matching `selector`.
+    ///
+    /// The first line with a matching key is replaced in place, further lines
+    /// with the same key are dropped, and the option is appended to interfaces
+    /// that do not have it yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - Chooses the interfaces to update.
+    /// * `key` - The option key, e.g. `mtu`.
+    /// * `value` - The value to set.
+    ///
+    /// # Returns
+    ///
+    /// The number of interfaces whose options changed. Interfaces that already
+    /// had exactly this one `key value` line are not counted.
+    ///
+    /// The collection keeps no dirty flag: which files the update changed is
+    /// reported by [`plan_save`](NetworkInterfaces::plan_save), which compares
+    /// them with what is on disk.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// let is_port = |iface: &interface_rs::interface::Interface| iface.name.starts_with("swp");
+    /// let changed = net_ifaces.set_option_on(is_port, "mtu", "9216");
+    /// println!("Updated {} ports", changed);
+    /// net_ifaces.save().unwrap();
+    /// ```
+    pub fn set_option_on(
+        &mut self,
+        selector: impl Fn(&Interface) -> bool,
+        key: &str,
+        value: &str,
+    ) -> usize {
+        let strict_keys = self.parser_options.strict_keys;
+        let mut changed = 0;
+        for iface in self.interfaces.values_mut().filter(|iface| selector(iface)) {
+            if set_single(&mut iface.options, key, value, strict_keys) {
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Removes every line with the given option key from the interfaces
+    /// matching `selector`.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - Chooses the interfaces to update.
+    /// * `key` - The option key to remove.
+    ///
+    /// # Returns
+    ///
+    /// The number of interfaces that had the option. As with
+    /// [`set_option_on`](NetworkInterfaces::set_option_on), the files changed
+    /// are reported by [`plan_save`](NetworkInterfaces::plan_save).
+    pub fn remove_option_on(&mut self, selector: impl Fn(&Interface) -> bool, key: &str) -> usize {
+        let strict_keys = self.parser_options.strict_keys;
+        let mut changed = 0;
+        for iface in self.interfaces.values_mut().filter(|iface| selector(iface)) {
+            if remove_matching(&mut iface.options, key, strict_keys) > 0 {
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Finds the next unused VLAN ID within a specified range.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting VLAN ID (inclusive).
+    /// * `end` - The ending VLAN ID (inclusive).
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u16>` - The next unused VLAN ID, or `None` if all are used.
+    pub fn next_unused_vlan_in_range(&self, start: u16, end: u16) -> Option<u16> {
+        for vlan_id in start..=end {
+            if !self.interfaces.contains_key(format!("vlan{}", vlan_id).as_str()) {
+                return Some(vlan_id);
+            }
+        }
+        None // All VLAN IDs in the specified range are used
+    }
+
+    /// Retrieves the VLAN associated with an existing VNI interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `vni_id` - The VNI ID to search for (e.g., `1347682`).
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u16>` - The VLAN ID specified in the `bridge-access` option, or `None` if
+    ///   the interface does not exist or the option is not present.
+    pub fn get_existing_vni_vlan(&self, vni_id: u32) -> Option<u16> {
+        // Check if the interface exists
+        let interface = self.interfaces.get(format!("vni{}", vni_id).as_str())?;
+
+        // Look for the `bridge-access` option
+        for (key, value) in &interface.options {
+            if keys_match(key, "bridge-access", self.parser_options.strict_keys) {
+                // Try to parse the value as a u16
+                if let Ok(vlan_id) = value.parse::<u16>() {
+                    return Some(vlan_id);
+                }
+            }
+        }
+
+        None // No `bridge-access` option or invalid value
+    }
+
+    /// Adds a VLAN to the `bridge-vids` of a VLAN-aware bridge.
+    ///
+    /// The VLAN is merged into the existing ranges, and the option is
+    /// rewritten in canonical form: adding `1005` to `1000-1004 1100` yields
+    /// `1000-1005 1100`. The option is created if the bridge has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `bridge` - The name of the bridge.
+    /// * `vid` - The VLAN id to add (1-4094).
+    ///
+    /// # Returns
+    ///
+    /// `true` if the VLAN was added, `false` if the bridge already carried it.
+    ///
+    /// # Errors
+    ///
+    /// - [`NetworkInterfacesError::NotFound`] if there is no such interface.
+    /// - [`NetworkInterfacesError::WrongKind`] if it is not a VLAN-aware bridge.
+    /// - [`NetworkInterfacesError::OptionRange`] if `vid` is not a valid VLAN id.
+    /// - [`NetworkInterfacesError::OptionParse`] if the existing `bridge-vids`
+    ///   value cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// if net_ifaces.add_vid_to_bridge("br_default", 1005).unwrap() {
+    ///     net_ifaces.save().unwrap();
+    /// }
+    /// ```
+    pub fn add_vid_to_bridge(
+        &mut self,
+        bridge: &str,
+        vid: u16,
+    ) -> Result<bool, NetworkInterfacesError> {
+        if !(VlanRanges::MIN..=VlanRanges::MAX).contains(&vid) {
+            return Err(NetworkInterfacesError::OptionRange(OptionRangeError {
+                key: "bridge-vids".to_string(),
+                value: u64::from(vid),
+                min: u64::from(VlanRanges::MIN),
+                max: u64::from(VlanRanges::MAX),
+            }));
+        }
+        let strict_keys = self.parser_options.strict_keys;
+        let iface = self.vlan_aware_bridge_mut(bridge)?;
+        let position = iface
+            .options
+            .iter()
+            .position(|(key, _)| keys_match(key, "bridge-vids", strict_keys));
+        let mut vids = match position {
+            Some(i) => {
+                let (key, value) = &iface.options[i];
+                value.parse::<VlanRanges>().map_err(|_| {
+                    NetworkInterfacesError::OptionParse(OptionParseError {
+                        key: key.to_string(),
+                        value: value.clone(),
+                    })
+                })?
+            }
+            None => VlanRanges::new(),
+        };
+        if !vids.insert(vid) {
+            return Ok(false);
+        }
+        match position {
+            Some(i) => iface.options[i].1 = vids.to_string(),
+            None => iface
+                .options
+                .push(("bridge-vids".into(), vids.to_string())),
+        }
+        Ok(true)
+    }
+
+    /// Removes a VLAN from the `bridge-vids` of a VLAN-aware bridge.
+    ///
+    /// The range containing the VLAN is split as needed: removing `1002` from
+    /// `1000-1004` yields `1000-1001 1003-1004`. If no VLANs remain, the
+    /// `bridge-vids` option is removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `bridge` - The name of the bridge.
+    /// * `vid` - The VLAN id to remove.
+    /// * `clear_access` - Whether to also remove `bridge-access <vid>` from the
+    ///   interfaces listed in the bridge's `bridge-ports`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if anything was changed.
+    ///
+    /// # Errors
+    ///
+    /// - [`NetworkInterfacesError::NotFound`] if there is no such interface.
+    /// - [`NetworkInterfacesError::WrongKind`] if it is not a VLAN-aware bridge.
+    /// - [`NetworkInterfacesError::OptionParse`] if the existing `bridge-vids`
+    ///   value cannot be parsed.
+    pub fn remove_vid_from_bridge(
+        &mut self,
+        bridge: &str,
+        vid: u16,
+        clear_access: bool,
+    ) -> Result<bool, NetworkInterfacesError> {
+        let strict_keys = self.parser_options.strict_keys;
+        let iface = self.vlan_aware_bridge_mut(bridge)?;
+        let mut changed = false;
+        if let Some(i) = iface
+            .options
+            .iter()
+            .position(|(key, _)| keys_match(key, "bridge-vids", strict_keys))
+        {
+            let (key, value) = &iface.options[i];
+            let mut vids = value.parse::<VlanRanges>().map_err(|_| {
+                NetworkInterfacesError::OptionParse(OptionParseError {
+                    key: key.to_string(),
+                    value: value.clone(),
+                })
+            })?;
+            if vids.remove(vid) {
+                changed = true;
+                if vids.is_empty() {
+                    iface.options.remove(i);
+                } else {
+                    iface.options[i].1 = vids.to_string();
+                }
+            }
+        }
+
+        if clear_access {
+            let ports: Vec<String> = graph::interface_dependencies(iface, strict_keys)
+                .into_iter()
+                .filter(|dep| dep.kind == graph::DependencyKind::BridgePort)
+                .map(|dep| dep.to)
+                .collect();
+            let access = vid.to_string();
+            for port in ports {
+                if let Some(port) = self.interfaces.get_mut(port.as_str()) {
+                    let before = port.options.len();
+                    port.options.retain(|(key, value)| {
+                        !(keys_match(key, "bridge-access", strict_keys) && value.trim() == access)
+                    });
+                    changed |= port.options.len() != before;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Looks up an interface that must be a VLAN-aware bridge.
+    fn vlan_aware_bridge_mut(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut Interface, NetworkInterfacesError> {
+        let strict_keys = self.parser_options.strict_keys;
+        let iface = self
+            .interfaces
+            .get_mut(name)
+            .ok_or_else(|| NetworkInterfacesError::NotFound {
+                name: name.to_string(),
+            })?;
+        let vlan_aware = iface.options.iter().any(|(key, value)| {
+            keys_match(key, "bridge-vlan-aware", strict_keys)
+                && matches!(value.as_str(), "yes" | "on" | "1" | "true")
+        });
+        if !vlan_aware {
+            return Err(NetworkInterfacesError::WrongKind {
+                name: name.to_string(),
+                expected: "VLAN-aware bridge",
+            });
+        }
+        Ok(iface)
+    }
+
+    /// Adds a port to the `bridge-ports` of a bridge, if it is not listed yet.
+    ///
+    /// The port is appended after the existing ports, replacing a `none`
+    /// placeholder; the option is created if the bridge has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `bridge` - The name of the bridge.
+    /// * `port` - The name of the port to add.
+    /// * `access` - If set, also set the port's `bridge-access` to this VLAN.
+    ///   The port must then be defined in the collection.
+    ///
+    /// # Returns
+    ///
+    /// `true` if anything was changed, so callers can skip saving otherwise.
+    ///
+    /// # Errors
+    ///
+    /// - [`NetworkInterfacesError::NotFound`] if the bridge does not exist, or
+    ///   `access` is set and the port does not exist.
+    /// - [`NetworkInterfacesError::WrongKind`] if `bridge` is not a bridge.
+    /// - [`NetworkInterfacesError::OptionRange`] if `access` is not a valid
+    ///   VLAN id.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// if net_ifaces.add_port_to_bridge("br_default", "swp3", Some(100)).unwrap() {
+    ///     net_ifaces.save().unwrap();
+    /// }
+    /// ```
+    pub fn add_port_to_bridge(
+        &mut self,
+        bridge: &str,
+        port: &str,
+        access: Option<u16>,
+    ) -> Result<bool, NetworkInterfacesError> {
+        let strict_keys = self.parser_options.strict_keys;
+        if let Some(vid) = access {
+            if !(VlanRanges::MIN..=VlanRanges::MAX).contains(&vid) {
+                return Err(NetworkInterfacesError::OptionRange(OptionRangeError {
+                    key: "bridge-access".to_string(),
+                    value: u64::from(vid),
+                    min: u64::from(VlanRanges::MIN),
+                    max: u64::from(VlanRanges::MAX),
+                }));
+            }
+            if self.get_interface(port).is_none() {
+                return Err(NetworkInterfacesError::NotFound {
+                    name: port.to_string(),
+                });
+            }
+        }
+
+        let iface = self.bridge_mut(bridge)?;
+        let mut changed = false;
+        match iface
+            .options
+            .iter()
+            .position(|(key, _)| keys_match(key, "bridge-ports", strict_keys))
+        {
+            Some(i) => {
+                let mut ports: Vec<&str> = iface.options[i]
+                    .1
+                    .split_whitespace()
+                    .filter(|p| *p != "none")
+                    .collect();
+                if !ports.contains(&port) {
+                    ports.push(port);
+                    iface.options[i].1 = ports.join(" ");
+                    changed = true;
+                }
+            }
             None => {
-                return Err(NetworkInterfacesError::Other(
-                    "No file path specified".to_string(),
-                ))
+                iface
+                    .options
+                    .push(("bridge-ports".into(), port.to_string()));
+                changed = true;
+            }
+        }
+
+        if let Some(vid) = access {
+            let port = self
+                .interfaces
+                .get_mut(port)
+                .expect("port existence was checked");
+            let access = vid.to_string();
+            changed |= set_single(&mut port.options, "bridge-access", &access, strict_keys);
+        }
+        Ok(changed)
+    }
+
+    /// Removes a port from the `bridge-ports` of a bridge.
+    ///
+    /// The order of the remaining ports is kept. If no ports remain, the
+    /// option is handled according to `when_empty`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bridge` - The name of the bridge.
+    /// * `port` - The name of the port to remove.
+    /// * `when_empty` - What to do with `bridge-ports` once it is empty.
+    /// * `clear_access` - Whether to also remove the port's `bridge-access`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if anything was changed, so callers can skip saving otherwise.
+    ///
+    /// # Errors
+    ///
+    /// - [`NetworkInterfacesError::NotFound`] if the bridge does not exist.
+    /// - [`NetworkInterfacesError::WrongKind`] if `bridge` is not a bridge.
+    pub fn remove_port_from_bridge(
+        &mut self,
+        bridge: &str,
+        port: &str,
+        when_empty: EmptyListPolicy,
+        clear_access: bool,
+    ) -> Result<bool, NetworkInterfacesError> {
+        let strict_keys = self.parser_options.strict_keys;
+        let iface = self.bridge_mut(bridge)?;
+        let mut changed = false;
+        if let Some(i) = iface
+            .options
+            .iter()
+            .position(|(key, _)| keys_match(key, "bridge-ports", strict_keys))
+        {
+            let ports: Vec<&str> = iface.options[i].1.split_whitespace().collect();
+            if ports.contains(&port) {
+                let remaining: Vec<&str> = ports
+                    .into_iter()
+                    .filter(|p| *p != port && *p != "none")
+                    .collect();
+                if !remaining.is_empty() {
+                    iface.options[i].1 = remaining.join(" ");
+                } else if when_empty.removes(iface, "bridge-ports", strict_keys) {
+                    iface.options.remove(i);
+                } else {
+                    iface.options[i].1 = "none".to_string();
+                }
+                changed = true;
+            }
+        }
+
+        if clear_access {
+            if let Some(port) = self.interfaces.get_mut(port) {
+                let before = port.options.len();
+                port.options
+                    .retain(|(key, _)| !keys_match(key, "bridge-access", strict_keys));
+                changed |= port.options.len() != before;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Sets, changes, or removes the VRF an interface is enslaved to.
+    ///
+    /// Equivalent to [`set_vrf_with`](NetworkInterfaces::set_vrf_with) with
+    /// default [`VrfOptions`].
+    ///
+    /// # Errors
+    ///
+    /// See [`set_vrf_with`](NetworkInterfaces::set_vrf_with).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.set_vrf("eth0", Some("mgmt")).unwrap();
+    /// net_ifaces.set_vrf("swp1", None).unwrap();
+    /// ```
+    pub fn set_vrf(
+        &mut self,
+        iface: &str,
+        vrf: Option<&str>,
+    ) -> Result<(), NetworkInterfacesError> {
+        self.set_vrf_with(iface, vrf, &VrfOptions::default())
+            .map(|_| ())
+    }
+
+    /// Sets, changes, or removes the VRF an interface is enslaved to.
+    ///
+    /// All `vrf` lines of the interface are replaced by a single one (or
+    /// removed when `vrf` is `None`), so duplicates cannot accumulate. The
+    /// new line takes the place of the first existing one.
+    ///
+    /// # Arguments
+    ///
+    /// * `iface` - The name of the interface to move.
+    /// * `vrf` - The VRF to enslave it to, or `None` to remove it from its VRF.
+    /// * `options` - See [`VrfOptions`].
+    ///
+    /// # Returns
+    ///
+    /// The name of the VRF the interface was moved out of, if no other
+    /// interface is a member of it anymore.
+    ///
+    /// # Errors
+    ///
+    /// - [`NetworkInterfacesError::NotFound`] if `iface` does not exist, or if
+    ///   `vrf` does not exist and `allow_missing_vrf` is not set.
+    /// - [`NetworkInterfacesError::WrongKind`] if `vrf` exists but has no
+    ///   `vrf-table`.
+    pub fn set_vrf_with(
+        &mut self,
+        iface: &str,
+        vrf: Option<&str>,
+        options: &VrfOptions,
+    ) -> Result<Option<String>, NetworkInterfacesError> {
+        let strict_keys = self.parser_options.strict_keys;
+        if let Some(vrf) = vrf {
+            match self.interfaces.get(vrf) {
+                Some(device) => {
+                    let has_table = device
+                        .options
+                        .iter()
+                        .any(|(key, _)| keys_match(key, "vrf-table", strict_keys));
+                    if !has_table {
+                        return Err(NetworkInterfacesError::WrongKind {
+                            name: vrf.to_string(),
+                            expected: "VRF",
+                        });
+                    }
+                }
+                None if options.allow_missing_vrf => {}
+                None => {
+                    return Err(NetworkInterfacesError::NotFound {
+                        name: vrf.to_string(),
+                    })
+                }
+            }
+        }
+
+        let member = self
+            .interfaces
+            .get_mut(iface)
+            .ok_or_else(|| NetworkInterfacesError::NotFound {
+                name: iface.to_string(),
+            })?;
+        let first = member
+            .options
+            .iter()
+            .position(|(key, _)| keys_match(key, "vrf", strict_keys));
+        let previous = first.map(|i| member.options[i].1.trim().to_string());
+        member
+            .options
+            .retain(|(key, _)| !keys_match(key, "vrf", strict_keys));
+        if let Some(vrf) = vrf {
+            let at = first.unwrap_or(member.options.len());
+            member.options.insert(at, ("vrf".into(), vrf.to_string()));
+        }
+
+        let emptied = previous.filter(|previous| {
+            Some(previous.as_str()) != vrf
+                && !self.interfaces.values().any(|other| {
+                    other.options.iter().any(|(key, value)| {
+                        keys_match(key, "vrf", strict_keys) && value.trim() == previous
+                    })
+                })
+        });
+        Ok(emptied)
+    }
+
+    /// Maps an EVPN VNI to a VLAN on a VLAN-aware bridge in one call.
+    ///
+    /// This composes the individual steps:
+    ///
+    /// 1. The VLAN is the `bridge-access` of an existing `vni<N>` interface
+    ///    ([`get_existing_vni_vlan`](NetworkInterfaces::get_existing_vni_vlan)),
+    ///    or else the first id in `vlan_range` without a `vlan<X>` interface
+    ///    ([`next_unused_vlan_in_range`](NetworkInterfaces::next_unused_vlan_in_range)).
+    /// 2. `vni<N>` is created with `vxlan-id` and `bridge-access` unless it
+    ///    exists.
+    /// 3. The SVI `vlan<X>` is created with `vlan-id` and `vlan-raw-device`
+    ///    unless it exists, in which case it is reused as is.
+    /// 4. The VLAN is added to the bridge's `bridge-vids` and the VNI to its
+    ///    `bridge-ports`.
+    ///
+    /// Every step is idempotent, so provisioning an existing VNI again
+    /// changes nothing (or repairs a missing bridge entry). If any step
+    /// fails, all changes made by the call are rolled back.
+    ///
+    /// # Arguments
+    ///
+    /// * `vni` - The VXLAN network identifier (1-16777215).
+    /// * `vlan_range` - The range to allocate a new VLAN from.
+    /// * `bridge` - The VLAN-aware bridge to attach the VNI to.
+    /// * `opts` - Settings for a newly created VNI interface.
+    ///
+    /// # Errors
+    ///
+    /// - [`NetworkInterfacesError::NotFound`] or
+    ///   [`NetworkInterfacesError::WrongKind`] if `bridge` is not an existing
+    ///   VLAN-aware bridge.
+    /// - [`NetworkInterfacesError::OptionRange`] if `vni` is out of range.
+    /// - [`NetworkInterfacesError::NoFreeVlan`] if a new VLAN is needed and
+    ///   every id in `vlan_range` is taken.
+    /// - [`NetworkInterfacesError::OptionParse`] if the bridge's `bridge-vids`
+    ///   cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::{NetworkInterfaces, VniOptions};
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// let provisioned = net_ifaces
+    ///     .provision_vni(100200, 200..=299, "br_default", VniOptions::default())
+    ///     .unwrap();
+    /// println!("VNI 100200 is VLAN {}", provisioned.vlan_id);
+    /// net_ifaces.save().unwrap();
+    /// ```
+    pub fn provision_vni(
+        &mut self,
+        vni: u32,
+        vlan_range: RangeInclusive<u16>,
+        bridge: &str,
+        opts: VniOptions,
+    ) -> Result<ProvisionedVni, NetworkInterfacesError> {
+        const MAX_VNI: u32 = 0x00ff_ffff;
+        if !(1..=MAX_VNI).contains(&vni) {
+            return Err(NetworkInterfacesError::OptionRange(OptionRangeError {
+                key: "vxlan-id".to_string(),
+                value: u64::from(vni),
+                min: 1,
+                max: u64::from(MAX_VNI),
+            }));
+        }
+        self.vlan_aware_bridge_mut(bridge)?;
+
+        let snapshot = self.interfaces.clone();
+        let result = self.provision_vni_steps(vni, vlan_range, bridge, opts);
+        if result.is_err() {
+            self.interfaces = snapshot;
+        }
+        result
+    }
+
+    /// Performs the steps of [`provision_vni`](NetworkInterfaces::provision_vni)
+    /// without rolling back on error.
+    fn provision_vni_steps(
+        &mut self,
+        vni: u32,
+        vlan_range: RangeInclusive<u16>,
+        bridge: &str,
+        opts: VniOptions,
+    ) -> Result<ProvisionedVni, NetworkInterfacesError> {
+        let strict_keys = self.parser_options.strict_keys;
+        let (start, end) = vlan_range.into_inner();
+        let vlan_id = match self.get_existing_vni_vlan(vni) {
+            Some(vlan_id) => vlan_id,
+            None => self
+                .next_unused_vlan_in_range(start, end)
+                .ok_or(NetworkInterfacesError::NoFreeVlan { start, end })?,
+        };
+        let vni_interface = format!("vni{}", vni);
+        let svi_interface = format!("vlan{}", vlan_id);
+        let mut created = Vec::new();
+
+        match self.interfaces.get_mut(vni_interface.as_str()) {
+            Some(existing) => {
+                let access = vlan_id.to_string();
+                set_single(&mut existing.options, "bridge-access", &access, strict_keys);
+            }
+            None => {
+                let mut builder = Interface::builder(vni_interface.as_str())
+                    .with_auto(true)
+                    .with_option("vxlan-id", vni.to_string())
+                    .with_option("bridge-access", vlan_id.to_string());
+                if let Some(ip) = opts.local_tunnel_ip {
+                    builder = builder.with_option("vxlan-local-tunnelip", ip);
+                }
+                if let Some(mtu) = opts.mtu {
+                    builder = builder.with_option("mtu", mtu.to_string());
+                }
+                self.add_interface(builder.build());
+                created.push(vni_interface.clone());
             }
+        }
+
+        if self.get_interface(&svi_interface).is_none() {
+            self.add_interface(
+                Interface::builder(svi_interface.as_str())
+                    .with_auto(true)
+                    .with_option("vlan-id", vlan_id.to_string())
+                    .with_option("vlan-raw-device", bridge)
+                    .build(),
+            );
+            created.push(svi_interface.clone());
+        }
+
+        self.add_vid_to_bridge(bridge, vlan_id)?;
+        self.add_port_to_bridge(bridge, &vni_interface, None)?;
+        Ok(ProvisionedVni {
+            vlan_id,
+            vni_interface,
+            svi_interface,
+            created,
+        })
+    }
+
+    /// Looks up an interface that must be a bridge.
+    fn bridge_mut(&mut self, name: &str) -> Result<&mut Interface, NetworkInterfacesError> {
+        let iface = self
+            .interfaces
+            .get_mut(name)
+            .ok_or_else(|| NetworkInterfacesError::NotFound {
+                name: name.to_string(),
+            })?;
+        if iface.kind() != InterfaceKind::Bridge {
+            return Err(NetworkInterfacesError::WrongKind {
+                name: name.to_string(),
+                expected: "bridge",
+            });
+        }
+        Ok(iface)
+    }
+
+    /// Retrieves all port names that have a `bridge-access` option defined.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<String>` containing the names of the ports with `bridge-access` defined,
+    /// in the order they were added.
+    pub fn get_bridge_interfaces(&self) -> Vec<String> {
+        self.interfaces
+            .values()
+            .filter_map(|iface| {
+                for (key, _) in &iface.options {
+                    if keys_match(key, "bridge-access", self.parser_options.strict_keys) {
+                        return Some(iface.name.clone());
+                    }
+                }
+                None
+            })
+            .collect()
+    }
+
+    /// Returns the slaves of a bond.
+    ///
+    /// The entries of the bond's `bond-slaves` option are resolved in order:
+    /// `none` is skipped, and an entry with `*` or `?` wildcards matches
+    /// every interface whose name fits it. Interfaces naming the bond with a
+    /// `bond-master` option follow. Slaves that are not defined in the
+    /// collection are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `bond` - The name of the bond.
+    ///
+    /// # Returns
+    ///
+    /// The slaves, each once, or an empty `Vec` if the bond is not defined.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("bond0").with_option("bond-slaves", "swp1*").build(),
+    ///     Interface::builder("swp1s0").build(),
+    ///     Interface::builder("swp1s1").build(),
+    ///     Interface::builder("swp2").with_option("bond-master", "bond0").build(),
+    ///     Interface::builder("swp3").build(),
+    /// ]);
+    /// let slaves: Vec<&str> =
+    ///     net_ifaces.slaves_of("bond0").iter().map(|i| i.name.as_str()).collect();
+    /// assert_eq!(slaves, ["swp1s0", "swp1s1", "swp2"]);
+    /// assert_eq!(net_ifaces.bond_of("swp1s1").unwrap().name, "bond0");
+    /// assert!(net_ifaces.bond_of("swp3").is_none());
+    /// ```
+    pub fn slaves_of(&self, bond: &str) -> Vec<&Interface> {
+        let strict_keys = self.parser_options.strict_keys;
+        let Some(bond_iface) = self.get_interface(bond) else {
+            return Vec::new();
+        };
+        let mut slaves: Vec<&Interface> = Vec::new();
+        for entry in graph::bond_slave_entries(bond_iface, strict_keys) {
+            if graph::is_glob(entry) {
+                slaves.extend(self.by_name().filter(|iface| {
+                    iface.name != bond && graph::slave_matches(entry, &iface.name)
+                }));
+            } else {
+                slaves.extend(self.get_interface(entry));
+            }
+        }
+        slaves.extend(
+            self.by_name()
+                .filter(|iface| graph::bond_master(iface, strict_keys) == Some(bond)),
+        );
+        let mut seen = HashSet::new();
+        slaves.retain(|iface| seen.insert(iface.name.as_str()));
+        slaves
+    }
+
+    /// Returns the bond a port belongs to.
+    ///
+    /// A `bond-master` option on the port decides; otherwise the first bond,
+    /// in natural order, whose `bond-slaves` lists the port or a glob
+    /// matching it is returned. See [`slaves_of`](NetworkInterfaces::slaves_of).
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - The name of the port, which need not be defined.
+    ///
+    /// # Returns
+    ///
+    /// The bond, or `None` if no defined bond claims the port.
+    pub fn bond_of(&self, port: &str) -> Option<&Interface> {
+        let strict_keys = self.parser_options.strict_keys;
+        let master = self
+            .get_interface(port)
+            .and_then(|iface| graph::bond_master(iface, strict_keys));
+        if let Some(master) = master {
+            return self.get_interface(master);
+        }
+        self.by_name().find(|iface| {
+            graph::bond_slave_entries(iface, strict_keys)
+                .into_iter()
+                .any(|entry| graph::slave_matches(entry, port))
+        })
+    }
+
+    /// Returns the interfaces that point at `name`: bridges listing it in
+    /// `bridge-ports`, bonds listing it in `bond-slaves`, VLANs using it as
+    /// their raw device (by `vlan-raw-device` or by a name like `eth0.100`),
+    /// its aliases (named like `eth0:1`), and members of it as a VRF (`vrf`).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface, which need not be defined.
+    ///
+    /// # Returns
+    ///
+    /// The referencing interfaces in natural order, each once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("br0").with_option("bridge-ports", "swp1").build(),
+    ///     Interface::builder("swp1.10").build(),
+    ///     Interface::builder("swp1").build(),
+    /// ]);
+    /// let names: Vec<&str> =
+    ///     net_ifaces.referenced_by("swp1").iter().map(|i| i.name.as_str()).collect();
+    /// assert_eq!(names, ["br0", "swp1.10"]);
+    /// ```
+    pub fn referenced_by(&self, name: &str) -> Vec<&Interface> {
+        let strict_keys = self.parser_options.strict_keys;
+        self.by_name()
+            .filter(|iface| {
+                graph::interface_dependencies(iface, strict_keys)
+                    .iter()
+                    .any(|dep| dep.to == name)
+            })
+            .collect()
+    }
+
+    /// Returns the interfaces stacked on `name`: VLANs using it as their raw
+    /// device, by `vlan-raw-device` or by a name like `eth0.100`, and its
+    /// aliases, named like `eth0:1`.
+    ///
+    /// Unlike [`referenced_by`](NetworkInterfaces::referenced_by), bridges,
+    /// bonds, and VRFs that `name` is a member of are not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the parent interface, which need not be defined.
+    ///
+    /// # Returns
+    ///
+    /// The child interfaces in natural order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("eth0").build(),
+    ///     Interface::builder("eth0:1").build(),
+    ///     Interface::builder("eth0.100").build(),
+    ///     Interface::builder("br0").with_option("bridge-ports", "eth0").build(),
+    /// ]);
+    /// let names: Vec<&str> =
+    ///     net_ifaces.children_of("eth0").iter().map(|i| i.name.as_str()).collect();
+    /// assert_eq!(names, ["eth0.100", "eth0:1"]);
+    /// ```
+    pub fn children_of(&self, name: &str) -> Vec<&Interface> {
+        let strict_keys = self.parser_options.strict_keys;
+        self.by_name()
+            .filter(|iface| {
+                graph::interface_dependencies(iface, strict_keys).iter().any(|dep| {
+                    dep.to == name
+                        && matches!(
+                            dep.kind,
+                            graph::DependencyKind::VlanRawDevice
+                                | graph::DependencyKind::AliasParent
+                        )
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the VLANs stacked on the device `name`, directly or through
+    /// other VLANs.
+    ///
+    /// A double-tagged (QinQ) VLAN such as `swp1.100.200` is stacked on the
+    /// outer VLAN `swp1.100`, so it is returned for both `swp1.100` and
+    /// `swp1`. VLANs are found by `vlan-raw-device` or by a dotted name, like
+    /// in [`children_of`](NetworkInterfaces::children_of).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the device, which need not be defined.
+    ///
+    /// # Returns
+    ///
+    /// The VLANs in natural order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("swp1.100").build(),
+    ///     Interface::builder("swp1.100.200").build(),
+    ///     Interface::builder("swp2.100").build(),
+    /// ]);
+    /// let names = |vlans: Vec<&Interface>| -> Vec<String> {
+    ///     vlans.iter().map(|vlan| vlan.name.clone()).collect()
+    /// };
+    /// assert_eq!(names(net_ifaces.vlans_on_device("swp1")), ["swp1.100", "swp1.100.200"]);
+    /// assert_eq!(names(net_ifaces.vlans_on_device("swp1.100")), ["swp1.100.200"]);
+    /// ```
+    pub fn vlans_on_device(&self, name: &str) -> Vec<&Interface> {
+        let strict_keys = self.parser_options.strict_keys;
+        let raw_devices: Vec<(&str, String)> = self
+            .interfaces
+            .values()
+            .flat_map(|iface| {
+                graph::interface_dependencies(iface, strict_keys)
+                    .into_iter()
+                    .filter(|dep| dep.kind == graph::DependencyKind::VlanRawDevice)
+                    .map(move |dep| (iface.name.as_str(), dep.to))
+            })
+            .collect();
+        let mut stacked: HashSet<&str> = HashSet::new();
+        let mut devices = vec![name];
+        while let Some(device) = devices.pop() {
+            for (vlan, raw_device) in &raw_devices {
+                if raw_device == device && stacked.insert(vlan) {
+                    devices.push(vlan);
+                }
+            }
+        }
+        self.by_name()
+            .filter(|iface| stacked.contains(iface.name.as_str()))
+            .collect()
+    }
+
+    /// Returns the interfaces that nothing brings up or points at, as
+    /// candidates for cleanup.
+    ///
+    /// An interface is an orphan if all of the following hold:
+    ///
+    /// - it is not marked `auto`;
+    /// - it has no `allow-*` class, such as `allow-hotplug`;
+    /// - no other interface [references](NetworkInterfaces::referenced_by)
+    ///   it through `bridge-ports`, `bond-slaves`, `vlan-raw-device` (or a
+    ///   VLAN name like `eth0.100`), an alias name like `eth0:1`, or `vrf`;
+    /// - it is not the loopback interface (named `lo` or using the
+    ///   `loopback` method).
+    ///
+    /// Disabled stanzas are judged the same way, and their references count.
+    ///
+    /// # Returns
+    ///
+    /// The orphaned interfaces in natural order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("lo").build(),
+    ///     Interface::builder("bond0").with_auto(true).with_option("bond-slaves", "swp1").build(),
+    ///     Interface::builder("swp1").build(),
+    ///     Interface::builder("swp2").build(),
+    /// ]);
+    /// let orphans: Vec<&str> = net_ifaces.orphans().iter().map(|i| i.name.as_str()).collect();
+    /// assert_eq!(orphans, ["swp2"]);
+    /// ```
+    pub fn orphans(&self) -> Vec<&Interface> {
+        let strict_keys = self.parser_options.strict_keys;
+        let referenced: HashSet<String> = self
+            .interfaces
+            .values()
+            .flat_map(|iface| graph::interface_dependencies(iface, strict_keys))
+            .map(|dep| dep.to)
+            .collect();
+        self.by_name()
+            .filter(|iface| {
+                !iface.auto
+                    && iface.allow.is_empty()
+                    && !referenced.contains(&iface.name)
+                    && iface.kind() != InterfaceKind::Loopback
+            })
+            .collect()
+    }
+
+    /// Returns every interface with at least one address inside `network/prefix`.
+    ///
+    /// Addresses are read with [`Interface::addresses`], so CIDR notation, a
+    /// dotted netmask after the slash and a separate `netmask` option are all
+    /// understood. IPv4 and IPv6 networks are supported; addresses of the other
+    /// family never match. Disabled stanzas are included.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Any address inside the network to search.
+    /// * `prefix` - The prefix length of the network.
+    /// * `vrf` - If given, only interfaces enslaved to this VRF with a `vrf`
+    ///   option, and the VRF device itself, are considered.
+    ///
+    /// # Returns
+    ///
+    /// The matching interfaces in natural order of their names.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("eth0").with_option("address", "10.0.0.1/24").build(),
+    ///     Interface::builder("eth1").with_option("address", "192.168.1.1/24").build(),
+    /// ]);
+    /// let found = net_ifaces.interfaces_in_subnet("10.0.0.0".parse().unwrap(), 8, None);
+    /// assert_eq!(found.len(), 1);
+    /// assert_eq!(found[0].name, "eth0");
+    /// ```
+    pub fn interfaces_in_subnet(
+        &self,
+        network: IpAddr,
+        prefix: u8,
+        vrf: Option<&str>,
+    ) -> Vec<&Interface> {
+        self.by_name()
+            .filter(|iface| self.in_vrf(iface, vrf))
+            .filter(|iface| iface.addresses().iter().any(|addr| addr.in_subnet(network, prefix)))
+            .collect()
+    }
+
+    /// Returns every configured address inside `network/prefix` with the
+    /// interface it is configured on.
+    ///
+    /// This takes the same arguments as
+    /// [`interfaces_in_subnet`](NetworkInterfaces::interfaces_in_subnet), but an
+    /// interface appears once for each of its matching addresses.
+    ///
+    /// # Returns
+    ///
+    /// `(interface, address)` pairs in natural order of the interface names,
+    /// and in file order within an interface.
+    pub fn addresses_in_subnet(
+        &self,
+        network: IpAddr,
+        prefix: u8,
+        vrf: Option<&str>,
+    ) -> Vec<(&Interface, Address)> {
+        self.by_name()
+            .filter(|iface| self.in_vrf(iface, vrf))
+            .flat_map(|iface| iface.addresses().into_iter().map(move |addr| (iface, addr)))
+            .filter(|(_, addr)| addr.in_subnet(network, prefix))
+            .collect()
+    }
+
+    /// Returns the lowest host address in `network/prefix` that is not in use.
+    ///
+    /// An address is in use if any interface configures it, as read by
+    /// [`Interface::addresses`], or if it is listed in `skip`. For IPv4 the
+    /// network and broadcast addresses are never returned, except in `/31`
+    /// and `/32` networks where every address is a host address. For IPv6 the
+    /// Subnet-Router anycast address (the network address itself) is never
+    /// returned, except in `/127` and `/128` networks, and the search is
+    /// limited to the first 256 addresses of the network, i.e. its first
+    /// `/120`.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - Any address inside the network to allocate from.
+    /// * `prefix` - The prefix length of the network.
+    /// * `skip` - Additional addresses that must not be returned, such as
+    ///   gateways configured elsewhere.
+    ///
+    /// # Returns
+    ///
+    /// The free address, or `None` if the searched range is exhausted or the
+    /// prefix is longer than the address family allows.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::Interface;
+    /// use std::net::IpAddr;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("lo").with_option("address", "10.0.0.1/32").build(),
+    /// ]);
+    /// let gateway: IpAddr = "10.0.0.2".parse().unwrap();
+    /// let free = net_ifaces.next_free_address("10.0.0.0".parse().unwrap(), 24, &[gateway]);
+    /// assert_eq!(free, Some("10.0.0.3".parse().unwrap()));
+    /// ```
+    pub fn next_free_address(
+        &self,
+        network: IpAddr,
+        prefix: u8,
+        skip: &[IpAddr],
+    ) -> Option<IpAddr> {
+        let used: HashSet<IpAddr> = self
+            .addresses_in_subnet(network, prefix, None)
+            .into_iter()
+            .map(|(_, addr)| addr.ip)
+            .chain(skip.iter().copied())
+            .collect();
+        match network {
+            IpAddr::V4(network) => {
+                let size = 1u64 << (32 - u32::from(prefix.min(32)));
+                let base = u64::from(u32::from(network)) & !(size - 1);
+                let hosts = if size > 2 { base + 1..base + size - 1 } else { base..base + size };
+                (prefix <= 32)
+                    .then_some(hosts)?
+                    .map(|ip| IpAddr::V4(Ipv4Addr::from(ip as u32)))
+                    .find(|ip| !used.contains(ip))
+            }
+            IpAddr::V6(network) => {
+                let size = 1u128.checked_shl(128 - u32::from(prefix.min(128))).unwrap_or(0);
+                let base = u128::from(network) & !size.wrapping_sub(1);
+                let first = if size > 2 || size == 0 { base + 1 } else { base };
+                let last = base + size.wrapping_sub(1).min(255);
+                (prefix <= 128)
+                    .then_some(first..=last)?
+                    .map(|ip| IpAddr::V6(Ipv6Addr::from(ip)))
+                    .find(|ip| !used.contains(ip))
+            }
+        }
+    }
+
+    /// Returns `true` if `iface` belongs to `vrf`, or if no VRF is given.
+    fn in_vrf(&self, iface: &Interface, vrf: Option<&str>) -> bool {
+        vrf.is_none_or(|vrf| {
+            iface.name == vrf
+                || iface.options.iter().any(|(key, value)| {
+                    keys_match(key, "vrf", self.parser_options.strict_keys) && value.trim() == vrf
+                })
+        })
+    }
+
+    /// Returns the comment lines written at the top of the file.
+    ///
+    /// These are the comments that precede the first stanza when the file is
+    /// loaded, including their leading `#`.
+    pub fn header_comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Replaces the comment lines written at the top of the file.
+    ///
+    /// Lines that do not start with `#` are prefixed with `# `; empty lines
+    /// become a bare `#`.
+    ///
+    /// # Arguments
+    ///
+    /// * `comments` - The new header, one entry per line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.set_header_comments(vec![
+    ///     "Generated by netd v1.2.3 - do not edit".to_string(),
+    /// ]);
+    /// assert_eq!(net_ifaces.header_comments(), ["# Generated by netd v1.2.3 - do not edit"]);
+    /// net_ifaces.save().unwrap();
+    /// ```
+    pub fn set_header_comments(&mut self, comments: Vec<String>) {
+        self.comments = comments.into_iter().map(comment_line).collect();
+    }
+
+    /// Appends a line to the comments written at the top of the file.
+    ///
+    /// The line is prefixed like in
+    /// [`set_header_comments`](NetworkInterfaces::set_header_comments).
+    ///
+    /// # Arguments
+    ///
+    /// * `comment` - The line to append.
+    pub fn add_header_comment(&mut self, comment: impl Into<String>) {
+        self.comments.push(comment_line(comment.into()));
+    }
+
+    /// Returns the style warnings found in the text of the files when they
+    /// were read.
+    pub(crate) fn style_warnings(&self) -> &[LintWarning] {
+        &self.files.style
+    }
+
+    /// Returns the `source` and `source-directory` lines, as written.
+    ///
+    /// Sources are written right after the header comments and before the
+    /// first stanza, in the order they were loaded or added.
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    /// Returns the source directives in typed form.
+    ///
+    /// Lines that cannot be parsed as a [`Source`] are skipped.
+    pub fn source_directives(&self) -> Vec<Source> {
+        self.sources
+            .iter()
+            .filter_map(|line| line.parse().ok())
+            .collect()
+    }
+
+    /// Appends a source directive unless an equivalent one is already present.
+    ///
+    /// Directives are compared in typed form, so `source  /a/*` and
+    /// `source /a/*` are the same directive.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The directive, e.g. `source /etc/network/interfaces.d/*`,
+    ///   or a [`Source`] converted with `to_string()`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the directive was added.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SourceParseError`] if `line` is not a single `source` or
+    /// `source-directory` directive with a path. Nothing is added then.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::source::Source;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.add_source(Source::glob("/etc/network/interfaces.d/*").to_string()).unwrap();
+    /// assert!(!net_ifaces.add_source("source /etc/network/interfaces.d/*").unwrap());
+    /// assert!(net_ifaces.add_source("iface eth0").is_err());
+    /// ```
+    pub fn add_source(&mut self, line: impl Into<String>) -> Result<bool, SourceParseError> {
+        let typed = line.into().parse::<Source>()?;
+        let exists = self
+            .sources
+            .iter()
+            .any(|existing| existing.parse::<Source>().ok().as_ref() == Some(&typed));
+        if exists {
+            return Ok(false);
+        }
+        self.sources.push(typed.to_string());
+        Ok(true)
+    }
+
+    /// Removes the source lines matching `predicate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Called with each line as written; return `true` to
+    ///   remove it.
+    ///
+    /// # Returns
+    ///
+    /// The number of lines removed.
+    pub fn remove_source(&mut self, predicate: impl Fn(&str) -> bool) -> usize {
+        let before = self.sources.len();
+        self.sources.retain(|line| !predicate(line));
+        before - self.sources.len()
+    }
+
+    /// Returns the options the collection was parsed with.
+    pub fn parser_options(&self) -> &ParserOptions {
+        &self.parser_options
+    }
+
+    /// Returns the options controlling how the collection is written.
+    pub fn write_options(&self) -> &WriteOptions {
+        &self.write_options
+    }
+
+    /// Sets the options controlling how the collection is written by
+    /// `Display` and [`save`](NetworkInterfaces::save).
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The [`WriteOptions`] to use.
+    pub fn set_write_options(&mut self, options: WriteOptions) {
+        self.write_options = options;
+    }
+
+    /// Installs a custom comparator used to order interfaces when writing.
+    ///
+    /// This is shorthand for setting [`WriteOptions::sort`] to
+    /// [`SortOrder::Custom`], and applies to `Display`, [`save`](NetworkInterfaces::save)
+    /// and [`sorted_iter`](NetworkInterfaces::sorted_iter).
+    ///
+    /// # Arguments
+    ///
+    /// * `compare` - A function ordering two interfaces.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::helper::sort::natural;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// // Loopback first, then everything else by name
+    /// net_ifaces.set_sort(Box::new(|a, b| {
+    ///     (a.name != "lo")
+    ///         .cmp(&(b.name != "lo"))
+    ///         .then_with(|| natural(&a.name, &b.name))
+    /// }));
+    /// ```
+    pub fn set_sort(&mut self, compare: Box<CompareFn>) {
+        self.write_options.sort = SortOrder::Custom(Comparator::from(compare));
+    }
+
+    /// Validates the configuration of every interface in the collection.
+    ///
+    /// Validation never modifies the collection; it returns a report of all
+    /// findings, ordered by interface name. Currently checked:
+    ///
+    /// - methods not defined for the interface's family (`method-family-mismatch`,
+    ///   warning), see [`Method::valid_for`](crate::interface::Method::valid_for);
+    /// - DHCP client options on interfaces whose method does not use DHCP
+    ///   (`dhcp-option-mismatch`, warning), see
+    ///   [`DHCP_KEYS`](crate::interface::option::DHCP_KEYS);
+    /// - numeric options whose value cannot be parsed (`invalid-option-value`);
+    /// - numeric options outside their allowed range (`option-out-of-range`),
+    ///   see [`InterfaceOption::validate`](crate::interface::InterfaceOption::validate).
+    /// - `address` and `netmask` values that are not addresses, prefix
+    ///   lengths, or contiguous netmasks (`invalid-address`), see
+    ///   [`addr`](crate::addr).
+    /// - MAC addresses set with `hwaddress` on more than one interface
+    ///   (`duplicate-hwaddress`, warning), see
+    ///   [`duplicate_hwaddresses`](NetworkInterfaces::duplicate_hwaddresses).
+    /// - ports claimed by two bonds, or by both a bond and a bridge, through
+    ///   `bond-slaves`, `bond-master`, or `bridge-ports`
+    ///   (`multiple-masters`).
+    /// - aliases such as `eth0:1` whose parent has no stanza
+    ///   (`undefined-alias-parent`, warning).
+    /// - a `wpa-psk` stored in plain text next to a `wpa-conf` file
+    ///   (`psk-with-wpa-conf`, warning), see
+    ///   [`Interface::wpa_conf_path`](crate::interface::Interface::wpa_conf_path).
+    /// - `bond-mode`, `bond-lacp-rate` and `bond-xmit-hash-policy` values not
+    ///   accepted by the kernel, and `bond-miimon` and `bond-min-links` values
+    ///   that are not integers (`invalid-bond-option`).
+    /// - `bond-lacp-rate` and `bond-xmit-hash-policy` on a bond whose mode
+    ///   does not use them (`bond-option-mode-mismatch`, warning).
+    /// - bonds without slaves in `bond-slaves` or ports naming them in
+    ///   `bond-master` (`bond-without-slaves`, warning).
+    ///
+    /// Further checks can be enabled with
+    /// [`validate_with`](NetworkInterfaces::validate_with).
+    ///
+    /// # Returns
+    ///
+    /// A [`ValidationReport`] listing every issue found.
+    pub fn validate(&self) -> ValidationReport {
+        self.validate_with(&ValidationOptions::default())
+    }
+
+    /// Validates the collection like [`validate`](NetworkInterfaces::validate),
+    /// with optional checks enabled by `options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The [`ValidationOptions`] selecting optional checks.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::validation::ValidationOptions;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// let options = ValidationOptions {
+    ///     flag_placeholders: true,
+    /// };
+    /// print!("{}", net_ifaces.validate_with(&options));
+    /// ```
+    pub fn validate_with(&self, options: &ValidationOptions) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let strict_keys = self.parser_options.strict_keys;
+        let duplicates = self.duplicate_hwaddresses();
+        let conflicts = validation::membership_conflicts(self.by_name(), strict_keys);
+        let bonds_with_ports: BTreeSet<&str> = self
+            .interfaces
+            .values()
+            .filter_map(|iface| graph::bond_master(iface, strict_keys))
+            .collect();
+        for iface in self.by_name() {
+            validation::validate_interface(iface, strict_keys, options, &mut report);
+            validation::validate_hwaddress(iface, &duplicates, &mut report);
+            let origin = iface.origin.as_ref();
+            validation::validate_membership(&iface.name, origin, &conflicts, &mut report);
+            validation::validate_alias(
+                iface,
+                |parent| self.get_interface(parent).is_some_and(Interface::is_declared),
+                &mut report,
+            );
+            validation::validate_wireless(iface, &mut report);
+            let has_ports = bonds_with_ports.contains(iface.name.as_str());
+            validation::validate_bond(iface, strict_keys, has_ports, &mut report);
+        }
+        // Ports that are claimed but not defined
+        for conflict in &conflicts {
+            if self.get_interface(&conflict.port).is_none() {
+                validation::validate_membership(&conflict.port, None, &conflicts, &mut report);
+            }
+        }
+        report
+    }
+
+    /// Returns the MAC addresses configured with `hwaddress` on more than one
+    /// interface.
+    ///
+    /// Values are parsed as [`MacAddr`], so
+    /// addresses match regardless of letter case, notation, or `ether` prefix.
+    /// Values that are not MAC addresses are ignored.
+    ///
+    /// # Returns
+    ///
+    /// One [`DuplicateHwaddress`] per shared address, ordered by address, naming
+    /// every interface that uses it together with its value as written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::Interface;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("eth0").with_option("hwaddress", "52:54:00:AB:CD:01").build(),
+    ///     Interface::builder("eth1").with_option("hwaddress", "52:54:00:ab:cd:01").build(),
+    /// ]);
+    /// let duplicates = net_ifaces.duplicate_hwaddresses();
+    /// assert_eq!(duplicates.len(), 1);
+    /// assert_eq!(duplicates[0].interfaces[0].1, "52:54:00:AB:CD:01");
+    /// ```
+    pub fn duplicate_hwaddresses(&self) -> Vec<DuplicateHwaddress> {
+        validation::duplicate_hwaddresses(self.by_name(), self.parser_options.strict_keys)
+    }
+
+    /// Saves changes back to the `interfaces(5)` file.
+    ///
+    /// Each interface is written to the file it was loaded from or assigned
+    /// to with [`set_file`](NetworkInterfaces::set_file); new interfaces go
+    /// to the [default target](NetworkInterfaces::set_default_target). See
+    /// the [`files`] module for details.
+    ///
+    /// Every file is checked for changes on disk and rendered before any is
+    /// written, so a failure in either step leaves all files untouched. With a
+    /// [backup](NetworkInterfaces::set_backup) set, the files about to change
+    /// are then copied, again before any is written. Each file is replaced
+    /// atomically through a temporary file in the same directory, keeping its
+    /// permissions, so it is never left half written. Files whose contents
+    /// are unchanged are not rewritten, and included files left without
+    /// interfaces are deleted. If any file changed, the
+    /// [post-save hook](NetworkInterfaces::set_post_save_hook) runs last.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkInterfacesError` if a file cannot be written or has been modified on
+    /// disk. The error carries the path of the file. If only the post-save hook
+    /// fails, the error is [`NetworkInterfacesError::PostSaveHook`] and the
+    /// files have been saved.
+    pub fn save(&mut self) -> Result<(), NetworkInterfacesError> {
+        let written = match self.path.clone() {
+            Some(path) => {
+                check_unmodified(&path, self.last_modified).map_err(|err| err.with_path(&path))?;
+                self.commit(Some(&path))?
+            }
+            // A collection loaded with `load_dir` has only included files
+            None if !self.files.included.is_empty() => self.commit(None)?,
+            None => {
+                return Err(NetworkInterfacesError::Other(
+                    "No file path specified".to_string(),
+                ))
+            }
+        };
+        self.run_post_save_hook(written.as_deref())
+    }
+
+    /// Saves the collection as a single file at `path`, and saves it there
+    /// from now on.
+    ///
+    /// The file receives what [`write_to`](NetworkInterfaces::write_to)
+    /// writes, and is replaced atomically like with
+    /// [`save`](NetworkInterfaces::save). Since it is not the file the
+    /// collection was loaded from, it is not checked for changes on disk, and
+    /// no other file is written or deleted. Afterwards every interface belongs
+    /// to `path`, and the collection no longer tracks included files or the
+    /// text around a managed block; use
+    /// [`save_copy`](NetworkInterfaces::save_copy) to keep them. If the file
+    /// changed, the [post-save hook](NetworkInterfaces::set_post_save_hook)
+    /// runs with `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write. Its directory must exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::DependencyCycle`] if the configured
+    /// order cannot be satisfied, or an I/O error carrying `path` if the file
+    /// cannot be written. The collection is left unchanged in either case. If
+    /// only the post-save hook fails, the error is
+    /// [`NetworkInterfacesError::PostSaveHook`] and the file has been saved.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.save_as("build/interfaces").unwrap();
+    /// ```
+    pub fn save_as<P: AsRef<Path>>(&mut self, path: P) -> Result<(), NetworkInterfacesError> {
+        let path = path.as_ref();
+        let changed = self.write_single(path)?;
+
+        self.path = Some(path.to_path_buf());
+        self.last_modified = Some(SystemTime::now());
+        for name in self.interfaces.keys() {
+            self.files.origins.insert(name.clone(), path.to_path_buf());
+        }
+        self.files.stanza_origins.clear();
+        self.files.included.clear();
+        self.files.managed = None;
+        self.files.style.clear();
+        self.run_post_save_hook(changed.then_some(path))
+    }
+
+    /// Saves a copy of the collection as a single file at `path`, without
+    /// changing where the collection is saved.
+    ///
+    /// The file is written like with [`save_as`](NetworkInterfaces::save_as),
+    /// but the collection keeps its path, the files its interfaces belong
+    /// to, and any managed block, so a later
+    /// [`save`](NetworkInterfaces::save) writes the original layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write. Its directory must exist.
+    ///
+    /// # Errors
+    ///
+    /// The same as for [`save_as`](NetworkInterfaces::save_as).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load_with_includes("/etc/network/interfaces").unwrap();
+    /// net_ifaces.save_copy("/var/backups/interfaces.flat").unwrap();
+    /// ```
+    pub fn save_copy<P: AsRef<Path>>(&self, path: P) -> Result<(), NetworkInterfacesError> {
+        let path = path.as_ref();
+        let changed = self.write_single(path)?;
+        self.run_post_save_hook(changed.then_some(path))
+    }
+
+    /// Writes the collection as a single file at `path`, backing up the
+    /// previous contents, and returns whether the file changed.
+    fn write_single(&self, path: &Path) -> Result<bool, NetworkInterfacesError> {
+        let planned = planned_file(path, self.render().map_err(|err| err.with_path(path))?);
+        self.back_up(std::slice::from_ref(&planned))?;
+        write_planned(&planned).map_err(|err| err.with_path(path))?;
+        Ok(planned.action != FileAction::Unchanged)
+    }
+
+    /// Writes the collection as a single `interfaces(5)` file to `writer`.
+    ///
+    /// The output is the [`Display`](fmt::Display) output: the header
+    /// comments, the source directives, and every interface in the
+    /// [configured order](WriteOptions::sort), including interfaces that
+    /// [`save`](NetworkInterfaces::save) would write to other files. No file
+    /// of the collection is touched.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write, such as a buffer, a socket, or an archive
+    ///   entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::DependencyCycle`] without writing
+    /// anything if the configured order cannot be satisfied, or the error
+    /// returned by `writer`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::interface::{Family, Interface};
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([Interface::builder("eth0")
+    ///     .with_auto(true)
+    ///     .with_family(Family::Inet)
+    ///     .with_method("dhcp")
+    ///     .build()]);
+    /// let mut buffer = Vec::new();
+    /// net_ifaces.write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer, b"auto eth0\niface eth0 inet dhcp\n");
+    /// ```
+    pub fn write_to<W: io::Write>(&self, mut writer: W) -> Result<(), NetworkInterfacesError> {
+        writer.write_all(self.render()?.as_bytes()).op(IoOp::Write)?;
+        writer.flush().op(IoOp::Write)
+    }
+
+    /// Renders the collection as a single file, refusing an order that
+    /// cannot be satisfied.
+    fn render(&self) -> Result<String, NetworkInterfacesError> {
+        let ordered = self.ordered()?;
+        let (options, write_options) = (&self.parser_options, &self.write_options);
+        Ok(render_file(&self.comments, &self.sources, &ordered, options, write_options))
+    }
+
+    /// Sets a hook to run after every save that changed a file, such as one
+    /// running `ifreload -a` or `systemctl restart networking`.
+    ///
+    /// The hook runs once per save, after all files have been written, with
+    /// the path of the main file. A collection without a main file, such as
+    /// one loaded with [`load_dir`](NetworkInterfaces::load_dir), passes the
+    /// first file that changed. It does not run if saving fails, if every file
+    /// already had its rendered contents, or for
+    /// [`plan_save`](NetworkInterfaces::plan_save), which writes nothing.
+    ///
+    /// The hook replaces any previous one and is shared with clones of the
+    /// collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Called with the saved file; returns an error message on
+    ///   failure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    /// use std::process::Command;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.set_post_save_hook(Box::new(|_path| {
+    ///     let status = Command::new("ifreload").arg("-a").status().map_err(|e| e.to_string())?;
+    ///     if status.success() {
+    ///         Ok(())
+    ///     } else {
+    ///         Err(format!("ifreload -a exited with {}", status))
+    ///     }
+    /// }));
+    /// net_ifaces.save().unwrap();
+    /// ```
+    pub fn set_post_save_hook(&mut self, hook: Box<PostSaveHookFn>) {
+        self.post_save_hook = Some(SaveHook::new(hook));
+    }
+
+    /// Removes the hook set with
+    /// [`set_post_save_hook`](NetworkInterfaces::set_post_save_hook).
+    pub fn clear_post_save_hook(&mut self) {
+        self.post_save_hook = None;
+    }
+
+    /// Makes every save first copy the files it overwrites or deletes to a
+    /// backup next to them.
+    ///
+    /// Files whose contents do not change are not backed up, and neither are
+    /// new files. All backups are made before any file is written, so a
+    /// failure to back up a file leaves every file untouched. See [`Backup`]
+    /// for how backups are named.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup` - How to name and keep the backups.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::Other`] for a [`Backup::Suffix`] that
+    /// is empty, which would name the backup after the file itself, or that
+    /// contains a `/`. The previous setting is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::files::Backup;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.set_backup(Backup::Timestamped { keep: Some(5) }).unwrap();
+    /// net_ifaces.save().unwrap();
+    /// ```
+    pub fn set_backup(&mut self, backup: Backup) -> Result<(), NetworkInterfacesError> {
+        backup.validate()?;
+        self.files.backup = Some(backup);
+        Ok(())
+    }
+
+    /// Stops saves from making backups, as set with
+    /// [`set_backup`](NetworkInterfaces::set_backup).
+    pub fn clear_backup(&mut self) {
+        self.files.backup = None;
+    }
+
+    /// Backs up the files that `plan` overwrites or deletes, if a backup is
+    /// set.
+    fn back_up(&self, plan: &[PlannedFile]) -> Result<(), NetworkInterfacesError> {
+        let Some(backup) = &self.files.backup else {
+            return Ok(());
+        };
+        let now = SystemTime::now();
+        for planned in plan {
+            // Keep backups of fragments out of `source` globs
+            let hidden = self.files.included.contains_key(&planned.path);
+            backup
+                .create(planned, hidden, now)
+                .map_err(|err| err.with_path(&planned.path))?;
+        }
+        Ok(())
+    }
+
+    /// Runs the post-save hook for `written`, the file reported by
+    /// [`commit`](NetworkInterfaces::commit), if any.
+    fn run_post_save_hook(&self, written: Option<&Path>) -> Result<(), NetworkInterfacesError> {
+        match (&self.post_save_hook, written) {
+            (Some(hook), Some(path)) => hook
+                .run(path)
+                .map_err(|msg| NetworkInterfacesError::PostSaveHook(msg).with_path(path)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes every file of the collection with `path` as the main file,
+    /// after checking that the other files are unmodified.
+    ///
+    /// Returns the file to report to the post-save hook: `path`, or the first
+    /// changed file without one, or `None` if no file changed.
+    fn commit(&mut self, path: Option<&Path>) -> Result<Option<PathBuf>, NetworkInterfacesError> {
+        for (file, included) in &self.files.included {
+            if included.last_modified.is_some() {
+                check_unmodified(file, included.last_modified)
+                    .map_err(|err| err.with_path(file))?;
+            }
+        }
+        let plan = self.plan(path).map_err(|err| match path {
+            Some(path) => err.with_path(path),
+            None => err,
+        })?;
+        self.back_up(&plan)?;
+        for planned in &plan {
+            write_planned(planned).map_err(|err| err.with_path(&planned.path))?;
+        }
+        let written = plan
+            .iter()
+            .find(|planned| planned.action != FileAction::Unchanged)
+            .map(|planned| path.map_or_else(|| planned.path.clone(), Path::to_path_buf));
+
+        // Interfaces saved to the default target stay there from now on
+        let now = SystemTime::now();
+        for name in self.interfaces.keys() {
+            if !self.files.origins.contains_key(name) {
+                if let Some(target) = self.default_target_path().or(path) {
+                    self.files.origins.insert(name.clone(), target.to_path_buf());
+                }
+            }
+        }
+        for planned in plan {
+            if Some(planned.path.as_path()) == path {
+                continue;
+            }
+            if planned.action == FileAction::Delete {
+                self.files.included.remove(&planned.path);
+            } else {
+                self.files.included.entry(planned.path).or_default().last_modified = Some(now);
+            }
+        }
+        if path.is_some() {
+            self.last_modified = Some(now);
+        }
+        // The files were rewritten, so their old text no longer applies
+        self.files.style.clear();
+        Ok(written)
+    }
+
+    /// Saves every interface to a file of its own under `dir`.
+    ///
+    /// Each interface is written to `<dir>/<name>`, with the name passed
+    /// through [`file_name_for`](crate::files::file_name_for) so the file is
+    /// read by `source-directory` as well as `source`. The main file keeps
+    /// only its comments and source lines, and gains a `source <dir>/*`
+    /// directive if it has none. From then on, [`save`](NetworkInterfaces::save)
+    /// keeps each interface in its file.
+    ///
+    /// Files in `dir` left over from an earlier split are deleted: those that
+    /// belong to the collection and no longer hold any interface, and those
+    /// that only define interfaces named after the file which are no longer
+    /// in the collection. Other files in `dir` are left alone.
+    ///
+    /// `ifup` reads the included files in name order, so the stanzas are no
+    /// longer written in the [configured order](WriteOptions::sort) across files.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory for the per-interface files. It is created if
+    ///   it does not exist.
+    /// * `main_file` - The main file to write, or `None` for the file the
+    ///   collection was loaded from. The collection is saved to this file
+    ///   from now on.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::FileConflict`] if two interface names
+    /// map to the same file name, or any error returned by
+    /// [`save`](NetworkInterfaces::save). The collection is left unchanged if
+    /// saving fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.save_split("/etc/network/interfaces.d", None).unwrap();
+    /// ```
+    pub fn save_split(
+        &mut self,
+        dir: impl AsRef<Path>,
+        main_file: Option<&Path>,
+    ) -> Result<(), NetworkInterfacesError> {
+        let dir = dir.as_ref();
+        let main = match main_file.or(self.path.as_deref()) {
+            Some(main) => main.to_path_buf(),
+            None => {
+                return Err(NetworkInterfacesError::Other(
+                    "No file path specified".to_string(),
+                ))
+            }
+        };
+
+        // Refuse names that would overwrite each other before touching anything
+        let mut targets: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+        for iface in self.by_name() {
+            let file = dir.join(files::file_name_for(&iface.name));
+            targets.entry(file).or_default().push(iface.name.clone());
+        }
+        if let Some((path, names)) = targets.iter().find(|(_, names)| names.len() > 1) {
+            return Err(NetworkInterfacesError::FileConflict {
+                path: path.clone(),
+                names: names.clone(),
+            });
+        }
+        let source = Source::glob(format!("{}/*", dir.display())).to_string();
+        if let Err(err) = source.parse::<Source>() {
+            return Err(NetworkInterfacesError::Other(err.to_string()));
+        }
+
+        if self.path.as_deref() == Some(main.as_path()) {
+            check_unmodified(&main, self.last_modified).map_err(|err| err.with_path(&main))?;
+        }
+        let stale = stale_split_files(dir, &targets).map_err(|err| err.with_path(dir))?;
+        fs::create_dir_all(dir).op(IoOp::CreateDir).map_err(|err| err.with_path(dir))?;
+
+        let previous = (
+            self.files.clone(),
+            self.sources.clone(),
+            self.path.clone(),
+            self.last_modified,
+        );
+        for (file, names) in targets {
+            self.files.included.entry(file.clone()).or_default();
+            let name = IfaceName::from(names[0].as_str());
+            self.files.stanza_origins.remove(&name);
+            self.files.origins.insert(name, file);
+        }
+        for file in stale {
+            self.files.included.entry(file).or_default();
+        }
+        self.files.included.remove(&main);
+        self.add_source(source).expect("the directive was checked");
+        if self.path.as_deref() != Some(main.as_path()) {
+            self.path = Some(main.clone());
+            self.last_modified = None;
+        }
+
+        match self.commit(Some(&main)) {
+            Ok(written) => self.run_post_save_hook(written.as_deref()),
+            Err(err) => {
+                (self.files, self.sources, self.path, self.last_modified) = previous;
+                Err(err)
+            }
+        }
+    }
+
+    /// Computes what [`save`](NetworkInterfaces::save) would write, without
+    /// touching the disk.
+    ///
+    /// The main file comes first, followed by the other files in path order.
+    /// Files that neither exist nor would be written are not listed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkInterfacesError` if the collection has no path, or if
+    /// the configured order cannot be satisfied.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    /// use interface_rs::files::FileAction;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// for planned in net_ifaces.plan_save().unwrap() {
+    ///     if planned.action != FileAction::Unchanged {
+    ///         println!("{:?} {}", planned.action, planned.path.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn plan_save(&self) -> Result<Vec<PlannedFile>, NetworkInterfacesError> {
+        match self.path.as_deref() {
+            Some(path) => self.plan(Some(path)).map_err(|err| err.with_path(path)),
+            None if !self.files.included.is_empty() => self.plan(None),
+            None => Err(NetworkInterfacesError::Other(
+                "No file path specified".to_string(),
+            )),
+        }
+    }
+
+    /// Renders every file of the collection, with `main` as the main file if
+    /// there is one.
+    fn plan(&self, main: Option<&Path>) -> Result<Vec<PlannedFile>, NetworkInterfacesError> {
+        // Refuse to write an order that cannot be satisfied
+        let ordered = self.ordered()?;
+        let options = &self.parser_options;
+        let write_options = &self.write_options;
+
+        let mut main_members = Vec::new();
+        let mut others: BTreeMap<&Path, Vec<Cow<'_, Interface>>> = self
+            .files
+            .included
+            .keys()
+            .map(|file| (file.as_path(), Vec::new()))
+            .collect();
+        // Looking names up in the naturally ordered map costs more than the
+        // rendering itself on large collections
+        let origins: HashMap<&str, &Path> = self
+            .files
+            .origins
+            .iter()
+            .map(|(name, path)| (name.as_str(), path.as_path()))
+            .collect();
+        let default_target = self.default_target_path();
+        for iface in ordered {
+            let target = origins.get(iface.name.as_str()).copied().or(default_target);
+            let Some(target) = target.or(main) else {
+                return Err(NetworkInterfacesError::Other(format!(
+                    "No file to save interface {} to",
+                    iface.name
+                )));
+            };
+            let (iface, parts) = self.split_stanzas(iface);
+            let parts = parts.into_iter().map(|(file, part)| (file, Cow::Owned(part)));
+            for (file, iface) in std::iter::once((target, iface)).chain(parts) {
+                match main {
+                    Some(main) if file == main => main_members.push(iface),
+                    _ => others.entry(file).or_default().push(iface),
+                }
+            }
+        }
+
+        let mut plan = Vec::new();
+        if let Some(main) = main {
+            let main_members: Vec<&Interface> = main_members.iter().map(AsRef::as_ref).collect();
+            let mut main_contents =
+                render_file(&self.comments, &self.sources, &main_members, options, write_options);
+            if let Some(managed) = &self.files.managed {
+                main_contents = managed.wrap(&main_contents);
+            }
+            plan.push(planned_file(main, main_contents));
+        }
+        for (file, members) in others {
+            if members.is_empty() {
+                if file.exists() {
+                    plan.push(PlannedFile {
+                        path: file.to_path_buf(),
+                        action: FileAction::Delete,
+                        contents: None,
+                    });
+                }
+                continue;
+            }
+            let (comments, sources) = match self.files.included.get(file) {
+                Some(included) => (&included.comments[..], &included.sources[..]),
+                None => (&[][..], &[][..]),
+            };
+            let members: Vec<&Interface> = members.iter().map(AsRef::as_ref).collect();
+            let contents = render_file(comments, sources, &members, options, write_options);
+            plan.push(planned_file(file, contents));
+        }
+        Ok(plan)
+    }
+
+    /// Splits off the family stanzas of an interface that are saved to
+    /// another file than the rest of it, each as an interface of its own.
+    fn split_stanzas<'a>(
+        &'a self,
+        iface: &'a Interface,
+    ) -> (Cow<'a, Interface>, Vec<(&'a Path, Interface)>) {
+        if self.files.stanza_origins.is_empty() {
+            return (Cow::Borrowed(iface), Vec::new());
+        }
+        let name = IfaceName::from(iface.name.as_str());
+        let Some(stanza_origins) = self.files.stanza_origins.get(&name) else {
+            return (Cow::Borrowed(iface), Vec::new());
+        };
+        let mut kept = iface.clone();
+        kept.family_stanzas.clear();
+        let mut parts = Vec::new();
+        for stanza in &iface.family_stanzas {
+            let file = stanza_origins.iter().find(|(family, _)| *family == stanza.family);
+            let Some((_, file)) = file else {
+                kept.family_stanzas.push(stanza.clone());
+                continue;
+            };
+            let mut part = Interface::builder(&iface.name).build();
+            part.family = stanza.family.clone();
+            part.method = stanza.method.clone();
+            part.options = stanza.options.clone();
+            part.disabled = iface.disabled;
+            parts.push((file.as_path(), part));
+        }
+        (Cow::Owned(kept), parts)
+    }
+
+    /// Returns where an interface was defined, as recorded by the parser.
+    ///
+    /// Unlike [`file_of`](NetworkInterfaces::file_of), this does not change
+    /// when the interface is assigned to another file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface.
+    ///
+    /// # Returns
+    ///
+    /// `None` if there is no such interface or it was built in memory.
+    pub fn origin_of(&self, name: &str) -> Option<&Origin> {
+        self.get_interface(name)?.origin()
+    }
+
+    /// Returns the file an interface is saved to, if the collection has one.
+    ///
+    /// This is the file the interface was loaded from or assigned to with
+    /// [`set_file`](NetworkInterfaces::set_file), or else the
+    /// [default target](NetworkInterfaces::set_default_target).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface.
+    ///
+    /// # Returns
+    ///
+    /// `None` if there is no such interface, or if it would be saved to the
+    /// main file and the collection has no path.
+    pub fn file_of(&self, name: &str) -> Option<&Path> {
+        if !self.interfaces.contains_key(name) {
+            return None;
+        }
+        self.target_of(&IfaceName::from(name))
+    }
+
+    /// Assigns an interface to the file it is saved to.
+    ///
+    /// All of its stanzas go to that file, including those for another address
+    /// family that were read from a file of their own. The file is created on
+    /// the next [`save`](NetworkInterfaces::save) if it does not exist. A file
+    /// left without interfaces by reassignment is deleted on save, unless it
+    /// is the main file. Relative paths are used as given; they are not
+    /// resolved against the main file.
+    ///
+    /// Note that the main file is not changed to include the new file; add a
+    /// matching [`source`](NetworkInterfaces::add_source) directive if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the interface.
+    /// * `path` - The file to save it to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::NotFound`] if no interface has that name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// net_ifaces.set_file("swp1", "/etc/network/interfaces.d/swp1").unwrap();
+    /// net_ifaces.add_source("source /etc/network/interfaces.d/*").unwrap();
+    /// net_ifaces.save().unwrap();
+    /// ```
+    pub fn set_file(
+        &mut self,
+        name: &str,
+        path: impl Into<PathBuf>,
+    ) -> Result<(), NetworkInterfacesError> {
+        if !self.interfaces.contains_key(name) {
+            return Err(NetworkInterfacesError::NotFound {
+                name: name.to_string(),
+            });
+        }
+        let path = path.into();
+        if self.path.as_ref() != Some(&path) {
+            self.files.included.entry(path.clone()).or_default();
+        }
+        let key = IfaceName::from(name);
+        self.files.stanza_origins.remove(&key);
+        self.files.origins.insert(key, path);
+        Ok(())
+    }
+
+    /// Returns where interfaces without a recorded file are saved.
+    pub fn default_target(&self) -> &SaveTarget {
+        &self.files.default_target
+    }
+
+    /// Sets where interfaces without a recorded file are saved.
+    ///
+    /// This applies to interfaces added since the collection was loaded or
+    /// last saved; once saved, an interface stays in its file.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The [`SaveTarget`] to use.
+    pub fn set_default_target(&mut self, target: SaveTarget) {
+        self.files.default_target = target;
+    }
+
+    /// Returns the file an interface is saved to, without checking that it
+    /// exists.
+    fn target_of(&self, name: &IfaceName) -> Option<&Path> {
+        match self.files.origins.get(name) {
+            Some(path) => Some(path),
+            None => self.default_target_path(),
+        }
+    }
+
+    /// Resolves the default target to a path.
+    fn default_target_path(&self) -> Option<&Path> {
+        match &self.files.default_target {
+            SaveTarget::Main => self.path.as_deref(),
+            SaveTarget::File(path) => Some(path),
+        }
+    }
+
+    /// Validates the collection and saves it only if no `Error`-severity
+    /// issues are found.
+    ///
+    /// Warnings do not prevent saving.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::Validation`] carrying the full
+    /// [`ValidationReport`] if validation finds errors, or any error returned
+    /// by [`save`](NetworkInterfaces::save).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// if let Err(err) = net_ifaces.save_validated() {
+    ///     if let Some(report) = err.validation_report() {
+    ///         for issue in report.errors() {
+    ///             eprintln!("{}", issue);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn save_validated(&mut self) -> Result<(), NetworkInterfacesError> {
+        let report = self.validate();
+        if !report.is_valid() {
+            let err = NetworkInterfacesError::Validation(report);
+            return Err(match &self.path {
+                Some(path) => err.with_path(path),
+                None => err,
+            });
+        }
+        self.save()
+    }
+
+    /// Reloads the interfaces file from disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `NetworkInterfacesError` if the file cannot be read or parsed,
+    /// or [`LimitExceeded`](NetworkInterfacesError::LimitExceeded) if it
+    /// exceeds a limit of `options`, such as
+    /// [`max_input_size`](ParserOptions::max_input_size). The error carries
+    /// the path of the file.
+    pub fn reload(&mut self) -> Result<(), NetworkInterfacesError> {
+        let path = match &self.path {
+            Some(p) => p.clone(),
+            None => {
+                return Err(NetworkInterfacesError::Other(
+                    "No file path specified".to_string(),
+                ))
+            }
+        };
+        let managed = self.files.managed.as_ref().map(|managed| managed.marker.as_str());
+        let mut reloaded = NetworkInterfaces::read(&path, self.parser_options.clone(), managed)
+            .map_err(|err| err.with_path(&path))?;
+        if self.parser_options.follow_sources {
+            reloaded.follow_sources()?;
+        }
+        self.interfaces = reloaded.interfaces;
+        self.comments = reloaded.comments;
+        self.sources = reloaded.sources;
+        self.files.origins = reloaded.files.origins;
+        self.files.stanza_origins = reloaded.files.stanza_origins;
+        self.files.style = reloaded.files.style;
+        self.files.managed = reloaded.files.managed;
+        self.files.included = reloaded.files.included;
+        self.last_modified = reloaded.last_modified;
+        Ok(())
+    }
+}
+
+// Implement Display for NetworkInterfaces to allow easy printing
+impl fmt::Display for NetworkInterfaces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = &self.parser_options;
+        let write_options = &self.write_options;
+        let interfaces: Vec<&Interface> = self.sorted_iter().collect();
+        // A single write to the formatter is much cheaper than one per part of
+        // each line
+        let contents =
+            render_file(&self.comments, &self.sources, &interfaces, options, write_options);
+        f.write_str(&contents)
+    }
+}
+
+/// Writes one file: the header comments, then the source directives, then
+/// each interface, with the blank lines and final newline of `write_options`.
+/// Disabled interfaces are prefixed with the disabled marker of `options` and
+/// tags written after its tag prefix, and option values are written according
+/// to `write_options`.
+fn write_file<'a>(
+    f: &mut impl fmt::Write,
+    comments: &[String],
+    sources: &[String],
+    interfaces: impl IntoIterator<Item = &'a Interface>,
+    options: &ParserOptions,
+    write_options: &WriteOptions,
+) -> fmt::Result {
+    if !write_options.final_newline {
+        // Which line is the last one is only known at the end
+        let with_newline = WriteOptions {
+            final_newline: true,
+            ..write_options.clone()
+        };
+        let mut contents = String::new();
+        write_file(&mut contents, comments, sources, interfaces, options, &with_newline)?;
+        return f.write_str(contents.strip_suffix('\n').unwrap_or(&contents));
+    }
+
+    let mut wrote_line = false;
+    for comment in comment_lines(comments) {
+        f.write_str(&comment)?;
+        f.write_str("\n")?;
+        wrote_line = true;
+    }
+    for source in sources {
+        f.write_str(source)?;
+        f.write_str("\n")?;
+        wrote_line = true;
+    }
+
+    let separator = "\n".repeat(write_options.blank_lines);
+    for iface in interfaces {
+        // No blank lines at the top of the file
+        if wrote_line {
+            f.write_str(&separator)?;
+        }
+        iface.write_stanza(f, &options.disabled_marker, &options.tag_prefix, write_options)?;
+        wrote_line = true;
+    }
+    Ok(())
+}
+
+/// Returns the lines written for header `comments`.
+///
+/// Each line is trimmed and prefixed like in [`comment_line`] if needed, so
+/// that it reads back as the same comment even if the comments were set
+/// without the setters or span several lines.
+pub(crate) fn comment_lines(comments: &[String]) -> impl Iterator<Item = Cow<'_, str>> {
+    comments.iter().flat_map(|comment| comment.split('\n')).map(|line| {
+        let line = line.trim();
+        if line.starts_with('#') {
+            Cow::Borrowed(line)
+        } else {
+            Cow::Owned(comment_line(line.to_string()))
+        }
+    })
+}
+
+/// Renders one file into a buffer sized up front for its contents.
+fn render_file(
+    comments: &[String],
+    sources: &[String],
+    interfaces: &[&Interface],
+    options: &ParserOptions,
+    write_options: &WriteOptions,
+) -> String {
+    let lines = comments.iter().chain(sources).map(|line| line.len() + 1);
+    let stanzas = interfaces.iter().map(|iface| {
+        // The `auto`, `allow-*` and `iface` lines, each about a name long
+        let header = (iface.name.len() + 24) * (2 + iface.allow.len());
+        let options: usize = iface.options.iter().map(|(k, v)| k.len() + v.len() + 6).sum();
+        header + options + write_options.blank_lines
+    });
+    let mut contents = String::with_capacity(lines.chain(stanzas).sum());
+    let interfaces = interfaces.iter().copied();
+    write_file(&mut contents, comments, sources, interfaces, options, write_options)
+        .expect("writing to a String cannot fail");
+    contents
+}
+
+/// Reads the file at `path`, failing as soon as more than `max` bytes have
+/// been read.
+///
+/// The size reported by the metadata is checked before reading, but it can
+/// be wrong (special files report 0) or change while reading, so the read
+/// itself is bounded too.
+fn read_bounded(path: &Path, max: Option<usize>) -> Result<Vec<u8>, NetworkInterfacesError> {
+    let file = fs::File::open(path).op(IoOp::ReadFile)?;
+    let bound = max.map_or(u64::MAX, |max| (max as u64).saturating_add(1));
+    let mut bytes = Vec::new();
+    file.take(bound).read_to_end(&mut bytes).op(IoOp::ReadFile)?;
+    match max {
+        Some(max) if bytes.len() > max => Err(LimitExceeded {
+            limit: Limit::InputSize,
+            max,
+            actual: None,
+            line: None,
+        }
+        .into()),
+        _ => Ok(bytes),
+    }
+}
+
+/// Fails with [`NetworkInterfacesError::FileModified`] if `path` was modified
+/// after `last_modified`.
+fn check_unmodified(
+    path: &Path,
+    last_modified: Option<SystemTime>,
+) -> Result<(), NetworkInterfacesError> {
+    let metadata = fs::metadata(path).op(IoOp::ReadMetadata)?;
+    let current_modified = metadata.modified().op(IoOp::ReadMetadata)?;
+    if let Some(last_modified) = last_modified {
+        if current_modified > last_modified {
+            // File has been modified since last read
+            return Err(NetworkInterfacesError::FileModified);
+        }
+    }
+    Ok(())
+}
+
+/// Finds the files in `dir` left over from an earlier
+/// [`save_split`](NetworkInterfaces::save_split): files with a name
+/// `run-parts` accepts that are not in `targets` and only define interfaces
+/// named after the file.
+fn stale_split_files(
+    dir: &Path,
+    targets: &BTreeMap<PathBuf, Vec<String>>,
+) -> Result<Vec<PathBuf>, NetworkInterfacesError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).op(IoOp::ReadDir),
+    };
+    let mut stale = Vec::new();
+    for entry in entries {
+        let path = entry.op(IoOp::ReadDir)?.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !files::is_valid_file_name(file_name) || targets.contains_key(&path) || !path.is_file()
+        {
+            continue;
+        }
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok((interfaces, _, _)) = Parser::new().parse(&contents) else {
+            continue;
+        };
+        if !interfaces.is_empty()
+            && interfaces.keys().all(|name| files::file_name_for(name) == file_name)
+        {
+            stale.push(path);
+        }
+    }
+    stale.sort();
+    Ok(stale)
+}
+
+/// Compares rendered contents with the file on disk.
+fn planned_file(path: &Path, contents: String) -> PlannedFile {
+    let action = match fs::read(path) {
+        Ok(existing) if existing == contents.as_bytes() => FileAction::Unchanged,
+        Ok(_) => FileAction::Update,
+        Err(_) if path.exists() => FileAction::Update,
+        Err(_) => FileAction::Create,
+    };
+    PlannedFile {
+        path: path.to_path_buf(),
+        action,
+        contents: Some(contents),
+    }
+}
+
+/// Returns whether `a` and `b` may have a stanza for the same address family.
+///
+/// A stanza without a family could be for any.
+fn shares_family(a: &Interface, b: &Interface) -> bool {
+    let same = |x: Option<&Family>, y: Option<&Family>| x.is_none() || y.is_none() || x == y;
+    a.stanzas().any(|x| b.stanzas().any(|y| same(x.family, y.family)))
+}
+
+/// Adds the `auto` flag and `allow-*` classes of `from` to `into`.
+fn merge_directives(into: &mut Interface, from: &Interface) {
+    into.auto |= from.auto;
+    for allow in &from.allow {
+        if !into.allow.contains(allow) {
+            into.allow.push(allow.clone());
+        }
+    }
+}
+
+/// Carries out one step of a save plan.
+fn write_planned(planned: &PlannedFile) -> Result<(), NetworkInterfacesError> {
+    match (planned.action, &planned.contents) {
+        (FileAction::Create | FileAction::Update, Some(contents)) => {
+            files::write_atomic(&planned.path, contents)
+        }
+        (FileAction::Delete, _) => fs::remove_file(&planned.path).op(IoOp::Remove),
+        _ => Ok(()),
+    }
+}
+
+impl Default for NetworkInterfaces {
+    /// Creates an empty collection; see [`NetworkInterfaces::new`].
+    fn default() -> Self {
+        NetworkInterfaces::new()
+    }
+}
+
+impl FromIterator<Interface> for NetworkInterfaces {
+    /// Collects interfaces into a collection; see
+    /// [`NetworkInterfaces::from_interfaces`].
+    fn from_iter<I: IntoIterator<Item = Interface>>(iter: I) -> Self {
+        NetworkInterfaces::from_interfaces(iter)
+    }
+}
+
+// Implement methods to access interfaces directly if needed
+impl NetworkInterfaces {
+    /// Returns an iterator over the interfaces in the order they were added.
+    ///
+    /// After loading, this is the order of the stanzas in the file: each
+    /// interface is placed at its first `iface` line, or at its first `auto`
+    /// or `allow-*` line if it has no stanza. Interfaces added later follow in
+    /// the order they were added. Replacing an interface with
+    /// [`add_interface`](NetworkInterfaces::add_interface) keeps its place,
+    /// and deleting one keeps the order of the others. The order never
+    /// depends on hashing, so it is the same on every run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let content = "iface swp10\niface swp2\niface bond0\n";
+    /// let net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+    /// let names: Vec<&String> = net_ifaces.iter().map(|(name, _)| name).collect();
+    /// assert_eq!(names, ["swp10", "swp2", "bond0"]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Interface)> {
+        self.interfaces.iter().map(|(name, iface)| (&name.0, iface))
+    }
+
+    /// Returns an iterator over the interfaces in natural order of their
+    /// names, for output whose order is documented as such.
+    pub(crate) fn iter_by_name(&self) -> impl Iterator<Item = (&String, &Interface)> {
+        let mut interfaces: Vec<(&IfaceName, &Interface)> = self.interfaces.iter().collect();
+        interfaces.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        interfaces.into_iter().map(|(name, iface)| (&name.0, iface))
+    }
+
+    /// Returns the interfaces in natural order of their names.
+    fn by_name(&self) -> impl Iterator<Item = &Interface> {
+        self.iter_by_name().map(|(_, iface)| iface)
+    }
+
+    /// Returns the interfaces whose names do not occur in `other`.
+    ///
+    /// Only names are compared; use a field-level comparison to find
+    /// interfaces whose configuration differs. The result is in natural
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let golden = NetworkInterfaces::load("golden/interfaces").unwrap();
+    /// let device = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// for iface in golden.difference(&device) {
+    ///     println!("missing on device: {}", iface.name);
+    /// }
+    /// ```
+    pub fn difference<'a>(&'a self, other: &NetworkInterfaces) -> Vec<&'a Interface> {
+        self.by_name()
+            .filter(|iface| !other.interfaces.contains_key(iface.name.as_str()))
+            .collect()
+    }
+
+    /// Returns the interfaces whose names also occur in `other`.
+    ///
+    /// The interfaces are taken from `self` and returned in natural order.
+    pub fn intersection<'a>(&'a self, other: &NetworkInterfaces) -> Vec<&'a Interface> {
+        self.by_name()
+            .filter(|iface| other.interfaces.contains_key(iface.name.as_str()))
+            .collect()
+    }
+
+    /// Returns the names that occur in exactly one of the two collections, in
+    /// natural order.
+    pub fn symmetric_difference_names(&self, other: &NetworkInterfaces) -> Vec<String> {
+        let ours = self.interfaces.keys().filter(|name| !other.interfaces.contains_key(*name));
+        let theirs = other.interfaces.keys().filter(|name| !self.interfaces.contains_key(*name));
+        let names: BTreeSet<&IfaceName> = ours.chain(theirs).collect();
+        names.into_iter().map(|name| name.0.clone()).collect()
+    }
+
+    /// Returns an iterator over the declared interfaces, skipping placeholders
+    /// for names that only appear on `auto` or `allow-*` lines.
+    ///
+    /// Interfaces are returned in the order they were added, like
+    /// [`iter`](NetworkInterfaces::iter). See [`Interface::is_declared`].
+    pub fn iter_declared(&self) -> impl Iterator<Item = (&String, &Interface)> {
+        self.iter().filter(|(_, iface)| iface.is_declared())
+    }
+
+    /// Returns an iterator over the interfaces in the order they are written,
+    /// as configured by [`WriteOptions::sort`].
+    ///
+    /// With [`SortOrder::Dependency`], interfaces forming a dependency cycle
+    /// cause the whole collection to be returned in natural order instead.
+    pub fn sorted_iter(&self) -> impl Iterator<Item = &Interface> {
+        self.ordered()
+            .unwrap_or_else(|_| self.by_name().collect())
+            .into_iter()
+    }
+
+    /// Returns the interfaces in an order they can be brought up in, one at a
+    /// time, such as with sequential `ifup` calls.
+    ///
+    /// Every interface comes after the interfaces it depends on: VLANs after
+    /// their raw devices, bridges and bonds after their members, and VRF
+    /// members after their VRF. The loopback interface comes first. Beyond
+    /// that, interfaces are in natural order. Disabled interfaces are left
+    /// out.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CycleError`] naming the interfaces involved if the
+    /// dependencies form a cycle.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::from_interfaces([
+    ///     Interface::builder("br0").with_option("bridge-ports", "bond0").build(),
+    ///     Interface::builder("bond0").with_option("bond-slaves", "swp1").build(),
+    ///     Interface::builder("swp1").build(),
+    ///     Interface::builder("lo").with_method("loopback").build(),
+    /// ]);
+    /// let order: Vec<&str> =
+    ///     net_ifaces.bringup_order().unwrap().iter().map(|i| i.name.as_str()).collect();
+    /// assert_eq!(order, ["lo", "swp1", "bond0", "br0"]);
+    /// ```
+    pub fn bringup_order(&self) -> Result<Vec<&Interface>, CycleError> {
+        let enabled = self.interfaces.values().filter(|iface| !iface.disabled);
+        graph::dependency_order(enabled, self.parser_options.strict_keys).map_err(CycleError)
+    }
+
+    /// Returns the interfaces in an order they can be taken down in: the
+    /// reverse of [`bringup_order`](NetworkInterfaces::bringup_order).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CycleError`] naming the interfaces involved if the
+    /// dependencies form a cycle.
+    pub fn teardown_order(&self) -> Result<Vec<&Interface>, CycleError> {
+        let mut order = self.bringup_order()?;
+        order.reverse();
+        Ok(order)
+    }
+
+    /// Returns the interfaces in the configured output order.
+    fn ordered(&self) -> Result<Vec<&Interface>, NetworkInterfacesError> {
+        if self.write_options.sort == SortOrder::Preserve {
+            // Storage is already in insertion order
+            return Ok(self.interfaces.values().collect());
+        }
+        // Interfaces a custom order ties are left in natural order
+        let mut interfaces: Vec<&Interface> = self.by_name().collect();
+        match &self.write_options.sort {
+            SortOrder::Preserve | SortOrder::Natural => {}
+            SortOrder::Dependency => {
+                return graph::dependency_order(interfaces, self.parser_options.strict_keys)
+                    .map_err(NetworkInterfacesError::DependencyCycle);
+            }
+            sort => interfaces.sort_by(|a, b| sort.compare(a, b)),
+        }
+        Ok(interfaces)
+    }
+}
+
+#[cfg(feature = "json")]
+impl NetworkInterfaces {
+    /// Serializes the collection to JSON, using the representation documented
+    /// on [`NetworkInterfaces`].
+    ///
+    /// # Arguments
+    ///
+    /// * `pretty` - Whether to indent the output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// println!("{}", net_ifaces.to_json_string(true));
+    /// ```
+    pub fn to_json_string(&self, pretty: bool) -> String {
+        let json = if pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        };
+        json.expect("NetworkInterfaces always serializes to JSON")
+    }
+
+    /// Deserializes a collection from JSON produced by
+    /// [`to_json_string`](NetworkInterfaces::to_json_string).
+    ///
+    /// The returned collection has no file path; use it for rendering, or
+    /// copy interfaces into a loaded collection to save them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::Json`] if the input is not valid JSON
+    /// or does not match the documented representation.
+    pub fn from_json_str(s: &str) -> Result<Self, NetworkInterfacesError> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl NetworkInterfaces {
+    /// Serializes the collection to TOML.
+    ///
+    /// The schema is the one documented on [`NetworkInterfaces`], with each
+    /// interface written as an `[interfaces.<name>]` table. Fields without a
+    /// value (`family`, `method`, `mapping`) are omitted, and `options` stays
+    /// an array of `[key, value]` arrays so duplicates and order survive:
+    ///
+    /// ```toml
+    /// comments = []
+    /// sources = []
+    ///
+    /// [interfaces.eth0]
+    /// name = "eth0"
+    /// auto = true
+    /// allow = []
+    /// family = "inet"
+    /// method = "static"
+    /// options = [["address", "192.168.1.10/24"], ["address", "192.168.1.11/24"]]
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// println!("{}", net_ifaces.to_toml_string());
+    /// ```
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(self).expect("NetworkInterfaces always serializes to TOML")
+    }
+
+    /// Deserializes a collection from TOML produced by
+    /// [`to_toml_string`](NetworkInterfaces::to_toml_string).
+    ///
+    /// The returned collection has no file path; use it for rendering, or
+    /// copy interfaces into a loaded collection to save them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::Toml`] if the input is not valid TOML
+    /// or does not match the documented representation.
+    pub fn from_toml_str(s: &str) -> Result<Self, NetworkInterfacesError> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NetworkInterfaces {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        /// Serializes the interfaces as a name-keyed map in natural order.
+        struct Interfaces<'a>(&'a NetworkInterfaces);
+
+        impl serde::Serialize for Interfaces<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_map(self.0.iter_by_name())
+            }
+        }
+
+        let mut state = serializer.serialize_struct("NetworkInterfaces", 3)?;
+        state.serialize_field("interfaces", &Interfaces(self))?;
+        state.serialize_field("comments", &self.comments)?;
+        state.serialize_field("sources", &self.sources)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NetworkInterfaces {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Repr {
+            #[serde(default)]
+            interfaces: HashMap<String, Interface>,
+            #[serde(default)]
+            comments: Vec<String>,
+            #[serde(default)]
+            sources: Vec<String>,
+        }
+
+        let repr = Repr::deserialize(deserializer)?;
+        if let Some((key, iface)) = repr.interfaces.iter().find(|(k, i)| **k != i.name) {
+            return Err(serde::de::Error::custom(format!(
+                "interface key {} does not match name {}",
+                key, iface.name
+            )));
+        }
+        Ok(NetworkInterfaces::from_parts(
+            repr.interfaces,
+            repr.comments,
+            repr.sources,
+            None,
+            None,
+            ParserOptions::default(),
+        ))
+    }
+}
+
+/// Turns `line` into a comment line, prefixing `# ` unless it already starts
+/// with `#`.
+fn comment_line(line: String) -> String {
+    if line.starts_with('#') {
+        line
+    } else if line.is_empty() {
+        "#".to_string()
+    } else {
+        format!("# {}", line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::interface::{Method, NameError};
+    use crate::helper::sort::natural;
+    use crate::write_options::ValueAlignment;
+
+    #[test]
+    fn test_next_unused_vlan_in_range() {
+        // Create a `NetworkInterfaces` instance with some used VLANs
+        let mut network_interfaces = NetworkInterfaces::new();
+
+        // Add some VLAN interfaces to simulate used IDs
+        network_interfaces.add_interface(Interface::builder("vlan1000").build());
+        network_interfaces.add_interface(Interface::builder("vlan1001").build());
+        network_interfaces.add_interface(Interface::builder("vlan1003").build());
+
+        // Test: Find the next unused VLAN ID in the range 1000 to 1999
+        let next_vlan_id = network_interfaces.next_unused_vlan_in_range(1000, 1999);
+        assert_eq!(next_vlan_id, Some(1002));
+
+        // Test: Find the next unused VLAN ID when all VLANs are used in the range
+        network_interfaces.add_interface(Interface::builder("vlan1002").build());
+        let next_vlan_id = network_interfaces.next_unused_vlan_in_range(1000, 1003);
+        assert_eq!(next_vlan_id, None);
+
+        // Test: Find the next unused VLAN ID in a different range
+        let next_vlan_id = network_interfaces.next_unused_vlan_in_range(2000, 2005);
+        assert_eq!(next_vlan_id, Some(2000));
+    }
+
+    #[test]
+    fn test_add_vid_to_bridge() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1")
+                .with_option("bridge-vlan-aware", "yes")
+                .with_option("bridge-vids", "1000-1004 1100")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("br1")
+                .with_option("bridge_vlan_aware", "yes")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("br2")
+                .with_option("bridge-ports", "swp2")
+                .build(),
+        );
+
+        assert!(network_interfaces.add_vid_to_bridge("br0", 1005).unwrap());
+        assert!(!network_interfaces.add_vid_to_bridge("br0", 1002).unwrap());
+        let br0 = network_interfaces.get_interface("br0").unwrap();
+        assert_eq!(br0.get_option("bridge-vids"), Some("1000-1005 1100"));
+
+        // The option is created when missing
+        assert!(network_interfaces.add_vid_to_bridge("br1", 10).unwrap());
+        let br1 = network_interfaces.get_interface("br1").unwrap();
+        assert_eq!(br1.get_option("bridge-vids"), Some("10"));
+
+        let err = network_interfaces.add_vid_to_bridge("br2", 10).unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::WrongKind { .. }));
+        let err = network_interfaces.add_vid_to_bridge("br9", 10).unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::NotFound { .. }));
+        let err = network_interfaces.add_vid_to_bridge("br0", 4095).unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::OptionRange(_)));
+        assert_eq!(
+            network_interfaces
+                .get_interface("br0")
+                .unwrap()
+                .get_option("bridge-vids"),
+            Some("1000-1005 1100")
+        );
+    }
+
+    #[test]
+    fn test_remove_vid_from_bridge() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1 swp2")
+                .with_option("bridge-vlan-aware", "yes")
+                .with_option("bridge-vids", "1000-1004 1100")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("swp1")
+                .with_option("bridge-access", "1100")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("swp2")
+                .with_option("bridge-access", "1000")
+                .build(),
+        );
+        let vids = |n: &NetworkInterfaces| {
+            n.get_interface("br0")
+                .unwrap()
+                .get_option("bridge-vids")
+                .map(str::to_string)
+        };
+
+        // Middle of a range
+        assert!(network_interfaces
+            .remove_vid_from_bridge("br0", 1002, false)
+            .unwrap());
+        assert_eq!(vids(&network_interfaces).as_deref(), Some("1000-1001 1003-1004 1100"));
+        // Edge of a range
+        assert!(network_interfaces
+            .remove_vid_from_bridge("br0", 1004, false)
+            .unwrap());
+        assert_eq!(vids(&network_interfaces).as_deref(), Some("1000-1001 1003 1100"));
+        // Not present
+        assert!(!network_interfaces
+            .remove_vid_from_bridge("br0", 1002, false)
+            .unwrap());
+        // Single-id entry, clearing member access ports
+        assert!(network_interfaces
+            .remove_vid_from_bridge("br0", 1100, true)
+            .unwrap());
+        assert_eq!(vids(&network_interfaces).as_deref(), Some("1000-1001 1003"));
+        let swp1 = network_interfaces.get_interface("swp1").unwrap();
+        assert_eq!(swp1.get_option("bridge-access"), None);
+        let swp2 = network_interfaces.get_interface("swp2").unwrap();
+        assert_eq!(swp2.get_option("bridge-access"), Some("1000"));
+
+        // The option is dropped once empty
+        for vid in [1000, 1001, 1003] {
+            assert!(network_interfaces
+                .remove_vid_from_bridge("br0", vid, false)
+                .unwrap());
+        }
+        assert_eq!(vids(&network_interfaces), None);
+        assert!(!network_interfaces
+            .remove_vid_from_bridge("br0", 1000, false)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_bridge_ports_idempotent() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(
+            Interface::builder("br0")
+                .with_option("bridge-ports", "none")
+                .build(),
+        );
+        network_interfaces.add_interface(Interface::builder("swp1").build());
+        network_interfaces.add_interface(Interface::builder("swp2").build());
+        let ports = |n: &NetworkInterfaces| {
+            n.get_interface("br0")
+                .unwrap()
+                .get_option("bridge-ports")
+                .map(str::to_string)
+        };
+
+        assert!(network_interfaces
+            .add_port_to_bridge("br0", "swp2", None)
+            .unwrap());
+        assert_eq!(ports(&network_interfaces).as_deref(), Some("swp2"));
+        assert!(network_interfaces
+            .add_port_to_bridge("br0", "swp1", Some(100))
+            .unwrap());
+        assert!(!network_interfaces
+            .add_port_to_bridge("br0", "swp1", Some(100))
+            .unwrap());
+        assert_eq!(ports(&network_interfaces).as_deref(), Some("swp2 swp1"));
+        let swp1 = network_interfaces.get_interface("swp1").unwrap();
+        assert_eq!(swp1.get_option("bridge-access"), Some("100"));
+
+        // Changing only the access VLAN is a change
+        assert!(network_interfaces
+            .add_port_to_bridge("br0", "swp1", Some(200))
+            .unwrap());
+
+        assert!(network_interfaces
+            .remove_port_from_bridge("br0", "swp2", EmptyListPolicy::WriteNone, false)
+            .unwrap());
+        assert!(!network_interfaces
+            .remove_port_from_bridge("br0", "swp2", EmptyListPolicy::WriteNone, false)
+            .unwrap());
+        assert_eq!(ports(&network_interfaces).as_deref(), Some("swp1"));
+        assert!(network_interfaces
+            .remove_port_from_bridge("br0", "swp1", EmptyListPolicy::WriteNone, true)
+            .unwrap());
+        assert_eq!(ports(&network_interfaces).as_deref(), Some("none"));
+        let swp1 = network_interfaces.get_interface("swp1").unwrap();
+        assert_eq!(swp1.get_option("bridge-access"), None);
+
+        // The last port of a bridge leaves `none` behind, so br0 is still a
+        // bridge that ports can be added to
+        network_interfaces
+            .add_port_to_bridge("br0", "swp1", None)
+            .unwrap();
+        assert!(network_interfaces
+            .remove_port_from_bridge("br0", "swp1", EmptyListPolicy::Remove, false)
+            .unwrap());
+        assert_eq!(ports(&network_interfaces).as_deref(), Some("none"));
+        assert!(network_interfaces
+            .add_port_to_bridge("br0", "swp1", None)
+            .unwrap());
+        assert_eq!(ports(&network_interfaces).as_deref(), Some("swp1"));
+
+        // A VLAN-aware bridge stays a bridge without the option
+        network_interfaces
+            .get_interface_mut("br0")
+            .unwrap()
+            .options
+            .push(("bridge-vlan-aware".into(), "yes".to_string()));
+        assert!(network_interfaces
+            .remove_port_from_bridge("br0", "swp1", EmptyListPolicy::Remove, false)
+            .unwrap());
+        assert_eq!(ports(&network_interfaces), None);
+        assert!(network_interfaces
+            .add_port_to_bridge("br0", "swp1", None)
+            .unwrap());
+
+        let err = network_interfaces
+            .add_port_to_bridge("swp1", "swp2", Some(10))
+            .unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::WrongKind { .. }));
+    }
+
+    #[test]
+    fn test_set_vrf() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(
+            Interface::builder("mgmt")
+                .with_option("vrf-table", "auto")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("red")
+                .with_option("vrf-table", "1001")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("eth0")
+                .with_option("mtu", "1500")
+                .with_option("vrf", "mgmt")
+                .with_option("address", "192.0.2.1/24")
+                .with_option("vrf", "mgmt")
+                .build(),
+        );
+        network_interfaces.add_interface(Interface::builder("swp1").build());
+
+        // Duplicates collapse into one line at the first position
+        let emptied = network_interfaces
+            .set_vrf_with("eth0", Some("red"), &VrfOptions::default())
+            .unwrap();
+        assert_eq!(emptied.as_deref(), Some("mgmt"));
+        let eth0 = network_interfaces.get_interface("eth0").unwrap();
+        assert_eq!(
+            eth0.options,
+            vec![
+                ("mtu".into(), "1500".to_string()),
+                ("vrf".into(), "red".to_string()),
+                ("address".into(), "192.0.2.1/24".to_string()),
+            ]
+        );
+
+        network_interfaces.set_vrf("swp1", Some("red")).unwrap();
+        let emptied = network_interfaces
+            .set_vrf_with("eth0", None, &VrfOptions::default())
+            .unwrap();
+        assert_eq!(emptied, None);
+        let eth0 = network_interfaces.get_interface("eth0").unwrap();
+        assert_eq!(eth0.get_option("vrf"), None);
+
+        let err = network_interfaces.set_vrf("swp1", Some("blue")).unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::NotFound { ref name } if name == "blue"));
+        let err = network_interfaces.set_vrf("swp1", Some("eth0")).unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::WrongKind { .. }));
+        let err = network_interfaces.set_vrf("swp9", Some("red")).unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::NotFound { ref name } if name == "swp9"));
+        assert_eq!(
+            network_interfaces
+                .get_interface("swp1")
+                .unwrap()
+                .get_option("vrf"),
+            Some("red")
+        );
+
+        let options = VrfOptions {
+            allow_missing_vrf: true,
+        };
+        let emptied = network_interfaces
+            .set_vrf_with("swp1", Some("blue"), &options)
+            .unwrap();
+        assert_eq!(emptied.as_deref(), Some("red"));
+    }
+
+    #[test]
+    fn test_set_and_remove_option_on() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(Interface::builder("swp1").build());
+        network_interfaces.add_interface(
+            Interface::builder("swp2")
+                .with_option("mtu", "1500")
+                .with_option("alias", "uplink")
+                .with_option("mtu", "9000")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("swp3")
+                .with_option("mtu", "9216")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("eth0")
+                .with_option("mtu", "1500")
+                .build(),
+        );
+        let eth0 = network_interfaces.get_interface("eth0").unwrap().clone();
+
+        let is_swp = |iface: &Interface| iface.name.starts_with("swp");
+        assert_eq!(network_interfaces.set_option_on(is_swp, "mtu", "9216"), 2);
+        assert_eq!(network_interfaces.set_option_on(is_swp, "mtu", "9216"), 0);
+        for name in ["swp1", "swp2", "swp3"] {
+            assert_eq!(
+                network_interfaces.get_interface(name).unwrap().get_option("mtu"),
+                Some("9216")
+            );
+        }
+        assert_eq!(
+            network_interfaces.get_interface("swp2").unwrap().options,
+            vec![
+                ("mtu".into(), "9216".to_string()),
+                ("alias".into(), "uplink".to_string()),
+            ]
+        );
+
+        assert_eq!(network_interfaces.remove_option_on(is_swp, "mtu"), 3);
+        assert_eq!(network_interfaces.remove_option_on(is_swp, "mtu"), 0);
+        assert!(network_interfaces.get_interface("swp3").unwrap().options.is_empty());
+        assert_eq!(network_interfaces.get_interface("eth0"), Some(&eth0));
+    }
+
+    #[test]
+    fn test_option_on_changes_in_plan_save() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-bulk-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        let original = "auto eth0\niface eth0 inet dhcp\n\nauto swp1\niface swp1\n    mtu 9216\n";
+        fs::write(&path, original).unwrap();
+        let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        let actions = |net: &NetworkInterfaces| -> Vec<FileAction> {
+            net.plan_save().unwrap().iter().map(|planned| planned.action).collect()
+        };
+        let is_swp = |iface: &Interface| iface.name.starts_with("swp");
+
+        // Nothing to change leaves the file as it is
+        assert_eq!(net_ifaces.set_option_on(is_swp, "mtu", "9216"), 0);
+        assert_eq!(net_ifaces.remove_option_on(is_swp, "alias"), 0);
+        assert_eq!(actions(&net_ifaces), vec![FileAction::Unchanged]);
+
+        assert_eq!(net_ifaces.set_option_on(is_swp, "mtu", "1500"), 1);
+        assert_eq!(actions(&net_ifaces), vec![FileAction::Update]);
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            original.replace("mtu 9216", "mtu 1500")
+        );
+
+        assert_eq!(net_ifaces.remove_option_on(is_swp, "mtu"), 1);
+        assert_eq!(actions(&net_ifaces), vec![FileAction::Update]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_header_comments() {
+        let mut network_interfaces = NetworkInterfaces::parse_str(
+            "# Managed by hand\n\nauto lo\niface lo inet loopback\n# not a header\n",
+        )
+        .unwrap();
+        assert_eq!(network_interfaces.header_comments(), ["# Managed by hand"]);
+
+        network_interfaces.set_header_comments(vec![
+            "Generated by netd v1.2.3".to_string(),
+            String::new(),
+            "#do not edit".to_string(),
+        ]);
+        network_interfaces.add_header_comment("see netd.conf");
+        assert_eq!(
+            network_interfaces.header_comments(),
+            ["# Generated by netd v1.2.3", "#", "#do not edit", "# see netd.conf"]
+        );
+        let header = "# Generated by netd v1.2.3\n#\n#do not edit\n# see netd.conf\n\nauto lo\n";
+        assert!(network_interfaces.to_string().starts_with(header));
+
+        network_interfaces.set_header_comments(Vec::new());
+        assert!(network_interfaces.to_string().starts_with("auto lo\n"));
+    }
+
+    #[test]
+    fn test_sources() {
+        let mut network_interfaces = NetworkInterfaces::parse_str(
+            "source /etc/network/interfaces.d/*\nsource-directory /run/network\n",
+        )
+        .unwrap();
+        assert_eq!(
+            network_interfaces.source_directives(),
+            vec![
+                Source::glob("/etc/network/interfaces.d/*"),
+                Source::directory("/run/network"),
+            ]
+        );
+
+        let mut add = |line: String| network_interfaces.add_source(line).unwrap();
+        assert!(!add("source   /etc/network/interfaces.d/*".to_string()));
+        assert!(!add(Source::directory("/run/network").to_string()));
+        assert!(add(" source /srv/extra  ".to_string()));
+        assert!(add(Source::glob("/run/network").to_string()));
+
+        // Anything but one directive is refused
+        for line in ["iface eth0", "source", "", "source /a\niface evil", "source /a\rb"] {
+            assert!(network_interfaces.add_source(line).is_err(), "{:?}", line);
+        }
+        let err = network_interfaces
+            .add_source(Source::glob("/a\nauto evil").to_string())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid source directive: source /a\nauto evil");
+        assert_eq!(
+            network_interfaces.sources(),
+            [
+                "source /etc/network/interfaces.d/*",
+                "source-directory /run/network",
+                "source /srv/extra",
+                "source /run/network",
+            ]
+        );
+
+        assert_eq!(network_interfaces.remove_source(|line| line.contains("/run/")), 2);
+        assert_eq!(network_interfaces.remove_source(|line| line.contains("/run/")), 0);
+        assert_eq!(
+            network_interfaces.to_string(),
+            "source /etc/network/interfaces.d/*\nsource /srv/extra\n"
+        );
+    }
+
+    #[test]
+    fn test_header_and_sources_survive_save() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-header-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        let original = "\
+# SPDX-License-Identifier: MIT
+# Copyright (c) Example Networks
+#
+# Managed by hand; see interfaces(5).
+source /etc/network/interfaces.d/*
+source-directory /run/network/interfaces.d
+
+auto eth0
+iface eth0 inet dhcp
+
+auto lo
+iface lo inet loopback
+";
+        fs::write(&path, original).unwrap();
+
+        let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        assert_eq!(net_ifaces.header_comments().len(), 4);
+        assert_eq!(net_ifaces.sources().len(), 2);
+        net_ifaces.set_option_on(|iface| iface.name == "eth0", "mtu", "9000");
+        net_ifaces.save().unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with(&original[..original.find("auto").unwrap()]));
+
+        net_ifaces.remove_option_on(|iface| iface.name == "eth0", "mtu");
+        net_ifaces.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_provision_vni() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(
+            Interface::builder("br_default")
+                .with_option("bridge-ports", "swp1")
+                .with_option("bridge-vlan-aware", "yes")
+                .with_option("bridge-vids", "100")
+                .build(),
+        );
+        network_interfaces.add_interface(Interface::builder("vlan100").build());
+
+        let opts = VniOptions {
+            local_tunnel_ip: Some("10.0.0.1".to_string()),
+            mtu: Some(9000),
+        };
+        let provisioned = network_interfaces
+            .provision_vni(100200, 100..=101, "br_default", opts.clone())
+            .unwrap();
+        assert_eq!(
+            provisioned,
+            ProvisionedVni {
+                vlan_id: 101,
+                vni_interface: "vni100200".to_string(),
+                svi_interface: "vlan101".to_string(),
+                created: vec!["vni100200".to_string(), "vlan101".to_string()],
+            }
+        );
+        let vni = network_interfaces.get_interface("vni100200").unwrap();
+        assert_eq!(vni.get_option("vxlan-local-tunnelip"), Some("10.0.0.1"));
+        assert_eq!(vni.get_option("mtu"), Some("9000"));
+        let svi = network_interfaces.get_interface("vlan101").unwrap();
+        assert_eq!(svi.get_option("vlan-raw-device"), Some("br_default"));
+        let bridge = network_interfaces.get_interface("br_default").unwrap();
+        assert_eq!(bridge.get_option("bridge-ports"), Some("swp1 vni100200"));
+        assert_eq!(bridge.get_option("bridge-vids"), Some("100-101"));
+
+        // Provisioning again reuses the existing mapping
+        let before = network_interfaces.to_string();
+        let again = network_interfaces
+            .provision_vni(100200, 100..=101, "br_default", opts)
+            .unwrap();
+        assert_eq!(again.vlan_id, 101);
+        assert!(again.created.is_empty());
+        assert_eq!(network_interfaces.to_string(), before);
+
+        let err = network_interfaces
+            .provision_vni(100300, 100..=101, "br_default", VniOptions::default())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            NetworkInterfacesError::NoFreeVlan {
+                start: 100,
+                end: 101
+            }
+        ));
+        let err = network_interfaces
+            .provision_vni(1 << 24, 200..=299, "br_default", VniOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::OptionRange(_)));
+
+        // A failure after interfaces were created rolls them back
+        network_interfaces
+            .get_interface_mut("br_default")
+            .unwrap()
+            .options
+            .retain(|(key, _)| key != "bridge-vids");
+        network_interfaces
+            .get_interface_mut("br_default")
+            .unwrap()
+            .options
+            .push(("bridge-vids".into(), "bogus".to_string()));
+        let before = network_interfaces.to_string();
+        let err = network_interfaces
+            .provision_vni(100300, 200..=299, "br_default", VniOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::OptionParse(_)));
+        assert_eq!(network_interfaces.to_string(), before);
+        assert!(network_interfaces.get_interface("vni100300").is_none());
+    }
+
+    #[test]
+    fn test_get_existing_vni_vlan() {
+        let mut network_interfaces = NetworkInterfaces::new();
+
+        // Add a VNI interface
+        network_interfaces.add_interface(
+            Interface::builder("vni123456")
+                .with_auto(true)
+                .with_option("bridge-access", "1002")
+                .build(),
+        );
+
+        // Test: Find VLAN ID for existing VNI
+        assert_eq!(network_interfaces.get_existing_vni_vlan(123456), Some(1002));
+
+        // Test: No `bridge-access` option
+        network_interfaces.add_interface(Interface::builder("vni987654").with_auto(true).build());
+        assert_eq!(network_interfaces.get_existing_vni_vlan(987654), None);
+
+        // Test: Nonexistent VNI
+        assert_eq!(network_interfaces.get_existing_vni_vlan(666), None);
+    }
+
+    #[test]
+    fn test_get_bridge_interfaces() {
+        let mut network_interfaces = NetworkInterfaces::new();
+
+        // Add interfaces with `bridge-access`
+        network_interfaces.add_interface(
+            Interface::builder("vni1234")
+                .with_option("bridge-access", "1000")
+                .build(),
+        );
+        network_interfaces.add_interface(
+            Interface::builder("swp2")
+                .with_option("bridge-access", "1001")
+                .build(),
+        );
+
+        // Add an interface without `bridge-access`
+        network_interfaces.add_interface(Interface::builder("swp1").build());
+
+        // Verify the result
+        let bridge_interfaces = network_interfaces.get_bridge_interfaces();
+        assert_eq!(bridge_interfaces, vec!["vni1234", "swp2"]);
+    }
+
+    #[test]
+    fn test_strict_keys() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(
+            Interface::builder("vni100")
+                .with_option("bridge_access", "1000")
+                .build(),
+        );
+        assert_eq!(network_interfaces.get_existing_vni_vlan(100), Some(1000));
+        assert_eq!(network_interfaces.get_bridge_interfaces(), vec!["vni100"]);
+
+        network_interfaces.parser_options.strict_keys = true;
+        assert_eq!(network_interfaces.get_existing_vni_vlan(100), None);
+        assert!(network_interfaces.get_bridge_interfaces().is_empty());
+    }
+
+    #[test]
+    fn test_display_sort_order() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        for name in ["Vlan100", "vlan2", "VLAN10"] {
+            network_interfaces.add_interface(Interface::builder(name).build());
+        }
+        let order = |net: &NetworkInterfaces| -> Vec<String> {
+            net.to_string()
+                .lines()
+                .filter_map(|l| l.strip_prefix("iface "))
+                .map(str::to_string)
+                .collect()
+        };
+
+        // The order they were added in, by default
+        assert_eq!(order(&network_interfaces), vec!["Vlan100", "vlan2", "VLAN10"]);
+
+        network_interfaces.set_write_options(WriteOptions {
+            sort: SortOrder::Natural,
+            ..Default::default()
+        });
+        assert_eq!(order(&network_interfaces), vec!["VLAN10", "Vlan100", "vlan2"]);
+
+        network_interfaces.set_write_options(WriteOptions {
+            sort: SortOrder::NaturalCaseInsensitive,
+            ..Default::default()
+        });
+        assert_eq!(order(&network_interfaces), vec!["vlan2", "VLAN10", "Vlan100"]);
+    }
+
+    /// Renders `net_ifaces` the way `Display` did before it skipped the
+    /// formatting machinery, one `writeln!` per line.
+    fn reference_render(net_ifaces: &NetworkInterfaces) -> String {
+        use std::fmt::Write;
+        let mut out = String::new();
+        for line in net_ifaces.comments.iter().chain(&net_ifaces.sources) {
+            writeln!(out, "{}", line).unwrap();
+        }
+        for iface in net_ifaces.iter().map(|(_, iface)| iface) {
+            writeln!(out).unwrap();
+            if iface.auto {
+                writeln!(out, "auto {}", iface.name).unwrap();
+            }
+            for allow_type in &iface.allow {
+                writeln!(out, "allow-{} {}", allow_type, iface.name).unwrap();
+            }
+            for (i, stanza) in iface.stanzas().enumerate() {
+                if i > 0 {
+                    writeln!(out).unwrap();
+                }
+                write!(out, "iface {}", iface.name).unwrap();
+                if let Some(family) = stanza.family {
+                    write!(out, " {}", family).unwrap();
+                }
+                if let Some(method) = stanza.method {
+                    write!(out, " {}", method).unwrap();
+                }
+                writeln!(out).unwrap();
+                for (option_name, option_value) in stanza.options {
+                    writeln!(out, "    {} {}", option_name, option_value).unwrap();
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_display_matches_reference_renderer() {
+        let mut content = String::from("# generated\nsource /etc/network/interfaces.d/*\n");
+        for i in 0..1000 {
+            content.push_str(&format!("\nauto swp{}\n", i));
+            if i % 3 == 0 {
+                content.push_str(&format!("allow-hotplug swp{}\n", i));
+            }
+            if i % 7 == 0 {
+                content.push_str(&format!("iface swp{}\n    mtu 9216\n", i));
+                continue;
+            }
+            content.push_str(&format!(
+                "iface swp{} inet static\n    address 10.{}.{}.1/24\n    Bridge_Access {}\n\
+                 \x20   post-up ip link set swp{} up\n    post-up /bin/true\n",
+                i,
+                i / 256,
+                i % 256,
+                i % 4094 + 1,
+                i
+            ));
+            if i % 5 == 0 {
+                content.push_str(&format!("iface swp{} inet6 auto\n    accept_ra 2\n", i));
+            }
+        }
+        let mut net_ifaces = NetworkInterfaces::parse_str(&content).unwrap();
+        let expected = reference_render(&net_ifaces);
+        assert_eq!(net_ifaces.to_string(), expected);
+
+        // Saving renders into a buffer of its own
+        net_ifaces.path = Some(std::env::temp_dir().join("interface-rs-reference-missing"));
+        let plan = net_ifaces.plan_save().unwrap();
+        assert_eq!(plan[0].contents.as_deref(), Some(expected.as_str()));
+    }
+
+    #[test]
+    fn test_save_keeps_stanza_order() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-order-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        let original = "\
+auto swp10
+iface swp10
+    mtu 9216
+    alias uplink
+
+auto lo
+iface lo inet loopback
+
+auto br0
+iface br0 inet static
+    address 192.0.2.1/24
+    netmask 255.255.255.0
+    gateway 192.0.2.254
+    bridge-ports swp2 swp10
+    bridge-vlan-aware yes
+
+auto swp2
+iface swp2
+    post-up /usr/local/bin/b
+    link-speed 10000
+    post-up /usr/local/bin/a
+";
+        fs::write(&path, original).unwrap();
+
+        let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        net_ifaces.add_interface(Interface::builder("eth0").with_auto(true).build());
+        net_ifaces.set_option_on(|iface| iface.name == "swp2", "mtu", "9216");
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            original.to_string() + "    mtu 9216\n\nauto eth0\niface eth0\n"
+        );
+
+        // Options can be sorted by key on request
+        net_ifaces.set_write_options(WriteOptions {
+            sort_options: true,
+            ..Default::default()
+        });
+        assert!(net_ifaces.to_string().contains(
+            "iface br0 inet static\n    address 192.0.2.1/24\n    bridge-ports swp2 swp10\n    \
+             bridge-vlan-aware yes\n    gateway 192.0.2.254\n    netmask 255.255.255.0\n"
+        ));
+
+        // Sorting is still available
+        net_ifaces.set_write_options(WriteOptions {
+            sort: SortOrder::Natural,
+            ..Default::default()
+        });
+        let names: Vec<&str> = net_ifaces.sorted_iter().map(|iface| iface.name.as_str()).collect();
+        assert_eq!(names, ["br0", "eth0", "lo", "swp2", "swp10"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_str() {
+        assert_eq!(NetworkInterfaces::default(), NetworkInterfaces::new());
+        assert!(NetworkInterfaces::new().is_empty());
+
+        let content = "# Fetched from leaf1\nsource /etc/network/interfaces.d/*\n\n\
+                       auto swp1\niface swp1\n    mtu 9216\n";
+        let mut net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+        assert_eq!(net_ifaces.get_interface("swp1").unwrap().get_option("mtu"), Some("9216"));
+        assert_eq!(net_ifaces.to_string(), content);
+
+        // Without a path there is nowhere to save to, until one is given
+        let err = net_ifaces.save().unwrap_err();
+        assert_eq!(err.to_string(), "Error: No file path specified");
+        let dir = std::env::temp_dir().join(format!("interface-rs-parse-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        net_ifaces.save_as(&path).unwrap();
+        net_ifaces.get_interface_mut("swp1").unwrap().set_option("mtu", "1500");
+        net_ifaces.save().unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("mtu 1500"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let err = NetworkInterfaces::parse_str("auto eth0\niface\n").unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::Parser(_)));
+    }
+
+    #[test]
+    fn test_save_as_and_write_to() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-save-as-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("interfaces");
+        let original = "# Generated\n\nauto lo\niface lo inet loopback\n";
+        fs::write(&source, original).unwrap();
+        let mut net_ifaces = NetworkInterfaces::load(&source).unwrap();
+        net_ifaces.add_interface(Interface::builder("eth0").with_method("dhcp").build());
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hook_calls = calls.clone();
+        net_ifaces.set_post_save_hook(Box::new(move |path| {
+            hook_calls.lock().unwrap().push(path.to_path_buf());
+            Ok(())
+        }));
+
+        // Writing to a buffer touches no file
+        let mut buffer = Vec::new();
+        net_ifaces.write_to(&mut buffer).unwrap();
+        let expected = format!("{}\niface eth0 dhcp\n", original);
+        assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+        assert_eq!(net_ifaces.to_string(), expected);
+        assert_eq!(fs::read_to_string(&source).unwrap(), original);
+
+        // A changed source file does not stop saving elsewhere
+        net_ifaces.last_modified = Some(SystemTime::UNIX_EPOCH);
+        let target = dir.join("generated");
+        net_ifaces.save_as(&target).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), expected);
+        assert_eq!(fs::read_to_string(&source).unwrap(), original);
+        assert_eq!(std::mem::take(&mut *calls.lock().unwrap()), vec![target.clone()]);
+
+        // The collection is saved to the new file from now on
+        net_ifaces.save_as(&target).unwrap();
+        assert!(calls.lock().unwrap().is_empty());
+        net_ifaces.delete_interface("eth0");
+        net_ifaces.save().unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), original);
+        assert_eq!(fs::read_to_string(&source).unwrap(), original);
+
+        // An order that cannot be satisfied writes nothing
+        for (name, port) in [("br0", "br1"), ("br1", "br0")] {
+            let bridge = Interface::builder(name).with_option("bridge-ports", port).build();
+            net_ifaces.add_interface(bridge);
+        }
+        net_ifaces.set_write_options(WriteOptions {
+            sort: SortOrder::Dependency,
+            ..Default::default()
+        });
+        let mut buffer = Vec::new();
+        let err = net_ifaces.write_to(&mut buffer).unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::DependencyCycle(_)));
+        assert!(buffer.is_empty());
+        let fresh = dir.join("fresh");
+        let err = net_ifaces.save_as(&fresh).unwrap_err();
+        assert_eq!(err.path(), Some(fresh.as_path()));
+        assert!(!fresh.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-backup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        let backup = dir.join("interfaces.bak");
+        let original = "auto swp1\niface swp1\n";
+        fs::write(&path, original).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        net_ifaces.set_backup(Backup::default()).unwrap();
+
+        // Nothing changes, so nothing is backed up
+        net_ifaces.save().unwrap();
+        assert!(!backup.exists());
+
+        net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", "9216");
+        net_ifaces.save().unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+        assert!(fs::read_to_string(&path).unwrap().contains("mtu 9216"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&backup).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+
+        // A backup that cannot be made stops the save
+        fs::remove_file(&backup).unwrap();
+        fs::create_dir(&backup).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", "1500");
+        let err = net_ifaces.save().unwrap_err();
+        assert_eq!(err.io_op(), Some(IoOp::Backup));
+        assert_eq!(err.path(), Some(path.as_path()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+        fs::remove_dir(&backup).unwrap();
+
+        // Timestamped backups are capped, oldest removed first
+        fs::write(dir.join("interfaces.notes"), "keep me\n").unwrap();
+        net_ifaces.set_backup(Backup::Timestamped { keep: Some(2) }).unwrap();
+        let mut contents = Vec::new();
+        for mtu in ["1500", "1600", "1700"] {
+            contents.push(fs::read_to_string(&path).unwrap());
+            net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", mtu);
+            net_ifaces.save().unwrap();
+        }
+        let mut backups: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext.to_string_lossy().ends_with('Z')))
+            .collect();
+        backups.sort();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), contents[1]);
+        assert_eq!(fs::read_to_string(&backups[1]).unwrap(), contents[2]);
+        assert!(dir.join("interfaces.notes").exists());
+
+        // Without a backup set, none is made
+        net_ifaces.clear_backup();
+        net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", "1800");
+        net_ifaces.save().unwrap();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 4);
+
+        let at = |secs: u64, micros: u64| {
+            let since_epoch = std::time::Duration::from_secs(secs)
+                + std::time::Duration::from_micros(micros);
+            files::backup_timestamp(SystemTime::UNIX_EPOCH + since_epoch)
+        };
+        assert_eq!(at(0, 0), "19700101T000000.000000Z");
+        assert_eq!(at(951_868_799, 500), "20000229T235959.000500Z");
+        assert_eq!(at(1_792_143_000, 0), "20261016T093000.000000Z");
+
+        // A suffix must name another file in the same directory
+        for suffix in ["", "/bak", "../interfaces.bak"] {
+            let err = net_ifaces.set_backup(Backup::Suffix(suffix.to_string())).unwrap_err();
+            assert!(err.to_string().contains("Invalid backup suffix"));
+        }
+        net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", "1900");
+        net_ifaces.save().unwrap();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_fragments() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-bakdir-{}", std::process::id()));
+        let fragments = dir.join("interfaces.d");
+        fs::create_dir_all(&fragments).unwrap();
+        let main = dir.join("interfaces");
+        fs::write(&main, "source interfaces.d/*\n").unwrap();
+        let uplinks = fragments.join("uplinks");
+        fs::write(&uplinks, "iface swp1\n").unwrap();
+
+        let mut net_ifaces = NetworkInterfaces::load_with_includes(&main).unwrap();
+        net_ifaces.set_backup(Backup::default()).unwrap();
+        for mtu in ["9000", "9216"] {
+            net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", mtu);
+            net_ifaces.save().unwrap();
+        }
+        // The backup is hidden from the glob, so loading again finds no
+        // second definition of swp1
+        assert_eq!(
+            fs::read_to_string(fragments.join(".uplinks.bak")).unwrap(),
+            "iface swp1\n    mtu 9000\n"
+        );
+        assert!(!fragments.join("uplinks.bak").exists());
+        let reloaded = NetworkInterfaces::load_with_includes(&main).unwrap();
+        assert_eq!(reloaded.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_atomic_save() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-atomic-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        let original = "auto br0\niface br0\n    bridge-ports swp1\n";
+        fs::write(&path, original).unwrap();
+        let leftovers = || {
+            fs::read_dir(&dir)
+                .unwrap()
+                .filter(|entry| {
+                    entry.as_ref().unwrap().file_name().to_string_lossy().ends_with(".tmp")
+                })
+                .count()
+        };
+
+        // A write that cannot complete leaves the original file intact
+        let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        net_ifaces.set_option_on(|iface| iface.name == "br0", "mtu", "9216");
+        let blocker = files::temp_path_for(&path);
+        fs::create_dir(&blocker).unwrap();
+        let err = net_ifaces.save().unwrap_err();
+        assert_eq!(err.io_op(), Some(IoOp::CreateTemp));
+        assert_eq!(err.path(), Some(path.as_path()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+        fs::remove_dir(&blocker).unwrap();
+
+        // So does a failure to render
+        net_ifaces.add_interface(
+            Interface::builder("swp1").with_option("bridge-ports", "br0").build(),
+        );
+        net_ifaces.set_write_options(WriteOptions {
+            sort: SortOrder::Dependency,
+            ..Default::default()
+        });
+        let err = net_ifaces.save().unwrap_err();
+        assert!(matches!(err.inner(), NetworkInterfacesError::DependencyCycle(_)));
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+        net_ifaces.delete_interface("swp1");
+        net_ifaces.set_write_options(WriteOptions::default());
+
+        // A successful save replaces the file and keeps its permissions
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "auto br0\niface br0\n    bridge-ports swp1\n    mtu 9216\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+        assert_eq!(leftovers(), 0);
+
+        // A symbolic link is followed rather than replaced
+        #[cfg(unix)]
+        {
+            let link = dir.join("link");
+            std::os::unix::fs::symlink(&path, &link).unwrap();
+            let mut net_ifaces = NetworkInterfaces::load(&link).unwrap();
+            net_ifaces.set_option_on(|iface| iface.name == "br0", "mtu", "1500");
+            net_ifaces.save().unwrap();
+            assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+            assert!(fs::read_to_string(&path).unwrap().contains("mtu 1500"));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_custom_sort() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.add_interface(
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1 swp2")
+                .build(),
+        );
+        for name in ["vlan10", "swp2", "bond0", "eth0", "swp10", "lo", "swp1"] {
+            network_interfaces.add_interface(Interface::builder(name).build());
+        }
+
+        // Loopback, then management, then physical ports, then logical devices
+        fn kind(iface: &Interface) -> u8 {
+            if iface.name == "lo" {
+                0
+            } else if iface.name.starts_with("eth") {
+                1
+            } else if iface.name.starts_with("swp") {
+                2
+            } else if iface.get_option("bridge-ports").is_some() {
+                3
+            } else {
+                4
+            }
+        }
+        network_interfaces.set_sort(Box::new(|a, b| {
+            kind(a)
+                .cmp(&kind(b))
+                .then_with(|| natural(&a.name, &b.name))
+        }));
+
+        let names: Vec<&str> = network_interfaces
+            .sorted_iter()
+            .map(|i| i.name.as_str())
+            .collect();
+        let expected = vec!["lo", "eth0", "swp1", "swp2", "swp10", "br0", "bond0", "vlan10"];
+        assert_eq!(names, expected);
+
+        let output = network_interfaces.to_string();
+        let rendered: Vec<&str> = output
+            .lines()
+            .filter_map(|l| l.strip_prefix("iface "))
+            .collect();
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_dependency_sort() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        network_interfaces.set_write_options(WriteOptions {
+            sort: SortOrder::Dependency,
+            ..Default::default()
+        });
+        network_interfaces.add_interface(
+            Interface::builder("br0")
+                .with_option("bridge-ports", "swp1 swp2")
+                .build(),
+        );
+        network_interfaces.add_interface(Interface::builder("br0.10").build());
+        network_interfaces.add_interface(Interface::builder("swp2").build());
+        network_interfaces.add_interface(Interface::builder("swp1").build());
+
+        let output = network_interfaces.to_string();
+        let rendered: Vec<&str> = output
+            .lines()
+            .filter_map(|l| l.strip_prefix("iface "))
+            .collect();
+        assert_eq!(rendered, vec!["swp1", "swp2", "br0", "br0.10"]);
+
+        // A cycle is reported when saving, and Display falls back to natural order
+        network_interfaces.add_interface(
+            Interface::builder("swp1")
+                .with_option("vrf", "br0.10")
+                .build(),
+        );
+        let err = network_interfaces.ordered().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Dependency cycle between interfaces: br0 -> swp1 -> br0.10 -> br0"
+        );
+        let names: Vec<&str> = network_interfaces
+            .sorted_iter()
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["br0", "br0.10", "swp1", "swp2"]);
+    }
+
+    #[test]
+    fn test_errors_carry_path() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-paths-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Missing file
+        let missing = dir.join("missing");
+        let err = NetworkInterfaces::load(&missing).unwrap_err();
+        assert_eq!(err.path(), Some(missing.as_path()));
+        assert!(matches!(err.inner(), NetworkInterfacesError::Io(_)));
+        assert_eq!(err.io_op(), Some(IoOp::ReadMetadata));
+        assert!(err
+            .to_string()
+            .starts_with(&format!("{}: read metadata failed: I/O error", missing.display())));
+
+        // A directory has metadata but cannot be read as a file
+        let err = NetworkInterfaces::load(&dir).unwrap_err();
+        assert_eq!(err.io_op(), Some(IoOp::ReadFile));
+        assert!(err.to_string().contains("read file failed"));
+
+        // Parse failure
+        let broken = dir.join("broken");
+        fs::write(&broken, "auto eth0\niface\n").unwrap();
+        let err = NetworkInterfaces::load(&broken).unwrap_err();
+        assert!(matches!(err.inner(), NetworkInterfacesError::Parser(_)));
+        let message = err.to_string();
+        assert!(message.contains(&broken.display().to_string()), "{}", message);
+        assert!(message.contains("line 2"), "{}", message);
+        assert!(std::error::Error::source(&err).is_some());
+
+        // Modified on disk before saving, then reloaded after removal
+        let file = dir.join("interfaces");
+        fs::write(&file, "auto lo\niface lo inet loopback\n").unwrap();
+        let mut net_ifaces = NetworkInterfaces::load(&file).unwrap();
+        net_ifaces.last_modified = Some(SystemTime::UNIX_EPOCH);
+        let err = net_ifaces.save().unwrap_err();
+        assert!(matches!(err.inner(), NetworkInterfacesError::FileModified));
+        assert_eq!(err.path(), Some(file.as_path()));
+
+        // Saving over a directory fails when moving the new file into place
+        net_ifaces.last_modified = None;
+        net_ifaces.path = Some(dir.clone());
+        let err = net_ifaces.save().unwrap_err();
+        assert_eq!(err.io_op(), Some(IoOp::Rename));
+        assert!(matches!(err.inner(), NetworkInterfacesError::Io(_)));
+        net_ifaces.path = Some(file.clone());
+
+        fs::remove_file(&file).unwrap();
+        let err = net_ifaces.reload().unwrap_err();
+        assert!(err.to_string().contains(&file.display().to_string()));
+        assert_eq!(err.io_op(), Some(IoOp::ReadMetadata));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_size_limit() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-size-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("interfaces");
+        fs::write(&file, "auto lo\niface lo inet loopback\n").unwrap();
+
+        let options = |max_input_size| ParserOptions {
+            max_input_size,
+            ..Default::default()
+        };
+        let err = NetworkInterfaces::load_with(&file, options(Some(16))).unwrap_err();
+        assert_eq!(err.path(), Some(file.as_path()));
+        assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+        let NetworkInterfacesError::LimitExceeded(exceeded) = err.inner() else {
+            panic!("expected a limit error, got {:?}", err);
+        };
+        assert_eq!(exceeded.limit, Limit::InputSize);
+        assert_eq!((exceeded.max, exceeded.actual), (16, Some(31)));
+        assert!(err.to_string().ends_with("Input exceeds the input size limit of 16 (found 31)"));
+
+        // The limit can be raised or disabled
+        assert!(NetworkInterfaces::load_with(&file, options(Some(31))).is_ok());
+        assert!(NetworkInterfaces::load_with(&file, options(None)).is_ok());
+        assert_eq!(
+            ParserOptions::default().max_input_size,
+            Some(ParserOptions::DEFAULT_MAX_INPUT_SIZE)
+        );
+
+        // Special files report no size, so the read itself is bounded
+        #[cfg(target_os = "linux")]
+        {
+            let err = NetworkInterfaces::load_with("/dev/zero", options(Some(1024))).unwrap_err();
+            let NetworkInterfacesError::LimitExceeded(exceeded) = err.inner() else {
+                panic!("expected a limit error, got {:?}", err);
+            };
+            assert_eq!((exceeded.max, exceeded.actual), (1024, None));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_validated() {
+        let dir =
+            std::env::temp_dir().join(format!("interface-rs-validated-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("interfaces");
+        fs::write(&file, "auto swp1\niface swp1\n    mtu 9000\n").unwrap();
+        let mut net_ifaces = NetworkInterfaces::load(&file).unwrap();
+
+        net_ifaces.add_interface(
+            Interface::builder("swp2")
+                .with_option("mtu", "10")
+                .with_option("bridge-access", "abc")
+                .build(),
+        );
+        let err = net_ifaces.save_validated().unwrap_err();
+        let report = err.validation_report().unwrap();
+        let codes: Vec<&str> = report.errors().map(|i| i.code).collect();
+        assert_eq!(codes, vec!["option-out-of-range", "invalid-option-value"]);
+        assert_eq!(err.kind(), crate::error::ErrorKind::Validation);
+        assert_eq!(err.path(), Some(file.as_path()));
+        assert!(fs::read_to_string(&file).unwrap().contains("mtu 9000"));
+        assert!(!fs::read_to_string(&file).unwrap().contains("swp2"));
+
+        net_ifaces.add_interface(Interface::builder("swp2").with_option("mtu", "1500").build());
+        net_ifaces.save_validated().unwrap();
+        assert!(fs::read_to_string(&file).unwrap().contains("iface swp2"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_post_save_hook() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-hook-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("interfaces");
+        fs::write(&file, "auto lo\niface lo inet loopback\n").unwrap();
+        let mut net_ifaces = NetworkInterfaces::load(&file).unwrap();
+
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let fail = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let (hook_calls, hook_fail) = (calls.clone(), fail.clone());
+        net_ifaces.set_post_save_hook(Box::new(move |path| {
+            hook_calls.lock().unwrap().push(path.to_path_buf());
+            if hook_fail.load(std::sync::atomic::Ordering::SeqCst) {
+                Err("ifreload -a exited with status 1".to_string())
+            } else {
+                Ok(())
+            }
+        }));
+        let calls = || std::mem::take(&mut *calls.lock().unwrap());
+
+        // Neither a dry run nor a save that changes nothing runs the hook
+        net_ifaces.plan_save().unwrap();
+        net_ifaces.save().unwrap();
+        assert!(calls().is_empty());
+
+        net_ifaces.get_interface_mut("lo").unwrap().add_option("mtu", "9000");
+        net_ifaces.save().unwrap();
+        assert_eq!(calls(), vec![file.clone()]);
+
+        // Nothing is written, so the hook does not run
+        let saved_at = net_ifaces.last_modified;
+        net_ifaces.last_modified = Some(SystemTime::UNIX_EPOCH);
+        net_ifaces.get_interface_mut("lo").unwrap().set_option("mtu", "1500");
+        let err = net_ifaces.save().unwrap_err();
+        assert!(matches!(err.inner(), NetworkInterfacesError::FileModified));
+        assert!(calls().is_empty());
+        net_ifaces.last_modified = saved_at;
+
+        // A failing hook reports that the file was saved anyway
+        fail.store(true, std::sync::atomic::Ordering::SeqCst);
+        let err = net_ifaces.save().unwrap_err();
+        assert_eq!(calls(), vec![file.clone()]);
+        assert!(matches!(err.inner(), NetworkInterfacesError::PostSaveHook(_)));
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(err.path(), Some(file.as_path()));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{}: Saved, but the post-save hook failed: ifreload -a exited with status 1",
+                file.display()
+            )
+        );
+        assert!(fs::read_to_string(&file).unwrap().contains("mtu 1500"));
+        // The save was recorded, so the next one is not refused as modified
+        net_ifaces.save().unwrap();
+        assert!(calls().is_empty());
+
+        // Clones share the hook until it is cleared
+        let mut copy = net_ifaces.clone();
+        copy.get_interface_mut("lo").unwrap().remove_option("mtu");
+        assert!(copy.save().is_err());
+        assert_eq!(calls(), vec![file.clone()]);
+        copy.clear_post_save_hook();
+        copy.get_interface_mut("lo").unwrap().add_option("mtu", "9000");
+        copy.save().unwrap();
+        assert!(calls().is_empty());
+
+        // A failing hook after a split save keeps the split
+        let mut net_ifaces = NetworkInterfaces::load(&file).unwrap();
+        net_ifaces.set_post_save_hook(Box::new(|_| Err("failed".to_string())));
+        let fragments = dir.join("interfaces.d");
+        let err = net_ifaces.save_split(&fragments, None).unwrap_err();
+        assert!(matches!(err.inner(), NetworkInterfacesError::PostSaveHook(_)));
+        assert_eq!(net_ifaces.file_of("lo"), Some(fragments.join("lo").as_path()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_multiple_files() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-files-{}", std::process::id()));
+        let fragments = dir.join("interfaces.d");
+        fs::create_dir_all(&fragments).unwrap();
+        let main = dir.join("interfaces");
+        fs::write(
+            &main,
+            "source interfaces.d/*\n\nauto lo\niface lo inet loopback\n\niface swp1\n",
+        )
+        .unwrap();
+        let mut net_ifaces = NetworkInterfaces::load(&main).unwrap();
+        assert_eq!(net_ifaces.file_of("swp1"), Some(main.as_path()));
+
+        let swp1_file = fragments.join("swp1");
+        let new_file = fragments.join("new");
+        net_ifaces.set_file("swp1", &swp1_file).unwrap();
+        net_ifaces.set_default_target(SaveTarget::File(new_file.clone()));
+        net_ifaces.add_interface(Interface::builder("swp2").build());
+        assert_eq!(net_ifaces.file_of("swp2"), Some(new_file.as_path()));
+        assert!(matches!(
+            net_ifaces.set_file("swp9", &swp1_file),
+            Err(NetworkInterfacesError::NotFound { .. })
+        ));
+
+        // A dry run reports every file without writing any
+        let plan = net_ifaces.plan_save().unwrap();
+        let actions: Vec<(&Path, FileAction)> = plan
+            .iter()
+            .map(|planned| (planned.path.as_path(), planned.action))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                (main.as_path(), FileAction::Update),
+                (new_file.as_path(), FileAction::Create),
+                (swp1_file.as_path(), FileAction::Create),
+            ]
+        );
+        assert!(!swp1_file.exists());
+
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&main).unwrap(),
+            "source interfaces.d/*\n\nauto lo\niface lo inet loopback\n"
+        );
+        assert_eq!(fs::read_to_string(&swp1_file).unwrap(), "iface swp1\n");
+        assert_eq!(fs::read_to_string(&new_file).unwrap(), "iface swp2\n");
+        assert!(net_ifaces
+            .plan_save()
+            .unwrap()
+            .iter()
+            .all(|planned| planned.action == FileAction::Unchanged));
+
+        // Saved interfaces stay put when the default target changes
+        net_ifaces.set_default_target(SaveTarget::Main);
+        assert_eq!(net_ifaces.file_of("swp2"), Some(new_file.as_path()));
+
+        // Emptied files are deleted
+        net_ifaces.delete_interface("swp1");
+        net_ifaces.save().unwrap();
+        assert!(!swp1_file.exists());
+        assert!(new_file.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_split() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-split-{}", std::process::id()));
+        let fragments = dir.join("interfaces.d");
+        fs::create_dir_all(&fragments).unwrap();
+        let main = dir.join("interfaces");
+        fs::write(
+            &main,
+            "# Managed by hand\n\nauto lo\niface lo inet loopback\n\n\
+             auto eth0.100\niface eth0.100 inet manual\n\niface swp1\n",
+        )
+        .unwrap();
+        // Left over from an earlier split, and a file that is not ours
+        fs::write(fragments.join("swp9"), "iface swp9\n").unwrap();
+        fs::write(fragments.join("extra"), "iface swp8\n").unwrap();
+
+        let mut net_ifaces = NetworkInterfaces::load(&main).unwrap();
+        net_ifaces.save_split(&fragments, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&main).unwrap(),
+            format!("# Managed by hand\nsource {}/*\n", fragments.display())
+        );
+        let mut names: Vec<String> = fs::read_dir(&fragments)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["eth0_100", "extra", "lo", "swp1"]);
+        assert!(names.iter().all(|name| files::is_valid_file_name(name)));
+        assert_eq!(
+            fs::read_to_string(fragments.join("eth0_100")).unwrap(),
+            "auto eth0.100\niface eth0.100 inet manual\n"
+        );
+        assert_eq!(net_ifaces.file_of("swp1"), Some(fragments.join("swp1").as_path()));
+
+        // Later saves keep the layout and delete emptied files
+        net_ifaces.delete_interface("swp1");
+        net_ifaces.add_interface(Interface::builder("swp2").build());
+        net_ifaces.set_default_target(SaveTarget::File(fragments.join("swp2")));
+        net_ifaces.save().unwrap();
+        assert!(!fragments.join("swp1").exists());
+        assert_eq!(fs::read_to_string(fragments.join("swp2")).unwrap(), "iface swp2\n");
+
+        // Names that map to the same file are refused before writing
+        net_ifaces.add_interface(Interface::builder("eth0_100").build());
+        let err = net_ifaces.save_split(&fragments, None).unwrap_err();
+        assert!(matches!(
+            err,
+            NetworkInterfacesError::FileConflict { ref names, .. }
+                if names == &["eth0.100", "eth0_100"]
+        ));
+        assert_eq!(net_ifaces.file_of("eth0_100"), Some(fragments.join("swp2").as_path()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disable_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-off-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        fs::write(&path, "auto eth0\niface eth0 inet static\n    address 10.0.0.1/24\n").unwrap();
+
+        let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        assert!(net_ifaces.disable_interface("eth0").unwrap());
+        assert!(!net_ifaces.disable_interface("eth0").unwrap());
+        assert!(matches!(
+            net_ifaces.disable_interface("eth9"),
+            Err(NetworkInterfacesError::NotFound { .. })
+        ));
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "#disabled# auto eth0\n#disabled# iface eth0 inet static\n\
+             #disabled#     address 10.0.0.1/24\n"
+        );
+
+        let mut reloaded = NetworkInterfaces::load(&path).unwrap();
+        let disabled: Vec<&String> = reloaded
+            .iter()
+            .filter(|(_, iface)| iface.disabled)
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(disabled, ["eth0"]);
+        assert!(reloaded.check_roundtrip().is_ok());
+
+        assert!(reloaded.enable_interface("eth0").unwrap());
+        reloaded.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "auto eth0\niface eth0 inet static\n    address 10.0.0.1/24\n"
+        );
+
+        // The marker is configurable
+        let options = ParserOptions {
+            disabled_marker: "## ".to_string(),
+            ..Default::default()
+        };
+        let mut net_ifaces = NetworkInterfaces::load_with(&path, options.clone()).unwrap();
+        net_ifaces.disable_interface("eth0").unwrap();
+        assert!(net_ifaces.to_string().contains("\n## iface eth0 inet static\n"));
+        net_ifaces.save().unwrap();
+        let reloaded = NetworkInterfaces::load_with(&path, options).unwrap();
+        assert!(reloaded.get_interface("eth0").unwrap().disabled);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_interfaces_in_subnet() {
+        let content = "iface eth0 inet static\n    address 10.0.1.1/24\n    address 10.9.0.1/16\n\
+                       iface eth1 inet static\n    address 10.0.2.1/255.255.255.0\n    vrf mgmt\n\
+                       iface eth2 inet static\n    address 10.0.3.1\n    netmask 255.255.255.0\n\
+                       iface eth2 inet6 static\n    address 2001:db8:1::1/64\n\
+                       iface mgmt\n    vrf-table auto\n    address 10.0.4.1/32\n\
+                       iface eth3 inet dhcp\n";
+        let net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+        let names = |found: Vec<&Interface>| -> Vec<String> {
+            found.iter().map(|iface| iface.name.clone()).collect()
+        };
+        let v4: IpAddr = "10.0.0.0".parse().unwrap();
+
+        assert_eq!(
+            names(net_ifaces.interfaces_in_subnet(v4, 16, None)),
+            ["eth0", "eth1", "eth2", "mgmt"]
+        );
+        assert_eq!(names(net_ifaces.interfaces_in_subnet(v4, 24, None)), Vec::<String>::new());
+        assert_eq!(
+            names(net_ifaces.interfaces_in_subnet("10.0.2.0".parse().unwrap(), 24, None)),
+            ["eth1"]
+        );
+        assert_eq!(
+            names(net_ifaces.interfaces_in_subnet("2001:db8::".parse().unwrap(), 32, None)),
+            ["eth2"]
+        );
+
+        // Only the VRF device and its members are searched
+        assert_eq!(
+            names(net_ifaces.interfaces_in_subnet(v4, 8, Some("mgmt"))),
+            ["eth1", "mgmt"]
+        );
+        assert!(net_ifaces.interfaces_in_subnet(v4, 8, Some("blue")).is_empty());
+        // Surrounding whitespace is ignored, as when setting the VRF
+        let padded = NetworkInterfaces::from_interfaces([Interface::builder("eth1")
+            .with_option("address", "10.0.2.1/24")
+            .with_option("vrf", "mgmt ")
+            .build()]);
+        assert_eq!(names(padded.interfaces_in_subnet(v4, 8, Some("mgmt"))), ["eth1"]);
+
+        let found: Vec<(String, String)> = net_ifaces
+            .addresses_in_subnet(v4, 8, None)
+            .into_iter()
+            .map(|(iface, addr)| (iface.name.clone(), addr.to_string()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("eth0".to_string(), "10.0.1.1/24".to_string()),
+                ("eth0".to_string(), "10.9.0.1/16".to_string()),
+                ("eth1".to_string(), "10.0.2.1/24".to_string()),
+                ("eth2".to_string(), "10.0.3.1/24".to_string()),
+                ("mgmt".to_string(), "10.0.4.1/32".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_free_address() {
+        let ip = |s: &str| -> IpAddr { s.parse().unwrap() };
+        let mut net_ifaces = NetworkInterfaces::from_interfaces([
+            Interface::builder("lo")
+                .with_option("address", "10.0.0.1/32")
+                .with_option("address", "2001:db8::1/128")
+                .build(),
+            Interface::builder("swp1")
+                .with_option("address", "10.0.0.2/28")
+                .with_option("address", "10.0.0.17/28")
+                .build(),
+            Interface::builder("swp2")
+                .with_option("address", "10.0.0.4")
+                .with_option("netmask", "255.255.255.240")
+                .build(),
+        ]);
+
+        // A partially populated /28
+        let network = ip("10.0.0.0");
+        assert_eq!(net_ifaces.next_free_address(network, 28, &[]), Some(ip("10.0.0.3")));
+        assert_eq!(
+            net_ifaces.next_free_address(ip("10.0.0.9"), 28, &[ip("10.0.0.3"), ip("10.0.0.5")]),
+            Some(ip("10.0.0.6"))
+        );
+
+        // An exhausted one; the broadcast address is not handed out
+        let skip: Vec<IpAddr> = (5..15).map(|i| ip(&format!("10.0.0.{}", i))).collect();
+        assert_eq!(net_ifaces.next_free_address(network, 28, &skip), Some(ip("10.0.0.3")));
+        net_ifaces.add_interface(
+            Interface::builder("swp3")
+                .with_option("address", "10.0.0.3/28")
+                .build(),
+        );
+        assert_eq!(net_ifaces.next_free_address(network, 28, &skip), None);
+
+        // Point-to-point networks have no network or broadcast address
+        assert_eq!(net_ifaces.next_free_address(ip("10.0.0.16"), 31, &[]), Some(ip("10.0.0.16")));
+        assert_eq!(net_ifaces.next_free_address(ip("10.0.0.17"), 32, &[]), None);
+        assert_eq!(net_ifaces.next_free_address(network, 33, &[]), None);
+
+        // IPv6 is searched within the first /120
+        let network = ip("2001:db8::");
+        assert_eq!(net_ifaces.next_free_address(network, 64, &[]), Some(ip("2001:db8::2")));
+        let skip: Vec<IpAddr> = (2..=255).map(|i| ip(&format!("2001:db8::{:x}", i))).collect();
+        assert_eq!(net_ifaces.next_free_address(network, 64, &skip), None);
+        assert_eq!(net_ifaces.next_free_address(network, 127, &[]), Some(ip("2001:db8::")));
+    }
+
+    #[test]
+    fn test_get_interface_by_mac() {
+        let net_ifaces = NetworkInterfaces::from_interfaces([
+            Interface::builder("eth0")
+                .with_option("hwaddress", "52:54:00:AB:CD:01")
+                .build(),
+            Interface::builder("eth1")
+                .with_option("hwaddress", "ether 52:54:00:ab:cd:01")
+                .build(),
+            Interface::builder("eth2")
+                .with_option("hwaddress", "52:54:00:ab:cd:02")
+                .build(),
+            Interface::builder("eth3").with_option("hwaddress", "random").build(),
+            Interface::builder("eth4")
+                .with_family(Family::Inet)
+                .with_family_stanza(FamilyStanza {
+                    family: Some(Family::Inet6),
+                    method: Some(Method::Auto),
+                    options: vec![("hwaddress".into(), "52:54:00:ab:cd:01".to_string())],
+                })
+                .build(),
+        ]);
+        let names = |found: Vec<&Interface>| -> Vec<String> {
+            found.iter().map(|iface| iface.name.clone()).collect()
         };
 
-        // Check if file has been modified since last load
-        let metadata = fs::metadata(&path)?;
-        let current_modified = metadata.modified()?;
-        if let Some(last_modified) = self.last_modified {
-            if current_modified > last_modified {
-                // File has been modified since last read
-                return Err(NetworkInterfacesError::FileModified);
-            }
+        let mac: MacAddr = "52:54:00:ab:cd:01".parse().unwrap();
+        assert_eq!(names(net_ifaces.get_interface_by_mac(&mac)), ["eth0", "eth1", "eth4"]);
+        let found = net_ifaces.get_interface_by_mac_str("5254.00ab.cd02").unwrap();
+        assert_eq!(names(found), ["eth2"]);
+        assert!(net_ifaces.get_interface_by_mac_str("52:54:00:ab:cd:03").unwrap().is_empty());
+        assert!(net_ifaces.get_interface_by_mac_str("random").is_err());
+    }
+
+    #[test]
+    fn test_find_by_tag() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/tags/interfaces");
+        let net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        let names = |found: Vec<&Interface>| -> Vec<String> {
+            found.into_iter().map(|iface| iface.name.clone()).collect()
+        };
+        assert_eq!(names(net_ifaces.find_by_tag("tenant", "acme")), ["swp1", "swp2"]);
+        assert_eq!(names(net_ifaces.find_by_tag("tier", "prod")), ["swp1", "swp4"]);
+        assert_eq!(names(net_ifaces.find_by_tag("spare", "")), ["swp2"]);
+        assert!(net_ifaces.find_by_tag("tenant", "initech").is_empty());
+
+        assert!(net_ifaces.tags_of("swp3").unwrap().is_empty());
+        assert!(net_ifaces.tags_of("swp9").is_none());
+        // Magic comments are not header comments
+        assert_eq!(
+            net_ifaces.comments,
+            ["# Leaf switch, tenant ports are tagged for the provisioning tools"]
+        );
+
+        // Tags are written back in canonical form and read back the same
+        let rendered = net_ifaces.to_string();
+        assert!(rendered.contains("\n# tags: spare, tenant=acme, tier=dev\nauto swp2\n"));
+        assert!(rendered.contains("\n#disabled# # tags: tenant=globex, tier=prod\n"));
+        assert!(net_ifaces.check_roundtrip().is_ok());
+        let (reparsed, _, _) = Parser::new().parse(&rendered).unwrap();
+        for (name, iface) in &reparsed {
+            assert_eq!(Some(&iface.tags), net_ifaces.tags_of(name));
         }
+    }
+
+    #[test]
+    fn test_bringup_order() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/interfaces");
+        let mut net_ifaces = NetworkInterfaces::load(path).unwrap();
+        let names = |order: Vec<&Interface>| -> Vec<String> {
+            order.into_iter().map(|iface| iface.name.clone()).collect()
+        };
+        let bringup = names(net_ifaces.bringup_order().unwrap());
+        assert_eq!(
+            bringup,
+            [
+                "lo", "eth1", "mgmt", "swp1", "swp2", "swp10", "vni100100", "bond0", "eth0",
+                "br_default", "vlan100",
+            ]
+        );
+        let mut teardown = names(net_ifaces.teardown_order().unwrap());
+        teardown.reverse();
+        assert_eq!(teardown, bringup);
 
-        // Write to the file using Display implementation
-        let mut file = fs::File::create(&path)?;
-        write!(file, "{}", self)?;
+        // Disabled interfaces are not brought up
+        net_ifaces.get_interface_mut("swp1").unwrap().disabled = true;
+        assert!(!names(net_ifaces.bringup_order().unwrap()).contains(&"swp1".to_string()));
 
-        // Update last_modified
-        self.last_modified = Some(SystemTime::now());
-        Ok(())
+        net_ifaces.add_interface(
+            Interface::builder("swp2").with_option("vrf", "vlan100").build(),
+        );
+        let err = net_ifaces.bringup_order().unwrap_err();
+        assert_eq!(err.0, ["bond0", "swp2", "vlan100", "br_default"]);
+        assert_eq!(
+            NetworkInterfacesError::from(err.clone()).to_string(),
+            err.to_string()
+        );
+        assert!(net_ifaces.teardown_order().is_err());
     }
 
-    /// Reloads the interfaces file from disk.
-    ///
-    /// # Errors
-    ///
-    /// Returns a `NetworkInterfacesError` if the file cannot be read or parsed.
-    pub fn reload(&mut self) -> Result<(), NetworkInterfacesError> {
-        let path = match &self.path {
-            Some(p) => p.clone(),
-            None => {
-                return Err(NetworkInterfacesError::Other(
-                    "No file path specified".to_string(),
-                ))
-            }
+    #[test]
+    fn test_bond_membership() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/bond/interfaces");
+        let mut net_ifaces = NetworkInterfaces::load(path).unwrap();
+        let names = |found: Vec<&Interface>| -> Vec<String> {
+            found.into_iter().map(|iface| iface.name.clone()).collect()
         };
-        let reloaded = NetworkInterfaces::load(path)?;
-        self.interfaces = reloaded.interfaces;
-        self.comments = reloaded.comments;
-        self.sources = reloaded.sources;
-        self.last_modified = reloaded.last_modified;
-        Ok(())
+        assert_eq!(names(net_ifaces.slaves_of("bond0")), ["swp1", "swp2"]);
+        assert!(net_ifaces.slaves_of("swp3").is_empty());
+        assert!(net_ifaces.slaves_of("bond9").is_empty());
+        assert_eq!(net_ifaces.bond_of("swp2").unwrap().name, "bond0");
+        assert!(net_ifaces.bond_of("swp3").is_none());
+        assert!(net_ifaces.bond_of("bond0").is_none());
+        assert!(net_ifaces.validate().is_valid());
+
+        // Old-style membership through `bond-master`, which wins over the
+        // lists of other bonds
+        net_ifaces.set_option_on(|iface| iface.name == "bond0", "bond-slaves", "none");
+        net_ifaces.set_option_on(|iface| iface.name.starts_with("swp"), "bond-master", "bond0");
+        net_ifaces.add_interface(
+            Interface::builder("bond1").with_option("bond-slaves", "swp?").build(),
+        );
+        assert_eq!(names(net_ifaces.slaves_of("bond0")), ["swp1", "swp2", "swp3"]);
+        assert_eq!(names(net_ifaces.slaves_of("bond1")), ["swp1", "swp2", "swp3"]);
+        assert_eq!(net_ifaces.bond_of("swp3").unwrap().name, "bond0");
+
+        // Each port now belongs to two bonds
+        let report = net_ifaces.validate();
+        let conflicts: Vec<(&str, &str)> = report
+            .errors()
+            .map(|issue| (issue.interface.as_deref().unwrap(), issue.message.as_str()))
+            .collect();
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(
+            conflicts[0],
+            ("swp1", "Port is claimed by bond0 (bond-master), bond1 (bond-slaves)")
+        );
+        assert!(report.errors().all(|issue| issue.code == "multiple-masters"));
     }
-}
 
-// Implement Display for NetworkInterfaces to allow easy printing
-impl fmt::Display for NetworkInterfaces {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // Print comments at the top if any
-        for comment in &self.comments {
-            writeln!(f, "{}", comment)?;
-        }
+    #[test]
+    fn test_orphans() {
+        let content = r#"
+iface lo inet loopback
+
+allow-hotplug eth0
+iface eth0 inet dhcp
+
+iface eth1
+    mtu 9000
+
+iface eth2
 
-        // Print source directives if any
-        for source in &self.sources {
-            writeln!(f, "{}", source)?;
+iface vlan10
+    vlan-raw-device eth1
+    vlan-id 10
+
+auto br0
+iface br0
+    bridge-ports vlan10 swp3
+    vrf red
+
+iface red
+    vrf-table auto
+
+iface swp1
+
+iface swp1.20
+
+#disabled# iface swp2
+"#;
+        let net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+        let names = |found: Vec<&Interface>| -> Vec<String> {
+            found.into_iter().map(|iface| iface.name.clone()).collect()
+        };
+        // eth1 is only referenced through vlan-raw-device, and swp1 through
+        // the name of swp1.20
+        assert_eq!(names(net_ifaces.orphans()), ["eth2", "swp1.20", "swp2"]);
+
+        assert_eq!(names(net_ifaces.referenced_by("eth1")), ["vlan10"]);
+        assert_eq!(names(net_ifaces.referenced_by("red")), ["br0"]);
+        assert_eq!(names(net_ifaces.referenced_by("swp1")), ["swp1.20"]);
+        assert_eq!(names(net_ifaces.referenced_by("swp3")), ["br0"]);
+        assert!(net_ifaces.referenced_by("eth2").is_empty());
+    }
+
+    #[test]
+    fn test_aliases() {
+        let content = r#"
+auto eth0
+iface eth0 inet static
+    address 192.0.2.1/24
+
+auto eth0:1
+iface eth0:1 inet static
+    address 192.0.2.2/24
+
+auto eth0:2
+iface eth0:2 inet static
+    address 192.0.2.3/24
+
+iface eth0.100
+
+auto eth1:1
+iface eth1:1 inet static
+    address 198.51.100.2/24
+"#;
+        let mut net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+        let names = |found: Vec<&Interface>| -> Vec<String> {
+            found.into_iter().map(|iface| iface.name.clone()).collect()
+        };
+
+        let alias = net_ifaces.get_interface("eth0:1").unwrap();
+        assert!(alias.is_alias());
+        assert_eq!(alias.alias_parent(), Some("eth0"));
+        assert_eq!(alias.kind(), InterfaceKind::Alias);
+        assert!(!net_ifaces.get_interface("eth0").unwrap().is_alias());
+
+        assert_eq!(names(net_ifaces.children_of("eth0")), ["eth0.100", "eth0:1", "eth0:2"]);
+        assert_eq!(names(net_ifaces.children_of("eth1")), ["eth1:1"]);
+        assert!(net_ifaces.children_of("eth0:1").is_empty());
+        let order = names(net_ifaces.bringup_order().unwrap());
+        assert!(order.iter().position(|n| n == "eth0") < order.iter().position(|n| n == "eth0:2"));
+
+        let report = net_ifaces.validate();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].code, "undefined-alias-parent");
+        assert_eq!(report.issues[0].interface.as_deref(), Some("eth1:1"));
+        assert_eq!(report.issues[0].message, "Alias of eth1, which is not defined");
+
+        let cascade = net_ifaces.delete_interface_cascade("eth0");
+        let removed: Vec<&str> = cascade.removed.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(removed, ["eth0", "eth0:2", "eth0:1"]);
+        assert_eq!(cascade.orphaned_vlans().collect::<Vec<_>>(), ["eth0.100"]);
+    }
+
+    #[test]
+    fn test_write_value_alignment() {
+        let mut net_ifaces = NetworkInterfaces::from_interfaces([
+            Interface::builder("lo").with_method("loopback").build(),
+            Interface::builder("swp1")
+                .with_option("address", "10.0.0.1/31")
+                .with_option("mtu", "9216")
+                .build(),
+        ]);
+        net_ifaces.set_write_options(WriteOptions {
+            alignment: ValueAlignment::Auto,
+            ..Default::default()
+        });
+        assert_eq!(
+            net_ifaces.to_string(),
+            "iface lo loopback\n\
+             \niface swp1\n    address 10.0.0.1/31\n    mtu     9216\n"
+        );
+    }
+
+    #[test]
+    fn test_write_layout_golden() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/output");
+        let golden = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        let mut net_ifaces = NetworkInterfaces::load(dir.join("interfaces")).unwrap();
+        assert_eq!(net_ifaces.to_string(), golden("default"));
+
+        net_ifaces.set_write_options(WriteOptions {
+            blank_lines: 2,
+            final_newline: false,
+            ..Default::default()
+        });
+        assert_eq!(net_ifaces.to_string(), golden("spaced"));
+        net_ifaces.set_write_options(WriteOptions {
+            blank_lines: 0,
+            ..Default::default()
+        });
+        assert_eq!(net_ifaces.to_string(), golden("compact"));
+
+        // Every layout reads back as the same interfaces
+        for name in ["default", "spaced", "compact"] {
+            let parsed = NetworkInterfaces::parse_str(&golden(name)).unwrap();
+            assert_eq!(parsed, net_ifaces, "{}", name);
         }
 
-        // Collect interfaces into a vector and sort them by name
-        let mut interfaces: Vec<&Interface> = self.interfaces.values().collect();
-        interfaces.sort_by(|a, b| natural(&a.name, &b.name));
+        // Nothing to write is an empty file, without a lone newline
+        let empty = NetworkInterfaces::from_interfaces([]);
+        assert_eq!(empty.to_string(), "");
+        let headerless = NetworkInterfaces::from_interfaces([Interface::builder("eth0").build()]);
+        assert_eq!(headerless.to_string(), "iface eth0\n");
+    }
+
+    #[test]
+    fn test_clone_and_eq() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-eq-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let content = "# lab\nsource /etc/network/interfaces.d/*\n\n\
+                       auto eth0\niface eth0 inet dhcp\n    mtu 9000\n";
+        fs::write(dir.join("a"), content).unwrap();
+        fs::write(dir.join("b"), format!("\n\n{}", content)).unwrap();
+
+        // Path, modification time, and origins are not compared
+        let a = NetworkInterfaces::load(dir.join("a")).unwrap();
+        let mut b = NetworkInterfaces::load(dir.join("b")).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a.origin_of("eth0"), b.origin_of("eth0"));
+        let parsed = NetworkInterfaces::parse_str(content).unwrap();
+        assert_eq!(a, parsed);
+
+        // Neither are the options
+        b.set_write_options(WriteOptions {
+            sort: SortOrder::Dependency,
+            ..Default::default()
+        });
+        assert_eq!(a, b);
+
+        // Content is
+        let snapshot = b.clone();
+        assert_eq!(snapshot.path, b.path);
+        assert_eq!(snapshot.last_modified, b.last_modified);
+        b.add_header_comment("changed");
+        assert_ne!(a, b);
+        assert_eq!(a, snapshot);
+        let mut c = snapshot.clone();
+        c.get_interface_mut("eth0").unwrap().options.clear();
+        assert_ne!(c, snapshot);
+        let mut d = snapshot.clone();
+        d.remove_source(|_| true);
+        assert_ne!(d, snapshot);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-dir-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let uplinks = dir.join("uplinks");
+        let vlans = dir.join("vlans");
+        fs::write(&uplinks, "# Uplinks\n\nauto swp2\niface swp2\n\niface swp1\n").unwrap();
+        fs::write(&vlans, "iface vlan10\n    vlan-raw-device br0\n").unwrap();
+        // Names `source-directory` skips
+        fs::write(dir.join("old.bak"), "iface swp1\n").unwrap();
+        fs::write(dir.join("swp3~"), "iface swp3\n").unwrap();
+
+        let mut net_ifaces = NetworkInterfaces::load_dir(&dir).unwrap();
+        assert_eq!(net_ifaces.len(), 3);
+        assert_eq!(net_ifaces.file_of("swp1"), Some(uplinks.as_path()));
+        assert_eq!(net_ifaces.file_of("vlan10"), Some(vlans.as_path()));
+        let origin = net_ifaces.origin_of("swp1").unwrap();
+        assert_eq!((origin.path.as_deref(), origin.line), (Some(uplinks.as_path()), 6));
+        let order: Vec<&String> = net_ifaces.iter().map(|(name, _)| name).collect();
+        assert_eq!(order, vec!["swp2", "swp1", "vlan10"]);
+
+        // Each fragment is saved back with its own comments
+        net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", "9000");
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&uplinks).unwrap(),
+            "# Uplinks\n\nauto swp2\niface swp2\n\niface swp1\n    mtu 9000\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&vlans).unwrap(),
+            "iface vlan10\n    vlan-raw-device br0\n"
+        );
+
+        // The error names both files defining an interface
+        let duplicate = dir.join("zz-extra");
+        fs::write(&duplicate, "iface swp1 inet manual\n").unwrap();
+        let err = NetworkInterfaces::load_dir(&dir).unwrap_err();
+        assert!(matches!(
+            &err,
+            NetworkInterfacesError::DuplicateDefinition { name, first, second }
+                if name == "swp1" && first == &uplinks && second == &duplicate
+        ));
+        assert_eq!(err.kind(), ErrorKind::Conflict);
+        assert!(err.to_string().contains(&uplinks.display().to_string()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_with_includes() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-sources-{}", std::process::id()));
+        let fragments = dir.join("interfaces.d");
+        fs::create_dir_all(fragments.join("nested")).unwrap();
+        let main = dir.join("interfaces");
+        let main_contents = "\
+source interfaces.d/*
+source /nonexistent/interface-rs/*
+
+auto lo
+iface lo inet loopback
+";
+        fs::write(&main, main_contents).unwrap();
+        let uplinks = fragments.join("uplinks");
+        fs::write(&uplinks, "source nested/*\n\nauto swp1\niface swp1\n").unwrap();
+        let nested = fragments.join("nested").join("vlans");
+        fs::write(&nested, "auto vlan10\niface vlan10\n    vlan-raw-device br0\n").unwrap();
+        // Hidden files do not match `*`, and a file is read only once
+        fs::write(fragments.join(".swp1.swp"), "iface swp1\n").unwrap();
+        fs::write(fragments.join("zz-again"), "source ../interfaces.d/uplinks\n").unwrap();
+
+        // Plain loading keeps the directives without following them
+        let net_ifaces = NetworkInterfaces::load(&main).unwrap();
+        assert_eq!(net_ifaces.len(), 1);
+        assert_eq!(net_ifaces.source_directives().len(), 2);
+
+        let mut net_ifaces = NetworkInterfaces::load_with_includes(&main).unwrap();
+        let names: Vec<&String> = net_ifaces.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["lo", "swp1", "vlan10"]);
+        assert_eq!(net_ifaces.file_of("lo"), Some(main.as_path()));
+        assert_eq!(net_ifaces.file_of("swp1"), Some(uplinks.as_path()));
+        assert_eq!(net_ifaces.file_of("vlan10"), Some(nested.as_path()));
+        assert_eq!(net_ifaces.source_directives().len(), 2);
+
+        // Each file is saved back with its own directives
+        net_ifaces.set_option_on(|iface| iface.name == "vlan10", "mtu", "9000");
+        net_ifaces.save().unwrap();
+        assert_eq!(fs::read_to_string(&main).unwrap(), main_contents);
+        assert_eq!(
+            fs::read_to_string(&uplinks).unwrap(),
+            "source nested/*\n\nauto swp1\niface swp1\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&nested).unwrap(),
+            "auto vlan10\niface vlan10\n    vlan-raw-device br0\n    mtu 9000\n"
+        );
+        net_ifaces.reload().unwrap();
+        assert_eq!(net_ifaces.len(), 3);
+        assert_eq!(net_ifaces.file_of("vlan10"), Some(nested.as_path()));
+
+        // An `auto` line in another file is merged into the definition
+        fs::write(fragments.join("zz-auto"), "auto lo swp1\n").unwrap();
+        let net_ifaces = NetworkInterfaces::load_with_includes(&main).unwrap();
+        assert_eq!(net_ifaces.len(), 3);
+        assert_eq!(net_ifaces.file_of("swp1"), Some(uplinks.as_path()));
+
+        // Errors name the file they occur in
+        let duplicate = fragments.join("zz-duplicate");
+        fs::write(&duplicate, "iface swp1 inet manual\n").unwrap();
+        let err = NetworkInterfaces::load_with_includes(&main).unwrap_err();
+        assert!(matches!(
+            &err,
+            NetworkInterfacesError::DuplicateDefinition { name, first, second }
+                if name == "swp1" && first == &uplinks && second == &duplicate
+        ));
+        fs::write(&duplicate, "iface\n").unwrap();
+        let err = NetworkInterfaces::load_with_includes(&main).unwrap_err();
+        assert_eq!(err.path(), Some(duplicate.as_path()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_family_stanza_from_fragment() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-family-{}", std::process::id()));
+        let fragments = dir.join("interfaces.d");
+        fs::create_dir_all(&fragments).unwrap();
+        let main = dir.join("interfaces");
+        let main_contents = "\
+source interfaces.d/*
+
+# tags: tier=prod
+auto eth0
+iface eth0 inet static
+    address 192.0.2.1/24
+";
+        fs::write(&main, main_contents).unwrap();
+        let eth0 = fragments.join("eth0");
+        let eth0_contents = "# tags: role=uplink\n\
+                             iface eth0 inet6 static\n    address 2001:db8::1/64\n";
+        fs::write(&eth0, eth0_contents).unwrap();
+
+        // The other family's stanza joins the interface from the main file
+        let mut net_ifaces = NetworkInterfaces::load_with_includes(&main).unwrap();
+        assert_eq!(net_ifaces.len(), 1);
+        let iface = net_ifaces.get_interface("eth0").unwrap();
+        assert_eq!(iface.family_stanzas.len(), 1);
+        assert_eq!(iface.family_stanzas[0].family, Some(Family::Inet6));
+        assert_eq!(iface.tags.len(), 2);
+        assert_eq!(net_ifaces.file_of("eth0"), Some(main.as_path()));
+
+        // Each stanza is saved back to its own file
+        net_ifaces.set_option_on(|iface| iface.name == "eth0", "mtu", "9000");
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&main).unwrap(),
+            "\
+source interfaces.d/*
+
+# tags: role=uplink, tier=prod
+auto eth0
+iface eth0 inet static
+    address 192.0.2.1/24
+    mtu 9000
+"
+        );
+        assert_eq!(
+            fs::read_to_string(&eth0).unwrap(),
+            "iface eth0 inet6 static\n    address 2001:db8::1/64\n"
+        );
+        net_ifaces.reload().unwrap();
+        assert_eq!(net_ifaces.get_interface("eth0").unwrap().family_stanzas.len(), 1);
+
+        // Moving the interface takes every stanza along
+        net_ifaces.set_file("eth0", &main).unwrap();
+        net_ifaces.save().unwrap();
+        assert!(fs::read_to_string(&main).unwrap().contains("iface eth0 inet6 static"));
+        assert!(!eth0.exists());
+
+        // A second stanza for the same family is still a duplicate
+        fs::write(&eth0, "iface eth0 inet dhcp\n").unwrap();
+        let err = NetworkInterfaces::load_with_includes(&main).unwrap_err();
+        assert!(matches!(
+            &err,
+            NetworkInterfacesError::DuplicateDefinition { name, second, .. }
+                if name == "eth0" && second == &eth0
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_source_directory() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-srcdir-{}", std::process::id()));
+        let fragments = dir.join("interfaces.d");
+        fs::create_dir_all(&fragments).unwrap();
+        let main = dir.join("interfaces");
+        let main_contents = "\
+source-directory   interfaces.d
+source-directory /nonexistent/interface-rs
+
+auto lo
+iface lo inet loopback
+";
+        fs::write(&main, main_contents).unwrap();
+        fs::write(fragments.join("uplinks"), "iface swp1\n").unwrap();
+        fs::write(fragments.join("bond_0-a"), "iface bond0\n").unwrap();
+        // Names `run-parts` skips
+        fs::write(fragments.join("vlans.cfg"), "iface vlan10\n").unwrap();
+        fs::write(fragments.join("swp2~"), "iface swp2\n").unwrap();
+        fs::write(fragments.join(".hidden"), "iface swp3\n").unwrap();
+
+        let mut net_ifaces = NetworkInterfaces::load_with_includes(&main).unwrap();
+        let names: Vec<&String> = net_ifaces.iter().map(|(name, _)| name).collect();
+        // The main file first, then the fragments in the order they are read
+        assert_eq!(names, vec!["lo", "bond0", "swp1"]);
+        assert_eq!(net_ifaces.file_of("swp1"), Some(fragments.join("uplinks").as_path()));
+        let kinds: Vec<SourceKind> =
+            net_ifaces.source_directives().iter().map(|source| source.kind).collect();
+        assert_eq!(kinds, [SourceKind::SourceDirectory, SourceKind::SourceDirectory]);
+
+        // The directives are written back exactly
+        net_ifaces.set_option_on(|iface| iface.name == "lo", "mtu", "65536");
+        net_ifaces.save().unwrap();
+        let contents = fs::read_to_string(&main).unwrap();
+        assert!(contents.starts_with(
+            "source-directory   interfaces.d\nsource-directory /nonexistent/interface-rs\n"
+        ));
+        assert_eq!(fs::read_to_string(fragments.join("uplinks")).unwrap(), "iface swp1\n");
+
+        // A copy flattens the layout without giving it up
+        let copy = dir.join("interfaces.flat");
+        net_ifaces.save_copy(&copy).unwrap();
+        assert_eq!(fs::read_to_string(&copy).unwrap(), net_ifaces.to_string());
+        assert_eq!(net_ifaces.path.as_deref(), Some(main.as_path()));
+        net_ifaces.set_option_on(|iface| iface.name == "swp1", "mtu", "9216");
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(fragments.join("uplinks")).unwrap(),
+            "iface swp1\n    mtu 9216\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_managed() {
+        let dir = std::env::temp_dir().join(format!("interface-rs-managed-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("interfaces");
+        let above = "# Maintained by hand\nauto lo\niface lo inet loopback\n\n# BEGIN netd\n";
+        let below = "# END netd\n\n  iface eth9   # odd spacing is kept\n";
+        fs::write(&path, format!("{}auto swp1\niface swp1\n    mtu 9000\n{}", above, below))
+            .unwrap();
+
+        // Only the block is visible, with origins in the whole file
+        let mut net_ifaces = NetworkInterfaces::load_managed(&path, "netd").unwrap();
+        assert_eq!(net_ifaces.len(), 1);
+        assert!(net_ifaces.get_interface("lo").is_none());
+        assert_eq!(net_ifaces.origin_of("swp1").unwrap().line, 7);
+
+        // Saving rewrites only the block
+        net_ifaces.add_interface(Interface::builder("swp2").build());
+        net_ifaces.save().unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with(above));
+        assert!(contents.ends_with(below));
+        net_ifaces.reload().unwrap();
+        assert_eq!(net_ifaces.len(), 2);
+        assert!(net_ifaces.get_interface("eth9").is_none());
+
+        // The block is added at the end of a file without one
+        fs::write(&path, "auto lo\niface lo inet loopback").unwrap();
+        let mut net_ifaces = NetworkInterfaces::load_managed(&path, "netd").unwrap();
+        assert!(net_ifaces.is_empty());
+        net_ifaces.add_interface(Interface::builder("swp1").build());
+        net_ifaces.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "auto lo\niface lo inet loopback\n# BEGIN netd\niface swp1\n# END netd\n"
+        );
 
-        // Print interfaces
-        for iface in interfaces {
-            writeln!(f)?;
-            write!(f, "{}", iface)?;
+        // Malformed blocks are rejected with the offending line
+        let cases = [
+            ("# BEGIN netd\niface swp1\n", 1),
+            ("iface swp1\n# END netd\n", 2),
+            ("# BEGIN netd\n# END netd\n# BEGIN netd\n# END netd\n", 3),
+            ("# BEGIN netd\n# BEGIN netd\n# END netd\n# END netd\n", 2),
+        ];
+        for (contents, line) in cases {
+            fs::write(&path, contents).unwrap();
+            let err = NetworkInterfaces::load_managed(&path, "netd").unwrap_err();
+            match err.inner() {
+                NetworkInterfacesError::Parser(err) => assert_eq!(err.line, Some(line)),
+                other => panic!("expected a parser error for {:?}, got {:?}", contents, other),
+            }
         }
-        Ok(())
+        // Other markers are left alone
+        fs::write(&path, "# BEGIN other\n# BEGIN netd\n# END netd\n").unwrap();
+        assert!(NetworkInterfaces::load_managed(&path, "netd").is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
     }
-}
 
-// Implement methods to access interfaces directly if needed
-impl NetworkInterfaces {
-    /// Returns an iterator over the interfaces.
-    pub fn iter(&self) -> impl Iterator<Item = (&String, &Interface)> {
-        self.interfaces.iter()
+    #[test]
+    fn test_origin_two_files() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/origin");
+        let main = dir.join("interfaces");
+        let uplinks = dir.join("interfaces.d/uplinks");
+        let net_ifaces = NetworkInterfaces::load(&main).unwrap();
+        let fragment = NetworkInterfaces::load(&uplinks).unwrap();
+
+        let origin = |net_ifaces: &NetworkInterfaces, name: &str| {
+            let origin = net_ifaces.origin_of(name).unwrap();
+            (origin.path.clone().unwrap(), origin.line)
+        };
+        assert_eq!(origin(&net_ifaces, "lo"), (main.clone(), 5));
+        // The stanza wins over earlier `auto` and `allow-*` lines
+        assert_eq!(origin(&net_ifaces, "eth0"), (main.clone(), 10));
+        // Without a stanza, the first line naming the interface is used
+        assert_eq!(origin(&net_ifaces, "mgmt"), (main.clone(), 13));
+        // Later stanzas for the same interface do not move it
+        assert_eq!(origin(&fragment, "swp1"), (uplinks.clone(), 3));
+        assert_eq!(net_ifaces.origin_of("swp1"), None);
+
+        let issue = &fragment.validate().issues[0];
+        assert_eq!(issue.origin.as_ref().map(|o| o.line), Some(3));
+        assert!(issue.to_string().ends_with(&format!("({}:3)", uplinks.display())));
+
+        // Origins survive edits but are not part of equality
+        let swp1 = fragment.get_interface("swp1").unwrap();
+        let edited = swp1.edit().with_option("mtu", "9000").build();
+        assert_eq!(edited.origin(), swp1.origin());
+        let rebuilt = Interface::builder("swp1").build();
+        let mut stripped = swp1.edit().build();
+        stripped.origin = None;
+        assert_eq!(stripped, swp1.clone());
+        assert_ne!(rebuilt.origin(), swp1.origin());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_dual_stack() {
+        let content = "\
+auto eth0
+iface eth0 inet static
+    address 192.0.2.10/24
+    gateway 192.0.2.1
+
+iface eth0 inet6 static
+    address 2001:db8::10/64
+    gateway 2001:db8::1
+
+auto eth1
+iface eth1 inet dhcp
+";
+        let net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+        assert_eq!(net_ifaces.len(), 2);
+        assert_eq!(net_ifaces.to_string(), content);
+
+        let eth0 = net_ifaces.get_interface("eth0").unwrap();
+        let families: Vec<&Family> = eth0.families().collect();
+        assert_eq!(families, [&Family::Inet, &Family::Inet6]);
+        assert_eq!(eth0.get_option("gateway"), Some("192.0.2.1"));
+        let inet6 = net_ifaces.get_interface_family("eth0", Family::Inet6).unwrap();
+        assert!(inet6.auto);
+        assert_eq!(inet6.method_str(), Some("static"));
+        let gateways: Vec<&str> = inet6.get_options("gateway").collect();
+        assert_eq!(gateways, ["2001:db8::1"]);
+        let inet = net_ifaces.get_interface_family("eth0", Family::Inet).unwrap();
+        assert!(inet.family_stanzas.is_empty());
+        assert_eq!(inet.get_option("address"), Some("192.0.2.10/24"));
+        assert!(net_ifaces.get_interface_family("eth1", Family::Inet6).is_none());
+        assert!(net_ifaces.get_interface_family("eth2", Family::Inet).is_none());
+
+        // Another stanza for the same family still adds to the first one
+        let (interfaces, _, _) =
+            Parser::new().parse("iface eth0 inet static\niface eth0 inet\n    mtu 9000\n").unwrap();
+        assert!(interfaces["eth0"].family_stanzas.is_empty());
+        assert_eq!(interfaces["eth0"].get_option("mtu"), Some("9000"));
+    }
 
     #[test]
-    fn test_next_unused_vlan_in_range() {
-        // Create a `NetworkInterfaces` instance with some used VLANs
-        let mut network_interfaces = NetworkInterfaces {
-            interfaces: HashMap::new(),
-            path: None,
-            last_modified: None,
-            comments: Vec::new(),
-            sources: Vec::new(),
+    fn test_from_interfaces() {
+        let make = |name: &str, mtu: &str| {
+            Interface::builder(name)
+                .with_option("mtu", mtu)
+                .build()
         };
+        let ifaces = vec![
+            make("swp10", "1500"),
+            make("swp2", "1500"),
+            make("swp10", "9216"),
+            make("swp2", "9000"),
+            make("swp1", "1500"),
+        ];
 
-        // Add some VLAN interfaces to simulate used IDs
-        network_interfaces.add_interface(Interface::builder("vlan1000").build());
-        network_interfaces.add_interface(Interface::builder("vlan1001").build());
-        network_interfaces.add_interface(Interface::builder("vlan1003").build());
+        let net_ifaces = NetworkInterfaces::from_interfaces(ifaces.clone());
+        assert_eq!(net_ifaces.len(), 3);
+        assert!(net_ifaces.path.is_none());
+        assert!(net_ifaces.header_comments().is_empty());
+        let mtu = |name| net_ifaces.get_interface(name).unwrap().get_option("mtu");
+        assert_eq!(mtu("swp10"), Some("9216"));
+        assert_eq!(mtu("swp2"), Some("9000"));
 
-        // Test: Find the next unused VLAN ID in the range 1000 to 1999
-        let next_vlan_id = network_interfaces.next_unused_vlan_in_range(1000, 1999);
-        assert_eq!(next_vlan_id, Some(1002));
+        let collected: NetworkInterfaces = ifaces.iter().cloned().collect();
+        assert_eq!(collected.to_string(), net_ifaces.to_string());
 
-        // Test: Find the next unused VLAN ID when all VLANs are used in the range
-        network_interfaces.add_interface(Interface::builder("vlan1002").build());
-        let next_vlan_id = network_interfaces.next_unused_vlan_in_range(1000, 1003);
-        assert_eq!(next_vlan_id, None);
+        let err = NetworkInterfaces::try_from_interfaces(ifaces).unwrap_err();
+        assert!(matches!(
+            &err,
+            NetworkInterfacesError::DuplicateInterfaces { names } if names == &["swp2", "swp10"]
+        ));
+        assert_eq!(err.to_string(), "Duplicate interfaces: swp2, swp10");
+        let ok = NetworkInterfaces::try_from_interfaces(vec![make("swp1", "1500")]).unwrap();
+        assert_eq!(ok.len(), 1);
+    }
 
-        // Test: Find the next unused VLAN ID in a different range
-        let next_vlan_id = network_interfaces.next_unused_vlan_in_range(2000, 2005);
-        assert_eq!(next_vlan_id, Some(2000));
+    #[test]
+    fn test_iteration_order() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/interfaces");
+        let mut net_ifaces = NetworkInterfaces::load(&path).unwrap();
+        let names = |net_ifaces: &NetworkInterfaces| -> Vec<String> {
+            net_ifaces.iter().map(|(name, _)| name.clone()).collect()
+        };
+        let file_order = [
+            "lo", "eth0", "mgmt", "swp1", "swp2", "swp10", "bond0", "vni100100", "br_default",
+            "vlan100", "eth1",
+        ];
+        assert_eq!(names(&net_ifaces), file_order);
+
+        // New interfaces go last, replaced ones keep their place, and the
+        // others keep their order when one is deleted
+        net_ifaces.add_interface(Interface::builder("swp0").build());
+        net_ifaces.add_interface(Interface::builder("lo").build());
+        net_ifaces.delete_interface("eth0");
+        assert_eq!(
+            names(&net_ifaces),
+            [
+                "lo", "mgmt", "swp1", "swp2", "swp10", "bond0", "vni100100", "br_default",
+                "vlan100", "eth1", "swp0",
+            ]
+        );
     }
 
     #[test]
-    fn test_get_existing_vni_vlan() {
-        let mut network_interfaces = NetworkInterfaces {
-            interfaces: HashMap::new(),
-            path: None,
-            last_modified: None,
-            comments: Vec::new(),
-            sources: Vec::new(),
+    fn test_iteration_order_is_stable() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/interfaces");
+        let content = fs::read_to_string(path).unwrap();
+        let file_order = [
+            "lo", "eth0", "mgmt", "swp1", "swp2", "swp10", "bond0", "vni100100", "br_default",
+            "vlan100", "eth1",
+        ];
+        let names = |net_ifaces: &NetworkInterfaces| -> Vec<String> {
+            net_ifaces.iter().map(|(name, _)| name.clone()).collect()
         };
 
-        // Add a VNI interface
-        network_interfaces.add_interface(
-            Interface::builder("vni123456")
-                .with_auto(true)
-                .with_option("bridge-access", "1002")
-                .build(),
+        // Each hash map is seeded differently, so an order that leaked from
+        // hashing would differ between these collections
+        for _ in 0..8 {
+            let net_ifaces = NetworkInterfaces::parse_str(&content).unwrap();
+            assert_eq!(names(&net_ifaces), file_order);
+            let ifaces = net_ifaces.iter().map(|(_, iface)| iface.clone());
+            let rebuilt = NetworkInterfaces::from_interfaces(ifaces);
+            assert_eq!(names(&rebuilt), file_order);
+        }
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+        let golden = NetworkInterfaces::load(dir.join("interfaces")).unwrap();
+        let device = NetworkInterfaces::load(dir.join("origin/interfaces")).unwrap();
+        let names = |ifaces: Vec<&Interface>| -> Vec<String> {
+            ifaces.iter().map(|iface| iface.name.clone()).collect()
+        };
+
+        assert_eq!(
+            names(golden.difference(&device)),
+            vec!["bond0", "br_default", "eth1", "swp1", "swp2", "swp10", "vlan100", "vni100100"]
+        );
+        assert!(device.difference(&golden).is_empty());
+        assert_eq!(names(golden.intersection(&device)), vec!["eth0", "lo", "mgmt"]);
+        // Interfaces come from the receiver
+        let mgmt = device.intersection(&golden)[2];
+        let device_path = dir.join("origin/interfaces");
+        assert_eq!(mgmt.origin().unwrap().path.as_deref(), Some(device_path.as_path()));
+        assert_eq!(
+            golden.symmetric_difference_names(&device),
+            device.symmetric_difference_names(&golden)
         );
+        assert_eq!(golden.symmetric_difference_names(&device).len(), 8);
+    }
 
-        // Test: Find VLAN ID for existing VNI
-        assert_eq!(network_interfaces.get_existing_vni_vlan(123456), Some(1002));
+    #[test]
+    fn test_try_add_and_delete() {
+        let mut network_interfaces = NetworkInterfaces::new();
+        let eth0 = Interface::builder("eth0").with_method("dhcp").build();
+        network_interfaces.try_add_interface(eth0.clone()).unwrap();
 
-        // Test: No `bridge-access` option
-        network_interfaces.add_interface(Interface::builder("vni987654").with_auto(true).build());
-        assert_eq!(network_interfaces.get_existing_vni_vlan(987654), None);
+        let err = network_interfaces
+            .try_add_interface(Interface::builder("eth0").with_method("static").build())
+            .unwrap_err();
+        assert!(matches!(&err, NetworkInterfacesError::Conflict { name } if name == "eth0"));
+        assert_eq!(err.to_string(), "Interface eth0 already exists");
+        assert_eq!(network_interfaces.get_interface("eth0"), Some(&eth0));
 
-        // Test: Nonexistent VNI
-        assert_eq!(network_interfaces.get_existing_vni_vlan(666), None);
+        let err = network_interfaces
+            .try_add_interface(Interface::builder("eth1 ").build())
+            .unwrap_err();
+        assert!(matches!(&err, NetworkInterfacesError::InvalidName(NameError::Whitespace(_))));
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(network_interfaces.len(), 1);
+
+        let err = network_interfaces.try_delete_interface("eth1").unwrap_err();
+        assert!(matches!(&err, NetworkInterfacesError::NotFound { name } if name == "eth1"));
+        assert_eq!(err.to_string(), "Interface eth1 not found");
+
+        assert_eq!(network_interfaces.try_delete_interface("eth0").unwrap(), eth0);
+        assert!(network_interfaces.is_empty());
+
+        network_interfaces.add_interface(eth0.clone());
+        assert_eq!(network_interfaces.delete_interface("eth0"), Some(eth0));
+        assert_eq!(network_interfaces.delete_interface("eth0"), None);
+        assert_eq!(network_interfaces.iter().count(), 0);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn test_get_bridge_interfaces() {
-        let mut network_interfaces = NetworkInterfaces {
-            interfaces: HashMap::new(),
-            path: None,
-            last_modified: None,
-            comments: Vec::new(),
-            sources: Vec::new(),
-        };
+    fn test_serde_round_trip_fixture() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/interfaces");
+        let mut net_ifaces = NetworkInterfaces::load(path).unwrap();
+        // The representation does not keep the order of the stanzas
+        net_ifaces.set_write_options(WriteOptions {
+            sort: SortOrder::Natural,
+            ..Default::default()
+        });
+
+        let json = serde_json::to_string_pretty(&net_ifaces).unwrap();
+        let restored: NetworkInterfaces = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.to_string(), net_ifaces.to_string());
+        assert_eq!(restored.path, None);
+        for (name, iface) in net_ifaces.iter() {
+            assert_eq!(restored.get_interface(name), Some(iface));
+        }
+
+        // Interfaces are keyed by name in natural order
+        let position = |name: &str| json.find(&format!("\n    \"{}\": {{", name)).unwrap();
+        assert!(position("swp2") < position("swp10"));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["interfaces"]["eth1"]["family"], "inet6");
+        assert_eq!(
+            value["interfaces"]["vlan100"]["options"][0],
+            serde_json::json!(["address", "10.1.100.2/24"])
+        );
+
+        let err = serde_json::from_str::<NetworkInterfaces>(
+            r#"{"interfaces": {"eth0": {"name": "eth1"}}}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("does not match"));
+        let minimal: NetworkInterfaces =
+            serde_json::from_str(r#"{"interfaces": {"eth0": {"name": "eth0"}}}"#).unwrap();
+        assert_eq!(minimal.to_string(), "iface eth0\n");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_golden() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let mut net_ifaces = NetworkInterfaces::load(format!("{}/tests/interfaces", dir)).unwrap();
+        net_ifaces.set_write_options(WriteOptions {
+            sort: SortOrder::Natural,
+            ..Default::default()
+        });
+        let golden = fs::read_to_string(format!("{}/tests/interfaces.json", dir)).unwrap();
+        assert_eq!(net_ifaces.to_json_string(true), golden.trim_end());
+
+        let restored = NetworkInterfaces::from_json_str(&golden).unwrap();
+        assert_eq!(restored.to_string(), net_ifaces.to_string());
+        let compact = net_ifaces.to_json_string(false);
+        assert!(!compact.contains('\n'));
+        assert_eq!(
+            NetworkInterfaces::from_json_str(&compact).unwrap().to_string(),
+            net_ifaces.to_string()
+        );
+
+        // Unknown keys are kept
+        let restored = NetworkInterfaces::from_json_str(
+            r#"{"interfaces": {"swp1": {"name": "swp1", "options": [["x-vendor-knob", "7"]]}}}"#,
+        )
+        .unwrap();
+        let swp1 = restored.get_interface("swp1").unwrap();
+        assert_eq!(
+            swp1.typed_options(),
+            vec![crate::interface::InterfaceOption::Other("x-vendor-knob".into(), "7".to_string())]
+        );
+
+        let err = NetworkInterfaces::from_json_str("{").unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::Json(_)));
+        assert_eq!(err.kind(), crate::error::ErrorKind::Parse);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_round_trip() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let mut net_ifaces = NetworkInterfaces::load(format!("{}/tests/interfaces", dir)).unwrap();
+        net_ifaces.set_write_options(WriteOptions {
+            sort: SortOrder::Natural,
+            ..Default::default()
+        });
+        let toml = net_ifaces.to_toml_string();
+        assert!(toml.contains("[interfaces.vlan100]"));
+        assert!(toml.contains(r#"method = "static""#));
+
+        let restored = NetworkInterfaces::from_toml_str(&toml).unwrap();
+        assert_eq!(restored.to_string(), net_ifaces.to_string());
+        for (name, iface) in net_ifaces.iter() {
+            assert_eq!(restored.get_interface(name), Some(iface));
+        }
+
+        // Duplicate keys keep their order
+        let restored = NetworkInterfaces::from_toml_str(
+            r#"
+            [interfaces.eth0]
+            name = "eth0"
+            options = [["address", "10.0.0.2/24"], ["address", "10.0.0.1/24"]]
+            "#,
+        )
+        .unwrap();
+        let eth0 = restored.get_interface("eth0").unwrap();
+        let addresses: Vec<&str> = eth0.get_options("address").collect();
+        assert_eq!(addresses, vec!["10.0.0.2/24", "10.0.0.1/24"]);
+
+        let err = NetworkInterfaces::from_toml_str("interfaces = 1").unwrap_err();
+        assert!(matches!(err, NetworkInterfacesError::Toml(_)));
+        assert_eq!(err.kind(), crate::error::ErrorKind::Parse);
+    }
+
+    #[test]
+    fn test_validate_numeric_ranges() {
+        let mut network_interfaces = NetworkInterfaces::new();
 
-        // Add interfaces with `bridge-access`
         network_interfaces.add_interface(
-            Interface::builder("vni1234")
-                .with_option("bridge-access", "1000")
+            Interface::builder("swp1")
+                .with_option("mtu", "9216")
+                .with_option("bridge-access", "100")
                 .build(),
         );
+        assert!(network_interfaces.validate().issues.is_empty());
+
         network_interfaces.add_interface(
-            Interface::builder("swp2")
-                .with_option("bridge-access", "1001")
+            Interface::builder("vlan10")
+                .with_option("mtu", "0")
+                .with_option("bridge-pvid", "0")
                 .build(),
         );
-
-        // Add an interface without `bridge-access`
-        network_interfaces.add_interface(Interface::builder("swp1").build());
-
-        // Verify the result
-        let bridge_interfaces = network_interfaces.get_bridge_interfaces();
-        assert_eq!(bridge_interfaces, vec!["swp2", "vni1234"]);
+        let report = network_interfaces.validate();
+        assert!(!report.is_valid());
+        assert_eq!(report.issues.len(), 2);
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| i.interface.as_deref() == Some("vlan10") && i.code == "option-out-of-range"));
     }
 }
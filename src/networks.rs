@@ -0,0 +1,236 @@
+//! Conversions to and from the `ipnet` crate.
+//!
+//! Enabled with the `ipnet` feature, this module lets callers that already
+//! use [`ipnet`] work with its network types directly:
+//!
+//! - [`Interface::networks`] returns the configured addresses as [`IpNet`];
+//! - [`InterfaceBuilder::with_address_net`] adds an `address` option from an
+//!   `IpNet`, `Ipv4Net`, or `Ipv6Net`;
+//! - [`NetworkInterfaces::interfaces_in_net`] and
+//!   [`NetworkInterfaces::addresses_in_net`] take the network to search as an
+//!   `IpNet`;
+//! - [`Address`] converts from all three network types, and back with
+//!   `TryFrom` when it has a prefix length of the right family.
+//!
+//! Like `ipnet` itself, these conversions keep the host bits, so
+//! `10.0.0.1/24` stays `10.0.0.1/24` rather than becoming `10.0.0.0/24`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::interface::Interface;
+//! use interface_rs::NetworkInterfaces;
+//! use ipnet::IpNet;
+//!
+//! let net: IpNet = "10.0.0.1/24".parse().unwrap();
+//! let iface = Interface::builder("eth0").with_address_net(net).build();
+//! assert_eq!(iface.get_option("address"), Some("10.0.0.1/24"));
+//! assert_eq!(iface.networks(), vec![net]);
+//!
+//! let net_ifaces = NetworkInterfaces::from_interfaces([iface]);
+//! let found = net_ifaces.interfaces_in_net("10.0.0.0/8".parse().unwrap(), None);
+//! assert_eq!(found[0].name, "eth0");
+//! ```
+
+use crate::error::ConversionError;
+use crate::interface::{Address, Interface, InterfaceBuilder};
+use crate::network_interfaces::NetworkInterfaces;
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::net::IpAddr;
+
+impl From<IpNet> for Address {
+    fn from(net: IpNet) -> Self {
+        Address::new(net.addr(), Some(net.prefix_len()))
+    }
+}
+
+impl From<Ipv4Net> for Address {
+    fn from(net: Ipv4Net) -> Self {
+        Address::new(IpAddr::V4(net.addr()), Some(net.prefix_len()))
+    }
+}
+
+impl From<Ipv6Net> for Address {
+    fn from(net: Ipv6Net) -> Self {
+        Address::new(IpAddr::V6(net.addr()), Some(net.prefix_len()))
+    }
+}
+
+impl TryFrom<Address> for IpNet {
+    type Error = ConversionError;
+
+    /// Converts an `Address` to an `IpNet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConversionError` if the address has no prefix length.
+    fn try_from(addr: Address) -> Result<Self, Self::Error> {
+        let prefix = addr
+            .prefix
+            .ok_or_else(|| ConversionError(format!("{} has no prefix length", addr)))?;
+        // `Address` only holds prefix lengths valid for its family
+        IpNet::new(addr.ip, prefix).map_err(|err| ConversionError(format!("{}: {}", addr, err)))
+    }
+}
+
+impl TryFrom<Address> for Ipv4Net {
+    type Error = ConversionError;
+
+    /// Converts an `Address` to an `Ipv4Net`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConversionError` if the address has no prefix length or is
+    /// an IPv6 address.
+    fn try_from(addr: Address) -> Result<Self, Self::Error> {
+        match IpNet::try_from(addr)? {
+            IpNet::V4(net) => Ok(net),
+            IpNet::V6(_) => Err(ConversionError(format!("{} is not an IPv4 address", addr))),
+        }
+    }
+}
+
+impl TryFrom<Address> for Ipv6Net {
+    type Error = ConversionError;
+
+    /// Converts an `Address` to an `Ipv6Net`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConversionError` if the address has no prefix length or is
+    /// an IPv4 address.
+    fn try_from(addr: Address) -> Result<Self, Self::Error> {
+        match IpNet::try_from(addr)? {
+            IpNet::V6(net) => Ok(net),
+            IpNet::V4(_) => Err(ConversionError(format!("{} is not an IPv6 address", addr))),
+        }
+    }
+}
+
+impl Interface {
+    /// Returns the addresses configured by the `address` options as networks.
+    ///
+    /// Addresses are read with [`Interface::addresses`]; those without a
+    /// prefix length, either after the slash or from a `netmask` option, are
+    /// skipped.
+    pub fn networks(&self) -> Vec<IpNet> {
+        self.addresses()
+            .into_iter()
+            .filter_map(|addr| IpNet::try_from(addr).ok())
+            .collect()
+    }
+}
+
+impl InterfaceBuilder {
+    /// Adds an `address` option for `net`, in CIDR notation.
+    ///
+    /// # Arguments
+    ///
+    /// * `net` - An `IpNet`, `Ipv4Net`, or `Ipv6Net`.
+    pub fn with_address_net(self, net: impl Into<IpNet>) -> Self {
+        self.with_option("address", net.into().to_string())
+    }
+}
+
+impl NetworkInterfaces {
+    /// Returns every interface with at least one address inside `net`.
+    ///
+    /// This is [`interfaces_in_subnet`](NetworkInterfaces::interfaces_in_subnet)
+    /// with the network given as an `IpNet`; host bits of `net` are ignored.
+    pub fn interfaces_in_net(&self, net: IpNet, vrf: Option<&str>) -> Vec<&Interface> {
+        self.interfaces_in_subnet(net.addr(), net.prefix_len(), vrf)
+    }
+
+    /// Returns every configured address inside `net` with the interface it
+    /// is configured on.
+    ///
+    /// This is [`addresses_in_subnet`](NetworkInterfaces::addresses_in_subnet)
+    /// with the network given as an `IpNet`; host bits of `net` are ignored.
+    pub fn addresses_in_net(&self, net: IpNet, vrf: Option<&str>) -> Vec<(&Interface, Address)> {
+        self.addresses_in_subnet(net.addr(), net.prefix_len(), vrf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_conversions() {
+        let v4: Ipv4Net = "10.0.0.1/24".parse().unwrap();
+        let v6: Ipv6Net = "2001:db8::1/64".parse().unwrap();
+
+        let addr = Address::from(v4);
+        assert_eq!(addr.to_string(), "10.0.0.1/24");
+        assert_eq!(Ipv4Net::try_from(addr).unwrap(), v4);
+        assert_eq!(IpNet::try_from(addr).unwrap(), IpNet::V4(v4));
+        assert!(Ipv6Net::try_from(addr).is_err());
+
+        let addr = Address::from(IpNet::V6(v6));
+        assert_eq!(addr, Address::from(v6));
+        assert_eq!(Ipv6Net::try_from(addr).unwrap(), v6);
+        assert_eq!(
+            Ipv4Net::try_from(addr).unwrap_err().to_string(),
+            "Conversion error: 2001:db8::1/64 is not an IPv4 address"
+        );
+
+        let bare: Address = "10.0.0.1".parse().unwrap();
+        assert_eq!(
+            IpNet::try_from(bare).unwrap_err().to_string(),
+            "Conversion error: 10.0.0.1 has no prefix length"
+        );
+    }
+
+    #[test]
+    fn test_networks_and_subnet_query() {
+        let v6: Ipv6Net = "2001:db8::1/64".parse().unwrap();
+        let eth0: Interface = "iface eth0 inet static\n    \
+            address 10.0.0.1\n    netmask 255.255.255.0\n    address 10.0.1.1/24\n    \
+            address 192.0.2.1"
+            .parse()
+            .unwrap();
+        let eth0 = eth0.edit().with_address_net(v6).build();
+        let expected: Vec<IpNet> = [
+            "10.0.0.1/24",
+            "10.0.1.1/24",
+            "192.0.2.1/24",
+            "2001:db8::1/64",
+        ]
+        .iter()
+        .map(|net| net.parse().unwrap())
+        .collect();
+        // The netmask applies to every bare address
+        assert_eq!(eth0.networks(), expected);
+
+        let net_ifaces = NetworkInterfaces::from_interfaces([
+            eth0,
+            Interface::builder("eth1")
+                .with_address_net("10.1.0.1/16".parse::<Ipv4Net>().unwrap())
+                .build(),
+        ]);
+        let names = |found: Vec<&Interface>| -> Vec<String> {
+            found.iter().map(|iface| iface.name.clone()).collect()
+        };
+        assert_eq!(
+            names(net_ifaces.interfaces_in_net("10.0.0.0/8".parse().unwrap(), None)),
+            vec!["eth0", "eth1"]
+        );
+        assert_eq!(
+            names(net_ifaces.interfaces_in_net("10.1.2.3/16".parse().unwrap(), None)),
+            vec!["eth1"]
+        );
+        assert_eq!(
+            names(net_ifaces.interfaces_in_net("2001:db8::/32".parse().unwrap(), None)),
+            vec!["eth0"]
+        );
+        let found: Vec<String> = net_ifaces
+            .addresses_in_net("10.0.0.0/15".parse().unwrap(), None)
+            .iter()
+            .map(|(iface, addr)| format!("{} {}", iface.name, addr))
+            .collect();
+        assert_eq!(
+            found,
+            vec!["eth0 10.0.0.1/24", "eth0 10.0.1.1/24", "eth1 10.1.0.1/16"]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Clean-up of hand-edited configurations.
+//!
+//! [`NetworkInterfaces::normalize`] removes redundancy that accumulates when
+//! a file is edited by hand, and reports each change as a
+//! [`NormalizeChange`]. It is never run implicitly: loading and saving keep
+//! the file as written.
+//!
+//! The parser already merges stanzas that declare the same interface more
+//! than once for the same address family: `auto` becomes a single flag, and
+//! the options of later stanzas are appended to those of earlier ones. Normalization resolves the
+//! duplicates this leaves behind:
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | `duplicate-allow` | a repeated `allow-*` class was dropped |
+//! | `duplicate-option` | an option line identical to an earlier one was dropped |
+//! | `overridden-option` | a single-valued option was set more than once; the last value was kept |
+//!
+//! Repeatable options (`address`, the DNS options, hook commands such as
+//! `post-up`, and unknown options) keep every distinct line in order, so
+//! hook commands from merged stanzas run in the order they were written.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+//! let report = net_ifaces.normalize();
+//! print!("{}", report);
+//! if !report.is_empty() {
+//!     net_ifaces.save().unwrap();
+//! }
+//! ```
+
+use crate::interface::option::keys_match;
+use crate::interface::{Interface, InterfaceOption, OptionKey};
+use crate::network_interfaces::NetworkInterfaces;
+use std::fmt;
+
+/// A single change made by [`NetworkInterfaces::normalize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizeChange {
+    /// A stable, machine-readable identifier for the change
+    /// (e.g., `"duplicate-option"`).
+    pub code: &'static str,
+    /// The interface that was changed.
+    pub interface: String,
+    /// A human-readable description of the change.
+    pub message: String,
+}
+
+impl fmt::Display for NormalizeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.code, self.interface, self.message)
+    }
+}
+
+/// The changes made by [`NetworkInterfaces::normalize`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizeReport {
+    /// All changes, ordered by interface name.
+    pub changes: Vec<NormalizeChange>,
+}
+
+impl NormalizeReport {
+    /// Returns `true` if nothing was changed.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Adds a change to the report.
+    fn push(&mut self, code: &'static str, interface: &str, message: String) {
+        self.changes.push(NormalizeChange {
+            code,
+            interface: interface.to_string(),
+            message,
+        });
+    }
+}
+
+impl fmt::Display for NormalizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl NetworkInterfaces {
+    /// Removes duplicate declarations and options; see the
+    /// [module documentation](crate::normalize) for the rules.
+    ///
+    /// Normalizing an already normalized collection changes nothing and
+    /// returns an empty report.
+    ///
+    /// # Returns
+    ///
+    /// A [`NormalizeReport`] listing every change.
+    pub fn normalize(&mut self) -> NormalizeReport {
+        let strict_keys = self.parser_options().strict_keys;
+        let names: Vec<String> = self.iter_by_name().map(|(name, _)| name.clone()).collect();
+        let mut report = NormalizeReport::default();
+        for name in names {
+            let iface = self.get_interface_mut(&name).unwrap();
+            dedupe_allow(iface, &mut report);
+            let name = iface.name.clone();
+            for options in iface.stanza_options_mut() {
+                dedupe_options(&name, options, strict_keys, &mut report);
+            }
+        }
+        report
+    }
+}
+
+/// Drops repeated `allow-*` classes, keeping the first.
+fn dedupe_allow(iface: &mut Interface, report: &mut NormalizeReport) {
+    let mut kept: Vec<String> = Vec::with_capacity(iface.allow.len());
+    for allow in iface.allow.drain(..) {
+        if kept.contains(&allow) {
+            report.push(
+                "duplicate-allow",
+                &iface.name,
+                format!("removed repeated allow-{}", allow),
+            );
+        } else {
+            kept.push(allow);
+        }
+    }
+    iface.allow = kept;
+}
+
+/// Drops identical option lines of one stanza of `name` and resolves
+/// single-valued options to their last value, kept at the position of their
+/// first line.
+fn dedupe_options(
+    name: &str,
+    options: &mut Vec<(OptionKey, String)>,
+    strict_keys: bool,
+    report: &mut NormalizeReport,
+) {
+    let mut kept: Vec<(OptionKey, String)> = Vec::with_capacity(options.len());
+    for (key, value) in options.drain(..) {
+        let option = if strict_keys {
+            InterfaceOption::from_key_value_strict(&key, &value)
+        } else {
+            InterfaceOption::from_key_value(&key, &value)
+        };
+        let same_key = |(k, _): &(OptionKey, String)| keys_match(k, &key, strict_keys);
+        if option.is_repeatable() {
+            if kept.iter().any(|line| same_key(line) && line.1 == value) {
+                report.push(
+                    "duplicate-option",
+                    name,
+                    format!("removed repeated `{} {}`", key, value),
+                );
+            } else {
+                kept.push((key, value));
+            }
+            continue;
+        }
+        match kept.iter_mut().find(|line| same_key(line)) {
+            Some(line) if line.1 == value => {
+                report.push(
+                    "duplicate-option",
+                    name,
+                    format!("removed repeated `{} {}`", key, value),
+                );
+            }
+            Some(line) => {
+                report.push(
+                    "overridden-option",
+                    name,
+                    format!("replaced `{} {}` with `{} {}`", line.0, line.1, key, value),
+                );
+                *line = (key, value);
+            }
+            None => kept.push((key, value)),
+        }
+    }
+    *options = kept;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_fixture() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let mut net_ifaces =
+            NetworkInterfaces::load(format!("{}/tests/interfaces.unnormalized", dir)).unwrap();
+        let report = net_ifaces.normalize();
+        let codes: Vec<(&str, &str)> = report
+            .changes
+            .iter()
+            .map(|change| (change.interface.as_str(), change.code))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("eth0", "duplicate-allow"),
+                ("eth0", "duplicate-option"),
+                ("eth0", "overridden-option"),
+                ("eth0", "duplicate-option"),
+                ("swp1", "duplicate-option"),
+            ]
+        );
+        let expected =
+            std::fs::read_to_string(format!("{}/tests/interfaces.normalized", dir)).unwrap();
+        assert_eq!(net_ifaces.to_string(), expected);
+
+        assert!(net_ifaces.normalize().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_family_stanza() {
+        let mut net_ifaces = NetworkInterfaces::parse_str(
+            "iface eth0 inet static\n    mtu 9000\n\
+             iface eth0 inet6 static\n    accept_ra 2\n    accept_ra 2\n",
+        )
+        .unwrap();
+        let report = net_ifaces.normalize();
+        let codes: Vec<(&str, &str)> = report
+            .changes
+            .iter()
+            .map(|change| (change.interface.as_str(), change.code))
+            .collect();
+        assert_eq!(codes, vec![("eth0", "duplicate-option")]);
+        assert_eq!(
+            net_ifaces.to_string(),
+            "iface eth0 inet static\n    mtu 9000\n\
+             \niface eth0 inet6 static\n    accept_ra 2\n"
+        );
+    }
+}
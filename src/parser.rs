@@ -1,19 +1,349 @@
-use crate::error::ParserError;
-use crate::interface::{Family, Interface};
-use std::collections::HashMap;
+use crate::error::{Limit, LimitExceeded, NetworkInterfacesError, ParserError};
+use crate::interface::option::intern_key;
+use crate::interface::{Family, FamilyStanza, Interface, InterfaceView, Mapping, Method, Origin};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Options controlling how an `interfaces(5)` file is parsed and interpreted.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use interface_rs::{NetworkInterfaces, ParserOptions};
+///
+/// let options = ParserOptions {
+///     strict_keys: true,
+///     max_input_size: Some(1 << 20),
+///     ..Default::default()
+/// };
+/// let net_ifaces = NetworkInterfaces::load_with("/etc/network/interfaces", options).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserOptions {
+    /// Match option keys exactly instead of folding case, underscores, and
+    /// known aliases (see [`normalize_key`](crate::interface::option::normalize_key)).
+    ///
+    /// Option keys are always stored and written back as they appear in the
+    /// file; this only affects how they are classified and looked up by
+    /// [`NetworkInterfaces`](crate::NetworkInterfaces). The accessors of a
+    /// single interface, such as
+    /// [`Interface::get_option`](crate::interface::Interface::get_option),
+    /// always fold keys.
+    pub strict_keys: bool,
+    /// The largest input accepted, in bytes. `None` means no limit.
+    ///
+    /// Defaults to [`DEFAULT_MAX_INPUT_SIZE`](ParserOptions::DEFAULT_MAX_INPUT_SIZE),
+    /// which is far larger than any real `interfaces(5)` file but stops a
+    /// loader pointed at the wrong file from exhausting memory. Set it to
+    /// `None` to accept input of any size.
+    pub max_input_size: Option<usize>,
+    /// The longest line accepted, in bytes. `None` means no limit.
+    pub max_line_length: Option<usize>,
+    /// The most interfaces accepted, counting names that only appear on
+    /// `auto`, `allow-*`, or `mapping` lines. `None` means no limit.
+    pub max_interfaces: Option<usize>,
+    /// The most options accepted for one interface, across all of its
+    /// stanzas. `None` means no limit.
+    pub max_options_per_interface: Option<usize>,
+    /// The prefix of every line of a disabled stanza; see
+    /// [`NetworkInterfaces::disable_interface`](crate::NetworkInterfaces::disable_interface).
+    ///
+    /// Lines starting with the marker (ignoring its trailing whitespace) are
+    /// read as disabled stanzas rather than comments, and disabled stanzas
+    /// are written with it. It should start with `#` so that `ifup` skips
+    /// those lines. An empty marker disables the feature.
+    pub disabled_marker: String,
+    /// The prefix of the magic comment holding the
+    /// [tags](crate::interface::Interface::tags) of the next stanza, such as
+    /// `# tags: tenant=acme, tier=prod`.
+    ///
+    /// The comment may sit anywhere between the previous stanza and the
+    /// `iface` line it tags, or among the options of that stanza. Tags are
+    /// written with this prefix on the first line of the stanza. An empty
+    /// prefix disables the feature, leaving such lines as comments.
+    pub tag_prefix: String,
+    /// Whether [`NetworkInterfaces::load_with`](crate::NetworkInterfaces::load_with)
+    /// and [`reload`](crate::NetworkInterfaces::reload) also read the files
+    /// included with `source` and `source-directory` directives. Defaults to
+    /// `false`; see
+    /// [`NetworkInterfaces::load_with_includes`](crate::NetworkInterfaces::load_with_includes).
+    pub follow_sources: bool,
+}
+
+impl ParserOptions {
+    /// The default for [`max_input_size`](ParserOptions::max_input_size):
+    /// 16 MiB.
+    pub const DEFAULT_MAX_INPUT_SIZE: usize = 16 * 1024 * 1024;
+
+    /// The default for [`disabled_marker`](ParserOptions::disabled_marker).
+    pub const DEFAULT_DISABLED_MARKER: &'static str = "#disabled# ";
+
+    /// The default for [`tag_prefix`](ParserOptions::tag_prefix).
+    pub const DEFAULT_TAG_PREFIX: &'static str = "# tags:";
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            strict_keys: false,
+            max_input_size: Some(ParserOptions::DEFAULT_MAX_INPUT_SIZE),
+            max_line_length: None,
+            max_interfaces: None,
+            max_options_per_interface: None,
+            disabled_marker: ParserOptions::DEFAULT_DISABLED_MARKER.to_string(),
+            tag_prefix: ParserOptions::DEFAULT_TAG_PREFIX.to_string(),
+            follow_sources: false,
+        }
+    }
+}
 
 /// A parser for an `interfaces(5)` file.
 ///
 /// The `Parser` struct provides methods to parse the content of the interfaces file
 /// and produce a collection of `Interface` instances.
-pub struct Parser;
+/// [`Parser::parse_borrowed`] produces borrowed [`InterfaceView`]s instead.
+///
+/// [`Parser::parse_bytes`] is the entry point for untrusted input: it
+/// accepts arbitrary bytes and enforces the limits set with
+/// [`with_options`](Parser::with_options).
+#[derive(Debug, Clone, Default)]
+pub struct Parser {
+    /// The file being parsed, recorded in the origin of each interface.
+    path: Option<PathBuf>,
+    /// The options whose limits [`Parser::parse_bytes`] enforces.
+    options: ParserOptions,
+}
+
+/// The interfaces, header comments, and source directives of a file.
+type Parsed = (HashMap<String, Interface>, Vec<String>, Vec<String>);
+
+type ParseResult = Result<Parsed, ParserError>;
+
+/// One meaningful line of an `interfaces(5)` file.
+enum Token<'a> {
+    /// A comment line, including the `#`.
+    Comment(&'a str),
+    /// A `source` or `source-directory` directive.
+    Source(&'a str),
+    /// An `auto` line with its whitespace-separated names.
+    Auto(&'a str),
+    /// An `allow-<class>` line with its whitespace-separated names.
+    Allow { class: &'a str, names: &'a str },
+    /// The header of a `mapping` stanza with its whitespace-separated names.
+    Mapping(&'a str),
+    /// The header of an `iface` stanza.
+    Iface {
+        name: &'a str,
+        family: Option<Family>,
+        method: Option<&'a str>,
+    },
+    /// An option line inside an `iface` or `mapping` stanza. The value is
+    /// the rest of the line as written.
+    Option { key: &'a str, value: &'a str },
+}
+
+/// Splits file content into [`Token`]s, shared by the owned and borrowed
+/// parsers.
+///
+/// Option lines outside a stanza are skipped. A tokenizer reads either the
+/// lines of disabled stanzas, with the marker stripped, or all other lines.
+struct Tokenizer<'a, 'm> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    /// Name of the `iface` or `mapping` stanza whose options are being read
+    open_stanza: Option<&'a str>,
+    /// The disabled marker without trailing whitespace, or empty for none
+    marker: &'m str,
+    /// Whether the lines of disabled stanzas are read instead of the others
+    disabled: bool,
+}
+
+impl<'a, 'm> Tokenizer<'a, 'm> {
+    /// Creates a tokenizer for the lines outside disabled stanzas.
+    fn new(content: &'a str, marker: &'m str) -> Self {
+        Tokenizer {
+            lines: content.lines().enumerate(),
+            open_stanza: None,
+            marker: marker.trim_end(),
+            disabled: false,
+        }
+    }
+
+    /// Creates a tokenizer for the lines of disabled stanzas.
+    fn disabled(content: &'a str, marker: &'m str) -> Self {
+        Tokenizer {
+            disabled: true,
+            ..Tokenizer::new(content, marker)
+        }
+    }
+
+    /// Classifies one line, returning `None` for lines without a token.
+    fn token(&mut self, line_number: usize, raw_line: &'a str) -> Option<ParseToken<'a>> {
+        let mut line = raw_line.trim();
+        let stripped = Some(line)
+            .filter(|_| !self.marker.is_empty())
+            .and_then(|line| line.strip_prefix(self.marker));
+        match (stripped, self.disabled) {
+            (Some(rest), true) => line = rest.trim(),
+            (None, false) => {}
+            _ => return None,
+        }
+        if line.starts_with('#') {
+            return Some(Ok((line_number, Token::Comment(line))));
+        }
+        if line.starts_with("source") {
+            return Some(Ok((line_number, Token::Source(line))));
+        }
+        let (keyword, rest) = match line.split_once(char::is_whitespace) {
+            Some((keyword, rest)) => (keyword, rest.trim_start()),
+            None if line.is_empty() => return None,
+            None => (line, ""),
+        };
+
+        // Context for errors on this line
+        let stanza = self.open_stanza.unwrap_or(keyword);
+        if matches!(keyword, "auto" | "mapping" | "iface") || keyword.starts_with("allow-") {
+            self.open_stanza = None;
+        }
 
-type ParseResult = Result<(HashMap<String, Interface>, Vec<String>, Vec<String>), ParserError>;
+        let token = match keyword {
+            "auto" => Token::Auto(rest),
+            "mapping" => {
+                self.open_stanza = rest.split_whitespace().next();
+                Token::Mapping(rest)
+            }
+            "iface" => {
+                let mut tokens = rest.split_whitespace();
+                let Some(name) = tokens.next() else {
+                    return Some(Err(ParserError {
+                        message: "Missing interface name in 'iface' stanza".to_string(),
+                        line: Some(line_number + 1),
+                        stanza: Some(stanza.to_string()),
+                        raw_line: Some(raw_line.to_string()),
+                    }));
+                };
+                self.open_stanza = Some(name);
+                let second = tokens.next();
+                let third = tokens.next();
+                let count = 2 + usize::from(second.is_some()) + usize::from(third.is_some());
+                let count = count + tokens.count();
+                let family = second.and_then(|s| s.parse::<Family>().ok());
+                let method = match count {
+                    // If family is valid, method is the next token
+                    4 if family.is_some() => third,
+                    // If family is absent, interpret the third token as the method
+                    3 if family.is_none() => second,
+                    _ => None,
+                };
+                Token::Iface {
+                    name,
+                    family,
+                    method,
+                }
+            }
+            _ => match keyword.strip_prefix("allow-") {
+                Some(class) => Token::Allow { class, names: rest },
+                None if self.open_stanza.is_some() => Token::Option {
+                    key: keyword,
+                    value: rest.trim_end(),
+                },
+                // Options outside a stanza are ignored
+                None => return None,
+            },
+        };
+        Some(Ok((line_number, token)))
+    }
+}
+
+/// Checks that `input` is UTF-8, reporting the line of the first invalid
+/// byte otherwise.
+pub(crate) fn decode(input: &[u8]) -> Result<&str, ParserError> {
+    std::str::from_utf8(input).map_err(|err| {
+        let valid = &input[..err.valid_up_to()];
+        ParserError {
+            message: "Input is not valid UTF-8".to_string(),
+            line: Some(valid.iter().filter(|&&b| b == b'\n').count() + 1),
+            stanza: None,
+            raw_line: None,
+        }
+    })
+}
+
+/// Reads the tags of a magic comment, given the text after its prefix:
+/// comma-separated `key=value` pairs, or bare keys with an empty value.
+fn parse_tags(body: &str) -> BTreeMap<String, String> {
+    body.split(',')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let key = key.trim();
+            (!key.is_empty()).then(|| (key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Records an `auto` or `allow-*` line (0-based) naming `iface`.
+fn add_directive_line(iface: &mut Interface, line_number: usize) {
+    if let Some(origin) = &mut iface.origin {
+        origin.directive_lines.push(line_number + 1);
+    }
+}
+
+/// Extends the span of `iface` to include `line_number` (0-based).
+fn extend_span(iface: &mut Interface, line_number: usize) {
+    if let Some(span) = iface
+        .origin
+        .as_mut()
+        .and_then(|origin| origin.span.as_mut())
+    {
+        span.end = line_number + 2;
+    }
+}
+
+/// A token with its 0-based line number.
+type ParseToken<'a> = Result<(usize, Token<'a>), ParserError>;
+
+impl<'a> Iterator for Tokenizer<'a, '_> {
+    type Item = ParseToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (line_number, raw_line) = self.lines.next()?;
+            if let Some(token) = self.token(line_number, raw_line) {
+                return Some(token);
+            }
+        }
+    }
+}
 
 impl Parser {
     /// Creates a new `Parser` instance.
     pub fn new() -> Self {
-        Parser
+        Parser::default()
+    }
+
+    /// Creates a `Parser` for the content of the file at `path`.
+    pub fn for_file(path: &Path) -> Self {
+        Parser {
+            path: Some(path.to_path_buf()),
+            ..Parser::default()
+        }
+    }
+
+    /// Sets the options whose limits [`parse_bytes`](Parser::parse_bytes)
+    /// enforces.
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the origin of a definition on `line` (0-based).
+    fn origin(&self, line: usize) -> Origin {
+        Origin {
+            path: self.path.clone(),
+            line: line + 1,
+            span: None,
+            directive_lines: Vec::new(),
+        }
     }
 
     /// Parses the content of the interfaces file.
@@ -26,162 +356,693 @@ impl Parser {
     ///
     /// A `Result` containing a tuple `(interfaces, comments, sources)` if successful,
     /// or a `ParserError` if parsing fails.
+    ///
+    /// The content is trusted: no limits are enforced. Use
+    /// [`parse_bytes`](Parser::parse_bytes) for untrusted input.
     pub fn parse(&self, content: &str) -> ParseResult {
-        let mut interfaces = HashMap::new();
-        let lines = content.lines().enumerate().peekable();
-        let mut current_interface: Option<Interface> = None;
-        let mut comments = Vec::new();
-        let mut sources = Vec::new();
-
-        for (line_number, line) in lines {
-            let line = line.trim();
-
-            // Collect comments at the top
-            if line.starts_with('#') {
-                if interfaces.is_empty() && current_interface.is_none() {
-                    comments.push(line.to_string());
+        self.parse_until(content, false).map(|(parsed, _)| parsed)
+    }
+
+    /// Parses untrusted input, enforcing the limits of the parser options.
+    ///
+    /// The input may be any bytes. It is rejected if it is larger than
+    /// [`max_input_size`](ParserOptions::max_input_size), if it is not
+    /// UTF-8, or if any line is longer than
+    /// [`max_line_length`](ParserOptions::max_line_length). Parsing stops as
+    /// soon as there are more than
+    /// [`max_interfaces`](ParserOptions::max_interfaces) interfaces or an
+    /// interface has more than
+    /// [`max_options_per_interface`](ParserOptions::max_options_per_interface)
+    /// options, so that memory use stays bounded.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The raw file content.
+    ///
+    /// # Returns
+    ///
+    /// A tuple `(interfaces, comments, sources)`, as from
+    /// [`parse`](Parser::parse).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NetworkInterfacesError::LimitExceeded`] if a limit is
+    /// exceeded, or [`NetworkInterfacesError::Parser`] if the input is not
+    /// UTF-8 or cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::error::{ErrorKind, Limit};
+    /// use interface_rs::{NetworkInterfacesError, Parser, ParserOptions};
+    ///
+    /// let parser = Parser::new().with_options(ParserOptions {
+    ///     max_interfaces: Some(1),
+    ///     ..Default::default()
+    /// });
+    /// let (interfaces, _, _) = parser.parse_bytes(b"iface eth0 inet dhcp\n").unwrap();
+    /// assert_eq!(interfaces.len(), 1);
+    ///
+    /// let err = parser.parse_bytes(b"auto eth0\nauto eth1\n").unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    /// assert!(matches!(
+    ///     err,
+    ///     NetworkInterfacesError::LimitExceeded(ref e) if e.limit == Limit::Interfaces
+    /// ));
+    ///
+    /// let err = parser.parse_bytes(b"auto eth0\n# \xff\n").unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Parse);
+    /// ```
+    pub fn parse_bytes(&self, input: &[u8]) -> Result<Parsed, NetworkInterfacesError> {
+        self.check_input_size(input.len() as u64)?;
+        self.parse_limited(decode(input)?)
+    }
+
+    /// Fails if an input of `size` bytes exceeds the input size limit.
+    pub(crate) fn check_input_size(&self, size: u64) -> Result<(), LimitExceeded> {
+        match self.options.max_input_size {
+            Some(max) if size > max as u64 => Err(LimitExceeded {
+                limit: Limit::InputSize,
+                max,
+                actual: usize::try_from(size).ok(),
+                line: None,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the input size limit, if any.
+    pub(crate) fn max_input_size(&self) -> Option<usize> {
+        self.options.max_input_size
+    }
+
+    /// Parses `content`, enforcing every limit except the input size.
+    pub(crate) fn parse_limited(&self, content: &str) -> Result<Parsed, NetworkInterfacesError> {
+        if let Some(max) = self.options.max_line_length {
+            let long_line = content
+                .lines()
+                .enumerate()
+                .find(|(_, line)| line.len() > max);
+            if let Some((line_number, line)) = long_line {
+                return Err(LimitExceeded {
+                    limit: Limit::LineLength,
+                    max,
+                    actual: Some(line.len()),
+                    line: Some(line_number + 1),
                 }
-                continue;
+                .into());
             }
+        }
+        match self.parse_until(content, true)? {
+            (_, Some(exceeded)) => Err(exceeded.into()),
+            (parsed, None) => Ok(parsed),
+        }
+    }
 
-            // Collect source directives
-            if line.starts_with("source") {
-                sources.push(line.to_string());
-                continue;
-            }
+    /// Parses `content`, stopping early if `enforce_limits` is set and the
+    /// number of interfaces or options exceeds the limits of the options.
+    fn parse_until(
+        &self,
+        content: &str,
+        enforce_limits: bool,
+    ) -> Result<(Parsed, Option<LimitExceeded>), ParserError> {
+        let limit = |max: Option<usize>| max.filter(|_| enforce_limits).unwrap_or(usize::MAX);
+        let limits = (
+            limit(self.options.max_interfaces),
+            limit(self.options.max_options_per_interface),
+        );
+        let marker = &self.options.disabled_marker;
+        let mut parsed = Parsed::default();
+        let tokens = Tokenizer::new(content, marker);
+        if let Some(exceeded) = self.parse_tokens(tokens, &mut parsed, limits, 0)? {
+            return Ok((parsed, Some(exceeded)));
+        }
 
-            // Skip empty lines
-            if line.is_empty() {
-                continue;
+        // Disabled stanzas are read on their own, so that they do not end
+        // the enabled stanzas around them
+        let mut disabled = Parsed::default();
+        let tokens = Tokenizer::disabled(content, marker);
+        let exceeded = self.parse_tokens(tokens, &mut disabled, limits, parsed.0.len())?;
+        for (name, mut iface) in disabled.0 {
+            // An enabled definition wins over a disabled one
+            if let Entry::Vacant(entry) = parsed.0.entry(name) {
+                iface.disabled = true;
+                entry.insert(iface);
             }
+        }
+        Ok((parsed, exceeded))
+    }
 
-            let tokens: Vec<&str> = line.split_whitespace().collect();
-            if tokens.is_empty() {
-                continue;
-            }
+    /// Adds the definitions read by `tokens` to `parsed`, stopping early if
+    /// the interfaces, plus `counted` others, or the options of one
+    /// interface exceed `(max_interfaces, max_options)`.
+    fn parse_tokens(
+        &self,
+        tokens: Tokenizer<'_, '_>,
+        parsed: &mut Parsed,
+        (max_interfaces, max_options): (usize, usize),
+        counted: usize,
+    ) -> Result<Option<LimitExceeded>, ParserError> {
+        let (interfaces, comments, sources) = parsed;
+        // Name of the `iface` stanza whose options are being read
+        let mut current: Option<&str> = None;
+        // Names of the `mapping` stanza whose lines are being read
+        let mut current_mapping: Option<&str> = None;
+        // Whether that stanza is the first for its interface
+        let mut current_is_first = false;
+        // Index of that stanza in the interface's family stanzas, if it is
+        // one for another address family
+        let mut current_family_stanza: Option<usize> = None;
+        // Interfaces whose first `iface` stanza has been seen
+        let mut stanzas_seen: HashSet<&str> = HashSet::new();
+        // Tags read from a magic comment, for the stanza they belong to
+        let mut pending_tags: Option<BTreeMap<String, String>> = None;
+        let tag_prefix = self.options.tag_prefix.trim_end();
 
-            // Finish the previous interface if necessary
-            match tokens[0] {
-                "auto" | "mapping" | "iface" => {
-                    if let Some(iface) = current_interface.take() {
-                        interfaces.insert(iface.name.clone(), iface);
+        for token in tokens {
+            let (line_number, token) = token?;
+            // Options of the interface the token added an option to
+            let mut options_len = 0;
+            match token {
+                Token::Comment(line) => {
+                    let body = Some(line)
+                        .filter(|_| !tag_prefix.is_empty())
+                        .and_then(|line| line.strip_prefix(tag_prefix));
+                    if let Some(body) = body {
+                        pending_tags = Some(parse_tags(body));
+                    } else if interfaces.is_empty() {
+                        // Collect comments at the top
+                        comments.push(line.to_string());
                     }
                 }
-                s if s.starts_with("allow-") => {
-                    if let Some(iface) = current_interface.take() {
-                        interfaces.insert(iface.name.clone(), iface);
+                Token::Source(line) => {
+                    pending_tags = None;
+                    sources.push(line.to_string());
+                }
+                Token::Auto(names) => {
+                    current = None;
+                    current_mapping = None;
+                    for iface_name in names.split_whitespace() {
+                        let iface = interfaces.entry(iface_name.to_string()).or_insert_with(|| {
+                            // Interface doesn't exist yet, create it here
+                            let mut iface = Interface::builder(iface_name).build();
+                            iface.origin = Some(self.origin(line_number));
+                            iface.placeholder = true;
+                            iface
+                        });
+                        iface.auto = true;
+                        add_directive_line(iface, line_number);
                     }
                 }
-                _ => {}
-            }
-
-            match tokens[0] {
-                "auto" => {
-                    for &iface_name in &tokens[1..] {
-                        if let Some(iface) = interfaces.get_mut(iface_name) {
-                            // If interface exists, set auto to true
-                            iface.auto = true;
-                        } else {
-                            // Interface doesn't exist yet, create it with auto = true
-                            interfaces.insert(
-                                iface_name.to_string(),
-                                Interface::builder(iface_name).with_auto(true).build(),
-                            );
-                        }
+                Token::Allow { class, names } => {
+                    current = None;
+                    current_mapping = None;
+                    for iface_name in names.split_whitespace() {
+                        let iface = interfaces.entry(iface_name.to_string()).or_insert_with(|| {
+                            // Interface doesn't exist yet, create it here
+                            let mut iface = Interface::builder(iface_name).build();
+                            iface.origin = Some(self.origin(line_number));
+                            iface.placeholder = true;
+                            iface
+                        });
+                        iface.allow.push(class.to_string());
+                        add_directive_line(iface, line_number);
                     }
                 }
-                s if s.starts_with("allow-") => {
-                    let allow_type = s.strip_prefix("allow-").unwrap();
-                    for &iface_name in &tokens[1..] {
-                        if let Some(iface) = interfaces.get_mut(iface_name) {
-                            // If interface exists, add to allow list
-                            iface.allow.push(allow_type.to_string());
-                        } else {
-                            // Interface doesn't exist yet, create it with allow
+                Token::Mapping(names) => {
+                    current = None;
+                    current_mapping = Some(names);
+                    pending_tags = None;
+                    for iface_name in names.split_whitespace() {
+                        let iface = interfaces.entry(iface_name.to_string()).or_insert_with(|| {
+                            // Interface doesn't exist yet, create it here
                             let mut iface = Interface::builder(iface_name).build();
-                            iface.allow.push(allow_type.to_string());
-                            interfaces.insert(iface_name.to_string(), iface);
-                        }
+                            iface.origin = Some(self.origin(line_number));
+                            iface
+                        });
+                        iface.placeholder = false;
+                        // A later stanza for the same name replaces an earlier one
+                        iface.mapping = Some(Mapping {
+                            script: String::new(),
+                            maps: Vec::new(),
+                        });
                     }
                 }
-                "iface" => {
-                    // Start a new interface
-                    let iface_name = tokens
-                        .get(1)
-                        .ok_or_else(|| ParserError {
-                            message: "Missing interface name in 'iface' stanza".to_string(),
-                            line: Some(line_number + 1),
-                        })?
-                        .to_string();
-
-                    // Remove existing interface if any
-                    let existing_iface = interfaces.remove(&iface_name);
+                Token::Iface {
+                    name,
+                    family,
+                    method,
+                } => {
+                    current_mapping = None;
+                    // A stanza for another family of a defined interface, as
+                    // in a dual-stack setup, is kept apart from the first one
+                    let defined = interfaces.get_mut(name).filter(|_| stanzas_seen.contains(name));
+                    if let Some(iface) = defined {
+                        let other_family = family.is_some() && iface.family != family;
+                        if other_family && iface.family.is_some() {
+                            // Tags of every stanza belong to the one interface
+                            if let Some(tags) = pending_tags.take() {
+                                iface.tags.extend(tags);
+                            }
+                            let stanzas = &mut iface.family_stanzas;
+                            let index = match stanzas.iter().position(|s| s.family == family) {
+                                Some(index) => index,
+                                None => {
+                                    stanzas.push(FamilyStanza {
+                                        family,
+                                        method: None,
+                                        options: Vec::new(),
+                                    });
+                                    stanzas.len() - 1
+                                }
+                            };
+                            if let Some(method) = method {
+                                stanzas[index].method = Some(Method::from(method));
+                            }
+                            current = Some(name);
+                            current_is_first = false;
+                            current_family_stanza = Some(index);
+                            continue;
+                        }
+                    }
+                    current_family_stanza = None;
 
                     // Build the interface using existing settings if available
-                    let mut builder = if let Some(existing_iface) = existing_iface {
-                        existing_iface.edit()
-                    } else {
-                        Interface::builder(iface_name.clone())
-                    };
-
-                    // Parse family
-                    let family = match tokens.get(2) {
-                        Some(s) => s.parse::<Family>().ok(),
-                        None => None,
-                    };
-
-                    // Parse method
-                    let method = match tokens.len() {
-                        // If family is valid, method is the next token
-                        4 if family.is_some() => Some(tokens[3].to_string()),
-                        // If family is absent, interpret the third token as the method
-                        3 if family.is_none() => Some(tokens[2].to_string()),
-                        _ => None,
+                    let mut builder = match interfaces.remove(name) {
+                        Some(existing_iface) => existing_iface.edit(),
+                        None => Interface::builder(name),
                     };
-
                     if let Some(family) = family {
                         builder = builder.with_family(family);
                     }
-
                     if let Some(method) = method {
                         builder = builder.with_method(method);
                     }
+                    if let Some(tags) = pending_tags.take() {
+                        builder.tags.extend(tags);
+                    }
+                    builder.placeholder = false;
 
-                    current_interface = Some(builder.build());
+                    // The first stanza is where the interface is defined
+                    current_is_first = stanzas_seen.insert(name);
+                    if current_is_first {
+                        let origin = builder
+                            .origin
+                            .get_or_insert_with(|| self.origin(line_number));
+                        origin.line = line_number + 1;
+                        origin.span = Some(line_number + 1..line_number + 2);
+                    }
+                    interfaces.insert(name.to_string(), builder.build());
+                    current = Some(name);
                 }
-                "mapping" => {
-                    // Handle 'mapping' stanzas if needed
-                    // For now, we ignore unknown stanzas
+                Token::Option { key, value } if current_mapping.is_some() => {
+                    let names = current_mapping.into_iter().flat_map(str::split_whitespace);
+                    let value = value.split_whitespace().collect::<Vec<&str>>().join(" ");
+                    for name in names {
+                        let Some(mapping) =
+                            interfaces.get_mut(name).and_then(|i| i.mapping.as_mut())
+                        else {
+                            continue;
+                        };
+                        match key {
+                            "script" => mapping.script = value.clone(),
+                            "map" => mapping.maps.push(value.clone()),
+                            // Other lines in a mapping stanza are ignored
+                            _ => {}
+                        }
+                    }
                 }
-                _ => {
-                    // Parse options under 'iface' stanza
-                    if let Some(iface) = &mut current_interface {
-                        let mut tokens = line.split_whitespace();
-                        if let Some(option_name) = tokens.next() {
-                            let option_value = tokens.collect::<Vec<&str>>().join(" ");
-                            iface.options.push((option_name.to_string(), option_value));
+                Token::Option { key, value } => {
+                    if let Some(iface) = current.and_then(|name| interfaces.get_mut(name)) {
+                        // A magic comment among the options tags this stanza
+                        if let Some(tags) = pending_tags.take() {
+                            iface.tags.extend(tags);
+                        }
+                        let value = value.split_whitespace().collect::<Vec<&str>>().join(" ");
+                        let options = match current_family_stanza {
+                            Some(index) => &mut iface.family_stanzas[index].options,
+                            None => &mut iface.options,
+                        };
+                        options.push((intern_key(key), value));
+                        options_len = options.len();
+                        if current_is_first {
+                            extend_span(iface, line_number);
                         }
-                    } else {
-                        // Handle global options if needed
-                        // For now, we ignore unknown stanzas outside of an 'iface'
                     }
                 }
             }
-        }
 
-        // Insert the last interface
-        if let Some(iface) = current_interface {
-            interfaces.insert(iface.name.clone(), iface);
+            let exceeded = if interfaces.len() + counted > max_interfaces {
+                Some((Limit::Interfaces, max_interfaces))
+            } else if options_len > max_options {
+                Some((Limit::OptionsPerInterface, max_options))
+            } else {
+                None
+            };
+            if let Some((limit, max)) = exceeded {
+                let exceeded = LimitExceeded {
+                    limit,
+                    max,
+                    actual: None,
+                    line: Some(line_number + 1),
+                };
+                return Ok(Some(exceeded));
+            }
+        }
+        // A magic comment after the last options tags the last stanza
+        if let Some(tags) = pending_tags {
+            if let Some(iface) = current.and_then(|name| interfaces.get_mut(name)) {
+                iface.tags.extend(tags);
+            }
         }
+        Ok(None)
+    }
 
-        Ok((interfaces, comments, sources))
+    /// Parses the content of an interfaces file without copying it.
+    ///
+    /// This is the borrowed counterpart of [`parse`](Parser::parse) for
+    /// read-only passes over many files: names, option keys, and values are
+    /// slices of `content`. Comments, source directives, `mapping` stanzas,
+    /// and disabled stanzas are not collected. Use [`InterfaceView::to_interface`] to
+    /// turn a view into an owned [`Interface`].
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The file content.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParserError` if the content cannot be parsed, exactly as
+    /// [`parse`](Parser::parse) does.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::Parser;
+    ///
+    /// let content = "auto eth0\niface eth0 inet dhcp\n    mtu 9000\n";
+    /// let views = Parser::new().parse_borrowed(content).unwrap();
+    /// assert_eq!(views["eth0"].options, [("mtu", "9000")]);
+    /// assert_eq!(views["eth0"].to_interface().to_string(), content);
+    /// ```
+    pub fn parse_borrowed<'a>(
+        &self,
+        content: &'a str,
+    ) -> Result<HashMap<&'a str, InterfaceView<'a>>, ParserError> {
+        let mut views: HashMap<&'a str, InterfaceView<'a>> = HashMap::new();
+        let mut current: Option<&'a str> = None;
+        let mut stanzas_seen: HashSet<&str> = HashSet::new();
+
+        for token in Tokenizer::new(content, &self.options.disabled_marker) {
+            let (line_number, token) = token?;
+            let line = line_number + 1;
+            match token {
+                Token::Comment(_) | Token::Source(_) => {}
+                Token::Mapping(_) => current = None,
+                Token::Auto(names) => {
+                    current = None;
+                    for name in names.split_whitespace() {
+                        views
+                            .entry(name)
+                            .or_insert_with(|| InterfaceView::new(name, line))
+                            .auto = true;
+                    }
+                }
+                Token::Allow { class, names } => {
+                    current = None;
+                    for name in names.split_whitespace() {
+                        views
+                            .entry(name)
+                            .or_insert_with(|| InterfaceView::new(name, line))
+                            .allow
+                            .push(class);
+                    }
+                }
+                Token::Iface {
+                    name,
+                    family,
+                    method,
+                } => {
+                    let view = views
+                        .entry(name)
+                        .or_insert_with(|| InterfaceView::new(name, line));
+                    if family.is_some() {
+                        view.family = family;
+                    }
+                    if method.is_some() {
+                        view.method = method;
+                    }
+                    if stanzas_seen.insert(name) {
+                        view.line = line;
+                    }
+                    current = Some(name);
+                }
+                Token::Option { key, value } => {
+                    if let Some(view) = current.and_then(|name| views.get_mut(name)) {
+                        view.options.push((key, value));
+                    }
+                }
+            }
+        }
+        Ok(views)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::interface::Family;
+    use crate::interface::{Family, Method};
+
+    #[test]
+    fn test_parse_error_context() {
+        let content = "auto br0
+iface br0 inet static
+    address 10.0.0.1/24
+    bridge-ports swp1
+    iface
+    mtu 9000
+";
+        let err = Parser::new().parse(content).unwrap_err();
+        assert_eq!(err.line, Some(5));
+        assert_eq!(err.stanza.as_deref(), Some("br0"));
+        assert_eq!(err.raw_line.as_deref(), Some("    iface"));
+        assert_eq!(
+            err.to_string(),
+            "Parser error on line 5 in stanza br0: Missing interface name in 'iface' stanza\n    5 |     iface"
+        );
+
+        // Outside any stanza, the keyword is reported
+        let err = Parser::new().parse("auto eth0\niface\n").unwrap_err();
+        assert_eq!(err.stanza.as_deref(), Some("iface"));
+        assert_eq!(err.raw_line.as_deref(), Some("iface"));
+    }
+
+    #[test]
+    fn test_parse_borrowed_matches_owned() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        for file in ["interfaces", "interfaces.unnormalized", "origin/interfaces"] {
+            let content = std::fs::read_to_string(format!("{}/tests/{}", dir, file)).unwrap();
+            let (owned, _comments, _sources) = Parser::new().parse(&content).unwrap();
+            let views = Parser::new().parse_borrowed(&content).unwrap();
+            assert_eq!(views.len(), owned.len(), "{}", file);
+            for (name, view) in &views {
+                let iface = view.to_interface();
+                assert_eq!(iface, owned[*name], "{}", file);
+                assert_eq!(
+                    iface.origin().unwrap().line,
+                    owned[*name].origin().unwrap().line
+                );
+            }
+        }
+
+        // Views point into the content
+        let content = "iface eth0\n    post-up  ip link   set eth0 up\n";
+        let views = Parser::new().parse_borrowed(content).unwrap();
+        let (key, value) = views["eth0"].options[0];
+        let range = content.as_bytes().as_ptr_range();
+        assert!(range.contains(&key.as_ptr()) && range.contains(&value.as_ptr()));
+        assert_eq!(value, "ip link   set eth0 up");
+        assert_eq!(
+            views["eth0"].to_interface().get_option("post-up"),
+            Some("ip link set eth0 up")
+        );
+
+        let err = Parser::new()
+            .parse_borrowed("auto eth0\niface\n")
+            .unwrap_err();
+        assert_eq!(err.line, Some(2));
+    }
+
+    #[test]
+    fn test_parse_records_spans() {
+        let content = "\
+auto eth0 eth1
+allow-hotplug eth1
+
+iface eth0 inet static
+    address 192.168.1.10/24
+
+    gateway 192.168.1.1
+iface eth1 inet dhcp
+
+iface eth0 inet6 auto
+    mtu 1500
+
+auto eth2
+";
+        let (interfaces, _, _) = Parser::new().parse(content).unwrap();
+
+        let eth0 = interfaces["eth0"].origin().unwrap();
+        assert_eq!(eth0.line, 4);
+        assert_eq!(eth0.span, Some(4..8));
+        assert_eq!(eth0.directive_lines, [1]);
+
+        let eth1 = interfaces["eth1"].origin().unwrap();
+        assert_eq!(eth1.line, 8);
+        assert_eq!(interfaces["eth1"].source_span(), Some(8..9));
+        assert_eq!(eth1.directive_lines, [1, 2]);
+
+        let eth2 = interfaces["eth2"].origin().unwrap();
+        assert_eq!(eth2.line, 13);
+        assert_eq!(eth2.span, None);
+        assert_eq!(eth2.directive_lines, [13]);
+    }
+
+    #[test]
+    fn test_parse_bytes_limits() {
+        let content =
+            "auto eth0 eth1\niface eth0 inet static\n    address 10.0.0.1/24\n    mtu 9000\n";
+        let exceeded = |options: ParserOptions| match Parser::new()
+            .with_options(options)
+            .parse_bytes(content.as_bytes())
+        {
+            Err(NetworkInterfacesError::LimitExceeded(err)) => err,
+            other => panic!("expected a limit error, got {:?}", other),
+        };
+
+        let err = exceeded(ParserOptions {
+            max_input_size: Some(16),
+            ..Default::default()
+        });
+        assert_eq!(err.limit, Limit::InputSize);
+        assert_eq!((err.actual, err.line), (Some(content.len()), None));
+
+        let err = exceeded(ParserOptions {
+            max_line_length: Some(21),
+            ..Default::default()
+        });
+        assert_eq!(err.limit, Limit::LineLength);
+        assert_eq!((err.actual, err.line), (Some(22), Some(2)));
+
+        let err = exceeded(ParserOptions {
+            max_interfaces: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(err.limit, Limit::Interfaces);
+        assert_eq!((err.max, err.actual, err.line), (1, None, Some(1)));
+
+        let err = exceeded(ParserOptions {
+            max_options_per_interface: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(err.limit, Limit::OptionsPerInterface);
+        assert_eq!(err.line, Some(4));
+
+        // Limits that are exactly met are not exceeded
+        let options = ParserOptions {
+            max_input_size: Some(content.len()),
+            max_line_length: Some(23),
+            max_interfaces: Some(2),
+            max_options_per_interface: Some(2),
+            ..Default::default()
+        };
+        let parser = Parser::new().with_options(options);
+        let (interfaces, _, _) = parser.parse_bytes(content.as_bytes()).unwrap();
+        assert_eq!(interfaces.len(), 2);
+        // `parse` does not enforce limits
+        assert!(parser.parse(&content.repeat(2)).is_ok());
+
+        let err = Parser::new()
+            .parse_bytes(b"auto eth0\niface eth0 inet dhcp\n    up \xc3\n")
+            .unwrap_err();
+        let NetworkInterfacesError::Parser(err) = err else {
+            panic!("expected a parser error, got {:?}", err)
+        };
+        assert_eq!(err.line, Some(3));
+    }
+
+    #[test]
+    fn test_parse_mapping() {
+        let content = "\
+mapping eth0 eth1
+    script /usr/local/sbin/map-scripts
+    map HOME  eth0-home
+    map WORK eth0-work
+    mtu 9000
+
+iface eth0-home inet dhcp
+";
+        let (interfaces, _, _) = Parser::new().parse(content).unwrap();
+        for name in ["eth0", "eth1"] {
+            let mapping = interfaces[name].mapping.as_ref().unwrap();
+            assert_eq!(mapping.script, "/usr/local/sbin/map-scripts");
+            assert_eq!(mapping.maps, ["HOME eth0-home", "WORK eth0-work"]);
+            assert!(interfaces[name].options.is_empty());
+        }
+        assert!(interfaces["eth0-home"].mapping.is_none());
+    }
+
+    #[test]
+    fn test_parse_disabled() {
+        let content = "\
+# header
+#disabled# auto eth1
+#disabled# iface eth1 inet dhcp
+auto eth0
+iface eth0 inet static
+#disabled#     mtu 9000
+    address 10.0.0.1/24
+#disabled# iface eth0 inet dhcp
+";
+        let (interfaces, comments, _) = Parser::new().parse(content).unwrap();
+        assert_eq!(comments, ["# header"]);
+        let eth1 = &interfaces["eth1"];
+        assert!(eth1.disabled && eth1.auto);
+        assert_eq!(eth1.origin().unwrap().line, 3);
+        // Disabled lines do not end the stanza around them, and an enabled
+        // definition wins over a disabled one
+        let eth0 = &interfaces["eth0"];
+        assert!(!eth0.disabled);
+        assert_eq!(eth0.get_option("address"), Some("10.0.0.1/24"));
+        assert_eq!(eth0.get_option("mtu"), None);
+        assert!(!Parser::new()
+            .parse_borrowed(content)
+            .unwrap()
+            .contains_key("eth1"));
+
+        // Other markers are plain comments
+        let parser = Parser::new().with_options(ParserOptions {
+            disabled_marker: "#off ".to_string(),
+            ..Default::default()
+        });
+        let (interfaces, _, _) = parser.parse("#off iface eth2\n#off    mtu 1500\n").unwrap();
+        assert!(interfaces["eth2"].disabled);
+        assert_eq!(interfaces["eth2"].get_option("mtu"), Some("1500"));
+        assert!(!parser.parse(content).unwrap().0.contains_key("eth1"));
+    }
+
+    #[test]
+    fn test_parse_interns_known_keys() {
+        let content = "iface swp1\n    mtu 9216\n    x-vendor-knob 7\n";
+        let (interfaces, _comments, _sources) = Parser::new().parse(content).unwrap();
+        let iface = &interfaces["swp1"];
+        assert!(matches!(
+            iface.options[0].0,
+            std::borrow::Cow::Borrowed("mtu")
+        ));
+        assert!(matches!(iface.options[1].0, std::borrow::Cow::Owned(_)));
+        assert_eq!(iface.to_string(), content);
+    }
 
     #[test]
     fn test_parse_iface_without_family_and_method() {
@@ -198,13 +1059,24 @@ iface eth0
         assert_eq!(iface.name, "eth0");
         assert_eq!(iface.family, None);
         assert_eq!(iface.method, None);
-        assert!(iface.options.contains(&(
-            "address".to_string(),
-            "10.130.17.36/255.255.255.128".to_string()
-        )));
         assert!(iface
             .options
-            .contains(&("vrf".to_string(), "mgmt".to_string())));
+            .contains(&("address".into(), "10.130.17.36/255.255.255.128".to_string())));
+        assert!(iface.options.contains(&("vrf".into(), "mgmt".to_string())));
+    }
+
+    #[test]
+    fn test_parse_placeholders() {
+        let content = "auto swp1 swp2 swp3\nallow-hotplug swp4\niface swp1\nmapping swp3\n";
+        let (interfaces, _, _) = Parser::new().parse(content).unwrap();
+        // A bare stanza declares the interface as much as a full one
+        assert!(interfaces["swp1"].is_declared());
+        assert!(!interfaces["swp2"].is_declared());
+        assert!(interfaces["swp3"].is_declared());
+        assert!(!interfaces["swp4"].is_declared());
+
+        let (interfaces, _, _) = Parser::new().parse("iface swp1\nauto swp1\n").unwrap();
+        assert!(interfaces["swp1"].is_declared());
     }
 
     #[test]
@@ -220,13 +1092,13 @@ iface eth1 inet static
         let iface = &interfaces["eth1"];
         assert_eq!(iface.name, "eth1");
         assert_eq!(iface.family, Some(Family::Inet));
-        assert_eq!(iface.method.as_deref(), Some("static"));
+        assert_eq!(iface.method, Some(Method::Static));
         assert!(iface
             .options
-            .contains(&("address".to_string(), "192.168.1.10".to_string())));
+            .contains(&("address".into(), "192.168.1.10".to_string())));
         assert!(iface
             .options
-            .contains(&("netmask".to_string(), "255.255.255.0".to_string())));
+            .contains(&("netmask".into(), "255.255.255.0".to_string())));
     }
 
     #[test]
@@ -251,29 +1123,29 @@ iface wlan0 inet static
         // Check 'lo' interface
         let lo_iface = &interfaces["lo"];
         assert_eq!(lo_iface.name, "lo");
-        assert_eq!(lo_iface.auto, true);
+        assert!(lo_iface.auto);
         assert_eq!(lo_iface.family, Some(Family::Inet));
-        assert_eq!(lo_iface.method.as_deref(), Some("loopback"));
+        assert_eq!(lo_iface.method_str(), Some("loopback"));
 
         // Check 'eth0' interface
         let eth0_iface = &interfaces["eth0"];
         assert_eq!(eth0_iface.name, "eth0");
-        assert_eq!(eth0_iface.auto, true);
+        assert!(eth0_iface.auto);
         assert_eq!(eth0_iface.family, Some(Family::Inet));
-        assert_eq!(eth0_iface.method.as_deref(), Some("dhcp"));
+        assert_eq!(eth0_iface.method_str(), Some("dhcp"));
 
         // Check 'wlan0' interface
         let wlan0_iface = &interfaces["wlan0"];
         assert_eq!(wlan0_iface.name, "wlan0");
-        assert_eq!(wlan0_iface.auto, true);
+        assert!(wlan0_iface.auto);
         assert_eq!(wlan0_iface.family, Some(Family::Inet));
-        assert_eq!(wlan0_iface.method.as_deref(), Some("static"));
+        assert_eq!(wlan0_iface.method_str(), Some("static"));
         assert!(wlan0_iface
             .options
-            .contains(&("address".to_string(), "192.168.0.100".to_string())));
+            .contains(&("address".into(), "192.168.0.100".to_string())));
         assert!(wlan0_iface
             .options
-            .contains(&("netmask".to_string(), "255.255.255.0".to_string())));
+            .contains(&("netmask".into(), "255.255.255.0".to_string())));
     }
 
     #[test]
@@ -297,29 +1169,51 @@ iface wlan0 inet static
         // Check 'lo' interface
         let lo_iface = &interfaces["lo"];
         assert_eq!(lo_iface.name, "lo");
-        assert_eq!(lo_iface.auto, true);
+        assert!(lo_iface.auto);
         assert_eq!(lo_iface.family, Some(Family::Inet));
-        assert_eq!(lo_iface.method.as_deref(), Some("loopback"));
+        assert_eq!(lo_iface.method_str(), Some("loopback"));
 
         // Check 'eth0' interface
         let eth0_iface = &interfaces["eth0"];
         assert_eq!(eth0_iface.name, "eth0");
-        assert_eq!(eth0_iface.auto, true);
+        assert!(eth0_iface.auto);
         assert_eq!(eth0_iface.family, Some(Family::Inet));
-        assert_eq!(eth0_iface.method.as_deref(), Some("dhcp"));
+        assert_eq!(eth0_iface.method_str(), Some("dhcp"));
 
         // Check 'wlan0' interface
         let wlan0_iface = &interfaces["wlan0"];
         assert_eq!(wlan0_iface.name, "wlan0");
-        assert_eq!(wlan0_iface.auto, true);
+        assert!(wlan0_iface.auto);
         assert_eq!(wlan0_iface.family, Some(Family::Inet));
-        assert_eq!(wlan0_iface.method.as_deref(), Some("static"));
+        assert_eq!(wlan0_iface.method_str(), Some("static"));
         assert!(wlan0_iface
             .options
-            .contains(&("address".to_string(), "192.168.0.100".to_string())));
+            .contains(&("address".into(), "192.168.0.100".to_string())));
         assert!(wlan0_iface
             .options
-            .contains(&("netmask".to_string(), "255.255.255.0".to_string())));
+            .contains(&("netmask".into(), "255.255.255.0".to_string())));
+    }
+
+    #[test]
+    fn test_parse_preserves_key_spelling() {
+        let content = r#"
+iface eth0 inet static
+    Address 192.168.1.10/24
+    post_up /bin/true
+    dns-nameserver 192.0.2.53
+"#;
+        let parser = Parser::new();
+        let (interfaces, _comments, _sources) = parser.parse(content).unwrap();
+        let iface = &interfaces["eth0"];
+
+        assert_eq!(iface.get_option("address"), Some("192.168.1.10/24"));
+        assert_eq!(iface.get_option("post-up"), Some("/bin/true"));
+        assert_eq!(iface.get_option("dns-nameservers"), Some("192.0.2.53"));
+
+        let output = iface.to_string();
+        assert!(output.contains("    Address 192.168.1.10/24\n"));
+        assert!(output.contains("    post_up /bin/true\n"));
+        assert!(output.contains("    dns-nameserver 192.0.2.53\n"));
     }
 
     #[test]
@@ -362,83 +1256,83 @@ iface vlan101
         // Check 'swp54' interface
         let swp54_iface = &interfaces["swp54"];
         assert_eq!(swp54_iface.name, "swp54");
-        assert_eq!(swp54_iface.auto, true);
+        assert!(swp54_iface.auto);
         assert_eq!(swp54_iface.family, None);
         assert_eq!(swp54_iface.method, None);
         // Check options
         assert!(swp54_iface
             .options
-            .contains(&("bridge-access".to_string(), "199".to_string())));
+            .contains(&("bridge-access".into(), "199".to_string())));
         assert!(swp54_iface
             .options
-            .contains(&("mstpctl-bpduguard".to_string(), "yes".to_string())));
+            .contains(&("mstpctl-bpduguard".into(), "yes".to_string())));
         assert!(swp54_iface
             .options
-            .contains(&("mstpctl-portadminedge".to_string(), "yes".to_string())));
+            .contains(&("mstpctl-portadminedge".into(), "yes".to_string())));
         assert!(swp54_iface
             .options
-            .contains(&("mtu".to_string(), "9216".to_string())));
+            .contains(&("mtu".into(), "9216".to_string())));
         assert!(swp54_iface
             .options
-            .contains(&("post-down".to_string(), "/some/script.sh".to_string())));
+            .contains(&("post-down".into(), "/some/script.sh".to_string())));
         assert!(swp54_iface
             .options
-            .contains(&("post-up".to_string(), "/some/script.sh".to_string())));
+            .contains(&("post-up".into(), "/some/script.sh".to_string())));
 
         // Check 'bridge' interface
         let bridge_iface = &interfaces["bridge"];
         assert_eq!(bridge_iface.name, "bridge");
-        assert_eq!(bridge_iface.auto, true);
+        assert!(bridge_iface.auto);
         assert_eq!(bridge_iface.family, None);
         assert_eq!(bridge_iface.method, None);
         // Check options
-        assert!(bridge_iface.options.contains(&("bridge-ports".to_string(), "swp1 swp2 swp3 swp4 swp5 swp6 swp7 swp8 swp9 swp10 swp11 swp12 swp13 swp14 swp15 swp16 swp17 swp18 swp19 swp20 swp21 swp22 swp23 swp24 swp31 swp32 swp33 swp34 swp35 swp36 swp37 swp38 swp39 swp40 swp41 swp42 swp43 swp44 swp45 swp46 swp47 swp48 swp49 swp50 swp51 swp52 swp53 swp54".to_string())));
+        assert!(bridge_iface.options.contains(&("bridge-ports".into(), "swp1 swp2 swp3 swp4 swp5 swp6 swp7 swp8 swp9 swp10 swp11 swp12 swp13 swp14 swp15 swp16 swp17 swp18 swp19 swp20 swp21 swp22 swp23 swp24 swp31 swp32 swp33 swp34 swp35 swp36 swp37 swp38 swp39 swp40 swp41 swp42 swp43 swp44 swp45 swp46 swp47 swp48 swp49 swp50 swp51 swp52 swp53 swp54".to_string())));
         assert!(bridge_iface
             .options
-            .contains(&("bridge-pvid".to_string(), "1".to_string())));
+            .contains(&("bridge-pvid".into(), "1".to_string())));
         assert!(bridge_iface
             .options
-            .contains(&("bridge-vids".to_string(), "100-154 199".to_string())));
+            .contains(&("bridge-vids".into(), "100-154 199".to_string())));
         assert!(bridge_iface
             .options
-            .contains(&("bridge-vlan-aware".to_string(), "yes".to_string())));
+            .contains(&("bridge-vlan-aware".into(), "yes".to_string())));
 
         // Check 'mgmt' interface
         let mgmt_iface = &interfaces["mgmt"];
         assert_eq!(mgmt_iface.name, "mgmt");
-        assert_eq!(mgmt_iface.auto, true);
+        assert!(mgmt_iface.auto);
         assert_eq!(mgmt_iface.family, None);
         assert_eq!(mgmt_iface.method, None);
         // Check options
         assert!(mgmt_iface
             .options
-            .contains(&("address".to_string(), "127.0.0.1/8".to_string())));
+            .contains(&("address".into(), "127.0.0.1/8".to_string())));
         assert!(mgmt_iface
             .options
-            .contains(&("address".to_string(), "::1/128".to_string())));
+            .contains(&("address".into(), "::1/128".to_string())));
         assert!(mgmt_iface
             .options
-            .contains(&("vrf-table".to_string(), "auto".to_string())));
+            .contains(&("vrf-table".into(), "auto".to_string())));
 
         // Check 'vlan101' interface
         let vlan101_iface = &interfaces["vlan101"];
         assert_eq!(vlan101_iface.name, "vlan101");
-        assert_eq!(vlan101_iface.auto, true);
+        assert!(vlan101_iface.auto);
         assert_eq!(vlan101_iface.family, None);
         assert_eq!(vlan101_iface.method, None);
         // Check options
         assert!(vlan101_iface
             .options
-            .contains(&("mtu".to_string(), "9216".to_string())));
+            .contains(&("mtu".into(), "9216".to_string())));
         assert!(vlan101_iface
             .options
-            .contains(&("post-up".to_string(), "/some/script.sh".to_string())));
+            .contains(&("post-up".into(), "/some/script.sh".to_string())));
         assert!(vlan101_iface
             .options
-            .contains(&("vlan-id".to_string(), "101".to_string())));
+            .contains(&("vlan-id".into(), "101".to_string())));
         assert!(vlan101_iface
             .options
-            .contains(&("vlan-raw-device".to_string(), "bridge".to_string())));
+            .contains(&("vlan-raw-device".into(), "bridge".to_string())));
 
         // Check print/display formatting
         // At the end of the test
@@ -486,4 +1380,58 @@ iface vlan101
 
         assert_eq!(output, expected_output);
     }
+
+    #[test]
+    fn test_tag_prefix() {
+        let content = "@tags swp1 role = uplink\n# tags: tier=prod\niface swp1\n";
+        let parser = Parser::new().with_options(ParserOptions {
+            tag_prefix: "@tags swp1".to_string(),
+            ..Default::default()
+        });
+        // The prefix is only matched on comment lines
+        let (interfaces, comments, _) = parser.parse(content).unwrap();
+        assert!(interfaces["swp1"].tags.is_empty());
+        assert_eq!(comments, ["# tags: tier=prod"]);
+
+        let content = "# @ role = uplink, =skipped\niface swp1\n";
+        let parser = Parser::new().with_options(ParserOptions {
+            tag_prefix: "# @".to_string(),
+            ..Default::default()
+        });
+        let (interfaces, _, _) = parser.parse(content).unwrap();
+        let tags: Vec<(&str, &str)> = interfaces["swp1"]
+            .tags
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(tags, [("role", "uplink")]);
+
+        // An empty prefix leaves magic comments as comments
+        let parser = Parser::new().with_options(ParserOptions {
+            tag_prefix: String::new(),
+            ..Default::default()
+        });
+        let (interfaces, comments, _) = parser.parse("# tags: a=b\niface swp1\n").unwrap();
+        assert!(interfaces["swp1"].tags.is_empty());
+        assert_eq!(comments, ["# tags: a=b"]);
+    }
+
+    #[test]
+    fn test_tags_of_every_family_stanza() {
+        let content = "\
+# tags: tier=prod
+iface eth0 inet static
+    address 192.0.2.1/24
+# tags: role=uplink
+iface eth0 inet6 static
+    address 2001:db8::1/64
+";
+        let (interfaces, _, _) = Parser::new().parse(content).unwrap();
+        let tags: Vec<(&str, &str)> = interfaces["eth0"]
+            .tags
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(tags, [("role", "uplink"), ("tier", "prod")]);
+    }
 }
@@ -0,0 +1,375 @@
+//! Checking that a collection survives being written and read back.
+//!
+//! [`NetworkInterfaces::check_roundtrip`] renders a collection the way it
+//! would be saved, parses the result, and compares the two. The guarantee
+//! it checks is that for any collection, parsing its rendering produces a
+//! semantically equal collection:
+//!
+//! - the same interface names;
+//! - for each interface, the same `auto` flag, `allow-*` classes, family,
+//!   method, mapping, disabled flag, and tags;
+//! - for each option key, the same values in the same order. Options with
+//!   different keys may be reordered, since they can be written sorted by
+//!   key with [`WriteOptions::sort_options`](crate::WriteOptions::sort_options);
+//! - the same header comments and source directives.
+//!
+//! Runs of whitespace in option values, comments, and sources are not
+//! significant. Origins are not compared, since a re-parsed interface points
+//! at the rendering rather than the original file.
+//!
+//! Each difference is reported as a [`Divergence`]:
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | `parse-failed` | the rendering cannot be parsed |
+//! | `interface-lost` | an interface is missing after re-parsing |
+//! | `interface-added` | re-parsing produced an interface that was not in the collection |
+//! | `auto-mismatch` | the `auto` flag differs |
+//! | `allow-mismatch` | the `allow-*` classes differ |
+//! | `family-mismatch` | the family differs |
+//! | `method-mismatch` | the method differs |
+//! | `option-mismatch` | the values of an option key differ |
+//! | `mapping-mismatch` | the mapping differs |
+//! | `disabled-mismatch` | the interface is disabled on one side only |
+//! | `tags-mismatch` | the tags differ |
+//! | `comments-mismatch` | the header comments differ |
+//! | `sources-mismatch` | the source directives differ |
+//!
+//! A collection can only diverge if it holds values the file format cannot
+//! express, such as an interface name or option key containing whitespace,
+//! or a method with no family that reads back as a family.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::interface::Interface;
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let mut net_ifaces = NetworkInterfaces::from_interfaces(None);
+//! net_ifaces.add_interface(Interface::builder("eth0").with_option("mtu", "9000").build());
+//! assert!(net_ifaces.check_roundtrip().is_ok());
+//!
+//! net_ifaces.add_interface(Interface::builder("eth1").with_method("inet6").build());
+//! let err = net_ifaces.check_roundtrip().unwrap_err();
+//! assert_eq!(err.divergences[0].code, "family-mismatch");
+//! ```
+
+use crate::helper::sort::natural;
+use crate::interface::Interface;
+use crate::network_interfaces::{comment_lines, NetworkInterfaces};
+use crate::parser::Parser;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+/// A single difference between a collection and its re-parsed rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// A stable, machine-readable identifier for the difference (e.g.,
+    /// `"option-mismatch"`).
+    pub code: &'static str,
+    /// The interface the difference applies to, or `None` for differences
+    /// in the file as a whole.
+    pub interface: Option<String>,
+    /// A human-readable description of the difference.
+    pub message: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.interface {
+            Some(name) => write!(f, "[{}] {}: {}", self.code, name, self.message),
+            None => write!(f, "[{}] {}", self.code, self.message),
+        }
+    }
+}
+
+/// The error returned by [`NetworkInterfaces::check_roundtrip`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundtripError {
+    /// The rendering that failed to read back.
+    pub rendered: String,
+    /// All differences: file-level ones first, then those of each interface
+    /// in the collection in natural order, then interfaces added by
+    /// re-parsing.
+    pub divergences: Vec<Divergence>,
+}
+
+impl fmt::Display for RoundtripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Rendered configuration does not read back ({} differences)",
+            self.divergences.len()
+        )?;
+        for divergence in &self.divergences {
+            write!(f, "\n  {}", divergence)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for RoundtripError {}
+
+/// Collects divergences.
+#[derive(Default)]
+struct Divergences(Vec<Divergence>);
+
+impl Divergences {
+    fn push(&mut self, code: &'static str, interface: Option<&str>, message: String) {
+        self.0.push(Divergence {
+            code,
+            interface: interface.map(str::to_string),
+            message,
+        });
+    }
+
+    /// Records a divergence if `expected` and `actual` differ.
+    fn compare<T: PartialEq + fmt::Debug>(
+        &mut self,
+        code: &'static str,
+        interface: Option<&str>,
+        expected: T,
+        actual: T,
+    ) {
+        if expected != actual {
+            let message = format!("expected {:?}, read back {:?}", expected, actual);
+            self.push(code, interface, message);
+        }
+    }
+}
+
+/// Collapses runs of whitespace, as the parser does.
+fn collapse(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Groups the option values of `iface` by key, in file order.
+fn options_by_key(iface: &Interface) -> BTreeMap<&str, Vec<String>> {
+    let mut options: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (key, value) in &iface.options {
+        options.entry(key).or_default().push(collapse(value));
+    }
+    options
+}
+
+/// Returns the script and map lines of the mapping of `iface`.
+fn mapping(iface: &Interface) -> Option<(String, Vec<String>)> {
+    let mapping = iface.mapping.as_ref()?;
+    let maps = mapping.maps.iter().map(|map| collapse(map)).collect();
+    Some((collapse(&mapping.script), maps))
+}
+
+/// Compares an interface with its re-parsed counterpart.
+fn compare_interfaces(out: &mut Divergences, expected: &Interface, actual: &Interface) {
+    let name = Some(expected.name.as_str());
+    out.compare("auto-mismatch", name, expected.auto, actual.auto);
+    out.compare("allow-mismatch", name, &expected.allow, &actual.allow);
+    out.compare("family-mismatch", name, &expected.family, &actual.family);
+    out.compare("method-mismatch", name, &expected.method, &actual.method);
+
+    let expected_options = options_by_key(expected);
+    let actual_options = options_by_key(actual);
+    let keys: BTreeSet<&str> = expected_options
+        .keys()
+        .chain(actual_options.keys())
+        .copied()
+        .collect();
+    for key in keys {
+        let expected_values = expected_options.get(key).map_or(&[][..], Vec::as_slice);
+        let actual_values = actual_options.get(key).map_or(&[][..], Vec::as_slice);
+        if expected_values != actual_values {
+            let message = format!(
+                "option {}: expected {:?}, read back {:?}",
+                key, expected_values, actual_values
+            );
+            out.push("option-mismatch", name, message);
+        }
+    }
+
+    out.compare("mapping-mismatch", name, mapping(expected), mapping(actual));
+    out.compare(
+        "disabled-mismatch",
+        name,
+        expected.disabled,
+        actual.disabled,
+    );
+    out.compare("tags-mismatch", name, &expected.tags, &actual.tags);
+}
+
+impl NetworkInterfaces {
+    /// Checks that the collection reads back unchanged once written.
+    ///
+    /// The collection is rendered as by its `Display` implementation,
+    /// parsed, and compared with the original. See the
+    /// [module documentation](crate::roundtrip) for what "unchanged" means
+    /// and the differences reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RoundtripError`] listing every difference if the
+    /// rendering does not read back as an equal collection.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// if let Err(err) = net_ifaces.check_roundtrip() {
+    ///     eprintln!("{}", err);
+    /// } else {
+    ///     net_ifaces.save().unwrap();
+    /// }
+    /// ```
+    pub fn check_roundtrip(&self) -> Result<(), RoundtripError> {
+        let rendered = self.to_string();
+        let mut out = Divergences::default();
+        let (reparsed, comments, sources) = match Parser::new()
+            .with_options(self.parser_options().clone())
+            .parse(&rendered)
+        {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                out.push("parse-failed", None, err.to_string());
+                return Err(RoundtripError {
+                    rendered,
+                    divergences: out.0,
+                });
+            }
+        };
+
+        let expected_comments: Vec<String> = comment_lines(self.header_comments())
+            .map(|line| collapse(&line))
+            .collect();
+        let actual_comments: Vec<String> = comments.iter().map(|line| collapse(line)).collect();
+        out.compare(
+            "comments-mismatch",
+            None,
+            expected_comments,
+            actual_comments,
+        );
+        let expected_sources: Vec<String> = self.sources().iter().map(|s| collapse(s)).collect();
+        let actual_sources: Vec<String> = sources.iter().map(|s| collapse(s)).collect();
+        out.compare("sources-mismatch", None, expected_sources, actual_sources);
+
+        let mut reparsed: HashMap<String, Interface> = reparsed;
+        for (name, iface) in self.iter_by_name() {
+            match reparsed.remove(name) {
+                Some(actual) => compare_interfaces(&mut out, iface, &actual),
+                None => out.push(
+                    "interface-lost",
+                    Some(name),
+                    "not found after re-parsing".to_string(),
+                ),
+            }
+        }
+        let mut added: Vec<String> = reparsed.into_keys().collect();
+        added.sort_by(|a, b| natural(a, b));
+        for name in added {
+            out.push(
+                "interface-added",
+                Some(&name),
+                "not in the collection".to_string(),
+            );
+        }
+
+        if out.0.is_empty() {
+            Ok(())
+        } else {
+            Err(RoundtripError {
+                rendered,
+                divergences: out.0,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::{Family, Mapping};
+    use crate::parser::ParserOptions;
+
+    #[test]
+    fn test_check_roundtrip_loaded_file() {
+        let net_ifaces = NetworkInterfaces::load("tests/interfaces").unwrap();
+        assert_eq!(net_ifaces.check_roundtrip(), Ok(()));
+    }
+
+    #[test]
+    fn test_check_roundtrip_fixed_divergences() {
+        let mapped = Interface::builder("eth0")
+            .with_family(Family::Inet)
+            .with_method("dhcp")
+            .with_mapping(Mapping {
+                script: "/usr/local/bin/map-scripts".to_string(),
+                maps: vec!["home eth0-home".to_string(), "work eth0-work".to_string()],
+            })
+            .build();
+        let mut net_ifaces: NetworkInterfaces = [
+            mapped,
+            Interface::builder("eth1")
+                .with_option("mtu", "9000")
+                .with_option("up", "ip  link set  eth1 up")
+                .build(),
+            Interface::builder("eth2").with_auto(true).build(),
+        ]
+        .into_iter()
+        .collect();
+        net_ifaces.set_header_comments(vec!["managed by netd".to_string()]);
+        net_ifaces.add_header_comment("# second line  ");
+        assert_eq!(net_ifaces.check_roundtrip(), Ok(()));
+
+        // Raw comments that do not start with `#` are still written as comments
+        let net_ifaces = NetworkInterfaces::from_parts(
+            HashMap::new(),
+            vec!["first\n\nsecond".to_string()],
+            Vec::new(),
+            None,
+            None,
+            ParserOptions::default(),
+        );
+        assert_eq!(net_ifaces.check_roundtrip(), Ok(()));
+        assert!(net_ifaces.to_string().starts_with("# first\n#\n# second\n"));
+    }
+
+    #[test]
+    fn test_check_roundtrip_reports_divergences() {
+        let net_ifaces: NetworkInterfaces = [
+            Interface::builder("eth0").with_method("inet6").build(),
+            Interface::builder("eth1")
+                .with_option("bad key", "1")
+                .with_option("mtu", "9000")
+                .build(),
+            Interface::builder("eth2 eth3").build(),
+        ]
+        .into_iter()
+        .collect();
+        let err = net_ifaces.check_roundtrip().unwrap_err();
+        let codes: Vec<(&str, Option<&str>)> = err
+            .divergences
+            .iter()
+            .map(|d| (d.code, d.interface.as_deref()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("family-mismatch", Some("eth0")),
+                ("method-mismatch", Some("eth0")),
+                ("option-mismatch", Some("eth1")),
+                ("option-mismatch", Some("eth1")),
+                ("interface-lost", Some("eth2 eth3")),
+                ("interface-added", Some("eth2")),
+            ]
+        );
+        assert_eq!(
+            err.divergences[2].to_string(),
+            "[option-mismatch] eth1: option bad: expected [], read back [\"key 1\"]"
+        );
+        assert!(err.rendered.contains("iface eth2 eth3\n"));
+        assert!(err.to_string().starts_with(
+            "Rendered configuration does not read back (6 differences)\n  [family-mismatch]"
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+//! Typed `source` and `source-directory` directives.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The kind of an include directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SourceKind {
+    /// `source <pattern>`: include the files matching a shell glob.
+    Source,
+    /// `source-directory <dir>`: include the files in a directory whose
+    /// names consist only of letters, digits, underscores, and hyphens.
+    SourceDirectory,
+}
+
+impl SourceKind {
+    /// Returns the directive keyword.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceKind::Source => "source",
+            SourceKind::SourceDirectory => "source-directory",
+        }
+    }
+}
+
+/// An include directive from an `interfaces(5)` file.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::source::{Source, SourceKind};
+///
+/// let source: Source = "source  /etc/network/interfaces.d/*".parse().unwrap();
+/// assert_eq!(source.kind, SourceKind::Source);
+/// assert_eq!(source.path, "/etc/network/interfaces.d/*");
+/// assert_eq!(source.to_string(), "source /etc/network/interfaces.d/*");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Source {
+    /// Whether this is a `source` or `source-directory` directive.
+    pub kind: SourceKind,
+    /// The glob pattern or directory, as written.
+    pub path: String,
+}
+
+impl Source {
+    /// Creates a `source <pattern>` directive.
+    pub fn glob(pattern: impl Into<String>) -> Self {
+        Source {
+            kind: SourceKind::Source,
+            path: pattern.into(),
+        }
+    }
+
+    /// Creates a `source-directory <dir>` directive.
+    pub fn directory(dir: impl Into<String>) -> Self {
+        Source {
+            kind: SourceKind::SourceDirectory,
+            path: dir.into(),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind.as_str(), self.path)
+    }
+}
+
+impl FromStr for Source {
+    type Err = SourceParseError;
+
+    /// Parses a directive line such as `source-directory /etc/network/interfaces.d`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SourceParseError` if the line does not start with `source`
+    /// or `source-directory`, has no path, or spans more than one line.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.trim();
+        if line.contains(['\n', '\r']) {
+            return Err(SourceParseError(s.to_string()));
+        }
+        let (keyword, path) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| SourceParseError(s.to_string()))?;
+        let kind = match keyword {
+            "source" => SourceKind::Source,
+            "source-directory" => SourceKind::SourceDirectory,
+            _ => return Err(SourceParseError(s.to_string())),
+        };
+        Ok(Source {
+            kind,
+            path: path.trim().to_string(),
+        })
+    }
+}
+
+/// An error that occurs when parsing a `Source` from a string.
+#[derive(Debug, Clone)]
+pub struct SourceParseError(pub String);
+
+impl fmt::Display for SourceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid source directive: {}", self.0)
+    }
+}
+
+impl Error for SourceParseError {}
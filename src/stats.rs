@@ -0,0 +1,211 @@
+//! Summary statistics for a collection of interfaces.
+
+use crate::interface::option::keys_match;
+use crate::interface::{Interface, InterfaceKind, VlanRanges};
+use crate::network_interfaces::NetworkInterfaces;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A census of a [`NetworkInterfaces`] collection, computed by
+/// [`NetworkInterfaces::stats`].
+///
+/// With the `serde` feature, `Stats` serializes as a struct of these fields,
+/// with `vlans_in_use` written as a VLAN list string such as `"1 100-110"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    /// The total number of interfaces.
+    pub interfaces: usize,
+    /// Interfaces of kind [`InterfaceKind::Loopback`].
+    pub loopback: usize,
+    /// Interfaces of kind [`InterfaceKind::Vrf`].
+    pub vrfs: usize,
+    /// Interfaces of kind [`InterfaceKind::Bridge`].
+    pub bridges: usize,
+    /// Interfaces of kind [`InterfaceKind::Bond`].
+    pub bonds: usize,
+    /// Interfaces of kind [`InterfaceKind::Vxlan`].
+    pub vxlans: usize,
+    /// Interfaces of kind [`InterfaceKind::Vlan`].
+    pub vlans: usize,
+    /// Interfaces of kind [`InterfaceKind::Alias`].
+    pub aliases: usize,
+    /// Interfaces of kind [`InterfaceKind::Physical`].
+    pub physical: usize,
+    /// Interfaces per address family, for stanzas that name one.
+    pub by_family: BTreeMap<String, usize>,
+    /// Interfaces per method, for stanzas that name one.
+    pub by_method: BTreeMap<String, usize>,
+    /// Interfaces brought up at boot (`auto`).
+    pub auto: usize,
+    /// Interfaces that are not `auto`: started on hotplug or by hand.
+    pub manual_start: usize,
+    /// The number of `address` lines.
+    pub addresses: usize,
+    /// VLAN ids referenced by `vlan-id`, `bridge-access`, `bridge-vids`,
+    /// `bridge-pvid`, or a VLAN interface name such as `eth0.100`.
+    pub vlans_in_use: VlanRanges,
+}
+
+impl Stats {
+    /// Adds one interface to the census.
+    fn count(&mut self, iface: &Interface, strict_keys: bool) {
+        self.interfaces += 1;
+        let kind = iface.kind();
+        *match kind {
+            InterfaceKind::Loopback => &mut self.loopback,
+            InterfaceKind::Vrf => &mut self.vrfs,
+            InterfaceKind::Bridge => &mut self.bridges,
+            InterfaceKind::Bond => &mut self.bonds,
+            InterfaceKind::Vxlan => &mut self.vxlans,
+            InterfaceKind::Vlan => &mut self.vlans,
+            InterfaceKind::Alias => &mut self.aliases,
+            InterfaceKind::Physical => &mut self.physical,
+        } += 1;
+        if let Some(family) = &iface.family {
+            *self.by_family.entry(family.to_string()).or_default() += 1;
+        }
+        if let Some(method) = iface.method_str() {
+            *self.by_method.entry(method.to_string()).or_default() += 1;
+        }
+        if iface.auto {
+            self.auto += 1;
+        } else {
+            self.manual_start += 1;
+        }
+
+        let mut has_vlan_id = false;
+        for (key, value) in &iface.options {
+            let is = |name: &str| keys_match(key, name, strict_keys);
+            if is("address") {
+                self.addresses += 1;
+            } else if is("bridge-vids") {
+                if let Ok(vids) = value.parse::<VlanRanges>() {
+                    for vid in vids.iter() {
+                        self.vlans_in_use.insert(vid);
+                    }
+                }
+            } else if is("vlan-id") || is("bridge-access") || is("bridge-pvid") {
+                has_vlan_id |= is("vlan-id");
+                self.insert_vid(value);
+            }
+        }
+        if kind == InterfaceKind::Vlan && !has_vlan_id {
+            let suffix = match iface.name.rsplit_once('.') {
+                Some((_, id)) => id,
+                None => iface.name.trim_start_matches("vlan"),
+            };
+            self.insert_vid(suffix);
+        }
+    }
+
+    /// Records a VLAN id if `value` is a valid one.
+    fn insert_vid(&mut self, value: &str) {
+        if let Ok(vid) = value.trim().parse::<u16>() {
+            if (VlanRanges::MIN..=VlanRanges::MAX).contains(&vid) {
+                self.vlans_in_use.insert(vid);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    /// Writes a short multi-line summary.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |map: &BTreeMap<String, usize>| {
+            map.iter()
+                .map(|(name, count)| format!("{} {}", name, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(
+            f,
+            "{} interfaces ({} auto, {} manual start)",
+            self.interfaces, self.auto, self.manual_start
+        )?;
+        writeln!(
+            f,
+            "  loopback {}, vrf {}, bridge {}, bond {}, vxlan {}, vlan {}, alias {}, physical {}",
+            self.loopback,
+            self.vrfs,
+            self.bridges,
+            self.bonds,
+            self.vxlans,
+            self.vlans,
+            self.aliases,
+            self.physical
+        )?;
+        writeln!(f, "  families: {}", counts(&self.by_family))?;
+        writeln!(f, "  methods: {}", counts(&self.by_method))?;
+        writeln!(f, "  addresses: {}", self.addresses)?;
+        writeln!(f, "  vlans in use: {}", self.vlans_in_use)
+    }
+}
+
+impl NetworkInterfaces {
+    /// Computes summary statistics for the collection in a single pass.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// let stats = net_ifaces.stats();
+    /// println!("{} bridges", stats.bridges);
+    /// print!("{}", stats);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let strict_keys = self.parser_options().strict_keys;
+        let mut stats = Stats::default();
+        for (_, iface) in self.iter() {
+            stats.count(iface, strict_keys);
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> NetworkInterfaces {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        NetworkInterfaces::load(format!("{}/tests/interfaces", dir)).unwrap()
+    }
+
+    #[test]
+    fn test_stats_fixture() {
+        let stats = fixture().stats();
+        assert_eq!(
+            stats.to_string(),
+            "11 interfaces (10 auto, 1 manual start)\n\
+             \x20 loopback 1, vrf 1, bridge 1, bond 1, vxlan 1, vlan 1, alias 0, physical 5\n\
+             \x20 families: inet 3, inet6 1\n\
+             \x20 methods: auto 1, dhcp 1, loopback 1, static 1\n\
+             \x20 addresses: 3\n\
+             \x20 vlans in use: 1 100 200-210\n"
+        );
+    }
+
+    #[test]
+    fn test_stats_vlan_names() {
+        let mut net_ifaces = fixture();
+        net_ifaces.add_interface(Interface::builder("eth0.42").build());
+        net_ifaces.add_interface(Interface::builder("vlan300").build());
+        net_ifaces.add_interface(Interface::builder("swp9.0").build());
+        assert_eq!(
+            net_ifaces.stats().vlans_in_use.to_string(),
+            "1 42 100 200-210 300"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_stats_serialize() {
+        let json = serde_json::to_value(fixture().stats()).unwrap();
+        assert_eq!(json["bridges"], 1);
+        assert_eq!(json["by_method"]["dhcp"], 1);
+        assert_eq!(json["vlans_in_use"], "1 100 200-210");
+    }
+}
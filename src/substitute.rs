@@ -0,0 +1,535 @@
+//! Variable substitution in option values.
+//!
+//! Template configurations can hold placeholders such as
+//! `address {{ loopback_ip }}/32` in option values.
+//! [`NetworkInterfaces::substitute`] replaces them with the values of a set of
+//! variables, and [`NetworkInterfaces::required_variables`] lists the
+//! variables a configuration needs so that inputs can be checked up front.
+//!
+//! A placeholder is `{{`, a variable name, and `}}`, with optional spaces or
+//! tabs around the name. A name starts with an ASCII letter or `_`, followed
+//! by ASCII letters, digits, `_`, `-`, or `.`.
+//!
+//! To write a literal `{{`, escape it as `\{{`; the backslash is removed and
+//! the text up to the next `}}` is not treated as a placeholder. Backslashes
+//! anywhere else, and `}}` without an opening `{{`, are kept as they are.
+//! Values of variables are inserted verbatim and are not scanned for
+//! placeholders themselves. Since escapes are removed, substitute a template
+//! only once.
+//!
+//! Header comments are left alone unless
+//! [`SubstitutionOptions::comments`] is set.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::interface::Interface;
+//! use interface_rs::NetworkInterfaces;
+//! use std::collections::HashMap;
+//!
+//! let mut net_ifaces = NetworkInterfaces::from_interfaces([Interface::builder("lo")
+//!     .with_option("address", "{{ loopback_ip }}/32")
+//!     .with_option("alias", "\\{{ literal }}")
+//!     .build()]);
+//! assert_eq!(net_ifaces.required_variables(), ["loopback_ip".to_string()].into());
+//!
+//! let vars = HashMap::from([("loopback_ip".to_string(), "10.0.0.11".to_string())]);
+//! let report = net_ifaces.substitute(&vars).unwrap();
+//! assert_eq!(report.interfaces, ["lo"]);
+//! let lo = net_ifaces.get_interface("lo").unwrap();
+//! assert_eq!(lo.get_option("address"), Some("10.0.0.11/32"));
+//! assert_eq!(lo.get_option("alias"), Some("{{ literal }}"));
+//! ```
+
+use crate::error::SubstitutionError;
+use crate::network_interfaces::NetworkInterfaces;
+use std::collections::{BTreeSet, HashMap};
+
+/// Options for variable substitution.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubstitutionOptions {
+    /// Also substitute placeholders in the header comments. Defaults to
+    /// `false`.
+    pub comments: bool,
+}
+
+/// What a successful [`NetworkInterfaces::substitute`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubstitutionReport {
+    /// The interfaces with at least one substituted option value, in natural
+    /// order.
+    pub interfaces: Vec<String>,
+    /// Whether any header comment had a placeholder substituted.
+    pub comments: bool,
+    /// The number of placeholders replaced.
+    pub replacements: usize,
+    /// The given variables that no placeholder refers to, sorted. A name here
+    /// is often a typo.
+    pub unused_variables: Vec<String>,
+}
+
+/// A part of a value: literal text or a placeholder.
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+/// Returns `true` if `name` is a valid variable name.
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Splits `value` into literal text and placeholders.
+///
+/// Returns the text of the first malformed placeholder as the error.
+fn segments(value: &str) -> Result<Vec<Segment<'_>>, &str> {
+    let mut segments = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        if let Some(text) = rest[..start].strip_suffix('\\') {
+            segments.push(Segment::Text(text));
+            segments.push(Segment::Text("{{"));
+            rest = after;
+            continue;
+        }
+        segments.push(Segment::Text(&rest[..start]));
+        let end = match after.find("}}") {
+            Some(end) => end,
+            None => return Err(&rest[start..]),
+        };
+        let name = after[..end].trim_matches(|c| c == ' ' || c == '\t');
+        if !is_variable_name(name) {
+            return Err(&rest[start..start + end + 4]);
+        }
+        segments.push(Segment::Variable(name));
+        rest = &after[end + 2..];
+    }
+    segments.push(Segment::Text(rest));
+    Ok(segments)
+}
+
+/// Substitutes the placeholders of one value.
+struct Substitution<'v> {
+    vars: &'v HashMap<String, String>,
+    undefined: BTreeSet<String>,
+    used: BTreeSet<&'v str>,
+    replacements: usize,
+}
+
+impl Substitution<'_> {
+    /// Returns `value` with its placeholders replaced, or `None` if it has
+    /// none. Undefined variables are recorded and left in place.
+    fn apply(&mut self, location: &str, value: &str) -> Result<Option<String>, SubstitutionError> {
+        if !value.contains("{{") {
+            return Ok(None);
+        }
+        let segments = segments(value).map_err(|text| SubstitutionError::InvalidPlaceholder {
+            location: location.to_string(),
+            text: text.to_string(),
+        })?;
+        let mut substituted = String::with_capacity(value.len());
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => substituted.push_str(text),
+                Segment::Variable(name) => match self.vars.get_key_value(name) {
+                    Some((key, var)) => {
+                        if var.contains(['\n', '\r']) {
+                            return Err(SubstitutionError::MultilineValue(name.to_string()));
+                        }
+                        substituted.push_str(var);
+                        self.used.insert(key);
+                        self.replacements += 1;
+                    }
+                    None => {
+                        self.undefined.insert(name.to_string());
+                    }
+                },
+            }
+        }
+        Ok(Some(substituted))
+    }
+}
+
+impl NetworkInterfaces {
+    /// Replaces `{{ name }}` placeholders in option values with the values of
+    /// `vars`.
+    ///
+    /// Equivalent to [`substitute_with`](NetworkInterfaces::substitute_with)
+    /// with default options, which leave the header comments alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `vars` - The value of each variable.
+    ///
+    /// # Returns
+    ///
+    /// A `SubstitutionReport` of the interfaces that were changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SubstitutionError`, and changes nothing, if a placeholder is
+    /// malformed, refers to variables missing from `vars` (all of which are
+    /// listed), or would insert a value spanning several lines.
+    pub fn substitute(
+        &mut self,
+        vars: &HashMap<String, String>,
+    ) -> Result<SubstitutionReport, SubstitutionError> {
+        self.substitute_with(vars, &SubstitutionOptions::default())
+    }
+
+    /// Replaces `{{ name }}` placeholders with the values of `vars`, with the
+    /// given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `vars` - The value of each variable.
+    /// * `options` - Whether to substitute in header comments too.
+    ///
+    /// # Returns
+    ///
+    /// A `SubstitutionReport` of what was changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SubstitutionError`, and changes nothing, if a placeholder is
+    /// malformed, refers to variables missing from `vars` (all of which are
+    /// listed), or would insert a value spanning several lines.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::error::SubstitutionError;
+    /// use interface_rs::interface::Interface;
+    /// use interface_rs::substitute::SubstitutionOptions;
+    /// use interface_rs::NetworkInterfaces;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut net_ifaces = NetworkInterfaces::from_interfaces([Interface::builder("vni100")
+    ///     .with_option("vxlan-id", "{{ vni }}")
+    ///     .with_option("vxlan-local-tunnelip", "{{ vtep_ip }}")
+    ///     .build()]);
+    /// net_ifaces.add_header_comment("Rendered for {{ hostname }}");
+    ///
+    /// let options = SubstitutionOptions { comments: true };
+    /// let err = net_ifaces.substitute_with(&HashMap::new(), &options).unwrap_err();
+    /// assert_eq!(
+    ///     err,
+    ///     SubstitutionError::UndefinedVariables(vec![
+    ///         "hostname".to_string(),
+    ///         "vni".to_string(),
+    ///         "vtep_ip".to_string(),
+    ///     ])
+    /// );
+    /// ```
+    pub fn substitute_with(
+        &mut self,
+        vars: &HashMap<String, String>,
+        options: &SubstitutionOptions,
+    ) -> Result<SubstitutionReport, SubstitutionError> {
+        let mut substitution = Substitution {
+            vars,
+            undefined: BTreeSet::new(),
+            used: BTreeSet::new(),
+            replacements: 0,
+        };
+
+        // Everything is substituted up front so that errors change nothing
+        let mut changes = Vec::new();
+        for (name, iface) in self.iter_by_name() {
+            let mut values = Vec::new();
+            for (stanza, options) in iface.stanzas().map(|stanza| stanza.options).enumerate() {
+                for (index, (key, value)) in options.iter().enumerate() {
+                    let location = format!("{} {}", name, key);
+                    if let Some(substituted) = substitution.apply(&location, value)? {
+                        values.push((stanza, index, substituted));
+                    }
+                }
+            }
+            if !values.is_empty() {
+                changes.push((name.clone(), values));
+            }
+        }
+        let mut comments = None;
+        if options.comments {
+            let mut substituted = Vec::with_capacity(self.header_comments().len());
+            let mut changed = false;
+            for comment in self.header_comments() {
+                match substitution.apply("header comment", comment)? {
+                    Some(comment) => {
+                        substituted.push(comment);
+                        changed = true;
+                    }
+                    None => substituted.push(comment.clone()),
+                }
+            }
+            if changed {
+                comments = Some(substituted);
+            }
+        }
+        if !substitution.undefined.is_empty() {
+            let names = substitution.undefined.into_iter().collect();
+            return Err(SubstitutionError::UndefinedVariables(names));
+        }
+
+        let mut unused_variables: Vec<String> = vars
+            .keys()
+            .filter(|name| !substitution.used.contains(name.as_str()))
+            .cloned()
+            .collect();
+        unused_variables.sort();
+        let mut report = SubstitutionReport {
+            interfaces: Vec::with_capacity(changes.len()),
+            comments: comments.is_some(),
+            replacements: substitution.replacements,
+            unused_variables,
+        };
+        for (name, values) in changes {
+            let iface = self
+                .get_interface_mut(&name)
+                .expect("substituted interfaces exist");
+            let mut stanzas: Vec<_> = iface.stanza_options_mut().collect();
+            for (stanza, index, value) in values {
+                stanzas[stanza][index].1 = value;
+            }
+            report.interfaces.push(name);
+        }
+        if let Some(comments) = comments {
+            self.set_header_comments(comments);
+        }
+        Ok(report)
+    }
+
+    /// Returns the names of the variables referred to by placeholders in
+    /// option values, sorted.
+    ///
+    /// Malformed placeholders are skipped; [`substitute`](NetworkInterfaces::substitute)
+    /// reports them.
+    pub fn required_variables(&self) -> BTreeSet<String> {
+        self.required_variables_with(&SubstitutionOptions::default())
+    }
+
+    /// Returns the names of the variables that
+    /// [`substitute_with`](NetworkInterfaces::substitute_with) needs with the
+    /// given options, sorted.
+    ///
+    /// Malformed placeholders are skipped.
+    pub fn required_variables_with(&self, options: &SubstitutionOptions) -> BTreeSet<String> {
+        let values = self
+            .iter()
+            .flat_map(|(_, iface)| iface.stanzas().flat_map(|stanza| stanza.options))
+            .map(|(_, value)| value);
+        let comments = self.header_comments().iter().filter(|_| options.comments);
+        values
+            .chain(comments)
+            .filter_map(|value| segments(value).ok())
+            .flatten()
+            .filter_map(|segment| match segment {
+                Segment::Variable(name) => Some(name.to_string()),
+                Segment::Text(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::Interface;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_segments() {
+        use Segment::{Text, Variable};
+
+        assert_eq!(segments("9216"), Ok(vec![Text("9216")]));
+        assert_eq!(
+            segments("{{ip}}/{{\tprefix }}"),
+            Ok(vec![
+                Text(""),
+                Variable("ip"),
+                Text("/"),
+                Variable("prefix"),
+                Text("")
+            ])
+        );
+        assert_eq!(
+            segments("a \\{{ b }} {{ c }}"),
+            Ok(vec![
+                Text("a "),
+                Text("{{"),
+                Text(" b }} "),
+                Variable("c"),
+                Text("")
+            ])
+        );
+        assert_eq!(segments("x }} y"), Ok(vec![Text("x }} y")]));
+        assert_eq!(
+            segments("{{ site.vtep-ip_2 }}").unwrap()[1],
+            Variable("site.vtep-ip_2")
+        );
+
+        for (value, error) in [
+            ("{{ ip", "{{ ip"),
+            ("a {{ }} b", "{{ }}"),
+            ("{{ 1ip }}", "{{ 1ip }}"),
+            ("{{ two words }}/32", "{{ two words }}"),
+            ("{{{ ip }}}", "{{{ ip }}"),
+        ] {
+            assert_eq!(segments(value), Err(error), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_substitute() {
+        let mut net_ifaces = NetworkInterfaces::from_interfaces([
+            Interface::builder("lo")
+                .with_option("address", "{{ loopback_ip }}/32")
+                .build(),
+            Interface::builder("swp1")
+                .with_option("mtu", "9216")
+                .build(),
+            Interface::builder("vni100")
+                .with_option("vxlan-id", "100")
+                .with_option("vxlan-local-tunnelip", "{{ vtep_ip }}")
+                .with_option("alias", "vtep {{ vtep_ip }} \\{{ not a var }}")
+                .build(),
+        ]);
+        net_ifaces.add_header_comment("Rendered for {{ hostname }}");
+        assert_eq!(
+            net_ifaces.required_variables(),
+            BTreeSet::from(["loopback_ip".to_string(), "vtep_ip".to_string()])
+        );
+        let with_comments = SubstitutionOptions { comments: true };
+        assert_eq!(net_ifaces.required_variables_with(&with_comments).len(), 3);
+
+        // Every missing variable is listed, and nothing changes
+        let before = net_ifaces.clone();
+        let err = net_ifaces
+            .substitute_with(&vars(&[("vtep_ip", "10.0.0.11")]), &with_comments)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SubstitutionError::UndefinedVariables(vec![
+                "hostname".to_string(),
+                "loopback_ip".to_string()
+            ])
+        );
+        assert_eq!(
+            err.to_string(),
+            "Undefined variables: hostname, loopback_ip"
+        );
+        assert_eq!(net_ifaces, before);
+
+        let err = net_ifaces
+            .substitute(&vars(&[("loopback_ip", "10.0.0.1"), ("vtep_ip", "a\nb")]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SubstitutionError::MultilineValue("vtep_ip".to_string())
+        );
+        assert_eq!(net_ifaces, before);
+
+        let report = net_ifaces
+            .substitute(&vars(&[
+                ("loopback_ip", "10.0.0.11"),
+                ("vtep_ip", "10.0.0.11"),
+                ("vtep", "unused"),
+            ]))
+            .unwrap();
+        assert_eq!(
+            report,
+            SubstitutionReport {
+                interfaces: vec!["lo".to_string(), "vni100".to_string()],
+                comments: false,
+                replacements: 3,
+                unused_variables: vec!["vtep".to_string()],
+            }
+        );
+        assert_eq!(
+            net_ifaces.to_string(),
+            "# Rendered for {{ hostname }}\n\
+             \niface lo\n    address 10.0.0.11/32\n\
+             \niface swp1\n    mtu 9216\n\
+             \niface vni100\n    vxlan-id 100\n    vxlan-local-tunnelip 10.0.0.11\n    \
+             alias vtep 10.0.0.11 {{ not a var }}\n"
+        );
+
+        // The unescaped braces are a placeholder to a second pass
+        let err = net_ifaces.substitute(&HashMap::new()).unwrap_err();
+        assert!(matches!(err, SubstitutionError::InvalidPlaceholder { .. }));
+
+        let mut net_ifaces = before;
+        let report = net_ifaces
+            .substitute_with(
+                &vars(&[
+                    ("hostname", "leaf01"),
+                    ("loopback_ip", "10.0.0.11"),
+                    ("vtep_ip", "10.0.0.11"),
+                ]),
+                &with_comments,
+            )
+            .unwrap();
+        assert!(report.comments);
+        assert_eq!(report.replacements, 4);
+        assert_eq!(net_ifaces.header_comments(), ["# Rendered for leaf01"]);
+    }
+
+    #[test]
+    fn test_substitute_family_stanza() {
+        let mut net_ifaces = NetworkInterfaces::parse_str(
+            "iface eth0 inet static\n    address {{ ipv4 }}\n\
+             iface eth0 inet6 static\n    address {{ ipv6 }}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            net_ifaces.required_variables(),
+            BTreeSet::from(["ipv4".to_string(), "ipv6".to_string()])
+        );
+        let err = net_ifaces
+            .substitute(&vars(&[("ipv4", "192.0.2.1/24")]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SubstitutionError::UndefinedVariables(vec!["ipv6".to_string()])
+        );
+
+        let report = net_ifaces
+            .substitute(&vars(&[
+                ("ipv4", "192.0.2.1/24"),
+                ("ipv6", "2001:db8::1/64"),
+            ]))
+            .unwrap();
+        assert_eq!(report.replacements, 2);
+        assert_eq!(
+            net_ifaces.to_string(),
+            "iface eth0 inet static\n    address 192.0.2.1/24\n\
+             \niface eth0 inet6 static\n    address 2001:db8::1/64\n"
+        );
+    }
+
+    #[test]
+    fn test_substitute_invalid_placeholder() {
+        let mut net_ifaces = NetworkInterfaces::from_interfaces([Interface::builder("swp1")
+            .with_option("mtu", "{{ mtu")
+            .build()]);
+        assert!(net_ifaces.required_variables().is_empty());
+        let err = net_ifaces
+            .substitute(&vars(&[("mtu", "9216")]))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid placeholder in swp1 mtu: {{ mtu");
+        let err = crate::NetworkInterfacesError::from(err);
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,1147 @@
+//! Semantic validation of network interface configurations.
+//!
+//! Parsing an `interfaces(5)` file is deliberately lenient so that existing
+//! files always load. Validation is the opt-in counterpart: it inspects a
+//! loaded [`NetworkInterfaces`](crate::NetworkInterfaces) collection and
+//! reports everything that `ifup` or the kernel would likely reject, without
+//! modifying anything.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use interface_rs::NetworkInterfaces;
+//!
+//! let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+//! let report = net_ifaces.validate();
+//! for issue in &report.issues {
+//!     println!("{}", issue);
+//! }
+//! if !report.is_valid() {
+//!     eprintln!("configuration has errors");
+//! }
+//! ```
+
+use crate::addr::{parse_address_spec, parse_prefix, AddressSpecParseError};
+use crate::graph::{self, DependencyKind};
+use crate::helper::sort::natural;
+use crate::interface::option::keys_match;
+use crate::interface::{
+    Family, HwAddress, Interface, InterfaceKind, InterfaceOption, MacAddr, Method, Origin,
+    StanzaRef,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Optional checks for
+/// [`NetworkInterfaces::validate_with`](crate::NetworkInterfaces::validate_with).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// Warn about names that appear on `auto` or `allow-*` lines without an
+    /// `iface` stanza (`undefined-interface`); see
+    /// [`Interface::is_declared`].
+    pub flag_placeholders: bool,
+}
+
+/// The severity of a [`ValidationIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The configuration is unusual but may still work.
+    Warning,
+    /// The configuration will not work as written.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity_str = match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}", severity_str)
+    }
+}
+
+/// A single finding produced by validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// How serious the finding is.
+    pub severity: Severity,
+    /// A stable, machine-readable identifier for the rule that fired
+    /// (e.g., `"option-out-of-range"`).
+    pub code: &'static str,
+    /// The interface the finding applies to, if any.
+    pub interface: Option<String>,
+    /// A human-readable description of the finding.
+    pub message: String,
+    /// Where the interface was defined, if it was parsed from a file or
+    /// string.
+    pub origin: Option<Origin>,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.interface {
+            Some(name) => write!(
+                f,
+                "{} [{}] {}: {}",
+                self.severity, self.code, name, self.message
+            ),
+            None => write!(f, "{} [{}] {}", self.severity, self.code, self.message),
+        }?;
+        match &self.origin {
+            Some(origin) => write!(f, " ({})", origin),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The result of validating a collection of interfaces.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// All findings, ordered by interface name.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if the report contains no `Error`-severity issues.
+    pub fn is_valid(&self) -> bool {
+        !self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    /// Returns an iterator over the `Error`-severity issues.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    /// Returns an iterator over the `Warning`-severity issues.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+    }
+
+    /// Adds an issue to the report.
+    pub(crate) fn push(
+        &mut self,
+        severity: Severity,
+        code: &'static str,
+        interface: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(ValidationIssue {
+            severity,
+            code,
+            interface: interface.map(str::to_string),
+            message: message.into(),
+            origin: None,
+        });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the per-interface validation rules, appending findings to `report`.
+///
+/// When `strict_keys` is set, option keys are matched exactly instead of
+/// being folded with [`normalize_key`](crate::interface::option::normalize_key).
+pub(crate) fn validate_interface(
+    iface: &Interface,
+    strict_keys: bool,
+    options: &ValidationOptions,
+    report: &mut ValidationReport,
+) {
+    let name = Some(iface.name.as_str());
+    let first_issue = report.issues.len();
+    if options.flag_placeholders && !iface.is_declared() {
+        let directive = match iface.allow.first() {
+            Some(allow) if !iface.auto => format!("allow-{}", allow),
+            _ => "auto".to_string(),
+        };
+        report.push(
+            Severity::Warning,
+            "undefined-interface",
+            name,
+            format!("{} references an undefined interface", directive),
+        );
+    }
+    for stanza in iface.stanzas() {
+        validate_stanza(stanza, name, strict_keys, report);
+    }
+    for issue in &mut report.issues[first_issue..] {
+        issue.origin = iface.origin.clone();
+    }
+}
+
+/// Checks the method and options of one stanza against its family and
+/// method.
+fn validate_stanza(
+    stanza: StanzaRef<'_>,
+    name: Option<&str>,
+    strict_keys: bool,
+    report: &mut ValidationReport,
+) {
+    if let (Some(family), Some(method)) = (stanza.family, stanza.method) {
+        if !method.valid_for(family) {
+            report.push(
+                Severity::Warning,
+                "method-family-mismatch",
+                name,
+                format!("Method {} is not defined for family {}", method, family),
+            );
+        }
+    }
+    for (key, value) in stanza.options {
+        match InterfaceOption::try_classify(key, value, strict_keys) {
+            Ok(opt) => {
+                if opt.is_dhcp() && !dhcp_option_applies(&opt, stanza.method) {
+                    report.push(
+                        Severity::Warning,
+                        "dhcp-option-mismatch",
+                        name,
+                        format!(
+                            "Option {} only applies to DHCP, but the method is {}",
+                            key,
+                            stanza.method.map(Method::as_str).unwrap_or_default()
+                        ),
+                    );
+                }
+                if let Err(err) = opt.validate(stanza.family) {
+                    report.push(
+                        Severity::Error,
+                        "option-out-of-range",
+                        name,
+                        err.to_string(),
+                    );
+                }
+                if let Some(err) = address_error(&opt, stanza.family) {
+                    report.push(
+                        Severity::Error,
+                        "invalid-address",
+                        name,
+                        format!("Option {}: {}", key, err),
+                    );
+                }
+            }
+            Err(err) => {
+                report.push(
+                    Severity::Error,
+                    "invalid-option-value",
+                    name,
+                    err.to_string(),
+                );
+            }
+        }
+    }
+}
+
+/// Checks the value of an `address` or `netmask` option. A `netmask` is
+/// checked against the family of the stanza, defaulting to IPv4.
+fn address_error(opt: &InterfaceOption, family: Option<&Family>) -> Option<AddressSpecParseError> {
+    match opt {
+        InterfaceOption::Address(value) => parse_address_spec(value).err(),
+        InterfaceOption::Netmask(value) => {
+            let ip = match family {
+                Some(Family::Inet6) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                _ => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            };
+            parse_prefix(value, ip).err()
+        }
+        _ => None,
+    }
+}
+
+/// Returns `true` unless `method` is known not to run a DHCP client that
+/// would use `opt`.
+///
+/// Besides `dhcp` and `dhcp6`, `request_prefix` also applies to `auto`, which
+/// can run a stateless DHCPv6 client. Interfaces without a method and unknown
+/// methods are given the benefit of the doubt.
+fn dhcp_option_applies(opt: &InterfaceOption, method: Option<&Method>) -> bool {
+    match method {
+        None | Some(Method::Dhcp | Method::Dhcp6 | Method::Other(_)) => true,
+        Some(Method::Auto) => matches!(opt, InterfaceOption::DhcpRequestPrefix(_)),
+        Some(_) => false,
+    }
+}
+
+/// A MAC address configured on more than one interface; see
+/// [`NetworkInterfaces::duplicate_hwaddresses`](crate::NetworkInterfaces::duplicate_hwaddresses).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateHwaddress {
+    /// The shared address.
+    pub mac: MacAddr,
+    /// The names of the interfaces using the address, each with its
+    /// `hwaddress` value as written, in natural order of the names.
+    pub interfaces: Vec<(String, String)>,
+}
+
+/// Groups the `hwaddress` options of `interfaces` by MAC address and returns
+/// the addresses used by more than one interface.
+///
+/// Values that are not MAC addresses are ignored. An interface that sets the
+/// same address twice, e.g. in its `inet` and `inet6` stanzas, counts once.
+pub(crate) fn duplicate_hwaddresses<'a>(
+    interfaces: impl IntoIterator<Item = &'a Interface>,
+    strict_keys: bool,
+) -> Vec<DuplicateHwaddress> {
+    let mut by_mac: BTreeMap<MacAddr, Vec<(String, String)>> = BTreeMap::new();
+    for iface in interfaces {
+        for (key, value) in iface.stanzas().flat_map(|stanza| stanza.options) {
+            if !keys_match(key, "hwaddress", strict_keys) {
+                continue;
+            }
+            if let Some(mac) = HwAddress::new(value.as_str()).mac() {
+                let users = by_mac.entry(mac).or_default();
+                if !users.iter().any(|(name, _)| *name == iface.name) {
+                    users.push((iface.name.clone(), value.clone()));
+                }
+            }
+        }
+    }
+    by_mac
+        .into_iter()
+        .filter(|(_, users)| users.len() > 1)
+        .map(|(mac, interfaces)| DuplicateHwaddress { mac, interfaces })
+        .collect()
+}
+
+/// A port claimed by a bond and at least one other bond or bridge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MembershipConflict {
+    /// The name of the port.
+    pub(crate) port: String,
+    /// The bonds and bridges claiming the port, each with the option that
+    /// claims it, in natural order of the names.
+    pub(crate) masters: Vec<(String, &'static str)>,
+}
+
+/// Finds the ports that are claimed by two bonds, or by both a bond and a
+/// bridge, through `bond-slaves`, `bond-master`, or `bridge-ports`.
+///
+/// Globs in `bond-slaves` are matched against the names of `interfaces`.
+pub(crate) fn membership_conflicts<'a>(
+    interfaces: impl IntoIterator<Item = &'a Interface>,
+    strict_keys: bool,
+) -> Vec<MembershipConflict> {
+    let interfaces: Vec<&Interface> = interfaces.into_iter().collect();
+    let mut claims: BTreeMap<String, Vec<(String, &'static str)>> = BTreeMap::new();
+    let mut claim = |port: &str, master: &str, key: &'static str| {
+        let masters = claims.entry(port.to_string()).or_default();
+        // A bond may claim a port both ways
+        if !masters.iter().any(|(name, _)| name == master) {
+            masters.push((master.to_string(), key));
+        }
+    };
+    for iface in &interfaces {
+        for entry in graph::bond_slave_entries(iface, strict_keys) {
+            if !graph::is_glob(entry) {
+                claim(entry, &iface.name, "bond-slaves");
+                continue;
+            }
+            for port in &interfaces {
+                if port.name != iface.name && graph::slave_matches(entry, &port.name) {
+                    claim(&port.name, &iface.name, "bond-slaves");
+                }
+            }
+        }
+        if let Some(bond) = graph::bond_master(iface, strict_keys) {
+            claim(&iface.name, bond, "bond-master");
+        }
+        for dep in graph::interface_dependencies(iface, strict_keys) {
+            if dep.kind == DependencyKind::BridgePort {
+                claim(&dep.to, &iface.name, "bridge-ports");
+            }
+        }
+    }
+    claims
+        .into_iter()
+        .filter(|(_, masters)| {
+            masters.len() > 1 && masters.iter().any(|(_, key)| *key != "bridge-ports")
+        })
+        .map(|(port, mut masters)| {
+            masters.sort_by(|a, b| natural(&a.0, &b.0));
+            MembershipConflict { port, masters }
+        })
+        .collect()
+}
+
+/// Reports a port claimed by more than one master (`multiple-masters`),
+/// naming the masters.
+pub(crate) fn validate_membership(
+    port: &str,
+    origin: Option<&Origin>,
+    conflicts: &[MembershipConflict],
+    report: &mut ValidationReport,
+) {
+    let Some(conflict) = conflicts.iter().find(|conflict| conflict.port == port) else {
+        return;
+    };
+    let masters: Vec<String> = conflict
+        .masters
+        .iter()
+        .map(|(name, key)| format!("{} ({})", name, key))
+        .collect();
+    report.push(
+        Severity::Error,
+        "multiple-masters",
+        Some(port),
+        format!("Port is claimed by {}", masters.join(", ")),
+    );
+    if let Some(issue) = report.issues.last_mut() {
+        issue.origin = origin.cloned();
+    }
+}
+
+/// Reports an alias whose parent is not defined (`undefined-alias-parent`).
+pub(crate) fn validate_alias(
+    iface: &Interface,
+    is_defined: impl Fn(&str) -> bool,
+    report: &mut ValidationReport,
+) {
+    let Some(parent) = iface.alias_parent() else {
+        return;
+    };
+    if !is_defined(parent) {
+        report.push(
+            Severity::Warning,
+            "undefined-alias-parent",
+            Some(&iface.name),
+            format!("Alias of {}, which is not defined", parent),
+        );
+        if let Some(issue) = report.issues.last_mut() {
+            issue.origin = iface.origin.clone();
+        }
+    }
+}
+
+/// Reports a `wpa-psk` stored in plain text next to a `wpa-conf` file that
+/// configures the network instead (`psk-with-wpa-conf`).
+pub(crate) fn validate_wireless(iface: &Interface, report: &mut ValidationReport) {
+    let Some(path) = iface.wpa_conf_path() else {
+        return;
+    };
+    if iface.get_option("wpa-psk").is_some() {
+        report.push(
+            Severity::Warning,
+            "psk-with-wpa-conf",
+            Some(&iface.name),
+            format!(
+                "wpa-psk is stored in plain text, but wpa-conf {} configures the network",
+                path.display()
+            ),
+        );
+        if let Some(issue) = report.issues.last_mut() {
+            issue.origin = iface.origin.clone();
+        }
+    }
+}
+
+/// Bonding modes accepted by the kernel, indexed by their numeric code.
+const BOND_MODES: &[&str] = &[
+    "balance-rr",
+    "active-backup",
+    "balance-xor",
+    "broadcast",
+    "802.3ad",
+    "balance-tlb",
+    "balance-alb",
+];
+
+/// Bond options that take one of a fixed set of names, or the index of a
+/// name. Each comes with its names and the modes that use the option.
+const BOND_CHOICES: &[(&str, &[&str], &[&str])] = &[
+    ("bond-lacp-rate", &["slow", "fast"], &["802.3ad"]),
+    (
+        "bond-xmit-hash-policy",
+        &[
+            "layer2",
+            "layer3+4",
+            "layer2+3",
+            "encap2+3",
+            "encap3+4",
+            "vlan+srcmac",
+        ],
+        &["balance-xor", "802.3ad", "balance-tlb"],
+    ),
+];
+
+/// Bond options that take a non-negative integer.
+const BOND_INTEGERS: &[&str] = &["bond-miimon", "bond-min-links"];
+
+/// Returns the name `value` stands for among `choices`, accepting a name
+/// exactly as the kernel spells it or its numeric index.
+fn bond_choice(value: &str, choices: &[&'static str]) -> Option<&'static str> {
+    choices
+        .iter()
+        .copied()
+        .find(|choice| *choice == value)
+        .or_else(|| {
+            let index: usize = value.parse().ok()?;
+            choices.get(index).copied()
+        })
+}
+
+/// Checks the bonding options of `iface`:
+///
+/// - `bond-mode`, `bond-lacp-rate` and `bond-xmit-hash-policy` must be names
+///   or numeric codes accepted by the kernel, and `bond-miimon` and
+///   `bond-min-links` integers (`invalid-bond-option`);
+/// - `bond-lacp-rate` only applies to 802.3ad mode, and
+///   `bond-xmit-hash-policy` to the balance-xor, 802.3ad and balance-tlb modes
+///   (`bond-option-mode-mismatch`, warning). Without a valid `bond-mode` the
+///   mode is left to the platform default and not checked;
+/// - a bond needs a slave, listed in `bond-slaves` or naming the bond in its
+///   `bond-master` option (`bond-without-slaves`, warning). `has_ports` tells
+///   whether any interface does the latter.
+///
+/// An interface is a bond if it has `bond-slaves` or `bond-mode`.
+pub(crate) fn validate_bond(
+    iface: &Interface,
+    strict_keys: bool,
+    has_ports: bool,
+    report: &mut ValidationReport,
+) {
+    let name = Some(iface.name.as_str());
+    let first_issue = report.issues.len();
+    let invalid = |report: &mut ValidationReport, key: &str, value: &str, expected: String| {
+        report.push(
+            Severity::Error,
+            "invalid-bond-option",
+            name,
+            format!(
+                "Option {} has invalid value {}; expected {}",
+                key, value, expected
+            ),
+        );
+    };
+    let one_of =
+        |choices: &[&str]| format!("one of {} or 0-{}", choices.join(", "), choices.len() - 1);
+
+    let mut is_bond = iface.kind() == InterfaceKind::Bond;
+    let mut mode = None;
+    let options = || iface.stanzas().flat_map(|stanza| stanza.options);
+    for (key, value) in options() {
+        if let Ok(InterfaceOption::BondMode(value)) =
+            InterfaceOption::try_classify(key, value, strict_keys)
+        {
+            is_bond = true;
+            match bond_choice(value.trim(), BOND_MODES) {
+                Some(valid) => mode = mode.or(Some(valid)),
+                None => invalid(report, key, &value, one_of(BOND_MODES)),
+            }
+        }
+    }
+    for (key, value) in options() {
+        let value = value.trim();
+        for &(option, choices, modes) in BOND_CHOICES {
+            if !keys_match(key, option, strict_keys) {
+                continue;
+            }
+            if bond_choice(value, choices).is_none() {
+                invalid(report, key, value, one_of(choices));
+            }
+            match mode {
+                Some(mode) if !modes.contains(&mode) => report.push(
+                    Severity::Warning,
+                    "bond-option-mode-mismatch",
+                    name,
+                    format!(
+                        "Option {} only applies to mode {}, but the mode is {}",
+                        key,
+                        modes.join(", "),
+                        mode
+                    ),
+                ),
+                _ => {}
+            }
+        }
+        let integer = BOND_INTEGERS
+            .iter()
+            .any(|option| keys_match(key, option, strict_keys));
+        if integer && value.parse::<u32>().is_err() {
+            invalid(report, key, value, "a non-negative integer".to_string());
+        }
+    }
+    if is_bond && !has_ports && graph::bond_slave_entries(iface, strict_keys).is_empty() {
+        report.push(
+            Severity::Warning,
+            "bond-without-slaves",
+            name,
+            "Bond has no slaves; expected bond-slaves or a port with bond-master",
+        );
+    }
+    for issue in &mut report.issues[first_issue..] {
+        issue.origin = iface.origin.clone();
+    }
+}
+
+/// Reports the MAC addresses `iface` shares with other interfaces
+/// (`duplicate-hwaddress`), naming the other interfaces.
+pub(crate) fn validate_hwaddress(
+    iface: &Interface,
+    duplicates: &[DuplicateHwaddress],
+    report: &mut ValidationReport,
+) {
+    for duplicate in duplicates {
+        let Some((_, raw)) = duplicate
+            .interfaces
+            .iter()
+            .find(|(name, _)| *name == iface.name)
+        else {
+            continue;
+        };
+        let others: Vec<String> = duplicate
+            .interfaces
+            .iter()
+            .filter(|(name, _)| *name != iface.name)
+            .map(|(name, raw)| format!("{} ({})", name, raw))
+            .collect();
+        report.push(
+            Severity::Warning,
+            "duplicate-hwaddress",
+            Some(&iface.name),
+            format!("hwaddress {} is also used by {}", raw, others.join(", ")),
+        );
+        if let Some(issue) = report.issues.last_mut() {
+            issue.origin = iface.origin.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_interface_ranges() {
+        let iface = Interface::builder("vlan5000")
+            .with_family(Family::Inet6)
+            .with_option("mtu", "1000")
+            .with_option("vlan-id", "5000")
+            .with_option("metric", "99999999999")
+            .with_option("bridge-pvid", "1")
+            .build();
+        let mut report = ValidationReport::default();
+        validate_interface(&iface, false, &ValidationOptions::default(), &mut report);
+
+        let codes: Vec<&str> = report.issues.iter().map(|i| i.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "option-out-of-range",
+                "option-out-of-range",
+                "invalid-option-value"
+            ]
+        );
+        assert!(report.issues[0].message.contains("(1280-65535)"));
+        assert!(report.issues[1].message.contains("vlan-id"));
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_validate_method_family() {
+        let cases = [
+            (Family::Inet6, "loopback", false),
+            (Family::Inet6, "v4tunnel", false),
+            (Family::Inet, "dhcp", false),
+            (Family::Can, "dhcp", true),
+            (Family::Inet, "auto", true),
+            (Family::Inet6, "auto", false),
+            (Family::Can, "vendor-magic", false),
+        ];
+        for (family, method, flagged) in cases {
+            let iface = Interface::builder("if0")
+                .with_family(family.clone())
+                .with_method(method)
+                .build();
+            let mut report = ValidationReport::default();
+            validate_interface(&iface, false, &ValidationOptions::default(), &mut report);
+            assert_eq!(!report.issues.is_empty(), flagged, "{} {}", family, method);
+            if flagged {
+                assert_eq!(report.issues[0].code, "method-family-mismatch");
+                assert_eq!(report.issues[0].severity, Severity::Warning);
+                assert!(report.is_valid());
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_family_stanza() {
+        let net_ifaces = crate::NetworkInterfaces::parse_str(
+            "iface eth0 inet6 static\n    netmask 64\n\
+             iface eth0 inet auto\n    netmask 64\n    hostname box\n",
+        )
+        .unwrap();
+        let iface = net_ifaces.get_interface("eth0").unwrap();
+        let mut report = ValidationReport::default();
+        validate_interface(iface, false, &ValidationOptions::default(), &mut report);
+        let codes: Vec<&str> = report.issues.iter().map(|issue| issue.code).collect();
+        assert_eq!(
+            codes,
+            [
+                "method-family-mismatch",
+                "invalid-address",
+                "dhcp-option-mismatch"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_dhcp_options() {
+        let cases = [
+            ("static", "hostname", true),
+            ("static", "leasetime", true),
+            ("loopback", "client", true),
+            ("auto", "vendor", true),
+            ("auto", "request_prefix", false),
+            ("dhcp", "hostname", false),
+            ("dhcp6", "request_prefix", false),
+            ("vendor-magic", "hostname", false),
+        ];
+        for (method, key, flagged) in cases {
+            let iface = Interface::builder("eth0")
+                .with_method(method)
+                .with_option(key, "1")
+                .build();
+            let mut report = ValidationReport::default();
+            validate_interface(&iface, false, &ValidationOptions::default(), &mut report);
+            assert_eq!(!report.issues.is_empty(), flagged, "{} {}", method, key);
+            if flagged {
+                assert_eq!(report.issues[0].code, "dhcp-option-mismatch");
+                assert_eq!(
+                    report.issues[0].message,
+                    format!(
+                        "Option {} only applies to DHCP, but the method is {}",
+                        key, method
+                    )
+                );
+                assert!(report.is_valid());
+            }
+        }
+
+        // Interfaces without a method are not flagged
+        let iface = Interface::builder("eth0")
+            .with_option("hostname", "box")
+            .build();
+        let mut report = ValidationReport::default();
+        validate_interface(&iface, false, &ValidationOptions::default(), &mut report);
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_validate_addresses() {
+        let cases = [
+            (Family::Inet, "address", "10.0.0.1/255.255.255.0", None),
+            (
+                Family::Inet,
+                "address",
+                "10.0.0.1/255.0.255.0",
+                Some("Option address: Netmask is not contiguous: 255.0.255.0"),
+            ),
+            (
+                Family::Inet,
+                "address",
+                "10.0.0.1/33",
+                Some("Option address: Invalid prefix length or netmask: 33"),
+            ),
+            (
+                Family::Inet,
+                "address",
+                "gateway.example",
+                Some("Option address: Invalid address: gateway.example"),
+            ),
+            (Family::Inet6, "address", "2001:db8::1/64", None),
+            (Family::Inet, "netmask", "255.255.255.0", None),
+            (
+                Family::Inet,
+                "netmask",
+                "255.255.255.1",
+                Some("Option netmask: Netmask is not contiguous: 255.255.255.1"),
+            ),
+            (Family::Inet6, "netmask", "64", None),
+            (
+                Family::Inet6,
+                "netmask",
+                "255.255.255.0",
+                Some("Option netmask: Invalid prefix length or netmask: 255.255.255.0"),
+            ),
+        ];
+        for (family, key, value, message) in cases {
+            let iface = Interface::builder("eth0")
+                .with_family(family)
+                .with_option(key, value)
+                .build();
+            let mut report = ValidationReport::default();
+            validate_interface(&iface, false, &ValidationOptions::default(), &mut report);
+            let found: Vec<&str> = report.issues.iter().map(|i| i.message.as_str()).collect();
+            assert_eq!(
+                found,
+                message.into_iter().collect::<Vec<_>>(),
+                "{} {}",
+                key,
+                value
+            );
+            assert!(report.issues.iter().all(|i| i.code == "invalid-address"));
+        }
+    }
+
+    #[test]
+    fn test_validate_interface_clean() {
+        let iface = Interface::builder("swp1")
+            .with_option("mtu", "9216")
+            .with_option("bridge-access", "100")
+            .build();
+        let mut report = ValidationReport::default();
+        validate_interface(&iface, false, &ValidationOptions::default(), &mut report);
+        assert!(report.issues.is_empty());
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_duplicate_hwaddresses() {
+        let net_ifaces = crate::NetworkInterfaces::parse_str(
+            "iface eth0 inet dhcp\n    hwaddress 52:54:00:AB:CD:01\n\
+             iface eth0 inet6 auto\n    hwaddress 52:54:00:ab:cd:01\n\
+             iface eth1 inet dhcp\n    hwaddress ether 52:54:00:ab:cd:01\n\
+             iface eth2 inet dhcp\n    hwaddress 5254.00AB.CD01\n\
+             iface eth3 inet dhcp\n    hwaddress 52:54:00:ab:cd:02\n\
+             iface eth4 inet dhcp\n    hwaddress random\n\
+             iface eth5 inet dhcp\n    hwaddress random\n",
+        )
+        .unwrap();
+
+        let duplicates = net_ifaces.duplicate_hwaddresses();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].mac, "52:54:00:ab:cd:01".parse().unwrap());
+        assert_eq!(
+            duplicates[0].interfaces,
+            vec![
+                ("eth0".to_string(), "52:54:00:AB:CD:01".to_string()),
+                ("eth1".to_string(), "ether 52:54:00:ab:cd:01".to_string()),
+                ("eth2".to_string(), "5254.00AB.CD01".to_string()),
+            ]
+        );
+
+        let report = net_ifaces.validate();
+        let messages: Vec<String> = report.warnings().map(|issue| issue.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "warning [duplicate-hwaddress] eth0: hwaddress 52:54:00:AB:CD:01 is also used by \
+                 eth1 (ether 52:54:00:ab:cd:01), eth2 (5254.00AB.CD01) (line 1)",
+                "warning [duplicate-hwaddress] eth1: hwaddress ether 52:54:00:ab:cd:01 is also \
+                 used by eth0 (52:54:00:AB:CD:01), eth2 (5254.00AB.CD01) (line 5)",
+                "warning [duplicate-hwaddress] eth2: hwaddress 5254.00AB.CD01 is also used by \
+                 eth0 (52:54:00:AB:CD:01), eth1 (ether 52:54:00:ab:cd:01) (line 7)",
+            ]
+        );
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_membership_conflicts() {
+        let interfaces = vec![
+            Interface::builder("bond0")
+                .with_option("bond-slaves", "swp1 swp2")
+                .build(),
+            Interface::builder("br0")
+                .with_option("bridge-ports", "bond0 swp2 swp9")
+                .build(),
+            Interface::builder("br1")
+                .with_option("bridge-ports", "swp9 swp10")
+                .build(),
+            Interface::builder("swp10")
+                .with_option("bond-master", "bond0")
+                .build(),
+            Interface::builder("bond1")
+                .with_option("bond-slaves", "swp1*")
+                .build(),
+        ];
+        let conflicts = membership_conflicts(&interfaces, false);
+        let summary: Vec<(&str, Vec<&str>)> = conflicts
+            .iter()
+            .map(|conflict| {
+                let masters = conflict.masters.iter().map(|(name, _)| name.as_str());
+                (conflict.port.as_str(), masters.collect())
+            })
+            .collect();
+        // Two bridges sharing swp9 are not reported, and the glob only
+        // matches defined interfaces
+        assert_eq!(
+            summary,
+            vec![
+                ("swp10", vec!["bond0", "bond1", "br1"]),
+                ("swp2", vec!["bond0", "br0"]),
+            ]
+        );
+
+        let mut report = ValidationReport::default();
+        validate_membership("swp2", None, &conflicts, &mut report);
+        validate_membership("swp9", None, &conflicts, &mut report);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(
+            report.issues[0].to_string(),
+            "error [multiple-masters] swp2: Port is claimed by bond0 (bond-slaves), br0 \
+             (bridge-ports)"
+        );
+    }
+
+    #[test]
+    fn test_validate_placeholders() {
+        let net_ifaces = crate::NetworkInterfaces::parse_str(
+            "auto lo swp1\nallow-hotplug swp2\niface lo inet loopback\n",
+        )
+        .unwrap();
+        assert_eq!(net_ifaces.len(), 3);
+        assert_eq!(net_ifaces.declared_len(), 1);
+        let declared: Vec<&String> = net_ifaces.iter_declared().map(|(name, _)| name).collect();
+        assert_eq!(declared, vec!["lo"]);
+
+        assert!(net_ifaces.validate().issues.is_empty());
+        let options = ValidationOptions {
+            flag_placeholders: true,
+        };
+        let report = net_ifaces.validate_with(&options);
+        let messages: Vec<String> = report.warnings().map(|issue| issue.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "warning [undefined-interface] swp1: auto references an undefined interface \
+                 (line 1)",
+                "warning [undefined-interface] swp2: allow-hotplug references an undefined \
+                 interface (line 2)",
+            ]
+        );
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_validate_wireless() {
+        let net_ifaces = crate::NetworkInterfaces::from_interfaces([
+            Interface::builder("wlan0")
+                .with_option("wpa-conf", "/etc/wpa_supplicant/wpa_supplicant.conf")
+                .with_option("wpa-ssid", "\"Home Network\"")
+                .with_option("wpa-psk", "\"my secret passphrase\"")
+                .build(),
+            Interface::builder("wlan1")
+                .with_option("wpa-ssid", "Home Network")
+                .with_option("wpa-psk", "my secret passphrase")
+                .build(),
+        ]);
+        let report = net_ifaces.validate();
+        let messages: Vec<String> = report.warnings().map(|issue| issue.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "warning [psk-with-wpa-conf] wlan0: wpa-psk is stored in plain text, but \
+                 wpa-conf /etc/wpa_supplicant/wpa_supplicant.conf configures the network"
+            ]
+        );
+        assert!(!messages[0].contains("secret"));
+    }
+
+    #[test]
+    fn test_validate_bond() {
+        // The options of bond0, whether a port names it in bond-master, and
+        // the start of each expected finding
+        type Case = (
+            &'static [(&'static str, &'static str)],
+            bool,
+            &'static [&'static str],
+        );
+        let cases: &[Case] = &[
+            (
+                &[("bond-slaves", "swp1 swp2"), ("bond-mode", "802.3ad")],
+                false,
+                &[],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-mode", "4"),
+                    ("bond-lacp-rate", "1"),
+                ],
+                false,
+                &[],
+            ),
+            (
+                &[("bond-slaves", "swp1"), ("bond-mode", "802.3AD")],
+                false,
+                &[
+                    "error [invalid-bond-option] bond0: Option bond-mode has invalid value \
+                     802.3AD; expected one of balance-rr, active-backup, balance-xor, \
+                     broadcast, 802.3ad, balance-tlb, balance-alb or 0-6",
+                ],
+            ),
+            (
+                &[("bond-slaves", "swp1"), ("bond-mode", "7")],
+                false,
+                &["error [invalid-bond-option]"],
+            ),
+            (
+                &[("bond-slaves", "swp1"), ("bond-lacp-rate", "Slow")],
+                false,
+                &[
+                    "error [invalid-bond-option] bond0: Option bond-lacp-rate has invalid value \
+                     Slow; expected one of slow, fast or 0-1",
+                ],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-xmit-hash-policy", "layer3-4"),
+                ],
+                false,
+                &["error [invalid-bond-option] bond0: Option bond-xmit-hash-policy"],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-mode", "active-backup"),
+                    ("bond-lacp-rate", "fast"),
+                ],
+                false,
+                &[
+                    "warning [bond-option-mode-mismatch] bond0: Option bond-lacp-rate only \
+                     applies to mode 802.3ad, but the mode is active-backup",
+                ],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-mode", "1"),
+                    ("bond-xmit-hash-policy", "layer2"),
+                ],
+                false,
+                &[
+                    "warning [bond-option-mode-mismatch] bond0: Option bond-xmit-hash-policy only \
+                     applies to mode balance-xor, 802.3ad, balance-tlb",
+                ],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-mode", "2"),
+                    ("bond-xmit-hash-policy", "layer2"),
+                ],
+                false,
+                &[],
+            ),
+            // Without a valid mode, the platform default is not second-guessed
+            (
+                &[("bond-slaves", "swp1"), ("bond-lacp-rate", "fast")],
+                false,
+                &[],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-mode", "fast-ish"),
+                    ("bond-lacp-rate", "fast"),
+                ],
+                false,
+                &["error [invalid-bond-option] bond0: Option bond-mode"],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-miimon", "100ms"),
+                    ("bond-min-links", "-1"),
+                ],
+                false,
+                &[
+                    "error [invalid-bond-option] bond0: Option bond-miimon has invalid value \
+                     100ms; expected a non-negative integer",
+                    "error [invalid-bond-option] bond0: Option bond-min-links has invalid value -1",
+                ],
+            ),
+            (
+                &[
+                    ("bond-slaves", "swp1"),
+                    ("bond-miimon", "100"),
+                    ("bond-min-links", "1"),
+                ],
+                false,
+                &[],
+            ),
+            (
+                &[("bond-mode", "active-backup")],
+                false,
+                &[
+                    "warning [bond-without-slaves] bond0: Bond has no slaves; expected \
+                     bond-slaves or a port with bond-master",
+                ],
+            ),
+            (
+                &[("bond-slaves", "none")],
+                false,
+                &["warning [bond-without-slaves]"],
+            ),
+            (
+                &[("bond-slaves", "none"), ("bond-mode", "balance-rr")],
+                true,
+                &[],
+            ),
+            // Keys are folded like elsewhere
+            (
+                &[("bond_slaves", "swp1"), ("BOND_MODE", "balance_rr")],
+                false,
+                &["error"],
+            ),
+        ];
+        for (options, has_ports, expected) in cases {
+            let mut builder = Interface::builder("bond0");
+            for (key, value) in options.iter() {
+                builder = builder.with_option(*key, *value);
+            }
+            let iface = builder.build();
+            let mut report = ValidationReport::default();
+            validate_bond(&iface, false, *has_ports, &mut report);
+            let messages: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+            assert_eq!(
+                messages.len(),
+                expected.len(),
+                "{:?}: {:?}",
+                options,
+                messages
+            );
+            for (message, expected) in messages.iter().zip(expected.iter()) {
+                assert!(message.starts_with(expected), "{:?}: {}", options, message);
+            }
+        }
+
+        // Ports can name their bond instead of being listed in bond-slaves
+        let net_ifaces = crate::NetworkInterfaces::from_interfaces([
+            Interface::builder("bond1")
+                .with_option("bond-mode", "802.3ad")
+                .build(),
+            Interface::builder("swp3")
+                .with_option("bond-master", "bond1")
+                .build(),
+            Interface::builder("bond2")
+                .with_option("bond-mode", "802.3ad")
+                .build(),
+        ]);
+        let messages: Vec<String> = net_ifaces
+            .validate()
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "warning [bond-without-slaves] bond2: Bond has no slaves; expected \
+                 bond-slaves or a port with bond-master"
+            ]
+        );
+    }
+}
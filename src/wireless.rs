@@ -0,0 +1,205 @@
+//! Wireless interfaces and their `wpa_supplicant` settings.
+//!
+//! With the `wpasupplicant` ifupdown hooks, a wireless stanza either points at
+//! a `wpa_supplicant` configuration file:
+//!
+//! ```text
+//! iface wlan0 inet dhcp
+//!     wpa-conf /etc/wpa_supplicant/wpa_supplicant.conf
+//! ```
+//!
+//! or configures a single network inline with `wpa-ssid` and `wpa-psk`.
+//! Values may be wrapped in double quotes, which are not part of the value;
+//! an SSID may contain spaces either way.
+//!
+//! The passphrase is returned as a [`Psk`], which hides its value from
+//! `Debug` output so that it does not end up in logs by accident.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use interface_rs::interface::Interface;
+//!
+//! let wlan0: Interface = "iface wlan0 inet dhcp\n    \
+//!     wpa-ssid \"Office Guest\"\n    wpa-psk correct horse"
+//!     .parse()
+//!     .unwrap();
+//! assert!(wlan0.is_wireless());
+//! let (ssid, psk) = wlan0.wireless_credentials().unwrap();
+//! assert_eq!(ssid, "Office Guest");
+//! assert_eq!(psk.expose(), "correct horse");
+//! assert_eq!(format!("{:?}", psk), "Psk(<redacted>)");
+//! ```
+
+use crate::interface::option::normalize_key;
+use crate::interface::Interface;
+use crate::network_interfaces::NetworkInterfaces;
+use std::fmt;
+use std::path::Path;
+
+/// A WPA pre-shared key from a `wpa-psk` option.
+///
+/// The key is either a passphrase of 8 to 63 characters or 64 hexadecimal
+/// digits, the raw key derived from a passphrase. `Debug` output never shows
+/// it, and there is deliberately no `Display`; use [`Psk::expose`] where the
+/// value is really needed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Psk(String);
+
+impl Psk {
+    /// Returns the key as written, without surrounding quotes.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if the key is a raw 256-bit key of 64 hexadecimal
+    /// digits rather than a passphrase.
+    pub fn is_raw_key(&self) -> bool {
+        self.0.len() == 64 && self.0.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+}
+
+impl fmt::Debug for Psk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Psk(<redacted>)")
+    }
+}
+
+/// Removes one pair of double quotes around a trimmed value.
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+impl Interface {
+    /// Returns `true` if this stanza configures a wireless interface.
+    ///
+    /// An interface is wireless if it has a `wpa-*` or `wireless-*` option,
+    /// or if its name starts with `wl`, like `wlan0` or the predictable name
+    /// `wlp2s0`.
+    pub fn is_wireless(&self) -> bool {
+        self.name.starts_with("wl")
+            || self.options.iter().any(|(key, _)| {
+                let key = normalize_key(key);
+                key.starts_with("wpa-") || key.starts_with("wireless-")
+            })
+    }
+
+    /// Returns the `wpa_supplicant` configuration file named by the
+    /// `wpa-conf` option.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use interface_rs::interface::Interface;
+    /// use std::path::Path;
+    ///
+    /// let wlan0 = Interface::builder("wlan0")
+    ///     .with_option("wpa-conf", "/etc/wpa_supplicant/wpa_supplicant.conf")
+    ///     .build();
+    /// assert_eq!(
+    ///     wlan0.wpa_conf_path(),
+    ///     Some(Path::new("/etc/wpa_supplicant/wpa_supplicant.conf"))
+    /// );
+    /// ```
+    pub fn wpa_conf_path(&self) -> Option<&Path> {
+        self.get_option("wpa-conf")
+            .map(|value| Path::new(unquote(value)))
+    }
+
+    /// Returns the SSID and pre-shared key configured inline with `wpa-ssid`
+    /// and `wpa-psk`.
+    ///
+    /// # Returns
+    ///
+    /// The SSID and key without surrounding quotes, or `None` unless both
+    /// options are set.
+    pub fn wireless_credentials(&self) -> Option<(&str, Psk)> {
+        let ssid = unquote(self.get_option("wpa-ssid")?);
+        let psk = unquote(self.get_option("wpa-psk")?);
+        Some((ssid, Psk(psk.to_string())))
+    }
+}
+
+impl NetworkInterfaces {
+    /// Returns the wireless interfaces, as decided by
+    /// [`Interface::is_wireless`], in natural order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use interface_rs::NetworkInterfaces;
+    ///
+    /// let net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+    /// for iface in net_ifaces.wireless_interfaces() {
+    ///     match iface.wpa_conf_path() {
+    ///         Some(path) => println!("{}: {}", iface.name, path.display()),
+    ///         None => println!("{}: inline configuration", iface.name),
+    ///     }
+    /// }
+    /// ```
+    pub fn wireless_interfaces(&self) -> Vec<&Interface> {
+        self.iter_by_name()
+            .map(|(_, iface)| iface)
+            .filter(|iface| iface.is_wireless())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wireless_credentials() {
+        let content = "\
+auto wlan0
+iface wlan0 inet dhcp
+    wpa-ssid \"Home Network 5G\"
+    wpa-psk \"my secret passphrase\"
+
+allow-hotplug wlp2s0
+iface wlp2s0 inet dhcp
+    wpa-conf \"/etc/wpa_supplicant/wpa_supplicant.conf\"
+
+iface usb0 inet dhcp
+    wpa-ssid Cafe Free WiFi
+    wpa-psk 0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef
+
+iface eth0 inet dhcp
+";
+        let net_ifaces = NetworkInterfaces::parse_str(content).unwrap();
+        let names: Vec<&str> = net_ifaces
+            .wireless_interfaces()
+            .iter()
+            .map(|iface| iface.name.as_str())
+            .collect();
+        assert_eq!(names, ["usb0", "wlan0", "wlp2s0"]);
+
+        let wlan0 = net_ifaces.get_interface("wlan0").unwrap();
+        let (ssid, psk) = wlan0.wireless_credentials().unwrap();
+        assert_eq!(ssid, "Home Network 5G");
+        assert_eq!(psk.expose(), "my secret passphrase");
+        assert!(!psk.is_raw_key());
+        assert_eq!(wlan0.wpa_conf_path(), None);
+        let debug = format!("{:?}", wlan0.wireless_credentials());
+        assert_eq!(debug, "Some((\"Home Network 5G\", Psk(<redacted>)))");
+
+        // Unquoted values keep their inner spaces
+        let usb0 = net_ifaces.get_interface("usb0").unwrap();
+        let (ssid, psk) = usb0.wireless_credentials().unwrap();
+        assert_eq!(ssid, "Cafe Free WiFi");
+        assert!(psk.is_raw_key());
+
+        let wlp2s0 = net_ifaces.get_interface("wlp2s0").unwrap();
+        assert_eq!(
+            wlp2s0.wpa_conf_path(),
+            Some(Path::new("/etc/wpa_supplicant/wpa_supplicant.conf"))
+        );
+        assert_eq!(wlp2s0.wireless_credentials(), None);
+        assert!(!net_ifaces.get_interface("eth0").unwrap().is_wireless());
+    }
+}
@@ -0,0 +1,229 @@
+//! Options controlling how a [`NetworkInterfaces`](crate::NetworkInterfaces)
+//! collection is rendered.
+
+use crate::helper::sort::{natural, natural_ci};
+use crate::interface::Interface;
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+/// Options controlling how interfaces are written out by `Display` and `save()`.
+///
+/// A file is written as its header comments and source directives, then
+/// each stanza. The defaults follow common style: stanzas are separated from
+/// the header and from each other by a single blank line, there is no blank
+/// line before the first line of the file, and the file ends with exactly one
+/// newline.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use interface_rs::{NetworkInterfaces, SortOrder, WriteOptions};
+///
+/// let mut net_ifaces = NetworkInterfaces::load("/etc/network/interfaces").unwrap();
+/// net_ifaces.set_write_options(WriteOptions {
+///     sort: SortOrder::NaturalCaseInsensitive,
+///     ..Default::default()
+/// });
+/// println!("{}", net_ifaces);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteOptions {
+    /// The order in which interfaces are written.
+    pub sort: SortOrder,
+    /// How option values are lined up within a stanza.
+    pub alignment: ValueAlignment,
+    /// The number of blank lines between stanzas, and between the header and
+    /// the first stanza. Defaults to 1.
+    pub blank_lines: usize,
+    /// Whether the last line ends with a newline. Defaults to `true`. An
+    /// empty collection is written as an empty file either way.
+    pub final_newline: bool,
+    /// Whether IPv6 addresses in `address`, `gateway`, `dns-nameservers`
+    /// and `vxlan-local-tunnelip` values are written in the canonical form of
+    /// RFC 5952, as done by [`canonical_ipv6`](crate::addr::canonical_ipv6).
+    /// Defaults to `false`, which writes values as they are.
+    pub canonical_ipv6: bool,
+    /// Whether the options of each stanza are sorted by key. Defaults to
+    /// `false`, which keeps them in the order they were read or added, so
+    /// saving a hand-maintained file does not reorder its options. Keys are
+    /// compared in their normalized form, so `post_up` and `post-up` lines
+    /// sort together, and the sort is stable, so repeated keys keep their
+    /// order.
+    pub sort_options: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            sort: SortOrder::default(),
+            alignment: ValueAlignment::default(),
+            blank_lines: 1,
+            final_newline: true,
+            canonical_ipv6: false,
+            sort_options: false,
+        }
+    }
+}
+
+/// How option values are lined up within a stanza.
+///
+/// Alignment only changes the spaces between an option's key and its value,
+/// which the parser ignores, so it never changes what a file means. Values are
+/// written exactly as they are, including any quoted text.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::Interface;
+/// use interface_rs::write_options::{ValueAlignment, WriteOptions};
+///
+/// let iface = Interface::builder("swp1")
+///     .with_option("address", "10.0.0.1/31")
+///     .with_option("mtu", "9216")
+///     .build();
+/// let options = WriteOptions {
+///     alignment: ValueAlignment::Auto,
+///     ..Default::default()
+/// };
+/// let mut out = String::new();
+/// iface.write_to(&mut out, &options).unwrap();
+/// assert_eq!(out, "iface swp1\n    address 10.0.0.1/31\n    mtu     9216\n");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueAlignment {
+    /// A single space between key and value (the default).
+    #[default]
+    Off,
+    /// Values start this many characters after the start of the key. Keys
+    /// that do not leave room are followed by a single space.
+    Column(usize),
+    /// Values start one space after the longest key of the stanza.
+    Auto,
+}
+
+impl ValueAlignment {
+    /// Returns the number of spaces to write after `key` in a stanza whose
+    /// longest key is `longest` characters long.
+    pub(crate) fn padding(&self, key: &str, longest: usize) -> usize {
+        let width = match self {
+            ValueAlignment::Off => 0,
+            ValueAlignment::Column(column) => *column,
+            ValueAlignment::Auto => longest + 1,
+        };
+        width.saturating_sub(key.chars().count()).max(1)
+    }
+}
+
+/// The order in which interfaces are written out.
+///
+/// By default a loaded file keeps the order of its stanzas, so saving it only
+/// changes the stanzas that were edited.
+///
+/// # Examples
+///
+/// ```rust
+/// use interface_rs::interface::Interface;
+/// use interface_rs::{NetworkInterfaces, SortOrder, WriteOptions};
+///
+/// let mut net_ifaces: NetworkInterfaces = ["swp2", "swp10", "swp1"]
+///     .into_iter()
+///     .map(|name| Interface::builder(name).build())
+///     .collect();
+/// net_ifaces.add_interface(Interface::builder("eth0").build());
+/// let names = |net_ifaces: &NetworkInterfaces| -> Vec<String> {
+///     net_ifaces.sorted_iter().map(|iface| iface.name.clone()).collect()
+/// };
+/// assert_eq!(names(&net_ifaces), ["swp2", "swp10", "swp1", "eth0"]);
+///
+/// net_ifaces.set_write_options(WriteOptions {
+///     sort: SortOrder::Natural,
+///     ..Default::default()
+/// });
+/// assert_eq!(names(&net_ifaces), ["eth0", "swp1", "swp2", "swp10"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SortOrder {
+    /// Keep the order in which interfaces were added, as returned by
+    /// [`NetworkInterfaces::iter`](crate::NetworkInterfaces::iter):
+    /// loaded interfaces in the order of their stanzas, followed by those
+    /// added later (the default).
+    #[default]
+    Preserve,
+    /// Sort by name using [`natural`].
+    Natural,
+    /// Sort by name using [`natural_ci`]. Names that only differ in case are
+    /// ordered by [`natural`] so the output stays deterministic.
+    NaturalCaseInsensitive,
+    /// Write every interface after the interfaces it depends on (bridge
+    /// ports, bond slaves, VLAN raw devices and VRF devices), in natural order
+    /// within each tier. Saving fails with
+    /// [`DependencyCycle`](crate::NetworkInterfacesError::DependencyCycle) if
+    /// the dependencies form a cycle; `Display` then falls back to natural
+    /// order.
+    Dependency,
+    /// Sort using a caller-supplied [`Comparator`].
+    Custom(Comparator),
+}
+
+impl SortOrder {
+    /// Compares two interfaces according to this order.
+    ///
+    /// [`SortOrder::Preserve`] and [`SortOrder::Dependency`] cannot be
+    /// expressed as a pairwise comparison of interfaces and compare names in
+    /// natural order here.
+    pub fn compare(&self, a: &Interface, b: &Interface) -> Ordering {
+        match self {
+            SortOrder::Preserve | SortOrder::Natural | SortOrder::Dependency => {
+                natural(&a.name, &b.name)
+            }
+            SortOrder::NaturalCaseInsensitive => {
+                natural_ci(&a.name, &b.name).then_with(|| natural(&a.name, &b.name))
+            }
+            SortOrder::Custom(comparator) => comparator.compare(a, b),
+        }
+    }
+}
+
+/// The signature of a function ordering two interfaces.
+pub type CompareFn = dyn Fn(&Interface, &Interface) -> Ordering + Send + Sync;
+
+/// A caller-supplied function ordering interfaces for output.
+///
+/// The comparator is shared, so cloning a `Comparator` is cheap. Two
+/// comparators are equal only if they are clones of the same function.
+#[derive(Clone)]
+pub struct Comparator(Arc<CompareFn>);
+
+impl Comparator {
+    /// Wraps a comparison function.
+    pub fn new<F>(compare: F) -> Self
+    where
+        F: Fn(&Interface, &Interface) -> Ordering + Send + Sync + 'static,
+    {
+        Comparator(Arc::new(compare))
+    }
+
+    /// Compares two interfaces with the wrapped function.
+    pub fn compare(&self, a: &Interface, b: &Interface) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+impl From<Box<CompareFn>> for Comparator {
+    fn from(compare: Box<CompareFn>) -> Self {
+        Comparator(Arc::from(compare))
+    }
+}
+
+impl fmt::Debug for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Comparator(..)")
+    }
+}
+
+impl PartialEq for Comparator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}